@@ -1,49 +1,446 @@
-use std::{collections::HashMap, sync::atomic::Ordering};
+//! Partitioning of tasks, `#[shared]` structs and `#[init]` functions across the cores of the
+//! application.
+//!
+//! Tasks, shared structs and init functions are the nodes of a graph. The edges join nodes that have
+//! to run on the same core:
+//! - a task using a resource and the struct declaring it, since resources can't be locked from
+//!   another core,
+//! - a task or init function calling `X::spawn(..)` and the software task `X`,
+//! - an init function and the shared struct it returns.
+//!
+//! Components containing an explicit `core = N` are placed there, the others are placed by worst-fit
+//! decreasing on their utilisation (the sum of `wcet / period` of their tasks), falling back to the
+//! number of tasks to spread components that carry no timing annotations. A component is never
+//! placed next to another one holding the shared struct, idle task or init function of a core, or
+//! next to a task it spawns with `X::spawn_from(..)`. Cores left without an init function get an
+//! empty one.
+
+use std::{cmp::Ordering as CmpOrdering, collections::HashMap, fmt, sync::atomic::Ordering};
+
+use rticx_core::parse_utils::spawn_calls::{SpawnCall, SpawnKind};
+use syn::Ident;
 
 use crate::{
     error::Error,
-    parse::{APP_CORES, App},
+    parse::{
+        APP_CORES, App,
+        ast::{InitFn, TaskKind},
+    },
 };
 
-pub fn run(parsed_app: &mut App) -> syn::Result<()> {
-    // create a mapping for a resource name and the core that it belongs to
-    let mut resource_core_map = HashMap::new();
-    for shared in parsed_app.shared_resources.iter() {
-        let core = shared.core;
+/// Computed placement of the application, published on the `InfoBus` as
+/// [`crate::INFO_ASSIGNMENT`].
+#[derive(Debug, Clone, Default)]
+pub struct Assignment {
+    pub cores: Vec<CoreAssignment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoreAssignment {
+    pub core: u32,
+    pub tasks: Vec<Placement>,
+    pub shared: Vec<Placement>,
+    pub inits: Vec<Placement>,
+    /// Sum of the utilisation of the tasks annotated with `wcet` and `period`
+    pub utilisation: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub name: Ident,
+    /// `false` if the user assigned the core explicitly
+    pub automatic: bool,
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |items: &[Placement]| {
+            items
+                .iter()
+                .map(|p| format!("{}{}", p.name, if p.automatic { "*" } else { "" }))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "core assignment (* = assigned automatically):")?;
+        for core in self.cores.iter() {
+            writeln!(
+                f,
+                "  core {} (utilisation {:.2}): init [{}], tasks [{}], shared [{}]",
+                core.core,
+                core.utilisation,
+                names(&core.inits),
+                names(&core.tasks),
+                names(&core.shared)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Items a core can only have one of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Slot {
+    Shared,
+    Init,
+    Idle,
+}
+
+impl Slot {
+    fn name(self) -> &'static str {
+        match self {
+            Slot::Shared => "#[shared] structs",
+            Slot::Init => "#[init] functions",
+            Slot::Idle => "#[idle] tasks",
+        }
+    }
+}
+
+/// Union-find over the graph nodes, each root remembers the core its component is pinned to.
+struct Components {
+    parent: Vec<usize>,
+    pin: Vec<Option<u32>>,
+}
+
+impl Components {
+    fn new(pin: Vec<Option<u32>>) -> Self {
+        Self {
+            parent: (0..pin.len()).collect(),
+            pin,
+        }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let parent = self.parent[node];
+        if parent == node {
+            return node;
+        }
+        let root = self.find(parent);
+        self.parent[node] = root;
+        root
+    }
+
+    /// Merges the components of `a` and `b`. Returns the core of `a`'s component if the two are
+    /// pinned to different cores.
+    fn union(&mut self, a: usize, b: usize) -> Result<(), u32> {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return Ok(());
+        }
+        match (self.pin[a], self.pin[b]) {
+            (Some(core_a), Some(core_b)) if core_a != core_b => return Err(core_a),
+            (None, pin) => self.pin[a] = pin,
+            _ => (),
+        }
+        self.parent[b] = a;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+struct Load {
+    utilisation: f64,
+    tasks: usize,
+}
+
+impl std::ops::AddAssign for Load {
+    fn add_assign(&mut self, other: Self) {
+        self.utilisation += other.utilisation;
+        self.tasks += other.tasks;
+    }
+}
+
+pub fn run(parsed_app: &mut App) -> syn::Result<Assignment> {
+    // create a mapping for a resource name and the index of the struct declaring it
+    let mut resource_struct_map = HashMap::new();
+    for (idx, shared) in parsed_app.shared_resources.iter().enumerate() {
         for element in shared.shared_items.iter() {
-            if resource_core_map.insert(element, core).is_some() {
+            if resource_struct_map.insert(element, idx).is_some() {
                 return Err(Error::DuplicatResourceName(element.to_string()).into());
             }
         }
     }
 
-    // shared resource structs are always less than task structs, so it is reasonable to make the outer loop
-    // iterate over tasks structs instead of iterating over shared resources structs
-    for task in parsed_app.tasks.iter_mut() {
-        let task_name = &task.task_struct.ident;
-        if task.core.is_some() {
-            continue;
-        } else if APP_CORES.load(Ordering::Relaxed) == 1 {
-            task.assign_core(0);
-            continue;
-        } else if task.shared_items.is_empty() {
-            return Err(Error::ExplicitCoreNeeded(task_name.to_string()).into());
+    // nodes are the tasks followed by the shared structs and the init functions
+    let n_tasks = parsed_app.tasks.len();
+    let n_shared = parsed_app.shared_resources.len();
+    let first_init = n_tasks + n_shared;
+    let shared_node = |name: &Ident| {
+        parsed_app
+            .shared_resources
+            .iter()
+            .position(|s| &s.shared_struct.ident == name)
+            .map(|idx| n_tasks + idx)
+    };
+    let sw_task_node = |name: &Ident| {
+        parsed_app
+            .tasks
+            .iter()
+            .position(|t| t.kind == TaskKind::Software && &t.task_struct.ident == name)
+    };
+
+    let pins = parsed_app
+        .tasks
+        .iter()
+        .map(|t| t.core)
+        .chain(parsed_app.shared_resources.iter().map(|s| s.core))
+        .chain(parsed_app.inits.iter().map(|i| i.core))
+        .collect();
+    let mut components = Components::new(pins);
+    // (caller, spawned task) pairs of `spawn_from` calls, which should cross cores
+    let mut remote_spawns = Vec::new();
+    let mut link_spawns = |components: &mut Components,
+                           caller: usize,
+                           caller_name: &Ident,
+                           spawns: &[SpawnCall]|
+     -> syn::Result<()> {
+        for call in spawns {
+            let Some(target) = sw_task_node(&call.target) else {
+                continue;
+            };
+            match call.kind {
+                SpawnKind::Spawn => components.union(caller, target).map_err(|core| {
+                    Error::SpawnCoreMismatch(call.target.to_string(), caller_name.to_string(), core)
+                })?,
+                SpawnKind::SpawnFrom => remote_spawns.push((caller, target)),
+            }
         }
+        Ok(())
+    };
 
-        // at this point we have a task that has at least one shared resource and a None for the `core` value
-        let mut shared_iter = task.shared_items.iter();
-        let first_element: &syn::Ident = shared_iter.next().unwrap(); // safe to unwrap
-        let assumed_core = resource_core_map
-            .get(first_element)
-            .ok_or(Error::ResourceNotFound(first_element.to_string()))?;
+    for (idx, task) in parsed_app.tasks.iter().enumerate() {
+        for item in task.shared_items.iter() {
+            let shared_idx = resource_struct_map
+                .get(item)
+                .ok_or(Error::ResourceNotFound(item.to_string()))?;
+            components
+                .union(idx, n_tasks + shared_idx)
+                .map_err(|core| Error::CoreMimatch(task.task_struct.ident.to_string(), core))?;
+        }
+        link_spawns(&mut components, idx, &task.task_struct.ident, &task.spawns)?;
+    }
+    for (idx, init) in parsed_app.inits.iter().enumerate() {
+        let init_name = &init.init_fn.sig.ident;
+        if let Some(shared) = init.returns.as_ref().and_then(shared_node) {
+            components
+                .union(first_init + idx, shared)
+                .map_err(|core| Error::InitCoreMismatch(init_name.to_string(), core))?;
+        }
+        link_spawns(&mut components, first_init + idx, init_name, &init.spawns)?;
+    }
 
-        // check that the other resources also belong to the same core
-        if shared_iter.any(|e| resource_core_map.get(e) != Some(assumed_core)) {
-            return Err(Error::CoreMimatch(task_name.to_string(), *assumed_core).into());
+    let slot_of = |node: usize| match node {
+        node if node < n_tasks => {
+            (parsed_app.tasks[node].kind == TaskKind::Idle).then_some(Slot::Idle)
         }
+        node if node < first_init => Some(Slot::Shared),
+        _ => Some(Slot::Init),
+    };
+    let n_nodes = first_init + parsed_app.inits.len();
 
-        // assign a core to task
-        task.assign_core(*assumed_core);
+    // load and slots of every component, in order of first appearance to keep the placement
+    // deterministic
+    let mut roots = Vec::new();
+    let mut component_load: HashMap<usize, Load> = HashMap::new();
+    let mut component_slots: HashMap<usize, Vec<Slot>> = HashMap::new();
+    for node in 0..n_nodes {
+        let root = components.find(node);
+        let load = component_load.entry(root).or_insert_with(|| {
+            roots.push(root);
+            Load::default()
+        });
+        if let Some(task) = parsed_app.tasks.get(node) {
+            *load += Load {
+                utilisation: task.utilisation().unwrap_or_default(),
+                tasks: 1,
+            };
+        }
+        component_slots
+            .entry(root)
+            .or_default()
+            .extend(slot_of(node));
     }
-    Ok(())
+
+    let cores = APP_CORES.load(Ordering::Relaxed);
+    let mut core_load = vec![Load::default(); cores as usize];
+    let mut core_slots = vec![Vec::new(); cores as usize];
+    let mut free_roots = Vec::new();
+    for root in roots.iter() {
+        match components.pin[*root] {
+            Some(core) => {
+                if core_load.len() <= core as usize {
+                    // out of range cores are reported by the core pass
+                    core_load.resize(core as usize + 1, Load::default());
+                    core_slots.resize(core as usize + 1, Vec::new());
+                }
+                core_load[core as usize] += component_load[root];
+                core_slots[core as usize].extend(component_slots[root].iter().copied());
+            }
+            None => free_roots.push(*root),
+        }
+    }
+
+    // worst-fit decreasing: heaviest components first, each on the currently least loaded core.
+    // Components spawned with `spawn_from` by an already placed one (or spawning one) go first, so
+    // chains of remote spawns alternate between cores.
+    let remote_spawns = remote_spawns
+        .iter()
+        .map(|(caller, task)| (components.find(*caller), components.find(*task)))
+        .collect::<Vec<_>>();
+    let remote_peers = |root: usize| {
+        remote_spawns.iter().filter_map(move |(a, b)| match root {
+            _ if *a == root => Some(*b),
+            _ if *b == root => Some(*a),
+            _ => None,
+        })
+    };
+    free_roots.sort_by(|a, b| {
+        component_load[b]
+            .partial_cmp(&component_load[a])
+            .unwrap_or(CmpOrdering::Equal)
+    });
+    while !free_roots.is_empty() {
+        let constrained = |root: &usize| {
+            remote_peers(*root)
+                .filter(|peer| components.pin[*peer].is_some())
+                .count()
+        };
+        let next = (0..free_roots.len())
+            .rev() // `max_by_key` returns the last maximum, so ties go to the heaviest
+            .max_by_key(|idx| constrained(&free_roots[*idx]))
+            .unwrap_or_default();
+        let root = free_roots.remove(next);
+        let slots = &component_slots[&root];
+        let mut allowed = (0..cores as usize)
+            .filter(|core| !core_slots[*core].iter().any(|slot| slots.contains(slot)))
+            .filter(|core| {
+                !remote_peers(root).any(|peer| components.pin[peer] == Some(*core as u32))
+            })
+            .collect::<Vec<_>>();
+        if allowed.is_empty() {
+            // the conflict is reported below, or by the software pass for `spawn_from`
+            allowed = (0..cores as usize).collect();
+        }
+        let core = if component_load[&root].tasks == 0 {
+            // components without tasks (a lone `#[init]`, an unused `#[shared]` struct) take the
+            // lowest allowed core, the `#[init]` of core 0 is usually the one setting up the system
+            allowed.first().copied()
+        } else {
+            allowed.into_iter().min_by(|a, b| {
+                core_load[*a]
+                    .partial_cmp(&core_load[*b])
+                    .unwrap_or(CmpOrdering::Equal)
+            })
+        }
+        .unwrap_or_default();
+        core_load[core] += component_load[&root];
+        core_slots[core].extend(slots.iter().copied());
+        components.pin[root] = Some(core as u32);
+    }
+
+    if let Some((core, load)) = core_load
+        .iter()
+        .enumerate()
+        .find(|(_, load)| load.utilisation > 1.0 + f64::EPSILON)
+    {
+        return Err(Error::Overloaded(core as u32, load.utilisation).into());
+    }
+
+    let mut assignment = Assignment {
+        cores: core_load
+            .iter()
+            .enumerate()
+            .map(|(core, load)| CoreAssignment {
+                core: core as u32,
+                tasks: Vec::new(),
+                shared: Vec::new(),
+                inits: Vec::new(),
+                utilisation: load.utilisation,
+            })
+            .collect(),
+    };
+
+    // every root has a core by now
+    let mut node_core = |node: usize| {
+        let root = components.find(node);
+        components.pin[root].unwrap_or_default()
+    };
+
+    // each core can have at most one shared struct, init function and idle task
+    let mut slot_owner: HashMap<(u32, Slot), Ident> = HashMap::new();
+    for node in 0..n_nodes {
+        let Some(slot) = slot_of(node) else {
+            continue;
+        };
+        let name = match node {
+            node if node < n_tasks => &parsed_app.tasks[node].task_struct.ident,
+            node if node < first_init => {
+                &parsed_app.shared_resources[node - n_tasks]
+                    .shared_struct
+                    .ident
+            }
+            node => &parsed_app.inits[node - first_init].init_fn.sig.ident,
+        };
+        let core = node_core(node);
+        if let Some(other) = slot_owner.insert((core, slot), name.clone()) {
+            return Err(Error::SlotConflict(
+                slot.name(),
+                other.to_string(),
+                name.to_string(),
+                core,
+            )
+            .into());
+        }
+    }
+
+    for (idx, task) in parsed_app.tasks.iter_mut().enumerate() {
+        let automatic = task.core.is_none();
+        let core = node_core(idx);
+        if automatic {
+            task.assign_core(core);
+        }
+        assignment.cores[core as usize].tasks.push(Placement {
+            name: task.task_struct.ident.clone(),
+            automatic,
+        });
+    }
+    for (idx, shared) in parsed_app.shared_resources.iter_mut().enumerate() {
+        let automatic = shared.core.is_none();
+        let core = node_core(n_tasks + idx);
+        if automatic {
+            shared.assign_core(core);
+        }
+        assignment.cores[core as usize].shared.push(Placement {
+            name: shared.shared_struct.ident.clone(),
+            automatic,
+        });
+    }
+    for (idx, init) in parsed_app.inits.iter_mut().enumerate() {
+        let automatic = init.core.is_none();
+        let core = node_core(first_init + idx);
+        if automatic {
+            init.assign_core(core);
+        }
+        assignment.cores[core as usize].inits.push(Placement {
+            name: init.init_fn.sig.ident.clone(),
+            automatic,
+        });
+    }
+
+    // complete the cores the user wrote no init function for
+    for core in 0..cores {
+        if slot_owner.contains_key(&(core, Slot::Init)) {
+            continue;
+        }
+        if let Some(shared) = slot_owner.get(&(core, Slot::Shared)) {
+            return Err(Error::NoInitForShared(shared.to_string(), core).into());
+        }
+        let init = InitFn::empty(core);
+        assignment.cores[core as usize].inits.push(Placement {
+            name: init.init_fn.sig.ident.clone(),
+            automatic: true,
+        });
+        parsed_app.inits.push(init);
+    }
+
+    Ok(assignment)
 }
@@ -26,12 +26,30 @@ impl CodeGen {
         let mod_visibility = &self.app.mod_visibility;
         let mod_ident = &self.app.mod_ident;
         let other_code = &self.app.rest_of_code;
-        let shared_resources = self.app.shared_resources.iter().map(|s| &s.shared_struct);
+        let shared_resources = self.app.shared_resources.iter_mut().map(|shared| {
+            let shared_attribute = &shared.params;
+            let shared_struct = &mut shared.shared_struct;
+            shared_struct.attrs.remove(shared.attr_idx); // same for shared structs, which may have been assigned a core too
+            quote! {
+                #shared_attribute
+                #shared_struct
+            }
+        });
+        let inits = self.app.inits.iter_mut().map(|init| {
+            let init_attribute = &init.params;
+            let init_fn = &mut init.init_fn;
+            init_fn.attrs.remove(init.attr_idx);
+            quote! {
+                #init_attribute
+                #init_fn
+            }
+        });
 
         parse_quote! {
             #mod_visibility mod #mod_ident {
                 #(#other_code)*
                 #(#shared_resources)*
+                #(#inits)*
                 #(#tasks)*
             }
         }
@@ -2,12 +2,6 @@ use proc_macro2::Span;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("`core=M` has to be explicitly assinged in the struct {0} with #[shared] attribute.")]
-    NoCoreArgShared(String),
-    #[error(
-        "A core needs to be explicitly assigned to {0} task since it uses no shared resources that allow automatic core assingment."
-    )]
-    ExplicitCoreNeeded(String),
     #[error(
         "The resource name `{0}` was found on multiple structs with #[shared] attribute, but resource names must be unique."
     )]
@@ -16,6 +10,30 @@ pub enum Error {
     ResourceNotFound(String),
     #[error("The task `{0}` is only allowed to use resources from core {1}.")]
     CoreMimatch(String, u32),
+    #[error(
+        "`{0}::spawn` is called by `{1}`, so both must run on the same core, but one of them is assigned to core {2} and the other one to another core. Use `{0}::spawn_from(..)` to spawn it from another core."
+    )]
+    SpawnCoreMismatch(String, String, u32),
+    #[error(
+        "The #[init] function `{0}` returns a #[shared] struct assigned to another core than core {1}."
+    )]
+    InitCoreMismatch(String, u32),
+    #[error(
+        "`{1}` and `{2}` are both assigned to core {3}, but a core can have at most one of its {0}."
+    )]
+    SlotConflict(&'static str, String, String, u32),
+    #[error(
+        "The #[shared] struct `{0}` is assigned to core {1}, which has no #[init] function to initialize it. Add an #[init] function returning `{0}`."
+    )]
+    NoInitForShared(String, u32),
+    #[error("The task `{0}` must have both `wcet` and `period` arguments, or none of them.")]
+    IncompleteTiming(String),
+    #[error("The `period` of task `{0}` must be greater than zero.")]
+    ZeroPeriod(String),
+    #[error(
+        "The tasks assigned to core {0} have a total utilisation of {1:.2}, which exceeds 1. Move some of them to other cores or reduce their `wcet`."
+    )]
+    Overloaded(u32, f64),
 }
 
 impl From<Error> for syn::Error {
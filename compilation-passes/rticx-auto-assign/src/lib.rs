@@ -6,18 +6,40 @@ pub mod parse;
 use crate::codegen::CodeGen;
 use crate::parse::App;
 use proc_macro2::TokenStream;
-use rticx_core::RticPass;
 use rticx_core::parse_utils::RticAttr;
+use rticx_core::{InfoBus, RticPass};
 use syn::ItemMod;
 
-pub struct AutoAssignPass;
+pub use auto_assign::Assignment;
+
+pub static INFO_ASSIGNMENT: &str = "rticx_auto_assign::Assignment";
+
+#[derive(Default)]
+pub struct AutoAssignPass {
+    info_bus: Option<InfoBus>,
+}
+
+impl AutoAssignPass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 impl RticPass for AutoAssignPass {
-    fn subscribe(&mut self, _info_bus: rticx_core::InfoBus) {}
+    fn subscribe(&mut self, info_bus: InfoBus) {
+        let _ = self.info_bus.insert(info_bus);
+    }
     fn run_pass(&self, args: TokenStream, app_mod: ItemMod) -> syn::Result<(TokenStream, ItemMod)> {
         let params = RticAttr::parse_from_tokens(args.clone())?;
         let mut parsed = App::parse(&params, app_mod)?;
-        auto_assign::run(&mut parsed)?;
+        let assignment = auto_assign::run(&mut parsed)?;
+        #[cfg(feature = "print_assignment")]
+        eprintln!("{assignment}");
+        self.info_bus.as_ref().inspect(|b| {
+            b.publish(INFO_ASSIGNMENT, assignment).unwrap_or_else(|_| {
+                panic!("no other crate is allowed to publish {INFO_ASSIGNMENT}")
+            })
+        });
         let code = CodeGen::new(parsed).run();
         Ok((args, code))
     }
@@ -1,28 +1,49 @@
 use std::sync::atomic::Ordering;
 
-use quote::{ToTokens, format_ident};
-use rticx_core::parse_utils::RticAttr;
-use syn::{Expr, ItemStruct, Lit, parse_quote};
+use quote::format_ident;
+use rticx_core::parse_utils::{
+    RticAttr,
+    spawn_calls::{self, SpawnCall},
+};
+use syn::{Expr, Ident, ItemFn, ItemStruct, Lit, ReturnType, Type, parse_quote, spanned::Spanned};
 
 use crate::error::Error;
 
 use super::APP_CORES;
 
+/// The attribute a task struct was declared with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    /// `#[task]`
+    Hardware,
+    /// `#[sw_task]`
+    Software,
+    /// `#[idle]`, at most one per core
+    Idle,
+}
+
 #[derive(Debug)]
 pub struct RticTask {
+    pub kind: TaskKind,
     pub params: RticAttr,
     pub attr_idx: usize,
     pub shared_items: Vec<syn::Ident>,
     pub task_struct: ItemStruct,
     /// Core to be assigned during automatic core assignment, if not set already by user
-    ///
-    /// Note that this may still be None after the auto assignment part if the
-    /// task doesn't use any shared resources (explicit assingment required).
     pub core: Option<u32>,
+    /// Optional `wcet = N` annotation, in the same time unit as `period`
+    pub wcet: Option<u64>,
+    /// Optional `period = N` annotation, in the same time unit as `wcet`
+    pub period: Option<u64>,
+    /// Software tasks spawned from the `impl` blocks of this task
+    pub spawns: Vec<SpawnCall>,
 }
 
 impl RticTask {
-    pub fn from_struct((task_struct, attr_idx): (ItemStruct, usize)) -> syn::Result<Self> {
+    pub fn from_struct(
+        kind: TaskKind,
+        (task_struct, attr_idx): (ItemStruct, usize),
+    ) -> syn::Result<Self> {
         let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
 
         let core = if let Some(Expr::Lit(syn::ExprLit {
@@ -35,21 +56,46 @@ impl RticTask {
             None
         };
 
+        // `&resource` reads a `#[readonly]` resource without locking it, the task doesn't need to
+        // run on the core of the resource
         let shared_items = if let Some(Expr::Array(arr)) = params.elements.get("shared") {
             arr.elems
                 .iter()
-                .map(|item| format_ident!("{}", item.to_token_stream().to_string()))
+                .filter(|item| !matches!(item, Expr::Reference(_)))
+                .filter_map(shared_resource)
                 .collect()
         } else {
             Vec::new()
         };
 
+        let wcet = int_arg(&params, "wcet")?;
+        let period = int_arg(&params, "period")?;
+        match (wcet, period) {
+            (None, None) | (Some(_), Some(1..)) => (),
+            (Some(_), Some(0)) => {
+                return Err(syn::Error::new(
+                    params.elements["period"].span(),
+                    Error::ZeroPeriod(task_struct.ident.to_string()),
+                ));
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    task_struct.attrs[attr_idx].span(),
+                    Error::IncompleteTiming(task_struct.ident.to_string()),
+                ));
+            }
+        }
+
         Ok(Self {
+            kind,
             core,
             params,
             attr_idx,
             shared_items,
             task_struct,
+            wcet,
+            period,
+            spawns: Vec::new(),
         })
     }
 
@@ -58,11 +104,33 @@ impl RticTask {
         let expr: syn::Expr = parse_quote!(#core);
         self.params.elements.insert(String::from("core"), expr);
     }
+
+    /// Share of the core needed by the task, `wcet / period`. `None` if the task has no timing annotations.
+    pub fn utilisation(&self) -> Option<f64> {
+        Some(self.wcet? as f64 / self.period? as f64)
+    }
+}
+
+/// Parses the optional integer argument `name`
+fn int_arg(params: &RticAttr, name: &str) -> syn::Result<Option<u64>> {
+    match params.elements.get(name) {
+        None => Ok(None),
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(int), ..
+        })) => int.base10_parse().map(Some),
+        Some(expr) => Err(syn::Error::new(
+            expr.span(),
+            format!("`{name}` must be an integer literal."),
+        )),
+    }
 }
 
 #[derive(Debug)]
 pub struct SharedResources {
-    pub core: u32,
+    pub params: RticAttr,
+    pub attr_idx: usize,
+    /// Core of the resources, set by the user or during automatic core assignment
+    pub core: Option<u32>,
     pub shared_items: Vec<syn::Ident>,
     pub shared_struct: ItemStruct,
 }
@@ -84,17 +152,109 @@ impl SharedResources {
             lit: Lit::Int(int), ..
         })) = params.elements.get("core")
         {
-            int.base10_parse().unwrap_or_default()
+            Some(int.base10_parse().unwrap_or_default())
         } else if APP_CORES.load(Ordering::Relaxed) == 1 {
-            0
+            Some(0)
         } else {
-            return Err(Error::NoCoreArgShared(shared_struct.ident.to_string()).into());
+            // placed next to the tasks using the resources during automatic core assignment
+            None
         };
 
         Ok(Self {
+            params,
+            attr_idx,
             shared_items,
             shared_struct,
             core,
         })
     }
+
+    pub fn assign_core(&mut self, core: u32) {
+        let _ = self.core.insert(core);
+        let expr: syn::Expr = parse_quote!(#core);
+        self.params.elements.insert(String::from("core"), expr);
+    }
+}
+
+/// An `#[init]` function. Every core needs exactly one.
+#[derive(Debug)]
+pub struct InitFn {
+    pub params: RticAttr,
+    pub attr_idx: usize,
+    /// Core of the init function, set by the user or during automatic core assignment
+    pub core: Option<u32>,
+    /// Name of the type returned by the function, or of the first element of a returned tuple.
+    /// Used to place the function on the core of the `#[shared]` struct it initializes.
+    pub returns: Option<syn::Ident>,
+    /// Software tasks spawned from the body of the function
+    pub spawns: Vec<SpawnCall>,
+    pub init_fn: ItemFn,
+}
+
+impl InitFn {
+    pub fn from_fn((init_fn, attr_idx): (ItemFn, usize)) -> syn::Result<Self> {
+        let params = RticAttr::parse_from_attr(&init_fn.attrs[attr_idx])?;
+        let core = int_arg(&params, "core")?
+            .map(|core| core as u32)
+            .or_else(|| (APP_CORES.load(Ordering::Relaxed) == 1).then_some(0));
+
+        let returns = match &init_fn.sig.output {
+            ReturnType::Type(_, ty) => match ty.as_ref() {
+                Type::Tuple(tuple) => tuple.elems.first().and_then(type_ident),
+                ty => type_ident(ty),
+            },
+            ReturnType::Default => None,
+        };
+
+        Ok(Self {
+            params,
+            attr_idx,
+            core,
+            returns,
+            spawns: spawn_calls::in_fn(&init_fn),
+            init_fn,
+        })
+    }
+
+    /// An empty `#[init]` function for a core the user didn't write one for
+    pub fn empty(core: u32) -> Self {
+        let ident = format_ident!("__rticx_internal__init_core{core}");
+        let init_fn: ItemFn = parse_quote! {
+            #[init(core = #core)]
+            fn #ident() {}
+        };
+        let params = RticAttr::parse_from_attr(&init_fn.attrs[0]).expect("valid init attribute");
+        Self {
+            params,
+            attr_idx: 0,
+            core: Some(core),
+            returns: None,
+            spawns: Vec::new(),
+            init_fn,
+        }
+    }
+
+    pub fn assign_core(&mut self, core: u32) {
+        let _ = self.core.insert(core);
+        let expr: syn::Expr = parse_quote!(#core);
+        self.params.elements.insert(String::from("core"), expr);
+    }
+}
+
+fn type_ident(ty: &Type) -> Option<syn::Ident> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.clone()),
+        _ => None,
+    }
+}
+
+/// Resource containing the `shared` item, a part like `channels[0]` or `motor.state` is assigned
+/// with its whole resource. Malformed items are left to the core pass to report.
+fn shared_resource(item: &Expr) -> Option<Ident> {
+    match item {
+        Expr::Path(path) => path.path.get_ident().cloned(),
+        Expr::Field(field) => shared_resource(&field.base),
+        Expr::Index(index) => shared_resource(&index.expr),
+        _ => None,
+    }
 }
@@ -1,9 +1,9 @@
 use std::sync::atomic::{AtomicU32, Ordering};
 
-use crate::parse::ast::RticTask;
+use crate::parse::ast::{InitFn, RticTask, TaskKind};
 use proc_macro2::Ident;
-use rticx_core::parse_utils::RticAttr;
-use syn::{Expr, Item, ItemMod, ItemStruct, Lit, Visibility};
+use rticx_core::parse_utils::{RticAttr, spawn_calls};
+use syn::{Attribute, Expr, Item, ItemMod, ItemStruct, Lit, Visibility};
 
 use self::ast::SharedResources;
 
@@ -15,6 +15,7 @@ pub struct App {
     pub mod_ident: Ident,
     pub shared_resources: Vec<SharedResources>,
     pub tasks: Vec<RticTask>,
+    pub inits: Vec<InitFn>,
     pub rest_of_code: Vec<Item>,
 }
 
@@ -29,6 +30,7 @@ impl App {
         let app_mod_items = app_mod.content.take().unwrap_or_default().1;
         let mut task_structs = Vec::new();
         let mut shared_structs = Vec::new();
+        let mut init_fns = Vec::new();
         let mut rest_of_code = Vec::with_capacity(app_mod_items.len());
 
         for item in app_mod_items {
@@ -37,23 +39,48 @@ impl App {
                     if let Some(attr_idx) = is_struct_with_attr(&strct, "shared") {
                         shared_structs.push((strct, attr_idx))
                     } else if let Some(attr_idx) = is_struct_with_attr(&strct, "task") {
-                        task_structs.push((strct, attr_idx))
+                        task_structs.push((TaskKind::Hardware, (strct, attr_idx)))
                     } else if let Some(attr_idx) = is_struct_with_attr(&strct, "sw_task") {
-                        task_structs.push((strct, attr_idx))
+                        task_structs.push((TaskKind::Software, (strct, attr_idx)))
                     } else if let Some(attr_idx) = is_struct_with_attr(&strct, "idle") {
-                        task_structs.push((strct, attr_idx))
+                        task_structs.push((TaskKind::Idle, (strct, attr_idx)))
                     } else {
                         rest_of_code.push(Item::Struct(strct))
                     }
                 }
+                Item::Fn(function) => {
+                    if let Some(attr_idx) = find_attr(&function.attrs, "init") {
+                        init_fns.push((function, attr_idx))
+                    } else {
+                        rest_of_code.push(Item::Fn(function))
+                    }
+                }
                 _ => rest_of_code.push(item),
             }
         }
-        let tasks = task_structs
+        let mut tasks: Vec<RticTask> = task_structs
+            .into_iter()
+            .map(|(kind, task)| RticTask::from_struct(kind, task))
+            .collect::<syn::Result<_>>()?;
+
+        let inits = init_fns
             .into_iter()
-            .map(RticTask::from_struct)
+            .map(InitFn::from_fn)
             .collect::<syn::Result<_>>()?;
 
+        // record the spawn calls made by every task
+        for item in rest_of_code.iter() {
+            let Item::Impl(impl_) = item else {
+                continue;
+            };
+            let Some(self_ty) = spawn_calls::impl_self_ident(impl_) else {
+                continue;
+            };
+            if let Some(task) = tasks.iter_mut().find(|t| &t.task_struct.ident == self_ty) {
+                task.spawns.extend(spawn_calls::in_impl(impl_));
+            }
+        }
+
         let shared_resources = shared_structs
             .into_iter()
             .map(SharedResources::from_struct)
@@ -64,6 +91,7 @@ impl App {
             mod_visibility: app_mod.vis,
             tasks,
             shared_resources,
+            inits,
             rest_of_code,
         })
     }
@@ -71,7 +99,12 @@ impl App {
 
 /// returns the index of the `attr_name` attribute if found in the attribute list of some struct
 fn is_struct_with_attr(strct: &ItemStruct, attr_name: &str) -> Option<usize> {
-    for (i, attr) in strct.attrs.iter().enumerate() {
+    find_attr(&strct.attrs, attr_name)
+}
+
+/// returns the index of the `attr_name` attribute if found in `attrs`
+fn find_attr(attrs: &[Attribute], attr_name: &str) -> Option<usize> {
+    for (i, attr) in attrs.iter().enumerate() {
         let path = attr.meta.path();
         if path.segments.len() == 1 && path.segments[0].ident == attr_name {
             return Some(i);
@@ -19,6 +19,15 @@ fn parse(args: &TokenStream, app_mod: syn::ItemMod) -> App {
     App::parse(&params, app_mod).expect("app parse")
 }
 
+/// Find a shared resources struct by name in the parsed app.
+fn shared_core<'a>(app: &'a App, name: &str) -> &'a Option<u32> {
+    app.shared_resources
+        .iter()
+        .find(|s| s.shared_struct.ident == name)
+        .map(|s| &s.core)
+        .unwrap_or_else(|| panic!("no shared struct named {name}"))
+}
+
 /// Find a task by name in the parsed app.
 fn task_core<'a>(app: &'a App, name: &str) -> &'a Option<u32> {
     app.tasks
@@ -28,6 +37,15 @@ fn task_core<'a>(app: &'a App, name: &str) -> &'a Option<u32> {
         .unwrap_or_else(|| panic!("no task named {name}"))
 }
 
+/// Find an init function by name in the parsed app.
+fn init_core<'a>(app: &'a App, name: &str) -> &'a Option<u32> {
+    app.inits
+        .iter()
+        .find(|i| i.init_fn.sig.ident == name)
+        .map(|i| &i.core)
+        .unwrap_or_else(|| panic!("no init named {name}"))
+}
+
 // ---------------------------------------------------------------------------
 // Positive cases
 // ---------------------------------------------------------------------------
@@ -65,6 +83,11 @@ fn multi_core_infers_core_from_shared() {
             x: u32,
         }
 
+        #[init]
+        fn init() -> S {
+            S { x: 0 }
+        }
+
         #[task(shared = [x])]
         struct T;
     });
@@ -82,6 +105,11 @@ fn multi_core_infers_core_zero_from_shared() {
             x: u32,
         }
 
+        #[init]
+        fn init() -> S {
+            S { x: 0 }
+        }
+
         #[task(shared = [x])]
         struct T;
     });
@@ -99,6 +127,11 @@ fn single_core_with_shared_still_assigns_zero() {
             x: u32,
         }
 
+        #[init]
+        fn init() -> S {
+            S { x: 0 }
+        }
+
         #[task(shared = [x])]
         struct T;
     });
@@ -107,21 +140,370 @@ fn single_core_with_shared_still_assigns_zero() {
     assert_eq!(*task_core(&parsed, "T"), Some(0));
 }
 
+#[test]
+fn multi_core_task_without_shared_gets_a_core() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task]
+        struct T;
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "T"), Some(0));
+}
+
+#[test]
+fn multi_core_keeps_resource_connected_tasks_together() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared]
+        struct S {
+            x: u32,
+            y: u32,
+        }
+
+        #[init]
+        fn init() -> S {
+            S { x: 0, y: 0 }
+        }
+
+        #[task(shared = [x])]
+        struct A;
+
+        // links A and C, so everything lands on a single core
+        #[task(shared = [x, y])]
+        struct B;
+
+        #[task(shared = [y])]
+        struct C;
+
+        #[task(core = 1)]
+        struct D;
+    });
+    let mut parsed = parse(&args, app);
+    let assignment = auto_assign::run(&mut parsed).expect("assign");
+    for name in ["A", "B", "C"] {
+        assert_eq!(*task_core(&parsed, name), Some(0), "task {name}");
+    }
+    assert_eq!(*shared_core(&parsed, "S"), Some(0));
+    assert_eq!(*task_core(&parsed, "D"), Some(1));
+
+    assert_eq!(assignment.cores.len(), 2);
+    assert_eq!(assignment.cores[0].tasks.len(), 3);
+    assert!(assignment.cores[0].tasks.iter().all(|t| t.automatic));
+    assert_eq!(assignment.cores[1].tasks[0].name, "D");
+    assert!(!assignment.cores[1].tasks[0].automatic);
+}
+
+#[test]
+fn multi_core_shared_follows_pinned_task() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared]
+        struct S {
+            x: u32,
+        }
+
+        #[init]
+        fn init() -> S {
+            S { x: 0 }
+        }
+
+        #[task(shared = [x], core = 1)]
+        struct T;
+
+        #[task(shared = [x])]
+        struct U;
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*shared_core(&parsed, "S"), Some(1));
+    assert_eq!(*task_core(&parsed, "U"), Some(1));
+}
+
+#[test]
+fn multi_core_balances_utilisation() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(wcet = 5, period = 10)]
+        struct Heavy;
+
+        #[task(wcet = 3, period = 10)]
+        struct Medium;
+
+        #[task(wcet = 2, period = 10)]
+        struct Light;
+
+        #[task(wcet = 1, period = 10)]
+        struct Tiny;
+    });
+    let mut parsed = parse(&args, app);
+    let assignment = auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "Heavy"), Some(0));
+    assert_eq!(*task_core(&parsed, "Medium"), Some(1));
+    assert_eq!(*task_core(&parsed, "Light"), Some(1));
+    assert_eq!(*task_core(&parsed, "Tiny"), Some(0));
+    assert!((assignment.cores[0].utilisation - 0.6).abs() < 1e-9);
+    assert!((assignment.cores[1].utilisation - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn multi_core_balances_pinned_load() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(core = 0, wcet = 7, period = 10)]
+        struct Pinned;
+
+        #[task(wcet = 2, period = 10)]
+        struct A;
+
+        #[task(wcet = 2, period = 10)]
+        struct B;
+
+        // unannotated tasks are spread by count
+        #[task]
+        struct C;
+
+        #[task]
+        struct D;
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "A"), Some(1));
+    assert_eq!(*task_core(&parsed, "B"), Some(1));
+    assert_eq!(*task_core(&parsed, "C"), Some(1));
+    assert_eq!(*task_core(&parsed, "D"), Some(1));
+}
+
+#[test]
+fn multi_core_sw_task_follows_local_spawner() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(binds = UART0, core = 1)]
+        struct Rx;
+        impl RticTask for Rx {
+            fn exec(&mut self) {
+                Parse::spawn(0).ok();
+            }
+        }
+
+        #[sw_task]
+        struct Parse;
+        impl RticSwTask for Parse {
+            fn exec(&mut self, byte: u8) {
+                Self::spawn_from(byte).ok();
+            }
+        }
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "Parse"), Some(1));
+}
+
+#[test]
+fn dual_core_ping_pong_needs_no_cores() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[init]
+        fn init_a() {}
+
+        #[init]
+        fn init_b() {}
+
+        #[idle]
+        struct Idle;
+        impl RticIdleTask for Idle {
+            fn exec(&mut self) -> ! {
+                Pong::spawn_from(0).ok();
+                loop {}
+            }
+        }
+
+        #[sw_task]
+        struct Ping;
+        impl RticSwTask for Ping {
+            fn exec(&mut self, n: u32) {
+                Pong::spawn_from(n + 1).ok();
+            }
+        }
+
+        #[sw_task]
+        struct Pong;
+        impl RticSwTask for Pong {
+            fn exec(&mut self, n: u32) {
+                Ping::spawn_from(n + 1).ok();
+            }
+        }
+    });
+    let mut parsed = parse(&args, app);
+    let assignment = auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "Idle"), Some(0));
+    assert_eq!(*task_core(&parsed, "Pong"), Some(1));
+    assert_eq!(*task_core(&parsed, "Ping"), Some(0));
+    assert_eq!(*init_core(&parsed, "init_a"), Some(0));
+    assert_eq!(*init_core(&parsed, "init_b"), Some(1));
+    assert_eq!(parsed.inits.len(), 2, "no init generated");
+    assert!(assignment.cores.iter().all(|c| c.inits.len() == 1));
+}
+
+#[test]
+fn multi_core_init_follows_returned_shared() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared]
+        struct S {
+            x: u32,
+        }
+
+        #[init]
+        fn init() -> (S, Late) {
+            unimplemented!()
+        }
+
+        #[task(shared = [x], core = 1)]
+        struct T;
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*init_core(&parsed, "init"), Some(1));
+    assert_eq!(*init_core(&parsed, "__rticx_internal__init_core0"), Some(0));
+}
+
+#[test]
+fn multi_core_idles_spread_over_cores() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[idle]
+        struct IdleA;
+
+        #[idle]
+        struct IdleB;
+
+        #[task(core = 0)]
+        struct T;
+    });
+    let mut parsed = parse(&args, app);
+    auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(*task_core(&parsed, "IdleA"), Some(1));
+    assert_eq!(*task_core(&parsed, "IdleB"), Some(0));
+}
+
+#[test]
+fn assignment_display() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared]
+        struct S {
+            x: u32,
+        }
+
+        #[init]
+        fn init() -> S {
+            S { x: 0 }
+        }
+
+        #[task(shared = [x], wcet = 1, period = 4)]
+        struct T;
+
+        #[task(core = 1)]
+        struct U;
+    });
+    let mut parsed = parse(&args, app);
+    let assignment = auto_assign::run(&mut parsed).expect("assign");
+    assert_eq!(
+        assignment.to_string(),
+        "core assignment (* = assigned automatically):\n  \
+         core 0 (utilisation 0.25): init [init*], tasks [T*], shared [S*]\n  \
+         core 1 (utilisation 0.00): init [__rticx_internal__init_core1*], tasks [U], shared []\n"
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Negative cases
 // ---------------------------------------------------------------------------
 
 #[test]
-fn multi_core_explicit_core_needed() {
+fn multi_core_overloaded_core() {
     let args = multi_core_args(2);
     let app = app_mod(quote! {
-        #[task]
+        #[shared]
+        struct S {
+            x: u32,
+        }
+
+        // connected by `x`, so they can't be split across the two cores
+        #[task(shared = [x], wcet = 6, period = 10)]
+        struct A;
+
+        #[task(shared = [x], wcet = 6, period = 10)]
+        struct B;
+    });
+    let mut parsed = parse(&args, app);
+    assert_err_contains(
+        auto_assign::run(&mut parsed),
+        "The tasks assigned to core 0 have a total utilisation of 1.20, which exceeds 1.",
+    );
+}
+
+#[test]
+fn multi_core_local_spawn_across_pinned_cores() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(core = 0)]
         struct T;
+        impl RticTask for T {
+            fn exec(&mut self) {
+                W::spawn(()).ok();
+            }
+        }
+
+        #[sw_task(core = 1)]
+        struct W;
+    });
+    let mut parsed = parse(&args, app);
+    assert_err_contains(
+        auto_assign::run(&mut parsed),
+        "`W::spawn` is called by `T`, so both must run on the same core",
+    );
+}
+
+#[test]
+fn multi_core_two_shared_structs_on_one_core() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared]
+        struct S0 {
+            x: u32,
+        }
+
+        #[shared]
+        struct S1 {
+            y: u32,
+        }
+
+        #[task(shared = [x, y])]
+        struct T;
+    });
+    let mut parsed = parse(&args, app);
+    assert_err_contains(
+        auto_assign::run(&mut parsed),
+        "`S0` and `S1` are both assigned to core 0, but a core can have at most one of its #[shared] structs.",
+    );
+}
+
+#[test]
+fn multi_core_shared_without_init() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[shared(core = 1)]
+        struct S {
+            x: u32,
+        }
     });
     let mut parsed = parse(&args, app);
     assert_err_contains(
         auto_assign::run(&mut parsed),
-        "A core needs to be explicitly assigned to T task",
+        "The #[shared] struct `S` is assigned to core 1, which has no #[init] function to initialize it.",
     );
 }
 
@@ -18,7 +18,7 @@ use common::{app_mod, assert_section_present, mod_to_string, multi_core_args, si
 
 /// Run the auto-assign pass end-to-end and return the generated module string.
 fn run_pass(args: TokenStream, app_mod: syn::ItemMod) -> String {
-    let (_, module) = AutoAssignPass
+    let (_, module) = AutoAssignPass::new()
         .run_pass(args, app_mod)
         .expect("pass succeeds");
     mod_to_string(&module)
@@ -62,6 +62,10 @@ fn codegen_multi_core_injects_inferred_core() {
             struct S {
                 x: u32,
             }
+            #[init(core = 1)]
+            fn init() -> S {
+                S { x: 0 }
+            }
             #[task(shared = [x])]
             struct T;
             #[task(core = 0)]
@@ -94,6 +98,69 @@ fn codegen_multi_core_injects_inferred_core() {
     mod_app_shell(&generated);
 }
 
+// ===========================================================================
+// Multi-core: coreless shared struct gets the core of the tasks using it
+// ===========================================================================
+
+#[test]
+fn codegen_multi_core_injects_shared_core() {
+    let generated = run_pass(
+        multi_core_args(2),
+        app_mod(quote! {
+            #[shared]
+            struct S {
+                x: u32,
+            }
+            #[init]
+            fn init() -> S {
+                S { x: 0 }
+            }
+            #[task(shared = [x], core = 1)]
+            struct T;
+        }),
+    );
+
+    assert_section_present(
+        &generated,
+        quote! { # [shared (core = 1u32)] struct S { x : u32 , } },
+        "shared struct with injected core",
+    );
+    mod_app_shell(&generated);
+}
+
+// ===========================================================================
+// Multi-core: the single init follows its shared struct, the other core gets an empty one
+// ===========================================================================
+
+#[test]
+fn codegen_multi_core_routes_and_completes_inits() {
+    let generated = run_pass(
+        multi_core_args(2),
+        app_mod(quote! {
+            #[shared(core = 1)]
+            struct S {
+                x: u32,
+            }
+            #[init]
+            fn init() -> S {
+                S { x: 0 }
+            }
+        }),
+    );
+
+    assert_section_present(
+        &generated,
+        quote! { # [init (core = 1u32)] fn init () -> S { S { x : 0 } } },
+        "routed init",
+    );
+    assert_section_present(
+        &generated,
+        quote! { # [init (core = 0u32)] fn __rticx_internal__init_core0 () { } },
+        "generated empty init",
+    );
+    mod_app_shell(&generated);
+}
+
 /// Asserts the `mod app { ... }` wrapper is present.
 fn mod_app_shell(generated: &str) {
     assert_section_present(generated, quote! { mod app }, "app module shell");
@@ -2,8 +2,10 @@
 
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
+use rticx_auto_assign::parse::ast::TaskKind;
 use rticx_auto_assign::parse::{APP_CORES, App};
 use rticx_core::parse_utils::RticAttr;
+use rticx_core::parse_utils::spawn_calls::SpawnKind;
 use std::sync::atomic::Ordering;
 
 mod common;
@@ -43,12 +45,12 @@ fn shared_without_core_single_core_defaults_zero() {
     });
     let parsed = App::parse(&params(&args), app).expect("parse");
     assert_eq!(parsed.shared_resources.len(), 1);
-    assert_eq!(parsed.shared_resources[0].core, 0);
+    assert_eq!(parsed.shared_resources[0].core, Some(0));
     assert_eq!(parsed.shared_resources[0].shared_items.len(), 1);
 }
 
 #[test]
-fn shared_without_core_multi_core_errors() {
+fn shared_without_core_multi_core_left_unassigned() {
     let args = multi_core_args(2);
     let app = app_mod(quote! {
         #[shared]
@@ -56,10 +58,8 @@ fn shared_without_core_multi_core_errors() {
             x: u32,
         }
     });
-    assert_err_contains(
-        App::parse(&params(&args), app),
-        "has to be explicitly assinged in the struct",
-    );
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    assert_eq!(parsed.shared_resources[0].core, None);
 }
 
 #[test]
@@ -91,6 +91,93 @@ fn task_shared_items_parsed() {
     assert_eq!(items, vec!["a", "b"]);
 }
 
+#[test]
+fn task_readonly_items_skipped() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(shared = [a, &config])]
+        struct T;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    let items: Vec<String> = parsed.tasks[0]
+        .shared_items
+        .iter()
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(items, vec!["a"]);
+}
+
+#[test]
+fn task_resource_parts_assigned_with_their_resource() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(shared = [channels[0], motor.state])]
+        struct T;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    let items: Vec<String> = parsed.tasks[0]
+        .shared_items
+        .iter()
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(items, vec!["channels", "motor"]);
+}
+
+#[test]
+fn task_timing_parsed() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(wcet = 250, period = 1000)]
+        struct T;
+        #[task]
+        struct U;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    assert_eq!(parsed.tasks[0].wcet, Some(250));
+    assert_eq!(parsed.tasks[0].period, Some(1000));
+    assert_eq!(parsed.tasks[0].utilisation(), Some(0.25));
+    assert_eq!(parsed.tasks[1].utilisation(), None);
+}
+
+#[test]
+fn task_timing_incomplete_errors() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(wcet = 250)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "The task `T` must have both `wcet` and `period` arguments",
+    );
+}
+
+#[test]
+fn task_timing_zero_period_errors() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(wcet = 1, period = 0)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "The `period` of task `T` must be greater than zero.",
+    );
+}
+
+#[test]
+fn task_timing_must_be_literal() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task(wcet = WCET, period = 10)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "`wcet` must be an integer literal.",
+    );
+}
+
 #[test]
 fn sw_task_recognized_as_task() {
     let args = single_core_args();
@@ -101,6 +188,7 @@ fn sw_task_recognized_as_task() {
     let parsed = App::parse(&params(&args), app).expect("parse");
     assert_eq!(parsed.tasks.len(), 1);
     assert_eq!(parsed.tasks[0].task_struct.ident, "T");
+    assert_eq!(parsed.tasks[0].kind, TaskKind::Software);
 }
 
 #[test]
@@ -113,6 +201,7 @@ fn idle_recognized_as_task() {
     let parsed = App::parse(&params(&args), app).expect("parse");
     assert_eq!(parsed.tasks.len(), 1);
     assert_eq!(parsed.tasks[0].task_struct.ident, "I");
+    assert_eq!(parsed.tasks[0].kind, TaskKind::Idle);
 }
 
 #[test]
@@ -129,3 +218,46 @@ fn mod_visibility_ident_and_plain_structs_preserved() {
     assert_eq!(parsed.tasks.len(), 0);
     assert_eq!(parsed.rest_of_code.len(), 1);
 }
+
+#[test]
+fn init_parsed_with_returned_struct() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[init]
+        fn init() -> (Shared, Late) {
+            Job::spawn(()).ok();
+            unimplemented!()
+        }
+        #[init(core = 1)]
+        fn init1() {}
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    assert_eq!(parsed.inits.len(), 2);
+    assert_eq!(parsed.inits[0].core, None);
+    assert_eq!(parsed.inits[0].returns.as_ref().unwrap(), "Shared");
+    assert_eq!(parsed.inits[0].spawns[0].target, "Job");
+    assert_eq!(parsed.inits[1].core, Some(1));
+    assert_eq!(parsed.inits[1].returns, None);
+}
+
+#[test]
+fn task_spawn_calls_recorded() {
+    let args = multi_core_args(2);
+    let app = app_mod(quote! {
+        #[task]
+        struct T;
+        impl RticTask for T {
+            fn exec(&mut self) {
+                A::spawn(1).ok();
+                B::spawn_from(2).ok();
+            }
+        }
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    let spawns = &parsed.tasks[0].spawns;
+    assert_eq!(spawns.len(), 2);
+    assert_eq!(spawns[0].target, "A");
+    assert_eq!(spawns[0].kind, SpawnKind::Spawn);
+    assert_eq!(spawns[1].target, "B");
+    assert_eq!(spawns[1].kind, SpawnKind::SpawnFrom);
+}
@@ -16,8 +16,8 @@ impl CodeGen {
         let tasks = self.app.tasks.iter_mut().map(|task| {
             let task_attribute = &task.params;
             let task_struct = &mut task.task_struct;
-            // remove the older task attribute and replace with the updated one which includes an
-            // automatically assigned core
+            // remove the older task attribute and replace with the updated one which includes the
+            // computed priority
             task_struct.attrs.remove(task.attr_idx);
             quote! {
                 #task_attribute
@@ -0,0 +1,94 @@
+//! Merging of deadline groups into shared priority levels, for cores with more distinct deadlines
+//! than the platform has priority levels.
+//!
+//! The deadline-monotonic order is kept: only groups of adjacent deadlines are merged. Levels are
+//! filled from the least urgent one, Audsley style: each level takes as many of the remaining
+//! longest-deadline groups as can meet their deadlines with every other remaining task above them.
+//! A task is checked with response time analysis, where tasks sharing its level count as
+//! interference (SRP never lets them preempt it, this is a safe bound) and the blocking is the
+//! longest `wcet` of the lower tasks using a resource whose ceiling reaches its level.
+
+use syn::Ident;
+
+/// Timing of a task, all values in the same unit
+#[derive(Debug, Clone)]
+pub struct TaskTiming {
+    pub deadline: u64,
+    pub wcet: u64,
+    /// Period or minimum inter-arrival time
+    pub period: u64,
+    pub shared: Vec<Ident>,
+}
+
+/// Assign a level to each of the `groups` of tasks with equal deadlines, shortest deadline first.
+/// Returns the level of every group, `0` being the least urgent, or `None` if no assignment into
+/// at most `max_levels` levels is feasible.
+pub fn merge_groups(groups: &[Vec<TaskTiming>], max_levels: usize) -> Option<Vec<usize>> {
+    let mut group_level = vec![0; groups.len()];
+    // groups `..unassigned` still need a level
+    let mut unassigned = groups.len();
+    let mut level = 0;
+    while unassigned > 0 {
+        if level == max_levels {
+            return None;
+        }
+        // the most urgent level has to take every remaining group
+        let candidates = if level + 1 == max_levels {
+            0..1
+        } else {
+            0..unassigned
+        };
+        let first = candidates
+            .into_iter()
+            .find(|first| level_feasible(groups, *first, unassigned))?;
+        group_level[first..unassigned].fill(level);
+        unassigned = first;
+        level += 1;
+    }
+    Some(group_level)
+}
+
+/// Whether the tasks of `groups[first..end]` meet their deadlines when sharing a level, with
+/// `groups[..first]` above them and `groups[end..]` below them.
+fn level_feasible(groups: &[Vec<TaskTiming>], first: usize, end: usize) -> bool {
+    let level = groups[first..end].iter().flatten();
+    let above_or_same = || groups[..end].iter().flatten();
+    let below = || groups[end..].iter().flatten();
+
+    // the resources used at this level or above have a ceiling reaching this level
+    let blocking = below()
+        .filter(|low| {
+            above_or_same().any(|task| task.shared.iter().any(|r| low.shared.contains(r)))
+        })
+        .map(|low| low.wcet)
+        .max()
+        .unwrap_or_default();
+
+    level.into_iter().all(|task| {
+        let interference = above_or_same().filter(|other| !std::ptr::eq(*other, task));
+        response_time(task, blocking, interference).is_some()
+    })
+}
+
+/// Worst case response time of `task`, or `None` if it exceeds its deadline.
+fn response_time<'a>(
+    task: &TaskTiming,
+    blocking: u64,
+    interference: impl Iterator<Item = &'a TaskTiming> + Clone,
+) -> Option<u64> {
+    let base = task.wcet.checked_add(blocking)?;
+    let mut response = base;
+    loop {
+        let next = interference.clone().try_fold(base, |acc, other| {
+            let releases = response.div_ceil(other.period.max(1));
+            acc.checked_add(releases.checked_mul(other.wcet)?)
+        })?;
+        if next > task.deadline {
+            return None;
+        }
+        if next == response {
+            return Some(response);
+        }
+        response = next;
+    }
+}
@@ -1,21 +1,34 @@
 pub mod codegen;
+pub mod merge;
 pub mod parse;
 pub use parse::App;
+use parse::ast::RticTask;
 
 use codegen::CodeGen;
 use proc_macro2::TokenStream;
+pub use rticx_core::MostUrgent;
 use rticx_core::RticPass;
-use rticx_core::parse_utils::RticAttr;
-use syn::{ItemMod, parse_quote};
+use rticx_core::parse_utils::{RticAttr, Time};
+use syn::ItemMod;
 
 pub struct DeadlineToPriorityPass {
     max_priority: u16,
+    most_urgent: MostUrgent,
 }
 
 impl DeadlineToPriorityPass {
     #[allow(clippy::new_without_default)]
     pub fn new(max_priority: u16) -> Self {
-        Self { max_priority }
+        Self {
+            max_priority,
+            most_urgent: MostUrgent::default(),
+        }
+    }
+
+    /// Set the end of the priority range that is given to the shortest deadlines
+    pub fn with_most_urgent(mut self, most_urgent: MostUrgent) -> Self {
+        self.most_urgent = most_urgent;
+        self
     }
 }
 
@@ -26,17 +39,7 @@ impl RticPass for DeadlineToPriorityPass {
 
         let mut parsed = App::parse(&params, app_mod)?;
 
-        self.analyze(&mut parsed);
-
-        for task in parsed.tasks.iter_mut() {
-            if let Some(deadline) = task.deadline {
-                task.params.elements.remove("deadline");
-                let expr: syn::Expr = parse_quote! { #deadline };
-                let _ = task.params.elements.insert("priority".into(), expr);
-            } else {
-                continue;
-            }
-        }
+        self.analyze(&mut parsed)?;
 
         let code = CodeGen::new(parsed).run();
         Ok((args, code))
@@ -48,37 +51,116 @@ impl RticPass for DeadlineToPriorityPass {
 }
 
 impl DeadlineToPriorityPass {
-    pub fn analyze(&self, app: &mut App) {
-        let mut deadlines: Vec<_> = app
-            .tasks
-            .iter()
-            .map(|task| task.deadline.unwrap_or(u32::MAX))
-            .collect();
-        eprintln!("task deadlines {:?}", deadlines);
-
-        deadlines.sort();
-        eprintln!("sorted {:?}", deadlines);
-        deadlines.dedup();
-        eprintln!("sorted dedup {:?}", deadlines);
-        // NOTE: Do NOT reverse - shorter deadlines should get higher priority (lower number)
-        // deadlines.reverse();
-        // eprintln!("sorted dedup reversed {:?}", deadlines);
-
-        if deadlines.len() as u16 > self.max_priority {
-            panic!(
-                "Exceeded number of priorities for this platform ({}), please coerce deadlines manually.",
-                self.max_priority
-            );
-        }
+    /// Deadline-monotonic priority assignment, done separately for every core: tasks with the
+    /// shortest deadline get the most urgent level and tasks with equal deadlines share a level.
+    /// Cores with more distinct deadlines than priority levels get adjacent deadlines merged into
+    /// shared levels, see [`merge`].
+    pub fn analyze(&self, app: &mut App) -> syn::Result<()> {
+        let mut cores: Vec<u32> = app.tasks.iter().map(|t| t.core).collect();
+        cores.sort_unstable();
+        cores.dedup();
+
+        for core in cores {
+            // a task of this core without deadline, its priority is chosen by the user
+            let undated = app
+                .tasks
+                .iter()
+                .find(|t| t.core == core && t.deadline.is_none())
+                .map(|t| t.task_struct.ident.clone());
+            let mut tasks: Vec<_> = app
+                .tasks
+                .iter_mut()
+                .filter(|t| t.core == core && t.deadline.is_some())
+                .collect();
+            tasks.sort_by_key(|t| t.deadline);
 
-        for t in app.tasks.iter_mut() {
-            match t.deadline {
-                None => {}
-                Some(v) => {
-                    let pos = deadlines.iter().position(|d| *d == v).unwrap();
-                    t.deadline = Some(pos as u32 + 1);
+            // plain numbers and durations can't be compared
+            if let (Some(first), Some(last)) = (tasks.first(), tasks.last())
+                && first.deadline.map(Time::is_duration) != last.deadline.map(Time::is_duration)
+            {
+                return Err(syn::Error::new(
+                    last.deadline_span,
+                    format!(
+                        "The deadline of task `{}` is a duration while the deadline of task `{}` on the same core is a plain number, use the same kind of deadline for every task of core {core}.",
+                        last.task_struct.ident, first.task_struct.ident
+                    ),
+                ));
+            }
+
+            let mut deadlines: Vec<_> = tasks.iter().map(|t| t.deadline).collect();
+            deadlines.dedup();
+            let group_of = |task: &RticTask| {
+                deadlines
+                    .iter()
+                    .position(|d| *d == task.deadline)
+                    .unwrap_or_default()
+            };
+
+            // level of every deadline group, 0 being the least urgent
+            let group_level = if deadlines.len() > self.max_priority as usize {
+                let mut groups = vec![Vec::new(); deadlines.len()];
+                for task in tasks.iter() {
+                    groups[group_of(task)].push(self.timing(core, task, undated.as_ref())?);
                 }
+                merge::merge_groups(&groups, self.max_priority as usize).ok_or_else(|| {
+                    syn::Error::new(
+                        tasks[tasks.len() - 1].deadline_span,
+                        format!(
+                            "Core {core} has {} distinct deadlines but only {} priority levels are available on this platform, and no feasible way to merge them into shared levels was found. Reduce the `wcet` of some tasks or relax their deadlines.",
+                            deadlines.len(),
+                            self.max_priority
+                        ),
+                    )
+                })?
+            } else {
+                (0..deadlines.len()).rev().collect()
+            };
+            let levels = group_level.iter().max().map_or(0, |max| max + 1);
+
+            for task in tasks {
+                let level = group_level[group_of(task)];
+                let priority = match self.most_urgent {
+                    MostUrgent::Highest => level + 1,
+                    MostUrgent::Lowest => levels - level,
+                };
+                task.set_priority(priority as u16);
             }
         }
+        Ok(())
+    }
+
+    /// Timing of a task of `core`, needed to merge its deadline with others
+    fn timing(
+        &self,
+        core: u32,
+        task: &RticTask,
+        undated: Option<&syn::Ident>,
+    ) -> syn::Result<merge::TaskTiming> {
+        let error = |reason: String| {
+            syn::Error::new(
+                task.deadline_span,
+                format!(
+                    "Core {core} has more distinct deadlines than the {} priority levels available on this platform, so they have to be merged, but {reason}",
+                    self.max_priority
+                ),
+            )
+        };
+        if let Some(undated) = undated {
+            return Err(error(format!(
+                "task `{undated}` has no deadline. Give every task of core {core} a deadline."
+            )));
+        }
+        let (Some(deadline), Some(wcet)) = (task.deadline, task.wcet) else {
+            return Err(error(format!(
+                "task `{}` has no `wcet`. Give every task of core {core} a `wcet`.",
+                task.task_struct.ident
+            )));
+        };
+        Ok(merge::TaskTiming {
+            deadline: deadline.value(),
+            wcet: wcet.value(),
+            period: task.period.unwrap_or(deadline).value(),
+            shared: task.shared.clone(),
+        })
     }
 }
@@ -1,43 +1,137 @@
-use rticx_core::parse_utils::RticAttr;
-use syn::{Expr, ItemStruct, Lit};
+use proc_macro2::{Ident, Span};
+use rticx_core::parse_utils::{RticAttr, Time, parse_time};
+use syn::{Expr, ItemStruct, Lit, parse_quote, spanned::Spanned};
+
+/// Arguments a deadline can be given with, and the number of nanoseconds in their unit.
+/// `deadline` itself takes either a plain number or a duration such as `500.us()`.
+const DEADLINE_ARGS: [(&str, Option<u64>); 5] = [
+    ("deadline", None),
+    ("deadline_ns", Some(1)),
+    ("deadline_us", Some(1_000)),
+    ("deadline_ms", Some(1_000_000)),
+    ("deadline_s", Some(1_000_000_000)),
+];
 
 #[derive(Debug)]
 pub struct RticTask {
     pub params: RticAttr,
     pub attr_idx: usize,
     pub task_struct: ItemStruct,
-    pub deadline: Option<u32>, // explicit deadline
+    /// Core the task runs on, `0` unless explicitly set
+    pub core: u32,
+    /// Explicit deadline, set by one of the `deadline*` arguments
+    pub deadline: Option<Time>,
+    /// Span of the deadline argument, used for error reporting
+    pub deadline_span: Span,
+    /// Optional worst case execution time, in the same kind of unit as the deadline
+    pub wcet: Option<Time>,
+    /// Optional period or minimum inter-arrival time, the deadline is used if not given
+    pub period: Option<Time>,
+    /// Resources used by the task, used to compute blocking when deadlines are merged
+    pub shared: Vec<Ident>,
+    /// Priority computed from the deadline during analysis
+    pub priority: Option<u16>,
 }
 
 impl RticTask {
     pub fn from_struct((task_struct, attr_idx): (ItemStruct, usize)) -> syn::Result<Self> {
         let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
+        let task_name = &task_struct.ident;
+
+        let core = match params.elements.get("core") {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => int.base10_parse()?,
+            _ => 0,
+        };
+
+        let mut deadline = None;
+        let mut deadline_span = task_struct.attrs[attr_idx].span();
+        for (arg, unit) in DEADLINE_ARGS {
+            let Some(expr) = params.elements.get(arg) else {
+                continue;
+            };
+            if deadline.is_some() {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    format!("Task `{task_name}` has more than one deadline argument."),
+                ));
+            }
+            deadline = Some(parse_time(arg, expr, unit)?);
+            deadline_span = expr.span();
+        }
+
+        let timing = |arg: &str| -> syn::Result<Option<Time>> {
+            let Some(expr) = params.elements.get(arg) else {
+                return Ok(None);
+            };
+            let time = parse_time(arg, expr, None)?;
+            if deadline.is_some_and(|d| d.is_duration() != time.is_duration()) {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    format!(
+                        "`{arg}` of task `{task_name}` must be given in the same kind of unit as its deadline, either both durations or both plain numbers."
+                    ),
+                ));
+            }
+            Ok(Some(time))
+        };
+        let wcet = timing("wcet")?;
+        let period = timing("period")?;
 
-        let deadline = if let Some(Expr::Lit(syn::ExprLit {
-            lit: Lit::Int(int), ..
-        })) = params.elements.get("deadline")
-        {
-            // deadline explicitly assigned by the user
-            int.base10_parse().ok()
-        } else {
-            None
+        // `&resource` reads a `#[readonly]` resource without locking it, it never blocks
+        let shared = match params.elements.get("shared") {
+            Some(Expr::Array(arr)) => arr
+                .elems
+                .iter()
+                .filter(|item| !matches!(item, Expr::Reference(_)))
+                .filter_map(shared_resource)
+                .collect(),
+            _ => Vec::new(),
         };
 
-        if let Some(Expr::Lit(syn::ExprLit {
-            lit: Lit::Int(_int),
-            ..
-        })) = params.elements.get("priority")
-        {
-            panic!(
-                "'priority' found, please use 'deadlines' only or compile with --no-default-features."
-            )
+        if let (Some(_), Some(priority)) = (deadline, params.elements.get("priority")) {
+            return Err(syn::Error::new(
+                priority.span(),
+                format!(
+                    "Task `{task_name}` has both a deadline and a `priority`, use only one of them."
+                ),
+            ));
         }
 
         Ok(Self {
             params,
             attr_idx,
             task_struct,
+            core,
             deadline,
+            deadline_span,
+            wcet,
+            period,
+            shared,
+            priority: None,
         })
     }
+
+    /// Replace the deadline arguments of the task attribute with `priority = N`
+    pub fn set_priority(&mut self, priority: u16) {
+        for (arg, _) in DEADLINE_ARGS {
+            self.params.elements.remove(arg);
+        }
+        let expr: syn::Expr = parse_quote! { #priority };
+        let _ = self.params.elements.insert("priority".into(), expr);
+        self.priority = Some(priority);
+    }
+}
+
+/// Resource containing the `shared` item, a part like `channels[0]` or `motor.state` counts as its
+/// whole resource, which over-estimates its blocking. Malformed items are left to the core pass to
+/// report.
+fn shared_resource(item: &Expr) -> Option<Ident> {
+    match item {
+        Expr::Path(path) => path.path.get_ident().cloned(),
+        Expr::Field(field) => shared_resource(&field.base),
+        Expr::Index(index) => shared_resource(&index.expr),
+        _ => None,
+    }
 }
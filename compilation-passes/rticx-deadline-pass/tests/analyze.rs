@@ -4,11 +4,11 @@
 //! `App` (positive scenarios) or assert the expected `syn::Error` (negative scenarios).
 
 use quote::quote;
-use rticx_deadline_pass::deadline_pass::App;
+use rticx_deadline_pass::deadline_pass::{App, MostUrgent};
 
 mod common;
 
-use common::{analyze, multi_core_args, single_core_args};
+use common::{analyze, assert_err_contains, multi_core_args, single_core_args};
 
 // ---------------------------------------------------------------------------
 // Positive scenarios: deadline-to-priority conversion
@@ -24,8 +24,8 @@ fn analyze_single_task_deadline_becomes_priority_1() {
     let app = analyze(args, items).expect("analyze succeeds");
 
     assert_eq!(app.tasks.len(), 1);
-    // After analyze, deadline is converted to priority (1-based index)
-    assert_eq!(app.tasks[0].deadline, Some(1));
+    // After analyze, the deadline is converted to a priority (1-based index)
+    assert_eq!(app.tasks[0].priority, Some(1));
 }
 
 #[test]
@@ -44,9 +44,9 @@ fn analyze_multiple_tasks_sorted_by_deadline() {
     let app = analyze(args, items).expect("analyze succeeds");
 
     assert_eq!(app.tasks.len(), 3);
-    // Shorter deadline = higher priority (lower number)
-    // 10 -> priority 1, 50 -> priority 2, 100 -> priority 3
-    let mut deadlines: Vec<u32> = app.tasks.iter().map(|t| t.deadline.unwrap()).collect();
+    // Shorter deadline = higher priority
+    // 10 -> priority 3, 50 -> priority 2, 100 -> priority 1
+    let mut deadlines: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
     deadlines.sort();
     assert_eq!(deadlines, vec![1, 2, 3]);
 }
@@ -74,6 +74,7 @@ fn analyze_tasks_without_deadline_unchanged() {
         .find(|t| t.task_struct.ident == "TaskWithoutDeadline")
         .unwrap();
     assert_eq!(without.deadline, None);
+    assert_eq!(without.priority, None);
 
     // Tasks with deadline get priority assigned
     let with10 = app
@@ -86,8 +87,8 @@ fn analyze_tasks_without_deadline_unchanged() {
         .iter()
         .find(|t| t.task_struct.ident == "AnotherWithDeadline")
         .unwrap();
-    assert_eq!(with10.deadline, Some(1));
-    assert_eq!(with20.deadline, Some(2));
+    assert_eq!(with10.priority, Some(2));
+    assert_eq!(with20.priority, Some(1));
 }
 
 #[test]
@@ -103,10 +104,10 @@ fn analyze_sw_tasks_with_deadlines() {
     let app = analyze(args, items).expect("analyze succeeds");
 
     assert_eq!(app.tasks.len(), 2);
-    // Shorter deadline = higher priority (lower number)
-    // 5 -> priority 1, 15 -> priority 2
-    let deadlines: Vec<u32> = app.tasks.iter().map(|t| t.deadline.unwrap()).collect();
-    assert_eq!(deadlines, vec![1, 2]);
+    // Shorter deadline = higher priority
+    // 5 -> priority 2, 15 -> priority 1
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1]);
 }
 
 #[test]
@@ -126,9 +127,9 @@ fn analyze_mixed_tasks_sw_tasks_and_hw_tasks() {
 
     assert_eq!(app.tasks.len(), 3);
     // All tasks with deadlines should get priorities assigned
-    let deadlines: Vec<Option<u32>> = app.tasks.iter().map(|t| t.deadline).collect();
+    let priorities: Vec<Option<u16>> = app.tasks.iter().map(|t| t.priority).collect();
     // All should be Some (have priorities assigned)
-    assert!(deadlines.iter().all(|d| d.is_some()));
+    assert!(priorities.iter().all(|p| p.is_some()));
 }
 
 #[test]
@@ -164,8 +165,8 @@ fn analyze_duplicate_deadlines_get_same_priority() {
         .find(|t| t.task_struct.ident == "Task3")
         .unwrap();
 
-    assert_eq!(task1.deadline, task2.deadline);
-    assert_ne!(task1.deadline, task3.deadline);
+    assert_eq!(task1.priority, task2.priority);
+    assert_ne!(task1.priority, task3.priority);
 }
 
 #[test]
@@ -178,7 +179,7 @@ fn analyze_priority_starts_at_1() {
     };
     let app = analyze(args, items).expect("analyze succeeds");
 
-    assert_eq!(app.tasks[0].deadline, Some(1));
+    assert_eq!(app.tasks[0].priority, Some(1));
 }
 
 #[test]
@@ -195,7 +196,7 @@ fn analyze_max_priority_not_exceeded_normal_case() {
     };
     let app = analyze(args, items).expect("analyze succeeds");
 
-    let max_prio = app.tasks.iter().map(|t| t.deadline.unwrap()).max().unwrap();
+    let max_prio = app.tasks.iter().map(|t| t.priority.unwrap()).max().unwrap();
     assert!(max_prio <= 255);
 }
 
@@ -204,7 +205,7 @@ fn analyze_max_priority_not_exceeded_normal_case() {
 // ---------------------------------------------------------------------------
 
 #[test]
-fn analyze_panics_when_more_unique_deadlines_than_max_priority() {
+fn analyze_errors_when_more_unique_deadlines_than_max_priority() {
     let args = single_core_args();
     let items = quote! {
         #[task(deadline = 10)]
@@ -222,17 +223,188 @@ fn analyze_panics_when_more_unique_deadlines_than_max_priority() {
     // Max priority = 2, but we have 3 unique deadlines
     let pass = rticx_deadline_pass::deadline_pass::DeadlineToPriorityPass::new(2);
 
-    // This should panic with the expected message
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        pass.analyze(&mut parsed);
-    }));
-
-    assert!(result.is_err());
-    let err = result.unwrap_err();
-    let empty = "".to_string();
-    let panic_msg = err.downcast_ref::<String>().unwrap_or(&empty);
-    assert!(panic_msg.contains("Exceeded number of priorities"));
-    assert!(panic_msg.contains("2"));
+    assert_err_contains(
+        pass.analyze(&mut parsed),
+        "so they have to be merged, but task `T1` has no `wcet`.",
+    );
+}
+
+/// Parse args + items and run the analysis with `max_priority` levels
+fn analyze_with_levels(items: proc_macro2::TokenStream, max_priority: u16) -> syn::Result<App> {
+    let app_mod = common::app_mod(items);
+    let params = rticx_core::parse_utils::RticAttr::parse_from_tokens(single_core_args())
+        .expect("params parse");
+    let mut parsed = App::parse(&params, app_mod).expect("app parse");
+    rticx_deadline_pass::deadline_pass::DeadlineToPriorityPass::new(max_priority)
+        .analyze(&mut parsed)?;
+    Ok(parsed)
+}
+
+#[test]
+fn analyze_merges_deadlines_into_available_levels() {
+    let items = quote! {
+        #[task(deadline = 10, wcet = 3)]
+        struct T1;
+        #[task(deadline = 20, wcet = 3)]
+        struct T2;
+        #[task(deadline = 40, wcet = 3)]
+        struct T3;
+        #[task(deadline = 80, wcet = 3)]
+        struct T4;
+        #[task(deadline = 160, wcet = 3)]
+        struct T5;
+    };
+    let app = analyze_with_levels(items, 2).expect("analyze succeeds");
+
+    // T1 can't share a level with the others and still meet its deadline
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1, 1, 1, 1]);
+}
+
+#[test]
+fn analyze_merge_accounts_for_srp_blocking() {
+    let items = |shared: proc_macro2::TokenStream| {
+        quote! {
+            #[task(deadline = 10, wcet = 4, shared = [#shared])]
+            struct A;
+            #[task(deadline = 20, wcet = 2)]
+            struct B;
+            #[task(deadline = 100, wcet = 7, shared = [r])]
+            struct C;
+        }
+    };
+
+    // without a shared resource, A preempts C right away
+    let app = analyze_with_levels(items(quote!(q)), 2).expect("analyze succeeds");
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1, 1]);
+
+    // A may be blocked for the whole `wcet` of C, and then misses its deadline
+    assert_err_contains(
+        analyze_with_levels(items(quote!(r)), 2),
+        "no feasible way to merge them into shared levels was found",
+    );
+}
+
+#[test]
+fn analyze_merge_uses_period_for_interference() {
+    let items = |period: u64| {
+        quote! {
+            #[task(deadline = 10, wcet = 4, period = #period)]
+            struct Fast;
+            #[task(deadline = 30, wcet = 5)]
+            struct Medium;
+            #[task(deadline = 100, wcet = 5)]
+            struct Slow;
+        }
+    };
+
+    // Fast released at most once while the others run
+    let app = analyze_with_levels(items(100), 2).expect("analyze succeeds");
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1, 1]);
+
+    // Fast released every 5 units leaves no room for the others
+    assert_err_contains(
+        analyze_with_levels(items(5), 2),
+        "no feasible way to merge them into shared levels was found",
+    );
+}
+
+#[test]
+fn analyze_merge_requires_deadline_on_every_task() {
+    let items = quote! {
+        #[task(deadline = 10, wcet = 1)]
+        struct T1;
+        #[task(deadline = 20, wcet = 1)]
+        struct T2;
+        #[task(priority = 1)]
+        struct Manual;
+    };
+    assert_err_contains(
+        analyze_with_levels(items, 1),
+        "task `Manual` has no deadline. Give every task of core 0 a deadline.",
+    );
+}
+
+#[test]
+fn analyze_max_priority_counted_per_core() {
+    let args = multi_core_args(2);
+    let items = quote! {
+        #[task(deadline = 10, core = 0)]
+        struct T1;
+        #[task(deadline = 20, core = 0)]
+        struct T2;
+        #[task(deadline = 30, core = 1)]
+        struct T3;
+        #[task(deadline = 40, core = 1)]
+        struct T4;
+    };
+
+    let app_mod = common::app_mod(items);
+    let params = rticx_core::parse_utils::RticAttr::parse_from_tokens(args).expect("params parse");
+    let mut parsed = App::parse(&params, app_mod).expect("app parse");
+
+    // 4 unique deadlines overall, but only 2 per core
+    let pass = rticx_deadline_pass::deadline_pass::DeadlineToPriorityPass::new(2);
+    pass.analyze(&mut parsed).expect("analyze succeeds");
+
+    let priorities: Vec<u16> = parsed.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1, 2, 1]);
+}
+
+#[test]
+fn analyze_lowest_level_most_urgent() {
+    let args = single_core_args();
+    let items = quote! {
+        #[task(deadline = 30)]
+        struct T1;
+        #[task(deadline = 10)]
+        struct T2;
+    };
+
+    let app_mod = common::app_mod(items);
+    let params = rticx_core::parse_utils::RticAttr::parse_from_tokens(args).expect("params parse");
+    let mut parsed = App::parse(&params, app_mod).expect("app parse");
+
+    let pass = rticx_deadline_pass::deadline_pass::DeadlineToPriorityPass::new(8)
+        .with_most_urgent(MostUrgent::Lowest);
+    pass.analyze(&mut parsed).expect("analyze succeeds");
+
+    let priorities: Vec<u16> = parsed.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1]);
+}
+
+#[test]
+fn analyze_duration_deadlines_compared_across_units() {
+    let args = single_core_args();
+    let items = quote! {
+        #[task(deadline = 2.ms())]
+        struct Slow;
+        #[task(deadline_us = 500)]
+        struct Fast;
+        #[task(deadline = 1500.us())]
+        struct Medium;
+    };
+    let app = analyze(args, items).expect("analyze succeeds");
+
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![1, 3, 2]);
+}
+
+#[test]
+fn analyze_errors_on_mixed_plain_and_duration_deadlines() {
+    let args = single_core_args();
+    let items = quote! {
+        #[task(deadline = 10)]
+        struct Plain;
+        #[task(deadline_ms = 1)]
+        struct Duration;
+    };
+    assert_err_contains(
+        analyze(args, items),
+        "The deadline of task `Duration` is a duration while the deadline of task `Plain` on the same core is a plain number",
+    );
 }
 
 #[test]
@@ -252,9 +424,9 @@ fn analyze_max_priority_edge_case_equal() {
 
     // Max priority = 2, 2 unique deadlines = OK
     let pass = rticx_deadline_pass::deadline_pass::DeadlineToPriorityPass::new(2);
-    pass.analyze(&mut parsed);
+    pass.analyze(&mut parsed).expect("analyze succeeds");
 
-    let priorities: Vec<u32> = parsed.tasks.iter().map(|t| t.deadline.unwrap()).collect();
+    let priorities: Vec<u16> = parsed.tasks.iter().map(|t| t.priority.unwrap()).collect();
     assert_eq!(priorities.len(), 2);
 }
 
@@ -283,7 +455,7 @@ fn analyze_deadline_zero_is_valid() {
     let app = analyze(args, items).expect("analyze succeeds");
 
     // Deadline 0 should be treated like any other deadline
-    assert_eq!(app.tasks[0].deadline, Some(1));
+    assert_eq!(app.tasks[0].priority, Some(1));
 }
 
 #[test]
@@ -298,9 +470,10 @@ fn analyze_large_deadline_values() {
     };
     let app = analyze(args, items).expect("analyze succeeds");
 
-    let priorities: Vec<u32> = app.tasks.iter().map(|t| t.deadline.unwrap()).collect();
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
     assert_eq!(priorities.len(), 2);
-    // Smaller deadline = higher priority (lower number)
+    // Smaller deadline = higher priority
+    assert_eq!(app.tasks[1].priority, Some(2));
     assert!(priorities.contains(&1));
     assert!(priorities.contains(&2));
 }
@@ -341,12 +514,9 @@ fn analyze_multi_core_with_deadlines() {
     let app = analyze(args, items).expect("analyze succeeds");
 
     assert_eq!(app.tasks.len(), 3);
-    // All tasks with deadlines should get priorities
-    for task in &app.tasks {
-        assert!(
-            task.deadline.is_some(),
-            "Task {:?} should have deadline",
-            task.task_struct.ident
-        );
-    }
+    // Priorities are assigned per core
+    // Core 0: deadlines 10, 20 -> priorities 2, 1
+    // Core 1: deadline 5 -> priority 1
+    let priorities: Vec<u16> = app.tasks.iter().map(|t| t.priority.unwrap()).collect();
+    assert_eq!(priorities, vec![2, 1, 1]);
 }
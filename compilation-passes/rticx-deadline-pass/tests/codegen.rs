@@ -3,7 +3,7 @@
 //! These run the full `DeadlineToPriorityPass::run_pass` pipeline (parse + analyze + codegen)
 //! and verify that the generated `ItemMod` contains the expected sections. Because `RticAttr`
 //! stores attribute elements in a `HashMap`, the emitted `#[task(...)]` element ordering is
-//! non-deterministic, so assertions use individual element fragments (e.g. `priority = 1u32`)
+//! non-deterministic, so assertions use individual element fragments (e.g. `priority = 1u16`)
 //! rather than the whole attribute.
 
 use proc_macro2::TokenStream;
@@ -42,12 +42,12 @@ fn codegen_single_core_injects_priority() {
     assert_section_present(&generated, quote! { struct Bar ; }, "passthrough struct");
     // the task struct is re-emitted (its old attr removed, new attr prepended)
     assert_section_present(&generated, quote! { struct T ; }, "task struct");
-    // the injected priority assignment (u32 suffix comes from `parse_quote!(#deadline)`)
+    // the injected priority assignment (u16 suffix comes from `parse_quote!(#priority)`)
     assert_section_present(&generated, quote! { task }, "task attribute name");
     assert_section_present(
         &generated,
-        quote! { priority = 1u32 },
-        "injected priority = 1u32",
+        quote! { priority = 1u16 },
+        "injected priority = 1u16",
     );
     // deadline attribute should be removed
     assert!(!generated.contains("deadline"));
@@ -81,19 +81,19 @@ fn codegen_single_core_multiple_tasks_different_priorities() {
     // Shortest deadline = highest priority (1)
     assert_section_present(
         &generated,
-        quote! { priority = 1u32 },
+        quote! { priority = 1u16 },
         "priority 1 for shortest deadline",
     );
     // Longest deadline = lowest priority (3)
     assert_section_present(
         &generated,
-        quote! { priority = 3u32 },
+        quote! { priority = 3u16 },
         "priority 3 for longest deadline",
     );
     // Middle deadline = middle priority (2)
     assert_section_present(
         &generated,
-        quote! { priority = 2u32 },
+        quote! { priority = 2u16 },
         "priority 2 for middle deadline",
     );
 
@@ -118,7 +118,7 @@ fn codegen_single_core_preserves_other_attributes() {
     assert_section_present(&generated, quote! { shared = [x] }, "shared preserved");
     assert_section_present(&generated, quote! { core = 0 }, "core preserved");
     // deadline removed, priority inserted
-    assert_section_present(&generated, quote! { priority = 1u32 }, "priority injected");
+    assert_section_present(&generated, quote! { priority = 1u16 }, "priority injected");
     assert!(!generated.contains("deadline"));
     mod_app_shell(&generated);
 }
@@ -137,7 +137,7 @@ fn codegen_single_core_sw_task_converted() {
     assert_section_present(&generated, quote! { sw_task }, "sw_task attribute name");
     assert_section_present(
         &generated,
-        quote! { priority = 1u32 },
+        quote! { priority = 1u16 },
         "priority injected (deadline 20 -> 1)",
     );
     assert_section_present(&generated, quote! { shared = [x] }, "shared preserved");
@@ -177,8 +177,8 @@ fn codegen_multi_core_injects_priority_per_core() {
     // Each core's tasks get priorities based on their deadlines
     // Core 0: deadlines 10, 20 -> priorities 1, 2
     // Core 1: deadline 5 -> priority 1
-    assert_section_present(&generated, quote! { priority = 1u32 }, "priority 1 exists");
-    assert_section_present(&generated, quote! { priority = 2u32 }, "priority 2 exists");
+    assert_section_present(&generated, quote! { priority = 1u16 }, "priority 1 exists");
+    assert_section_present(&generated, quote! { priority = 2u16 }, "priority 2 exists");
     assert!(!generated.contains("deadline"));
     mod_app_shell(&generated);
 }
@@ -218,19 +218,45 @@ fn codegen_task_without_deadline_preserved() {
     );
     assert_section_present(
         &generated,
-        quote! { priority = 1u32 },
+        quote! { priority = 1u16 },
         "new priority injected",
     );
     mod_app_shell(&generated);
 }
 
+#[test]
+fn codegen_duration_deadlines_removed() {
+    let generated = run_pass(
+        single_core_args(),
+        app_mod(quote! {
+            #[task(deadline_us = 100)]
+            struct Fast;
+
+            #[task(deadline = 1.ms())]
+            struct Slow;
+        }),
+    );
+
+    assert_section_present(
+        &generated,
+        quote! { # [task (priority = 2u16)] struct Fast ; },
+        "shortest deadline",
+    );
+    assert_section_present(
+        &generated,
+        quote! { # [task (priority = 1u16)] struct Slow ; },
+        "longest deadline",
+    );
+    assert!(!generated.contains("deadline"));
+}
+
 #[test]
 fn codegen_plain_structs_passed_through() {
     let generated = run_pass(
         single_core_args(),
         app_mod(quote! {
             struct Plain1;
-            struct Plain2 { field: u32 }
+            struct Plain2 { field: u16 }
             #[task(deadline = 10)]
             struct Task1;
         }),
@@ -240,7 +266,7 @@ fn codegen_plain_structs_passed_through() {
     // Note: the generated output for struct with fields doesn't have trailing comma in the expected format
     assert_section_present(
         &generated,
-        quote! { struct Plain2 { field : u32 } },
+        quote! { struct Plain2 { field : u16 } },
         "plain struct 2 with field",
     );
     assert_section_present(&generated, quote! { struct Task1 ; }, "task struct");
@@ -268,7 +294,7 @@ fn codegen_shared_resources_passed_through() {
         "shared struct",
     );
     assert_section_present(&generated, quote! { struct T ; }, "task struct");
-    assert_section_present(&generated, quote! { priority = 1u32 }, "priority injected");
+    assert_section_present(&generated, quote! { priority = 1u16 }, "priority injected");
     mod_app_shell(&generated);
 }
 
@@ -34,7 +34,7 @@ pub fn analyze(args: TokenStream, items: TokenStream) -> syn::Result<App> {
     let params = RticAttr::parse_from_tokens(args).expect("params parse");
     let mut parsed = App::parse(&params, app_mod).expect("app parse");
     let pass = DeadlineToPriorityPass::new(255); // High max_priority for tests
-    pass.analyze(&mut parsed);
+    pass.analyze(&mut parsed)?;
     Ok(parsed)
 }
 
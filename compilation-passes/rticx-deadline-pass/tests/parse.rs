@@ -2,12 +2,12 @@
 
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
-use rticx_core::parse_utils::RticAttr;
+use rticx_core::parse_utils::{RticAttr, Time};
 use rticx_deadline_pass::deadline_pass::parse::App;
 
 mod common;
 
-use common::{app_mod, single_core_args};
+use common::{app_mod, assert_err_contains, single_core_args};
 
 /// Builds a params `RticAttr` from a raw args tokenstream
 fn params(args: &TokenStream) -> RticAttr {
@@ -24,7 +24,7 @@ fn parse_task_with_deadline() {
     let parsed = App::parse(&params(&args), app).expect("parse");
 
     assert_eq!(parsed.tasks.len(), 1);
-    assert_eq!(parsed.tasks[0].deadline, Some(10));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(10)));
     assert_eq!(parsed.tasks[0].task_struct.ident.to_string(), "Task1");
 }
 
@@ -38,7 +38,7 @@ fn parse_sw_task_with_deadline() {
     let parsed = App::parse(&params(&args), app).expect("parse");
 
     assert_eq!(parsed.tasks.len(), 1);
-    assert_eq!(parsed.tasks[0].deadline, Some(5));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(5)));
     assert_eq!(parsed.tasks[0].task_struct.ident.to_string(), "SwTask1");
 }
 
@@ -65,7 +65,7 @@ fn parse_task_with_deadline_and_other_attrs() {
     let parsed = App::parse(&params(&args), app).expect("parse");
 
     assert_eq!(parsed.tasks.len(), 1);
-    assert_eq!(parsed.tasks[0].deadline, Some(100));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(100)));
 
     // Verify other attributes are preserved in params
     let params = &parsed.tasks[0].params;
@@ -90,8 +90,11 @@ fn parse_multiple_tasks_with_different_deadlines() {
     let parsed = App::parse(&params(&args), app).expect("parse");
 
     assert_eq!(parsed.tasks.len(), 3);
-    let deadlines: Vec<u32> = parsed.tasks.iter().map(|t| t.deadline.unwrap()).collect();
-    assert_eq!(deadlines, vec![10, 5, 20]);
+    let deadlines: Vec<Time> = parsed.tasks.iter().map(|t| t.deadline.unwrap()).collect();
+    assert_eq!(
+        deadlines,
+        vec![Time::Plain(10), Time::Plain(5), Time::Plain(20)]
+    );
 }
 
 #[test]
@@ -104,13 +107,47 @@ fn parse_sw_task_with_all_attrs() {
     let parsed = App::parse(&params(&args), app).expect("parse");
 
     assert_eq!(parsed.tasks.len(), 1);
-    assert_eq!(parsed.tasks[0].deadline, Some(15));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(15)));
     let params = &parsed.tasks[0].params;
     assert!(params.elements.contains_key("shared"));
     assert!(params.elements.contains_key("core"));
     assert!(params.elements.contains_key("spawn_by"));
 }
 
+#[test]
+fn parse_task_skips_readonly_resources() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10, shared = [x, &config])]
+        struct Task1;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    let shared: Vec<String> = parsed.tasks[0]
+        .shared
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    assert_eq!(shared, ["x"]);
+}
+
+#[test]
+fn parse_task_counts_resource_parts_as_their_resource() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10, shared = [channels[0], motor.state])]
+        struct Task1;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    let shared: Vec<String> = parsed.tasks[0]
+        .shared
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    assert_eq!(shared, ["channels", "motor"]);
+}
+
 #[test]
 fn parse_preserves_plain_structs() {
     let args = single_core_args();
@@ -154,7 +191,7 @@ fn parse_task_deadline_zero() {
     });
     let parsed = App::parse(&params(&args), app).expect("parse");
 
-    assert_eq!(parsed.tasks[0].deadline, Some(0));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(0)));
 }
 
 #[test]
@@ -166,7 +203,7 @@ fn parse_task_large_deadline() {
     });
     let parsed = App::parse(&params(&args), app).expect("parse");
 
-    assert_eq!(parsed.tasks[0].deadline, Some(u32::MAX));
+    assert_eq!(parsed.tasks[0].deadline, Some(Time::Plain(u32::MAX as u64)));
 }
 
 #[test]
@@ -181,3 +218,135 @@ fn parse_sw_task_recognized_as_task() {
     assert_eq!(parsed.tasks.len(), 1);
     assert_eq!(parsed.tasks[0].task_struct.ident.to_string(), "SwTask");
 }
+
+#[test]
+fn parse_task_duration_deadlines() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 500.us())]
+        struct T1;
+        #[task(deadline = 2.millis())]
+        struct T2;
+        #[task(deadline_ms = 3)]
+        struct T3;
+        #[task(deadline_s = 1)]
+        struct T4;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    let deadlines: Vec<Time> = parsed.tasks.iter().map(|t| t.deadline.unwrap()).collect();
+    assert_eq!(
+        deadlines,
+        vec![
+            Time::Nanos(500_000),
+            Time::Nanos(2_000_000),
+            Time::Nanos(3_000_000),
+            Time::Nanos(1_000_000_000)
+        ]
+    );
+}
+
+#[test]
+fn parse_task_core_defaults_zero() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 1)]
+        struct T0;
+        #[task(deadline = 1, core = 1)]
+        struct T1;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    assert_eq!(parsed.tasks[0].core, 0);
+    assert_eq!(parsed.tasks[1].core, 1);
+}
+
+#[test]
+fn parse_task_deadline_and_priority_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10, priority = 2)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "Task `T` has both a deadline and a `priority`, use only one of them.",
+    );
+}
+
+#[test]
+fn parse_task_priority_without_deadline_allowed() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(priority = 2)]
+        struct T;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    assert_eq!(parsed.tasks[0].deadline, None);
+}
+
+#[test]
+fn parse_task_two_deadlines_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10, deadline_us = 10)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "Task `T` has more than one deadline argument.",
+    );
+}
+
+#[test]
+fn parse_task_unknown_time_unit_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10.minutes())]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "Unknown time unit `minutes`, expected one of `ns`, `us`, `ms` or `s`.",
+    );
+}
+
+#[test]
+fn parse_task_non_literal_deadline_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = DEADLINE)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "`deadline` must be an integer literal or a duration",
+    );
+}
+
+#[test]
+fn parse_task_timing_arguments() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 2.ms(), wcet = 300.us(), period = 5.ms(), shared = [a, b])]
+        struct T;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+    let task = &parsed.tasks[0];
+    assert_eq!(task.wcet, Some(Time::Nanos(300_000)));
+    assert_eq!(task.period, Some(Time::Nanos(5_000_000)));
+    assert_eq!(task.shared, vec!["a", "b"]);
+}
+
+#[test]
+fn parse_task_timing_unit_mismatch_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(deadline = 10, wcet = 300.us())]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "`wcet` of task `T` must be given in the same kind of unit as its deadline",
+    );
+}
@@ -0,0 +1,332 @@
+//! Runtime support of task monitoring, to be re-exported by distributions.
+//!
+//! Times are cycles of a free running 32-bit counter provided by the distribution, compared with
+//! wrapping arithmetic so the counter may overflow. A single job must take less than `2^32`
+//! cycles to be measured correctly.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Which limit of a task a job did not respect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The job executed for longer than the `budget` of the task
+    Budget,
+    /// The `exec` function of the job ran for longer than the `deadline` of the task, preemptions
+    /// included. The job then missed its deadline, but a job can also miss it while waiting for
+    /// `exec` to start, which is not detected.
+    Elapsed,
+}
+
+/// A job that did not respect a limit of its task, given to the `#[overrun]` handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    /// Name of the task
+    pub task: &'static str,
+    pub kind: ViolationKind,
+    /// Observed execution time (for a budget) or elapsed time (for a deadline), in cycles
+    pub observed: u32,
+    /// The budget or deadline of the task, in cycles
+    pub limit: u32,
+}
+
+/// Limits and observed timing of a task.
+///
+/// Only the jobs of the task write to it. The instances of a task type share its monitor and may
+/// preempt each other, so the counters are updated with `fetch_add` and `fetch_max` where the
+/// target supports them. Elsewhere, a job preempted by another instance while recording may lose
+/// the update of that instance.
+pub struct Monitor {
+    task: &'static str,
+    budget: Option<u32>,
+    deadline: Option<u32>,
+    jobs: AtomicU32,
+    worst_execution: AtomicU32,
+    worst_elapsed: AtomicU32,
+    budget_overruns: AtomicU32,
+    elapsed_overruns: AtomicU32,
+}
+
+impl Monitor {
+    pub const fn new(task: &'static str, budget: Option<u32>, deadline: Option<u32>) -> Self {
+        Self {
+            task,
+            budget,
+            deadline,
+            jobs: AtomicU32::new(0),
+            worst_execution: AtomicU32::new(0),
+            worst_elapsed: AtomicU32::new(0),
+            budget_overruns: AtomicU32::new(0),
+            elapsed_overruns: AtomicU32::new(0),
+        }
+    }
+
+    /// Name of the task
+    pub fn task(&self) -> &'static str {
+        self.task
+    }
+
+    /// Execution-time budget of the task, in cycles
+    pub fn budget(&self) -> Option<u32> {
+        self.budget
+    }
+
+    /// Relative deadline of the task, in cycles
+    pub fn deadline(&self) -> Option<u32> {
+        self.deadline
+    }
+
+    /// Number of finished jobs
+    pub fn jobs(&self) -> u32 {
+        self.jobs.load(Ordering::Relaxed)
+    }
+
+    /// Longest execution time of a job, without the time spent in the tasks preempting it
+    pub fn worst_execution(&self) -> u32 {
+        self.worst_execution.load(Ordering::Relaxed)
+    }
+
+    /// Longest time between the start and the end of the `exec` function of a job, preemptions
+    /// included. This is not a response time: the time a job waits for `exec` to start, in a
+    /// queue or behind other interrupts, is not counted.
+    pub fn worst_elapsed(&self) -> u32 {
+        self.worst_elapsed.load(Ordering::Relaxed)
+    }
+
+    /// Number of jobs that executed for longer than the budget
+    pub fn budget_overruns(&self) -> u32 {
+        self.budget_overruns.load(Ordering::Relaxed)
+    }
+
+    /// Number of jobs whose `exec` function ran for longer than the deadline, see
+    /// [ViolationKind::Elapsed]
+    pub fn elapsed_overruns(&self) -> u32 {
+        self.elapsed_overruns.load(Ordering::Relaxed)
+    }
+
+    /// Record a finished job, returns the limits it did not respect
+    fn record(&self, execution: u32, elapsed: u32) -> [Option<Violation>; 2] {
+        increment(&self.jobs);
+        raise(&self.worst_execution, execution);
+        raise(&self.worst_elapsed, elapsed);
+
+        let violation = |kind, observed, limit: Option<u32>, counter| {
+            let limit = limit.filter(|limit| observed > *limit)?;
+            increment(counter);
+            Some(Violation {
+                task: self.task,
+                kind,
+                observed,
+                limit,
+            })
+        };
+        [
+            violation(
+                ViolationKind::Budget,
+                execution,
+                self.budget,
+                &self.budget_overruns,
+            ),
+            violation(
+                ViolationKind::Elapsed,
+                elapsed,
+                self.deadline,
+                &self.elapsed_overruns,
+            ),
+        ]
+    }
+}
+
+/// Add one to `counter`
+#[cfg(target_has_atomic = "32")]
+fn increment(counter: &AtomicU32) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(not(target_has_atomic = "32"))]
+fn increment(counter: &AtomicU32) {
+    counter.store(
+        counter.load(Ordering::Relaxed).wrapping_add(1),
+        Ordering::Relaxed,
+    );
+}
+
+/// Set `worst` to `value` if it is larger
+#[cfg(target_has_atomic = "32")]
+fn raise(worst: &AtomicU32, value: u32) {
+    worst.fetch_max(value, Ordering::Relaxed);
+}
+
+#[cfg(not(target_has_atomic = "32"))]
+fn raise(worst: &AtomicU32, value: u32) {
+    worst.store(worst.load(Ordering::Relaxed).max(value), Ordering::Relaxed);
+}
+
+/// Cycles executed by the finished jobs of a core, used to remove the preemptions from the
+/// execution time of a job
+pub struct CoreClock {
+    executed: AtomicU32,
+}
+
+impl CoreClock {
+    pub const fn new() -> Self {
+        Self {
+            executed: AtomicU32::new(0),
+        }
+    }
+}
+
+impl Default for CoreClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A running job of a monitored task, finished when dropped so early returns from `exec` are
+/// accounted for.
+///
+/// Preemptions are nested, so the jobs finishing while a job runs are exactly the ones preempting
+/// it, and their execution times are removed from its own.
+pub struct Job {
+    monitor: &'static Monitor,
+    clock: &'static CoreClock,
+    now: fn() -> u32,
+    on_violation: Option<fn(Violation)>,
+    critical_section: fn(&mut dyn FnMut()),
+    start: u32,
+    executed_at_start: u32,
+}
+
+impl Job {
+    pub fn start(
+        monitor: &'static Monitor,
+        clock: &'static CoreClock,
+        now: fn() -> u32,
+        on_violation: Option<fn(Violation)>,
+        critical_section: fn(&mut dyn FnMut()),
+    ) -> Self {
+        let (mut start, mut executed_at_start) = (0, 0);
+        critical_section(&mut || {
+            start = now();
+            executed_at_start = clock.executed.load(Ordering::Relaxed);
+        });
+        Self {
+            monitor,
+            clock,
+            now,
+            on_violation,
+            critical_section,
+            start,
+            executed_at_start,
+        }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        let (mut elapsed, mut execution) = (0, 0);
+        (self.critical_section)(&mut || {
+            elapsed = (self.now)().wrapping_sub(self.start);
+            let executed = self.clock.executed.load(Ordering::Relaxed);
+            execution = elapsed.wrapping_sub(executed.wrapping_sub(self.executed_at_start));
+            self.clock
+                .executed
+                .store(executed.wrapping_add(execution), Ordering::Relaxed);
+        });
+
+        let violations = self.monitor.record(execution, elapsed);
+        if let Some(on_violation) = self.on_violation {
+            violations.into_iter().flatten().for_each(on_violation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_critical_section(f: &mut dyn FnMut()) {
+        f()
+    }
+
+    #[test]
+    fn preemptions_are_removed_from_execution_time() {
+        static NOW: AtomicU32 = AtomicU32::new(0);
+        static CLOCK: CoreClock = CoreClock::new();
+        static LOW: Monitor = Monitor::new("Low", None, None);
+        static MID: Monitor = Monitor::new("Mid", None, None);
+        static HIGH: Monitor = Monitor::new("High", None, None);
+        fn now() -> u32 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let job = |monitor| Job::start(monitor, &CLOCK, now, None, no_critical_section);
+        let at = |time| NOW.store(time, Ordering::Relaxed);
+
+        let low = job(&LOW);
+        at(10);
+        let mid = job(&MID);
+        at(15);
+        let high = job(&HIGH);
+        at(20);
+        drop(high);
+        at(30);
+        drop(mid);
+        at(50);
+        drop(low);
+
+        assert_eq!((HIGH.worst_execution(), HIGH.worst_elapsed()), (5, 5));
+        assert_eq!((MID.worst_execution(), MID.worst_elapsed()), (15, 20));
+        assert_eq!((LOW.worst_execution(), LOW.worst_elapsed()), (30, 50));
+        assert_eq!(LOW.jobs(), 1);
+    }
+
+    #[test]
+    fn violations_are_counted_and_reported() {
+        static NOW: AtomicU32 = AtomicU32::new(0);
+        static CLOCK: CoreClock = CoreClock::new();
+        static TASK: Monitor = Monitor::new("Task", Some(10), Some(20));
+        static REPORTED: AtomicU32 = AtomicU32::new(0);
+        fn now() -> u32 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn on_violation(violation: Violation) {
+            assert_eq!(violation.task, "Task");
+            let expected = match violation.kind {
+                ViolationKind::Budget => (22, 10),
+                ViolationKind::Elapsed => (22, 20),
+            };
+            assert_eq!((violation.observed, violation.limit), expected);
+            REPORTED.fetch_add(1, Ordering::Relaxed);
+        }
+        let job = || Job::start(&TASK, &CLOCK, now, Some(on_violation), no_critical_section);
+
+        // within limits
+        NOW.store(100, Ordering::Relaxed);
+        let first = job();
+        NOW.store(110, Ordering::Relaxed);
+        drop(first);
+
+        // over both limits
+        let second = job();
+        NOW.store(132, Ordering::Relaxed);
+        drop(second);
+
+        assert_eq!(TASK.jobs(), 2);
+        assert_eq!(TASK.budget_overruns(), 1);
+        assert_eq!(TASK.elapsed_overruns(), 1);
+        assert_eq!(REPORTED.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn counter_overflow_is_handled() {
+        static NOW: AtomicU32 = AtomicU32::new(u32::MAX - 4);
+        static CLOCK: CoreClock = CoreClock::new();
+        static TASK: Monitor = Monitor::new("Task", None, None);
+        fn now() -> u32 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let job = Job::start(&TASK, &CLOCK, now, None, no_critical_section);
+        NOW.store(5, Ordering::Relaxed);
+        drop(job);
+        assert_eq!(TASK.worst_execution(), 10);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::MonitorPassBackend;
+use crate::monitor_pass::parse::ast::{Limit, MonitoredTask};
+use crate::monitor_pass::parse::{App, exec_fn, impl_self_ident, init_core};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use rticx_core::rticx_functions::INTERRUPT_FREE_FN;
+use syn::{ImplItem, Item, ItemMod, parse_quote};
+
+/// Static holding the limits and observed timing of `task`
+fn monitor_ident(task: &Ident) -> Ident {
+    format_ident!("__rticx_monitor_{task}")
+}
+
+/// Static accumulating the cycles executed by the finished jobs of `core`
+fn clock_ident(core: u32) -> Ident {
+    format_ident!("__rticx_monitor_clock_core{core}")
+}
+
+/// Function reading the cycle counter of `core`
+fn cycles_fn_ident(core: u32) -> Ident {
+    format_ident!("__rticx_monitor_cycles_core{core}")
+}
+
+/// Function running the time accounting of jobs with interrupts disabled
+fn critical_section_fn_ident() -> Ident {
+    format_ident!("__rticx_monitor_critical_section")
+}
+
+pub struct CodeGen<'a> {
+    app: App,
+    backend: &'a dyn MonitorPassBackend,
+}
+
+impl<'a> CodeGen<'a> {
+    pub fn new(app: App, backend: &'a dyn MonitorPassBackend) -> CodeGen<'a> {
+        Self { app, backend }
+    }
+
+    pub fn run(&mut self) -> ItemMod {
+        let mut cores: Vec<u32> = self.app.tasks.iter().map(|task| task.core).collect();
+        cores.sort_unstable();
+        cores.dedup();
+
+        let mut items = std::mem::take(&mut self.app.items);
+        for item in items.iter_mut() {
+            match item {
+                Item::Impl(impl_) => {
+                    let task = impl_self_ident(impl_)
+                        .and_then(|ident| self.app.tasks.iter().find(|task| task.ident == *ident));
+                    if let Some(task) = task
+                        && let Some(exec) = exec_fn(impl_)
+                    {
+                        let exec = exec.sig.ident.clone();
+                        self.wrap_exec(task, &exec, &mut impl_.items);
+                    }
+                }
+                Item::Fn(init_fn) => {
+                    if let Some(core) = init_core(init_fn)
+                        && cores.contains(&core)
+                        && let Some(start_counter) = self.backend.start_counter(core)
+                    {
+                        let stmts = &init_fn.block.stmts;
+                        init_fn.block = parse_quote!({
+                            #start_counter
+                            #(#stmts)*
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let monitors = self.app.tasks.iter().map(|task| self.monitor_def(task));
+        let cores_code = cores.iter().map(|core| self.core_code(*core));
+        let critical_section_fn = critical_section_fn_ident();
+        let interrupt_free_fn = format_ident!("{INTERRUPT_FREE_FN}");
+        let critical_section = (!self.app.tasks.is_empty()).then(|| {
+            quote! {
+                #[doc(hidden)]
+                fn #critical_section_fn(f: &mut dyn FnMut()) {
+                    #interrupt_free_fn(f)
+                }
+            }
+        });
+        let mod_visibility = &self.app.mod_visibility;
+        let mod_ident = &self.app.mod_ident;
+
+        parse_quote! {
+            #mod_visibility mod #mod_ident {
+                #(#items)*
+                #(#monitors)*
+                #(#cores_code)*
+                #critical_section
+            }
+        }
+    }
+
+    /// Time the `exec_fn` function of `task` as a job, which finishes when the function returns
+    fn wrap_exec(&self, task: &MonitoredTask, exec_fn: &Ident, impl_items: &mut [ImplItem]) {
+        let export = self.backend.export_path();
+        let monitor = monitor_ident(&task.ident);
+        let clock = clock_ident(task.core);
+        let cycles_fn = cycles_fn_ident(task.core);
+        let critical_section_fn = critical_section_fn_ident();
+        let on_violation = match &self.app.overrun_handler {
+            Some(handler) => quote!(Some(#handler)),
+            None => quote!(None),
+        };
+        for item in impl_items.iter_mut() {
+            if let ImplItem::Fn(exec) = item
+                && exec.sig.ident == *exec_fn
+            {
+                let stmts = &exec.block.stmts;
+                exec.block = parse_quote!({
+                    let __rticx_monitor_job = #export::Job::start(
+                        &#monitor,
+                        &#clock,
+                        #cycles_fn,
+                        #on_violation,
+                        #critical_section_fn,
+                    );
+                    #(#stmts)*
+                });
+            }
+        }
+    }
+
+    /// Limits and observed timing of `task`
+    fn monitor_def(&self, task: &MonitoredTask) -> TokenStream {
+        let export = self.backend.export_path();
+        let monitor = monitor_ident(&task.ident);
+        let task_ident = &task.ident;
+        let task_name = task.ident.to_string();
+        let cycles = |limit: &Option<Limit>| match limit {
+            Some(limit) => {
+                let cycles = limit.cycles;
+                quote!(Some(#cycles))
+            }
+            None => quote!(None),
+        };
+        let budget = cycles(&task.budget);
+        let deadline = cycles(&task.deadline);
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #monitor: #export::Monitor = #export::Monitor::new(#task_name, #budget, #deadline);
+
+            impl #task_ident {
+                /// Limits and worst observed execution and elapsed times of this task
+                pub fn monitor() -> &'static #export::Monitor {
+                    &#monitor
+                }
+            }
+        }
+    }
+
+    /// Cycle counter function and clock of `core`
+    fn core_code(&self, core: u32) -> TokenStream {
+        let export = self.backend.export_path();
+        let clock = clock_ident(core);
+        let cycles_fn_ident = cycles_fn_ident(core);
+        let cycles_fn = self.backend.generate_cycles_fn(
+            core,
+            parse_quote! {
+                #[doc(hidden)]
+                #[inline]
+                pub fn #cycles_fn_ident() -> u32 {
+                    // To be implemented by distributor
+                    // example:
+                    // DWT::cycle_count()
+                }
+            },
+        );
+        quote! {
+            #cycles_fn
+
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #clock: #export::CoreClock = #export::CoreClock::new();
+        }
+    }
+}
@@ -0,0 +1,138 @@
+mod codegen;
+pub mod parse;
+
+use crate::monitor_pass::codegen::CodeGen;
+pub use crate::monitor_pass::parse::App;
+use proc_macro2::TokenStream;
+use rticx_core::parse_utils::{RticAttr, Time};
+use rticx_core::{InfoBus, RticPass};
+use syn::ItemMod;
+
+/// Measures the execution and elapsed time of every task job, and checks them against the
+/// `budget` and deadline of the task.
+///
+/// The `exec` function of every task implemented in the application is timed with the cycle
+/// counter of the backend. The execution time of a job excludes the jobs preempting it, while its
+/// elapsed time runs from the start to the end of `exec`, preemptions included. It is not a
+/// response time, the wait before `exec` starts is not measured, so only the deadline misses of
+/// jobs whose `exec` alone takes longer than the deadline are detected. A job executing for longer
+/// than the budget of its task, or elapsing longer than its deadline, is reported to the
+/// `#[overrun]` handler of the application once it finishes.
+///
+/// The pass must run before the deadline pass, which replaces the deadlines with priorities.
+pub struct MonitorPass {
+    backend: Box<dyn MonitorPassBackend>,
+}
+
+impl MonitorPass {
+    pub fn new<T: MonitorPassBackend + 'static>(backend: T) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+}
+
+impl RticPass for MonitorPass {
+    fn subscribe(&mut self, info_bus: InfoBus) {
+        self.backend.subscribe(info_bus);
+    }
+
+    fn run_pass(&self, args: TokenStream, app_mod: ItemMod) -> syn::Result<(TokenStream, ItemMod)> {
+        let params = RticAttr::parse_from_tokens(args.clone())?;
+        let mut parsed = App::parse(&params, app_mod)?;
+
+        self.analyze(&mut parsed)?;
+
+        let code = CodeGen::new(parsed, self.backend.as_ref()).run();
+        Ok((args, code))
+    }
+
+    fn pass_name(&self) -> &str {
+        "monitor_pass"
+    }
+}
+
+impl MonitorPass {
+    /// Convert the budgets and deadlines to counter cycles
+    pub fn analyze(&self, app: &mut App) -> syn::Result<()> {
+        for task in app.tasks.iter_mut() {
+            let core = task.core;
+            for (name, limit) in [
+                ("budget", &mut task.budget),
+                ("deadline", &mut task.deadline),
+            ] {
+                let Some(limit) = limit else {
+                    continue;
+                };
+                let cycles = match limit.time {
+                    Time::Plain(cycles) => cycles,
+                    Time::Nanos(nanos) => {
+                        let Some(hz) = self.backend.cycles_hz(core) else {
+                            return Err(syn::Error::new(
+                                limit.span,
+                                format!(
+                                    "The cycle counter frequency of core {core} is unknown on this platform, give the {name} of task `{}` in cycles.",
+                                    task.ident
+                                ),
+                            ));
+                        };
+                        (nanos as u128 * hz as u128 / 1_000_000_000) as u64
+                    }
+                };
+                limit.cycles = u32::try_from(cycles).map_err(|_| {
+                    syn::Error::new(
+                        limit.span,
+                        format!(
+                            "The {name} of task `{}` is {cycles} cycles, more than the {} cycles the counter can measure.",
+                            task.ident,
+                            u32::MAX
+                        ),
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interface for providing the hardware-specific backend needed by the
+/// monitor compilation pass.
+///
+/// Every core with tasks needs a free running 32-bit cycle counter, read by the `cycles`
+/// function, e.g. the DWT cycle counter on Cortex-M or `mcycle` on RISC-V.
+pub trait MonitorPassBackend {
+    /// Path to the re-exported `rticx_monitor_pass::export` module.
+    ///
+    /// Typical implementation for a distribution:
+    /// ```ignore
+    /// fn export_path(&self) -> syn::Path {
+    ///     parse_quote!(rticx_cortex_m::export::monitor)
+    /// }
+    /// ```
+    fn export_path(&self) -> syn::Path;
+
+    /// Frequency of the cycle counter of `core` in Hz, needed for limits given as durations.
+    ///
+    /// Return `None` if the frequency is not known at compile time, limits then have to be given
+    /// in cycles.
+    fn cycles_hz(&self, _core: u32) -> Option<u64> {
+        None
+    }
+
+    /// Statements starting the cycle counter of `core`, run at the start of its `#[init]`.
+    fn start_counter(&self, _core: u32) -> Option<TokenStream> {
+        None
+    }
+
+    /// Body of the function reading the cycle counter of `core`.
+    ///
+    /// # Contract
+    /// * The generated function takes no argument and returns the current counter value as `u32`.
+    /// * The counter must count up and wrap around at `u32::MAX`.
+    /// * Do NOT change the function signature.
+    fn generate_cycles_fn(&self, core: u32, empty_body_fn: syn::ItemFn) -> syn::ItemFn;
+
+    /// Subscribe to info_bus
+    /// This method is guaranteed to be called before any other methods in this trait.
+    fn subscribe(&mut self, _info_bus: InfoBus) {}
+}
@@ -0,0 +1,81 @@
+use proc_macro2::{Ident, Span};
+use rticx_core::parse_utils::{RticAttr, Time, parse_time};
+use syn::{Expr, ItemStruct, Lit, spanned::Spanned};
+
+/// Arguments a deadline can be given with, and the number of nanoseconds in their unit, like the
+/// deadline pass accepts them. `deadline` itself takes either a plain number or a duration.
+const DEADLINE_ARGS: [(&str, Option<u64>); 5] = [
+    ("deadline", None),
+    ("deadline_ns", Some(1)),
+    ("deadline_us", Some(1_000)),
+    ("deadline_ms", Some(1_000_000)),
+    ("deadline_s", Some(1_000_000_000)),
+];
+
+/// A limit of a task, converted to cycles during analysis
+#[derive(Debug)]
+pub struct Limit {
+    /// A plain number of counter cycles, or a duration
+    pub time: Time,
+    /// Span of the argument, used for error reporting
+    pub span: Span,
+    pub cycles: u32,
+}
+
+#[derive(Debug)]
+pub struct MonitoredTask {
+    pub ident: Ident,
+    /// Core the task runs on, `0` unless explicitly set
+    pub core: u32,
+    /// Execution-time budget, set by the `budget` argument
+    pub budget: Option<Limit>,
+    /// Relative deadline, set by one of the `deadline*` arguments
+    pub deadline: Option<Limit>,
+}
+
+impl MonitoredTask {
+    pub fn from_struct(task_struct: &ItemStruct, attr_idx: usize) -> syn::Result<Self> {
+        let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
+        let ident = task_struct.ident.clone();
+
+        let core = match params.elements.get("core") {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => int.base10_parse()?,
+            _ => 0,
+        };
+
+        let limit = |arg: &str, expr: &Expr, unit| -> syn::Result<Limit> {
+            Ok(Limit {
+                time: parse_time(arg, expr, unit)?,
+                span: expr.span(),
+                cycles: 0,
+            })
+        };
+        let budget = params
+            .elements
+            .get("budget")
+            .map(|expr| limit("budget", expr, None))
+            .transpose()?;
+        let mut deadline = None;
+        for (arg, unit) in DEADLINE_ARGS {
+            let Some(expr) = params.elements.get(arg) else {
+                continue;
+            };
+            if deadline.is_some() {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    format!("Task `{ident}` has more than one deadline argument."),
+                ));
+            }
+            deadline = Some(limit(arg, expr, unit)?);
+        }
+
+        Ok(Self {
+            ident,
+            core,
+            budget,
+            deadline,
+        })
+    }
+}
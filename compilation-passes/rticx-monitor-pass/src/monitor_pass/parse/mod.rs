@@ -0,0 +1,134 @@
+use super::parse::ast::MonitoredTask;
+use proc_macro2::Ident;
+use rticx_core::parse_utils::RticAttr;
+use syn::{Expr, FnArg, ImplItem, Item, ItemFn, ItemImpl, ItemMod, Lit, Type, Visibility};
+
+pub mod ast;
+
+/// Type to represent an RTICX application for the monitor pass
+pub struct App {
+    pub mod_visibility: Visibility,
+    pub mod_ident: Ident,
+
+    /// Hardware and software tasks whose `exec` function is implemented in the application
+    pub tasks: Vec<MonitoredTask>,
+    /// The function marked `#[overrun]`, the attribute is removed
+    pub overrun_handler: Option<Ident>,
+    /// All the items of the application. Tasks and their `impl` blocks stay in place, codegen
+    /// only rewrites the `exec` and `#[init]` functions.
+    pub items: Vec<Item>,
+}
+
+impl App {
+    pub fn parse(_params: &RticAttr, mut app_mod: ItemMod) -> syn::Result<Self> {
+        let mut items = app_mod.content.take().unwrap_or_default().1;
+        let mut tasks = Vec::new();
+        let mut overrun_handler: Option<Ident> = None;
+
+        for item in items.iter_mut() {
+            match item {
+                Item::Struct(strct) => {
+                    let Some(attr_idx) = find_attr(&strct.attrs, "task")
+                        .or_else(|| find_attr(&strct.attrs, "sw_task"))
+                    else {
+                        continue;
+                    };
+                    tasks.push(MonitoredTask::from_struct(strct, attr_idx)?);
+                }
+                Item::Fn(handler) => {
+                    let Some(attr_idx) = find_attr(&handler.attrs, "overrun") else {
+                        continue;
+                    };
+                    if let Some(first) = &overrun_handler {
+                        return Err(syn::Error::new(
+                            handler.sig.ident.span(),
+                            format!(
+                                "Only one `#[overrun]` handler is allowed, `{first}` is already one."
+                            ),
+                        ));
+                    }
+                    if handler.sig.inputs.len() != 1
+                        || !matches!(handler.sig.inputs.first(), Some(FnArg::Typed(_)))
+                        || !handler.sig.generics.params.is_empty()
+                    {
+                        return Err(syn::Error::new_spanned(
+                            &handler.sig,
+                            "The `#[overrun]` handler must take a single `Violation` argument.",
+                        ));
+                    }
+                    handler.attrs.remove(attr_idx);
+                    overrun_handler = Some(handler.sig.ident.clone());
+                }
+                _ => {}
+            }
+        }
+
+        // tasks implemented outside of the application can't be measured
+        let mut monitored = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let implemented = items.iter().any(|item| {
+                matches!(item, Item::Impl(impl_) if impl_self_ident(impl_) == Some(&task.ident) && exec_fn(impl_).is_some())
+            });
+            if implemented {
+                monitored.push(task);
+            } else if let Some(limit) = task.budget.as_ref().or(task.deadline.as_ref()) {
+                return Err(syn::Error::new(
+                    limit.span,
+                    format!(
+                        "Task `{}` has no `exec` function in the application, its limits can't be monitored.",
+                        task.ident
+                    ),
+                ));
+            }
+        }
+
+        Ok(Self {
+            mod_ident: app_mod.ident,
+            mod_visibility: app_mod.vis,
+            tasks: monitored,
+            overrun_handler,
+            items,
+        })
+    }
+}
+
+/// Returns the index of the `attr_name` attribute if found in the attribute list
+pub(crate) fn find_attr(attrs: &[syn::Attribute], attr_name: &str) -> Option<usize> {
+    attrs.iter().position(|attr| {
+        let path = attr.meta.path();
+        path.segments.len() == 1 && path.segments[0].ident == attr_name
+    })
+}
+
+/// Core of an `#[init]` function, `None` if `init_fn` is not one
+pub(crate) fn init_core(init_fn: &ItemFn) -> Option<u32> {
+    let attr_idx = find_attr(&init_fn.attrs, "init")?;
+    let params = RticAttr::parse_from_attr(&init_fn.attrs[attr_idx]).ok()?;
+    match params.elements.get("core") {
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(int), ..
+        })) => int.base10_parse().ok(),
+        _ => Some(0),
+    }
+}
+
+/// The type an `impl` block is written for, when it is a plain identifier
+pub(crate) fn impl_self_ident(impl_: &ItemImpl) -> Option<&Ident> {
+    match impl_.self_ty.as_ref() {
+        Type::Path(path) => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// The function running the jobs of a task trait implementation: `exec_from` when a task bound to
+/// several interrupts implements it, `exec` otherwise
+pub(crate) fn exec_fn(impl_: &ItemImpl) -> Option<&syn::ImplItemFn> {
+    impl_.trait_.as_ref()?;
+    let find_fn = |name: &str| {
+        impl_.items.iter().find_map(|item| match item {
+            ImplItem::Fn(f) if f.sig.ident == name => Some(f),
+            _ => None,
+        })
+    };
+    find_fn("exec_from").or_else(|| find_fn("exec"))
+}
@@ -0,0 +1,46 @@
+//! Integration tests for the analysis phase of `rticx-monitor-pass`.
+
+use quote::quote;
+
+mod common;
+
+use common::{MockMonitorBackend, analyze, assert_err_contains};
+
+#[test]
+fn analyze_converts_limits_to_cycles() {
+    let app = analyze(MockMonitorBackend::default(), common::monitored_app_items())
+        .expect("analysis succeeds");
+
+    assert_eq!(app.tasks[0].budget.as_ref().unwrap().cycles, 200);
+    // 5 us at 100 MHz
+    assert_eq!(app.tasks[1].deadline.as_ref().unwrap().cycles, 500);
+}
+
+#[test]
+fn analyze_rejects_duration_without_counter_frequency() {
+    let result = analyze(
+        MockMonitorBackend { cycles_hz: None },
+        common::monitored_app_items(),
+    );
+    assert_err_contains(
+        result,
+        "frequency of core 0 is unknown on this platform, give the deadline of task `Logger` in cycles",
+    );
+}
+
+#[test]
+fn analyze_rejects_limits_the_counter_cant_measure() {
+    let result = analyze(
+        MockMonitorBackend::default(),
+        quote! {
+            #[task(binds = UART0, budget = 60.s())]
+            struct Sampler;
+
+            impl RticTask for Sampler {
+                fn init() -> Self { Sampler }
+                fn exec(&mut self) {}
+            }
+        },
+    );
+    assert_err_contains(result, "The budget of task `Sampler` is 6000000000 cycles");
+}
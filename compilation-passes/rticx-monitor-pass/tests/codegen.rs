@@ -0,0 +1,190 @@
+//! Integration tests for the codegen phase of `rticx-monitor-pass`.
+//!
+//! These run the full `MonitorPass::run_pass` pipeline (parse + analysis +
+//! codegen) and verify that the expanded `ItemMod` contains the expected
+//! sections, each built with `quote!{...}` and searched inside the generated
+//! `.to_string()`.
+
+use quote::quote;
+use rticx_core::RticPass;
+use rticx_monitor_pass::MonitorPass;
+
+mod common;
+
+use common::{MockMonitorBackend, app_mod, assert_section_present, mod_to_string};
+
+/// Run the monitor pass end-to-end and return the generated module string.
+fn run_pass(app_mod: syn::ItemMod) -> String {
+    let pass = MonitorPass::new(MockMonitorBackend::default());
+    let (_, module) = pass
+        .run_pass(common::single_core_args(), app_mod)
+        .expect("pass succeeds");
+    mod_to_string(&module)
+}
+
+#[test]
+fn codegen_expands_monitored_app() {
+    let generated = run_pass(app_mod(common::monitored_app_items()));
+
+    // ---- task monitors ----
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_monitor_Sampler: rticx::export::monitor::Monitor =
+                rticx::export::monitor::Monitor::new("Sampler", Some(200u32), None);
+        },
+        "monitor with a budget",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_monitor_Logger: rticx::export::monitor::Monitor =
+                rticx::export::monitor::Monitor::new("Logger", None, Some(500u32));
+        },
+        "monitor with a deadline, converted to cycles",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            impl Sampler {
+                #[doc = r" Limits and worst observed execution and elapsed times of this task"]
+                pub fn monitor() -> &'static rticx::export::monitor::Monitor {
+                    &__rticx_monitor_Sampler
+                }
+            }
+        },
+        "monitor accessor",
+    );
+
+    // ---- jobs ----
+    assert_section_present(
+        &generated,
+        quote! {
+            fn exec(&mut self, value: u32) {
+                let __rticx_monitor_job = rticx::export::monitor::Job::start(
+                    &__rticx_monitor_Logger,
+                    &__rticx_monitor_clock_core0,
+                    __rticx_monitor_cycles_core0,
+                    Some(on_overrun),
+                    __rticx_monitor_critical_section,
+                );
+                log(value);
+            }
+        },
+        "software task job",
+    );
+
+    // ---- overrun handler, cycle counter and clock ----
+    assert_section_present(
+        &generated,
+        quote! {
+            fn on_overrun(violation: Violation) {
+                report(violation);
+            }
+        },
+        "overrun handler without its attribute",
+    );
+    assert!(!generated.contains("# [overrun]"));
+    assert_section_present(
+        &generated,
+        quote! {
+            fn init() {
+                mock_start_counter();
+                setup();
+            }
+        },
+        "counter started by init",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn __rticx_monitor_cycles_core0() -> u32 { mock_cycles() }
+        },
+        "cycle counter function",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_monitor_clock_core0: rticx::export::monitor::CoreClock =
+                rticx::export::monitor::CoreClock::new();
+        },
+        "core clock",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            fn __rticx_monitor_critical_section(f: &mut dyn FnMut()) {
+                __rticx_interrupt_free(f)
+            }
+        },
+        "critical section",
+    );
+}
+
+#[test]
+fn codegen_without_overrun_handler_only_records() {
+    let generated = run_pass(app_mod(quote! {
+        #[task(binds = UART0, budget = 10)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self { Sampler }
+            fn exec(&mut self) {}
+        }
+    }));
+
+    assert_section_present(
+        &generated,
+        quote! {
+            let __rticx_monitor_job = rticx::export::monitor::Job::start(
+                &__rticx_monitor_Sampler,
+                &__rticx_monitor_clock_core0,
+                __rticx_monitor_cycles_core0,
+                None,
+                __rticx_monitor_critical_section,
+            );
+        },
+        "job without handler",
+    );
+}
+
+#[test]
+fn codegen_times_exec_from_of_multi_bound_tasks() {
+    let generated = run_pass(app_mod(quote! {
+        #[task(binds = [EXTI0, EXTI1], budget = 10)]
+        struct Buttons;
+
+        impl RticTask for Buttons {
+            fn init() -> Self { Buttons }
+            fn exec(&mut self) {}
+            fn exec_from(&mut self, source: InterruptSource) {
+                press(source);
+            }
+        }
+    }));
+
+    // the job is timed once, in the function the handlers run
+    assert_section_present(
+        &generated,
+        quote! {
+            fn exec_from(&mut self, source: InterruptSource) {
+                let __rticx_monitor_job = rticx::export::monitor::Job::start(
+                    &__rticx_monitor_Buttons,
+                    &__rticx_monitor_clock_core0,
+                    __rticx_monitor_cycles_core0,
+                    None,
+                    __rticx_monitor_critical_section,
+                );
+                press(source);
+            }
+        },
+        "job of exec_from",
+    );
+    assert_section_present(
+        &generated,
+        quote!(
+            fn exec(&mut self) {}
+        ),
+        "exec left as is",
+    );
+}
@@ -0,0 +1,156 @@
+//! Shared helpers for the `rticx-monitor-pass` integration tests.
+
+#![allow(dead_code)]
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use quote::quote;
+use rticx_core::parse_utils::RticAttr;
+use rticx_monitor_pass::{App, MonitorPass, MonitorPassBackend};
+use syn::{ItemFn, parse_quote};
+
+/// Single-core macro arguments with one PAC path.
+pub fn single_core_args() -> TokenStream {
+    quote!(device = mypac)
+}
+
+/// Wraps the given `items` tokenstream in `mod app { ... }` and parses it into
+/// an `ItemMod`.
+pub fn app_mod(items: TokenStream) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            #items
+        }
+    }
+}
+
+/// Parse args + items into a monitor pass `App`.
+pub fn parse(items: TokenStream) -> syn::Result<App> {
+    let params = RticAttr::parse_from_tokens(single_core_args()).expect("params parse");
+    App::parse(&params, app_mod(items))
+}
+
+/// Parse items and run the monitor pass analysis with `backend`.
+pub fn analyze(backend: MockMonitorBackend, items: TokenStream) -> syn::Result<App> {
+    let mut parsed = parse(items).expect("app parse");
+    MonitorPass::new(backend).analyze(&mut parsed)?;
+    Ok(parsed)
+}
+
+/// The items of an app with a hardware task `Sampler` with a budget, a
+/// software task `Logger` with a deadline and an `#[overrun]` handler.
+pub fn monitored_app_items() -> TokenStream {
+    quote! {
+        #[init]
+        fn init() {
+            setup();
+        }
+
+        #[task(binds = UART0, priority = 3, budget = 200)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self {
+                Sampler
+            }
+            fn exec(&mut self) {
+                sample();
+            }
+        }
+
+        #[sw_task(priority = 2, deadline = 5.us())]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self {
+                Logger
+            }
+            fn exec(&mut self, value: u32) {
+                log(value);
+            }
+        }
+
+        #[overrun]
+        fn on_overrun(violation: Violation) {
+            report(violation);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Assertion helpers
+// ---------------------------------------------------------------------------
+
+/// Asserts that `result` is an `Err` whose message contains `substr`, without
+/// requiring the `Ok` variant to implement `Debug`.
+pub fn assert_err_contains<T>(result: syn::Result<T>, substr: &str) {
+    let err = match result {
+        Ok(_) => panic!("expected an error, but parsing/analysis succeeded"),
+        Err(e) => e,
+    };
+    assert!(
+        err.to_string().contains(substr),
+        "expected error to contain {substr:?}, got: {err}"
+    );
+}
+
+/// Asserts that the `expected` tokenstream (rendered to a string) is present
+/// as a contiguous substring of the `generated` string. A `label` is used to
+/// make failures easier to diagnose.
+pub fn assert_section_present(generated: &str, expected: TokenStream, label: &str) {
+    let expected = expected.to_string();
+    assert!(
+        generated.contains(&expected),
+        "missing expected section `{label}` in the generated output\n\
+         expected:\n{expected}\n\n\
+         generated:\n{generated}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Mock MonitorPassBackend
+// ---------------------------------------------------------------------------
+
+/// A mock `MonitorPassBackend` used by the analysis and codegen tests. Its
+/// cycle counter runs at `cycles_hz` when known.
+pub struct MockMonitorBackend {
+    pub cycles_hz: Option<u64>,
+}
+
+impl Default for MockMonitorBackend {
+    fn default() -> Self {
+        Self {
+            cycles_hz: Some(100_000_000),
+        }
+    }
+}
+
+impl MonitorPassBackend for MockMonitorBackend {
+    fn export_path(&self) -> syn::Path {
+        parse_quote!(rticx::export::monitor)
+    }
+
+    fn cycles_hz(&self, _core: u32) -> Option<u64> {
+        self.cycles_hz
+    }
+
+    fn start_counter(&self, _core: u32) -> Option<TokenStream> {
+        Some(quote!(mock_start_counter();))
+    }
+
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block = Box::new(parse_quote!({ mock_cycles() }));
+        empty_body_fn
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Utility: stringify an ItemMod for codegen assertions
+// ---------------------------------------------------------------------------
+
+/// Convenience: render an `ItemMod` into its token-stream string form.
+pub fn mod_to_string(item_mod: &syn::ItemMod) -> String {
+    item_mod.to_token_stream().to_string()
+}
@@ -0,0 +1,105 @@
+//! Integration tests for the parse phase of `rticx-monitor-pass`.
+
+use quote::quote;
+use rticx_core::parse_utils::Time;
+
+mod common;
+
+use common::{assert_err_contains, parse};
+
+#[test]
+fn parse_monitored_tasks() {
+    let app = parse(common::monitored_app_items()).expect("app parses");
+
+    assert_eq!(app.tasks.len(), 2);
+    let sampler = &app.tasks[0];
+    assert_eq!(sampler.ident, "Sampler");
+    assert_eq!(
+        sampler.budget.as_ref().map(|limit| limit.time),
+        Some(Time::Plain(200))
+    );
+    assert!(sampler.deadline.is_none());
+    let logger = &app.tasks[1];
+    assert!(logger.budget.is_none());
+    assert_eq!(
+        logger.deadline.as_ref().map(|limit| limit.time),
+        Some(Time::Nanos(5_000))
+    );
+    assert_eq!(app.overrun_handler.as_ref().unwrap(), "on_overrun");
+}
+
+#[test]
+fn parse_deadline_pass_arguments() {
+    let app = parse(quote! {
+        #[task(binds = UART0, deadline_ms = 2)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self { Sampler }
+            fn exec(&mut self) {}
+        }
+    })
+    .expect("app parses");
+    assert_eq!(
+        app.tasks[0].deadline.as_ref().map(|limit| limit.time),
+        Some(Time::Nanos(2_000_000))
+    );
+}
+
+#[test]
+fn parse_skips_tasks_implemented_elsewhere() {
+    let app = parse(quote! {
+        #[task(binds = UART0)]
+        struct External;
+    })
+    .expect("app parses");
+    assert!(app.tasks.is_empty());
+}
+
+#[test]
+fn parse_rejects_limits_of_tasks_implemented_elsewhere() {
+    let result = parse(quote! {
+        #[task(binds = UART0, budget = 100)]
+        struct External;
+    });
+    assert_err_contains(result, "its limits can't be monitored");
+}
+
+#[test]
+fn parse_rejects_two_deadlines() {
+    let result = parse(quote! {
+        #[task(binds = UART0, deadline = 100, deadline_us = 1)]
+        struct Sampler;
+    });
+    assert_err_contains(result, "more than one deadline argument");
+}
+
+#[test]
+fn parse_rejects_unknown_unit() {
+    let result = parse(quote! {
+        #[task(binds = UART0, budget = 3.minutes())]
+        struct Sampler;
+    });
+    assert_err_contains(result, "Unknown time unit `minutes`");
+}
+
+#[test]
+fn parse_rejects_two_overrun_handlers() {
+    let result = parse(quote! {
+        #[overrun]
+        fn first(violation: Violation) {}
+
+        #[overrun]
+        fn second(violation: Violation) {}
+    });
+    assert_err_contains(result, "`first` is already one");
+}
+
+#[test]
+fn parse_rejects_overrun_handler_signature() {
+    let result = parse(quote! {
+        #[overrun]
+        fn on_overrun() {}
+    });
+    assert_err_contains(result, "must take a single `Violation` argument");
+}
@@ -0,0 +1,220 @@
+//! Runtime support of periodic tasks, to be re-exported by distributions.
+//!
+//! Instants are ticks of a free running 32-bit timer provided by the distribution, compared with
+//! wrapping arithmetic so the timer may overflow. Periods must stay below `2^31` ticks.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Whether `instant` is not in the future at `now`
+#[inline]
+fn reached(instant: u32, now: u32) -> bool {
+    (now.wrapping_sub(instant) as i32) >= 0
+}
+
+/// Release times and overrun counter of a periodic task.
+///
+/// Every field is written by a single context at a time (the job itself, or the release handler
+/// while no job is active), so plain loads and stores are enough, even on targets without
+/// compare-and-swap. The overrun counter, read from any context, is incremented with `fetch_add`
+/// where the target supports it.
+pub struct Release {
+    period: u32,
+    /// Release time of the next job
+    next: AtomicU32,
+    /// Release time of the current (or last) job
+    current: AtomicU32,
+    /// A job has been released and has not finished yet
+    active: AtomicBool,
+    overruns: AtomicU32,
+}
+
+impl Release {
+    pub const fn new(period: u32) -> Self {
+        Self {
+            period,
+            next: AtomicU32::new(0),
+            current: AtomicU32::new(0),
+            active: AtomicBool::new(false),
+            overruns: AtomicU32::new(0),
+        }
+    }
+
+    /// Schedule the first release one period after `now`
+    pub fn start(&self, now: u32) {
+        self.current.store(now, Ordering::Relaxed);
+        self.next
+            .store(now.wrapping_add(self.period), Ordering::Relaxed);
+    }
+
+    /// Release time of the next job
+    pub fn next_release(&self) -> u32 {
+        self.next.load(Ordering::Relaxed)
+    }
+
+    /// Number of jobs that were still running at the release of the following job
+    pub fn overruns(&self) -> u32 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Move the next release one period ahead, skipping releases that already passed at `now`
+    /// so the timer is never programmed in the past. Release times stay multiples of the period
+    /// after the start, whatever the release latency.
+    fn advance(&self, released: u32, now: u32) {
+        let mut next = released.wrapping_add(self.period);
+        while reached(next, now) {
+            next = next.wrapping_add(self.period);
+        }
+        self.next.store(next, Ordering::Relaxed);
+    }
+
+    /// Release a job of a hardware task whose interrupt fired at its release time, returns the
+    /// release time of the next job which the timer compare must be set to
+    pub fn release_now(&self, now: u32) -> u32 {
+        let released = self.next_release();
+        self.current.store(released, Ordering::Relaxed);
+        self.active.store(true, Ordering::Relaxed);
+        self.advance(released, now);
+        self.next_release()
+    }
+
+    /// Release a job of a software task if its release time is reached at `now`. Returns `false`
+    /// when it is not time yet, or when the previous job is still active, in which case that job
+    /// is counted as an overrun once it finishes.
+    pub fn release_due(&self, now: u32) -> bool {
+        let released = self.next_release();
+        if !reached(released, now) {
+            return false;
+        }
+        self.advance(released, now);
+        if self.active.load(Ordering::Relaxed) {
+            return false;
+        }
+        self.current.store(released, Ordering::Relaxed);
+        self.active.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Forget about a released job that could not be spawned
+    pub fn cancel(&self) {
+        self.active.store(false, Ordering::Relaxed);
+    }
+
+    /// A job finished at `now`, count it as an overrun if it was still running when the next
+    /// job was released
+    pub fn finish(&self, now: u32) {
+        let current = self.current.load(Ordering::Relaxed);
+        if now.wrapping_sub(current) >= self.period {
+            #[cfg(target_has_atomic = "32")]
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+            // only the job of the task writes the counter, readers see either value
+            #[cfg(not(target_has_atomic = "32"))]
+            self.overruns
+                .store(self.overruns().wrapping_add(1), Ordering::Relaxed);
+        }
+        self.active.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The release time that comes first after `now`, used to program the release timer of
+/// software tasks
+pub fn earliest(now: u32, releases: &[u32]) -> u32 {
+    releases
+        .iter()
+        .copied()
+        .min_by_key(|release| release.wrapping_sub(now))
+        .unwrap_or(now)
+}
+
+/// A running job of a periodic task, finished when dropped so early returns from `exec` are
+/// accounted for
+pub struct Job {
+    release: &'static Release,
+    now: fn() -> u32,
+}
+
+impl Job {
+    /// Job of a hardware task, the timer compare of its interrupt is moved to the next release
+    pub fn hardware(release: &'static Release, now: fn() -> u32, set_compare: fn(u32)) -> Self {
+        set_compare(release.release_now(now()));
+        Self { release, now }
+    }
+
+    /// Job of a software task, released by the release handler of its core
+    pub fn software(release: &'static Release, now: fn() -> u32) -> Self {
+        Self { release, now }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.release.finish((self.now)());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_are_drift_free() {
+        let release = Release::new(10);
+        release.start(100);
+        assert_eq!(release.next_release(), 110);
+
+        // released late, the next release stays on the grid
+        assert_eq!(release.release_now(113), 120);
+        release.finish(115);
+        assert_eq!(release.release_now(121), 130);
+        release.finish(125);
+        assert_eq!(release.overruns(), 0);
+    }
+
+    #[test]
+    fn late_jobs_are_counted_and_missed_releases_skipped() {
+        let release = Release::new(10);
+        release.start(0);
+
+        assert_eq!(release.release_now(10), 20);
+        release.finish(35);
+        assert_eq!(release.overruns(), 1);
+
+        // the release at 20 is late and the one at 30 already passed
+        assert_eq!(release.release_now(36), 40);
+    }
+
+    #[test]
+    fn software_release_waits_for_active_job() {
+        let release = Release::new(10);
+        release.start(0);
+
+        assert!(!release.release_due(5));
+        assert!(release.release_due(10));
+        // previous job still running at its next release
+        assert!(!release.release_due(20));
+        assert_eq!(release.next_release(), 30);
+        release.finish(22);
+        assert_eq!(release.overruns(), 1);
+        assert!(release.release_due(30));
+        release.finish(31);
+        assert_eq!(release.overruns(), 1);
+    }
+
+    #[test]
+    fn cancelled_release_frees_the_task() {
+        let release = Release::new(10);
+        release.start(0);
+        assert!(release.release_due(10));
+        release.cancel();
+        assert!(release.release_due(20));
+    }
+
+    #[test]
+    fn timer_overflow_is_handled() {
+        let release = Release::new(10);
+        release.start(u32::MAX - 4);
+        assert_eq!(release.next_release(), 5);
+        assert!(!release.release_due(u32::MAX));
+        assert!(release.release_due(6));
+        assert_eq!(earliest(u32::MAX - 1, &[3, u32::MAX]), u32::MAX);
+    }
+}
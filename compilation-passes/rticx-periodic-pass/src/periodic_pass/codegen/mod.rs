@@ -0,0 +1,286 @@
+use crate::PeriodicPassBackend;
+use crate::periodic_pass::parse::ast::{PeriodicTask, TaskKind};
+use crate::periodic_pass::parse::{App, exec_fn, impl_self_ident, init_core};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{ImplItem, Item, ItemFn, ItemMod, LitInt, Path, ReturnType, parse_quote};
+
+/// Static holding the release times and overruns of `task`
+fn release_ident(task: &Ident) -> Ident {
+    format_ident!("__rticx_periodic_release_{task}")
+}
+
+/// Function reading the timer of `core`
+fn now_fn_ident(core: u32) -> Ident {
+    format_ident!("__rticx_periodic_now_core{core}")
+}
+
+/// Function programming the timer compare pending `irq` on `core`
+fn set_compare_fn_ident(core: u32, irq: &Path) -> Ident {
+    let irq = &irq
+        .segments
+        .last()
+        .expect("a path has a last segment")
+        .ident;
+    format_ident!("__rticx_periodic_set_compare_core{core}_{irq}")
+}
+
+/// Function starting the releases of `core`, called at the end of its `#[init]`
+fn start_fn_ident(core: u32) -> Ident {
+    format_ident!("__rticx_periodic_start_core{core}")
+}
+
+/// Hardware task spawning the periodic software tasks of `core`
+fn releaser_ident(core: u32) -> Ident {
+    format_ident!("__rticx_periodic_releaser_core{core}")
+}
+
+pub struct CodeGen<'a> {
+    app: App,
+    backend: &'a dyn PeriodicPassBackend,
+}
+
+impl<'a> CodeGen<'a> {
+    pub fn new(app: App, backend: &'a dyn PeriodicPassBackend) -> CodeGen<'a> {
+        Self { app, backend }
+    }
+
+    pub fn run(&mut self) -> ItemMod {
+        let mut cores: Vec<u32> = self.app.tasks.iter().map(|task| task.core).collect();
+        cores.sort_unstable();
+        cores.dedup();
+
+        let mut items = std::mem::take(&mut self.app.items);
+        for item in items.iter_mut() {
+            match item {
+                Item::Impl(impl_) => {
+                    let task = impl_self_ident(impl_)
+                        .and_then(|ident| self.app.tasks.iter().find(|task| task.ident == *ident));
+                    if let Some(task) = task
+                        && exec_fn(impl_).is_some()
+                    {
+                        self.wrap_exec(task, &mut impl_.items);
+                    }
+                }
+                Item::Fn(init_fn) => {
+                    if let Some(core) = init_core(init_fn)
+                        && cores.contains(&core)
+                    {
+                        start_after_init(init_fn, core);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let releases = self.app.tasks.iter().map(|task| self.release_def(task));
+        let cores_code = cores.iter().map(|core| self.core_code(*core));
+        let mod_visibility = &self.app.mod_visibility;
+        let mod_ident = &self.app.mod_ident;
+
+        parse_quote! {
+            #mod_visibility mod #mod_ident {
+                #(#items)*
+                #(#releases)*
+                #(#cores_code)*
+            }
+        }
+    }
+
+    /// Run the `exec` function of `task` as a job, which finishes when the function returns
+    fn wrap_exec(&self, task: &PeriodicTask, impl_items: &mut [ImplItem]) {
+        let export = self.backend.export_path();
+        let release = release_ident(&task.ident);
+        let now_fn = now_fn_ident(task.core);
+        let job = match (task.kind, &task.binds) {
+            (TaskKind::Hardware, Some(irq)) => {
+                let set_compare_fn = set_compare_fn_ident(task.core, irq);
+                quote!(#export::Job::hardware(&#release, #now_fn, #set_compare_fn))
+            }
+            _ => quote!(#export::Job::software(&#release, #now_fn)),
+        };
+        for item in impl_items.iter_mut() {
+            if let ImplItem::Fn(exec) = item
+                && exec.sig.ident == "exec"
+            {
+                let stmts = &exec.block.stmts;
+                exec.block = parse_quote!({
+                    let __rticx_job = #job;
+                    #(#stmts)*
+                });
+            }
+        }
+    }
+
+    /// Release state and overrun counter of `task`
+    fn release_def(&self, task: &PeriodicTask) -> TokenStream {
+        let export = self.backend.export_path();
+        let release = release_ident(&task.ident);
+        let ticks = task.ticks;
+        let task_ident = &task.ident;
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #release: #export::Release = #export::Release::new(#ticks);
+
+            impl #task_ident {
+                /// Number of jobs of this periodic task that were still running at their next release
+                pub fn overruns() -> u32 {
+                    #release.overruns()
+                }
+            }
+        }
+    }
+
+    /// Timer functions, start function and release handler of `core`
+    fn core_code(&self, core: u32) -> TokenStream {
+        let export = self.backend.export_path();
+        let tasks: Vec<_> = self
+            .app
+            .tasks
+            .iter()
+            .filter(|task| task.core == core)
+            .collect();
+        let (hw_tasks, sw_tasks): (Vec<&PeriodicTask>, Vec<_>) = tasks
+            .into_iter()
+            .partition(|task| task.kind == TaskKind::Hardware);
+
+        let now_fn_ident = now_fn_ident(core);
+        let now_fn = self.backend.generate_now_fn(
+            core,
+            parse_quote! {
+                #[doc(hidden)]
+                #[inline]
+                pub fn #now_fn_ident() -> u32 {
+                    // To be implemented by distributor
+                    // example:
+                    // TIMER.counter()
+                }
+            },
+        );
+
+        let release_irq = (!sw_tasks.is_empty())
+            .then(|| self.backend.release_timer_interrupt(core))
+            .flatten();
+        let compare_irqs = hw_tasks
+            .iter()
+            .filter_map(|task| task.binds.as_ref())
+            .chain(release_irq.as_ref());
+        let set_compare_fns = compare_irqs.map(|irq| {
+            let fn_ident = set_compare_fn_ident(core, irq);
+            self.backend.generate_set_compare_fn(
+                core,
+                irq,
+                parse_quote! {
+                    #[doc(hidden)]
+                    #[inline]
+                    pub fn #fn_ident(instant: u32) {
+                        // To be implemented by distributor
+                        // example:
+                        // TIMER.set_compare(CHANNEL, instant);
+                    }
+                },
+            )
+        });
+
+        let start_timer = self.backend.start_timer(core);
+        let start_hw_tasks = hw_tasks.iter().filter_map(|task| {
+            let release = release_ident(&task.ident);
+            let set_compare_fn = set_compare_fn_ident(core, task.binds.as_ref()?);
+            Some(quote! {
+                #release.start(now);
+                #set_compare_fn(#release.next_release());
+            })
+        });
+        let sw_releases: Vec<_> = sw_tasks
+            .iter()
+            .map(|task| release_ident(&task.ident))
+            .collect();
+        let (start_sw_tasks, releaser) = match &release_irq {
+            Some(irq) => {
+                let set_compare_fn = set_compare_fn_ident(core, irq);
+                let start = quote! {
+                    #(#sw_releases.start(now);)*
+                    #set_compare_fn(#export::earliest(now, &[#(#sw_releases.next_release()),*]));
+                };
+                (start, self.releaser(core, irq, &sw_tasks))
+            }
+            None => (quote!(), quote!()),
+        };
+        let start_fn = start_fn_ident(core);
+
+        quote! {
+            #now_fn
+            #(#set_compare_fns)*
+
+            #[doc(hidden)]
+            pub fn #start_fn() {
+                #start_timer
+                let now = #now_fn_ident();
+                #(#start_hw_tasks)*
+                #start_sw_tasks
+            }
+
+            #releaser
+        }
+    }
+
+    /// Hardware task spawning the periodic software tasks of `core` at their release times. It
+    /// runs at the highest priority of these tasks so they are released on time.
+    fn releaser(&self, core: u32, irq: &Path, sw_tasks: &[&PeriodicTask]) -> TokenStream {
+        let export = self.backend.export_path();
+        let releaser = releaser_ident(core);
+        let now_fn = now_fn_ident(core);
+        let set_compare_fn = set_compare_fn_ident(core, irq);
+        let priority = sw_tasks
+            .iter()
+            .map(|task| task.priority)
+            .max()
+            .unwrap_or_default();
+        let releases: Vec<_> = sw_tasks
+            .iter()
+            .map(|task| release_ident(&task.ident))
+            .collect();
+        let task_idents = sw_tasks.iter().map(|task| &task.ident);
+        let priority = LitInt::new(&priority.to_string(), Span::call_site());
+        let core = LitInt::new(&core.to_string(), Span::call_site());
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #[task(binds = #irq, priority = #priority, core = #core)]
+            pub struct #releaser;
+
+            impl RticTask for #releaser {
+                fn init() -> Self {
+                    Self
+                }
+
+                fn exec(&mut self) {
+                    let now = #now_fn();
+                    #(
+                        if #releases.release_due(now) && #task_idents::spawn(()).is_err() {
+                            #releases.cancel();
+                        }
+                    )*
+                    #set_compare_fn(#export::earliest(now, &[#(#releases.next_release()),*]));
+                }
+            }
+        }
+    }
+}
+
+/// Call the start function of `core` once the `#[init]` function body is done. The body is run as
+/// a closure so that its `return`s still reach the start function.
+fn start_after_init(init_fn: &mut ItemFn, core: u32) {
+    let start_fn = start_fn_ident(core);
+    let block = &init_fn.block;
+    let closure = match &init_fn.sig.output {
+        ReturnType::Default => quote!(move || #block),
+        ReturnType::Type(_, ty) => quote!(move || -> #ty #block),
+    };
+    init_fn.block = parse_quote!({
+        let __rticx_init_result = (#closure)();
+        #start_fn();
+        __rticx_init_result
+    });
+}
@@ -0,0 +1,164 @@
+mod codegen;
+pub mod parse;
+
+use crate::periodic_pass::codegen::CodeGen;
+pub use crate::periodic_pass::parse::App;
+use crate::periodic_pass::parse::ast::TaskKind;
+use proc_macro2::TokenStream;
+use rticx_core::parse_utils::{RticAttr, Time};
+use rticx_core::{InfoBus, RticPass};
+use syn::ItemMod;
+
+/// Releases tasks given a `period` and counts their overruns.
+///
+/// Hardware tasks are released by a timer compare triggering their bound interrupt, which is moved
+/// one period ahead at the start of every job. Software tasks are spawned by a release handler
+/// generated for every core, bound to the release timer interrupt of the backend. The first release
+/// happens one period after the end of `#[init]` and every following one is a whole number of
+/// periods later, whatever the release latency.
+///
+/// The pass must run before the software pass, which then sees the `spawn` calls of the release
+/// handlers.
+pub struct PeriodicPass {
+    backend: Box<dyn PeriodicPassBackend>,
+}
+
+impl PeriodicPass {
+    pub fn new<T: PeriodicPassBackend + 'static>(backend: T) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+}
+
+impl RticPass for PeriodicPass {
+    fn subscribe(&mut self, info_bus: InfoBus) {
+        self.backend.subscribe(info_bus);
+    }
+
+    fn run_pass(&self, args: TokenStream, app_mod: ItemMod) -> syn::Result<(TokenStream, ItemMod)> {
+        let params = RticAttr::parse_from_tokens(args.clone())?;
+        let mut parsed = App::parse(&params, app_mod)?;
+
+        self.analyze(&mut parsed)?;
+
+        let code = CodeGen::new(parsed, self.backend.as_ref()).run();
+        Ok((args, code))
+    }
+
+    fn pass_name(&self) -> &str {
+        "periodic_pass"
+    }
+}
+
+impl PeriodicPass {
+    /// Convert the periods to timer ticks and check the backend can release every task
+    pub fn analyze(&self, app: &mut App) -> syn::Result<()> {
+        for task in app.tasks.iter_mut() {
+            let ticks = match task.period {
+                Time::Plain(ticks) => ticks,
+                Time::Nanos(nanos) => {
+                    let Some(hz) = self.backend.tick_hz(task.core) else {
+                        return Err(syn::Error::new(
+                            task.period_span,
+                            format!(
+                                "The timer frequency of core {} is unknown on this platform, give the period of task `{}` in timer ticks.",
+                                task.core, task.ident
+                            ),
+                        ));
+                    };
+                    (nanos as u128 * hz as u128 / 1_000_000_000) as u64
+                }
+            };
+            if ticks == 0 || ticks > i32::MAX as u64 {
+                return Err(syn::Error::new(
+                    task.period_span,
+                    format!(
+                        "The period of task `{}` is {ticks} timer ticks, it must be between 1 and {} ticks.",
+                        task.ident,
+                        i32::MAX
+                    ),
+                ));
+            }
+            task.ticks = ticks as u32;
+
+            if task.kind == TaskKind::Software
+                && self.backend.release_timer_interrupt(task.core).is_none()
+            {
+                return Err(syn::Error::new(
+                    task.period_span,
+                    format!(
+                        "Core {} has no release timer on this platform, periodic software task `{}` can't be released. Make it a hardware task bound to a timer interrupt instead.",
+                        task.core, task.ident
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interface for providing the hardware-specific backend needed by the
+/// periodic tasks compilation pass.
+///
+/// Every core with periodic tasks needs a free running 32-bit timer, read by the `now` function,
+/// and one compare channel per interrupt releasing tasks: the bound interrupt of every periodic
+/// hardware task, and the release timer interrupt for periodic software tasks.
+pub trait PeriodicPassBackend {
+    /// Path to the re-exported `rticx_periodic_pass::export` module.
+    ///
+    /// Typical implementation for a distribution:
+    /// ```ignore
+    /// fn export_path(&self) -> syn::Path {
+    ///     parse_quote!(rticx_atalanta::export::periodic)
+    /// }
+    /// ```
+    fn export_path(&self) -> syn::Path;
+
+    /// Frequency of the timer of `core` in Hz, needed for periods given as durations.
+    ///
+    /// Return `None` if the frequency is not known at compile time, periods then have to be given
+    /// in timer ticks.
+    fn tick_hz(&self, _core: u32) -> Option<u64> {
+        None
+    }
+
+    /// Statements starting the timer of `core`, run at the end of `#[init]` before the first
+    /// release is programmed.
+    fn start_timer(&self, _core: u32) -> Option<TokenStream> {
+        None
+    }
+
+    /// Body of the function reading the timer of `core`.
+    ///
+    /// # Contract
+    /// * The generated function takes no argument and returns the current timer value as `u32`.
+    /// * The timer must count up and wrap around at `u32::MAX`.
+    /// * Do NOT change the function signature.
+    fn generate_now_fn(&self, core: u32, empty_body_fn: syn::ItemFn) -> syn::ItemFn;
+
+    /// Body of the function programming the timer compare that pends `irq` on `core`.
+    ///
+    /// # Contract
+    /// * The generated function takes a single `instant: u32` argument, the timer value at which
+    ///   `irq` must be pended. It is always in the future when called.
+    /// * The compare fires once, it is reprogrammed for every release.
+    /// * Do NOT change the function signature.
+    fn generate_set_compare_fn(
+        &self,
+        core: u32,
+        irq: &syn::Path,
+        empty_body_fn: syn::ItemFn,
+    ) -> syn::ItemFn;
+
+    /// Interrupt of the compare channel releasing the periodic software tasks of `core`.
+    ///
+    /// Return `None` if there is no such channel, periodic software tasks are then rejected.
+    fn release_timer_interrupt(&self, _core: u32) -> Option<syn::Path> {
+        None
+    }
+
+    /// Subscribe to info_bus
+    /// This method is guaranteed to be called before any other methods in this trait.
+    fn subscribe(&mut self, _info_bus: InfoBus) {}
+}
@@ -0,0 +1,82 @@
+use proc_macro2::{Ident, Span};
+use rticx_core::parse_utils::{RticAttr, Time, parse_time};
+use syn::{Expr, ItemStruct, Lit, spanned::Spanned};
+
+/// Whether the periodic task is a hardware task bound to a timer interrupt or a software task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Hardware,
+    Software,
+}
+
+#[derive(Debug)]
+pub struct PeriodicTask {
+    pub ident: Ident,
+    pub kind: TaskKind,
+    /// Core the task runs on, `0` unless explicitly set
+    pub core: u32,
+    pub priority: u16,
+    /// Interrupt of a hardware task, which the timer compare of the task has to trigger
+    pub binds: Option<syn::Path>,
+    /// A plain number of timer ticks, or a duration
+    pub period: Time,
+    /// Span of the `period` argument, used for error reporting
+    pub period_span: Span,
+    /// Period in timer ticks, computed during analysis
+    pub ticks: u32,
+}
+
+impl PeriodicTask {
+    /// Parse the task attribute of `task_struct`, returns `None` for tasks without a `period`
+    pub fn from_struct(
+        kind: TaskKind,
+        task_struct: &ItemStruct,
+        attr_idx: usize,
+    ) -> syn::Result<Option<Self>> {
+        let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
+        let ident = task_struct.ident.clone();
+        let Some(period_expr) = params.elements.get("period") else {
+            return Ok(None);
+        };
+        let period = parse_time("period", period_expr, None)?;
+        if period.value() == 0 {
+            return Err(syn::Error::new(
+                period_expr.span(),
+                format!("The period of task `{ident}` must be greater than zero."),
+            ));
+        }
+
+        let int_arg = |name: &str| match params.elements.get(name) {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => int.base10_parse().map(Some),
+            _ => Ok(None),
+        };
+        let core = int_arg("core")?.unwrap_or_default();
+        let priority = int_arg("priority")?.unwrap_or_default() as u16;
+
+        let binds = match params.elements.get("binds") {
+            Some(Expr::Path(path)) => Some(path.path.clone()),
+            _ => None,
+        };
+        if kind == TaskKind::Hardware && binds.is_none() {
+            return Err(syn::Error::new(
+                task_struct.attrs[attr_idx].span(),
+                format!(
+                    "Periodic task `{ident}` must bind the interrupt of the timer that releases it, or be a `sw_task`."
+                ),
+            ));
+        }
+
+        Ok(Some(Self {
+            ident,
+            kind,
+            core,
+            priority,
+            binds,
+            period,
+            period_span: period_expr.span(),
+            ticks: 0,
+        }))
+    }
+}
@@ -0,0 +1,133 @@
+use super::parse::ast::{PeriodicTask, TaskKind};
+use proc_macro2::Ident;
+use rticx_core::parse_utils::RticAttr;
+use syn::{Expr, ImplItem, Item, ItemFn, ItemImpl, ItemMod, Lit, Type, Visibility};
+
+pub mod ast;
+
+/// Type to represent an RTICX application for the periodic tasks pass
+pub struct App {
+    pub mod_visibility: Visibility,
+    pub mod_ident: Ident,
+
+    pub tasks: Vec<PeriodicTask>,
+    /// All the items of the application. Periodic tasks and their `impl` blocks stay in place,
+    /// codegen only rewrites the `exec` and `#[init]` functions.
+    pub items: Vec<Item>,
+}
+
+impl App {
+    pub fn parse(_params: &RticAttr, mut app_mod: ItemMod) -> syn::Result<Self> {
+        let items = app_mod.content.take().unwrap_or_default().1;
+        let mut tasks = Vec::new();
+
+        for item in items.iter() {
+            let Item::Struct(strct) = item else {
+                continue;
+            };
+            let task = if let Some(attr_idx) = find_attr(&strct.attrs, "task") {
+                PeriodicTask::from_struct(TaskKind::Hardware, strct, attr_idx)?
+            } else if let Some(attr_idx) = find_attr(&strct.attrs, "sw_task") {
+                PeriodicTask::from_struct(TaskKind::Software, strct, attr_idx)?
+            } else {
+                None
+            };
+            tasks.extend(task);
+        }
+
+        for task in tasks.iter() {
+            let Some(task_impl) = items.iter().find_map(|item| match item {
+                Item::Impl(impl_)
+                    if impl_self_ident(impl_) == Some(&task.ident) && exec_fn(impl_).is_some() =>
+                {
+                    Some(impl_)
+                }
+                _ => None,
+            }) else {
+                return Err(syn::Error::new(
+                    task.ident.span(),
+                    format!(
+                        "Periodic task `{}` has no `exec` function, implement the task trait for it.",
+                        task.ident
+                    ),
+                ));
+            };
+
+            // the release handler spawns software tasks without any input
+            if task.kind == TaskKind::Software {
+                let spawn_input = task_impl.items.iter().find_map(|item| match item {
+                    ImplItem::Type(ty) if ty.ident == "SpawnInput" => Some(&ty.ty),
+                    _ => None,
+                });
+                if let Some(ty) = spawn_input
+                    && !matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+                {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        format!(
+                            "Periodic software task `{}` is spawned by its release timer, its `SpawnInput` must be `()`.",
+                            task.ident
+                        ),
+                    ));
+                }
+            }
+
+            if !items
+                .iter()
+                .any(|item| matches!(item, Item::Fn(f) if init_core(f) == Some(task.core)))
+            {
+                return Err(syn::Error::new(
+                    task.period_span,
+                    format!(
+                        "Periodic task `{}` is started at the end of `#[init]`, but core {} has no `#[init]` function.",
+                        task.ident, task.core
+                    ),
+                ));
+            }
+        }
+
+        Ok(Self {
+            mod_ident: app_mod.ident,
+            mod_visibility: app_mod.vis,
+            tasks,
+            items,
+        })
+    }
+}
+
+/// Returns the index of the `attr_name` attribute if found in the attribute list
+pub(crate) fn find_attr(attrs: &[syn::Attribute], attr_name: &str) -> Option<usize> {
+    attrs.iter().position(|attr| {
+        let path = attr.meta.path();
+        path.segments.len() == 1 && path.segments[0].ident == attr_name
+    })
+}
+
+/// Core of an `#[init]` function, `None` if `init_fn` is not one
+pub(crate) fn init_core(init_fn: &ItemFn) -> Option<u32> {
+    let attr_idx = find_attr(&init_fn.attrs, "init")?;
+    let params = RticAttr::parse_from_attr(&init_fn.attrs[attr_idx]).ok()?;
+    match params.elements.get("core") {
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(int), ..
+        })) => int.base10_parse().ok(),
+        _ => Some(0),
+    }
+}
+
+/// The type an `impl` block is written for, when it is a plain identifier
+pub(crate) fn impl_self_ident(impl_: &ItemImpl) -> Option<&Ident> {
+    match impl_.self_ty.as_ref() {
+        Type::Path(path) => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// The `exec` function of a task trait implementation
+pub(crate) fn exec_fn(impl_: &ItemImpl) -> Option<&syn::ImplItemFn> {
+    impl_.trait_.as_ref()?;
+    impl_.items.iter().find_map(|item| match item {
+        ImplItem::Fn(f) if f.sig.ident == "exec" => Some(f),
+        _ => None,
+    })
+}
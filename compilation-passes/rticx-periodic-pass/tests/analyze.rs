@@ -0,0 +1,91 @@
+//! Integration tests for the analysis phase of `rticx-periodic-pass`.
+
+use quote::quote;
+
+mod common;
+
+use common::{MockPeriodicBackend, analyze, assert_err_contains};
+
+#[test]
+fn analyze_converts_periods_to_ticks() {
+    let app = analyze(MockPeriodicBackend::default(), common::periodic_app_items())
+        .expect("analysis succeeds");
+
+    assert_eq!(app.tasks[0].ticks, 1000);
+    // 5 ms at 1 MHz
+    assert_eq!(app.tasks[1].ticks, 5000);
+}
+
+#[test]
+fn analyze_rejects_duration_without_tick_frequency() {
+    let result = analyze(
+        MockPeriodicBackend {
+            tick_hz: None,
+            release_timer: true,
+        },
+        quote! {
+            #[init]
+            fn init() {}
+
+            #[task(binds = TIMER0, period = 1.ms())]
+            struct Sampler;
+
+            impl RticTask for Sampler {
+                fn init() -> Self { Sampler }
+                fn exec(&mut self) {}
+            }
+        },
+    );
+    assert_err_contains(result, "timer frequency of core 0 is unknown");
+}
+
+#[test]
+fn analyze_rejects_periods_out_of_range() {
+    let task = |period| {
+        quote! {
+            #[init]
+            fn init() {}
+
+            #[task(binds = TIMER0, period = #period)]
+            struct Sampler;
+
+            impl RticTask for Sampler {
+                fn init() -> Self { Sampler }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+
+    // shorter than a tick
+    let result = analyze(MockPeriodicBackend::default(), task(quote!(100.ns())));
+    assert_err_contains(result, "is 0 timer ticks");
+
+    // does not fit half of the timer range
+    let result = analyze(MockPeriodicBackend::default(), task(quote!(3000.s())));
+    assert_err_contains(result, "must be between 1 and 2147483647 ticks");
+}
+
+#[test]
+fn analyze_rejects_sw_task_without_release_timer() {
+    let result = analyze(
+        MockPeriodicBackend {
+            tick_hz: Some(1_000_000),
+            release_timer: false,
+        },
+        quote! {
+            #[init]
+            fn init() {}
+
+            #[sw_task(priority = 1, period = 100)]
+            struct Logger;
+
+            impl RticSwTask for Logger {
+                type InitArgs = ();
+                type SpawnInput = ();
+                fn init(_: ()) -> Self { Logger }
+                fn exec(&mut self, _: ()) {}
+            }
+        },
+    );
+    assert_err_contains(result, "Core 0 has no release timer");
+}
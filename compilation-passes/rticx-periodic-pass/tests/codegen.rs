@@ -0,0 +1,185 @@
+//! Integration tests for the codegen phase of `rticx-periodic-pass`.
+//!
+//! These run the full `PeriodicPass::run_pass` pipeline (parse + analysis +
+//! codegen) and verify that the expanded `ItemMod` contains the expected
+//! sections, each built with `quote!{...}` and searched inside the generated
+//! `.to_string()`.
+
+use quote::quote;
+use rticx_core::RticPass;
+use rticx_periodic_pass::PeriodicPass;
+
+mod common;
+
+use common::{MockPeriodicBackend, app_mod, assert_section_present, mod_to_string};
+
+/// Run the periodic pass end-to-end and return the generated module string.
+fn run_pass(app_mod: syn::ItemMod) -> String {
+    let pass = PeriodicPass::new(MockPeriodicBackend::default());
+    let (_, module) = pass
+        .run_pass(common::single_core_args(), app_mod)
+        .expect("pass succeeds");
+    mod_to_string(&module)
+}
+
+#[test]
+fn codegen_expands_periodic_app() {
+    let generated = run_pass(app_mod(common::periodic_app_items()));
+
+    // ---- release state ----
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_periodic_release_Sampler: rticx::export::periodic::Release =
+                rticx::export::periodic::Release::new(1000u32);
+        },
+        "hardware task release",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_periodic_release_Logger: rticx::export::periodic::Release =
+                rticx::export::periodic::Release::new(5000u32);
+        },
+        "software task release, converted to ticks",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            impl Sampler {
+                #[doc = r" Number of jobs of this periodic task that were still running at their next release"]
+                pub fn overruns() -> u32 {
+                    __rticx_periodic_release_Sampler.overruns()
+                }
+            }
+        },
+        "overrun counter",
+    );
+
+    // ---- jobs ----
+    assert_section_present(
+        &generated,
+        quote! {
+            fn exec(&mut self) {
+                let __rticx_job = rticx::export::periodic::Job::hardware(
+                    &__rticx_periodic_release_Sampler,
+                    __rticx_periodic_now_core0,
+                    __rticx_periodic_set_compare_core0_TIMER0
+                );
+                sample();
+            }
+        },
+        "hardware task job",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            fn exec(&mut self, _: ()) {
+                let __rticx_job = rticx::export::periodic::Job::software(
+                    &__rticx_periodic_release_Logger,
+                    __rticx_periodic_now_core0
+                );
+                log();
+            }
+        },
+        "software task job",
+    );
+
+    // ---- timer functions from the backend ----
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn __rticx_periodic_now_core0() -> u32 { mock_now() }
+        },
+        "now function",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn __rticx_periodic_set_compare_core0_TIMER_RELEASE(instant: u32) {
+                mock_set_compare(TIMER_RELEASE, instant);
+            }
+        },
+        "release timer compare function",
+    );
+
+    // ---- start after init ----
+    // (the closure bars are written apart as syn prints them)
+    assert_section_present(
+        &generated,
+        quote! {
+            fn init() {
+                let __rticx_init_result = (move | | {
+                    setup();
+                })();
+                __rticx_periodic_start_core0();
+                __rticx_init_result
+            }
+        },
+        "init followed by the start function",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn __rticx_periodic_start_core0() {
+                let now = __rticx_periodic_now_core0();
+                __rticx_periodic_release_Sampler.start(now);
+                __rticx_periodic_set_compare_core0_TIMER0(
+                    __rticx_periodic_release_Sampler.next_release()
+                );
+                __rticx_periodic_release_Logger.start(now);
+                __rticx_periodic_set_compare_core0_TIMER_RELEASE(
+                    rticx::export::periodic::earliest(
+                        now,
+                        &[__rticx_periodic_release_Logger.next_release()]
+                    )
+                );
+            }
+        },
+        "start function",
+    );
+
+    // ---- release handler of the software tasks ----
+    assert_section_present(
+        &generated,
+        quote! {
+            #[task(binds = TIMER_RELEASE, priority = 2, core = 0)]
+            pub struct __rticx_periodic_releaser_core0;
+        },
+        "release handler task",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            if __rticx_periodic_release_Logger.release_due(now) && Logger::spawn(()).is_err() {
+                __rticx_periodic_release_Logger.cancel();
+            }
+        },
+        "release handler spawn",
+    );
+}
+
+#[test]
+fn codegen_without_periodic_tasks_leaves_init_untouched() {
+    let generated = run_pass(app_mod(quote! {
+        #[init]
+        fn init() {
+            setup();
+        }
+
+        #[task(binds = UART0, priority = 1)]
+        struct Uart;
+    }));
+
+    assert_section_present(
+        &generated,
+        quote! {
+            #[init]
+            fn init() {
+                setup();
+            }
+        },
+        "untouched init",
+    );
+    assert!(!generated.contains("__rticx_periodic"));
+}
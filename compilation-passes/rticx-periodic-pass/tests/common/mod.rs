@@ -0,0 +1,167 @@
+//! Shared helpers for the `rticx-periodic-pass` integration tests.
+
+#![allow(dead_code)]
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use quote::quote;
+use rticx_core::parse_utils::RticAttr;
+use rticx_periodic_pass::{App, PeriodicPass, PeriodicPassBackend};
+use syn::{ItemFn, parse_quote};
+
+/// Single-core macro arguments with one PAC path.
+pub fn single_core_args() -> TokenStream {
+    quote!(device = mypac)
+}
+
+/// Wraps the given `items` tokenstream in `mod app { ... }` and parses it into
+/// an `ItemMod`.
+pub fn app_mod(items: TokenStream) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            #items
+        }
+    }
+}
+
+/// Parse args + items into a periodic pass `App`.
+pub fn parse(items: TokenStream) -> syn::Result<App> {
+    let params = RticAttr::parse_from_tokens(single_core_args()).expect("params parse");
+    App::parse(&params, app_mod(items))
+}
+
+/// Parse items and run the periodic pass analysis with `backend`.
+pub fn analyze(backend: MockPeriodicBackend, items: TokenStream) -> syn::Result<App> {
+    let mut parsed = parse(items).expect("app parse");
+    PeriodicPass::new(backend).analyze(&mut parsed)?;
+    Ok(parsed)
+}
+
+/// The items of an app with one periodic hardware task `Sampler` and one
+/// periodic software task `Logger`, both on core 0.
+pub fn periodic_app_items() -> TokenStream {
+    quote! {
+        #[init]
+        fn init() {
+            setup();
+        }
+
+        #[task(binds = TIMER0, priority = 3, period = 1000)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self {
+                Sampler
+            }
+            fn exec(&mut self) {
+                sample();
+            }
+        }
+
+        #[sw_task(priority = 2, period = 5.ms())]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = ();
+            fn init(_: ()) -> Self {
+                Logger
+            }
+            fn exec(&mut self, _: ()) {
+                log();
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Assertion helpers
+// ---------------------------------------------------------------------------
+
+/// Asserts that `result` is an `Err` whose message contains `substr`, without
+/// requiring the `Ok` variant to implement `Debug`.
+pub fn assert_err_contains<T>(result: syn::Result<T>, substr: &str) {
+    let err = match result {
+        Ok(_) => panic!("expected an error, but parsing/analysis succeeded"),
+        Err(e) => e,
+    };
+    assert!(
+        err.to_string().contains(substr),
+        "expected error to contain {substr:?}, got: {err}"
+    );
+}
+
+/// Asserts that the `expected` tokenstream (rendered to a string) is present
+/// as a contiguous substring of the `generated` string. A `label` is used to
+/// make failures easier to diagnose.
+pub fn assert_section_present(generated: &str, expected: TokenStream, label: &str) {
+    let expected = expected.to_string();
+    assert!(
+        generated.contains(&expected),
+        "missing expected section `{label}` in the generated output\n\
+         expected:\n{expected}\n\n\
+         generated:\n{generated}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Mock PeriodicPassBackend
+// ---------------------------------------------------------------------------
+
+/// A mock `PeriodicPassBackend` used by the analysis and codegen tests.
+///
+/// The timer runs at `tick_hz` when known, and periodic software tasks are
+/// released by `TIMER_RELEASE` when `release_timer` is `true`.
+pub struct MockPeriodicBackend {
+    pub tick_hz: Option<u64>,
+    pub release_timer: bool,
+}
+
+impl Default for MockPeriodicBackend {
+    fn default() -> Self {
+        Self {
+            tick_hz: Some(1_000_000),
+            release_timer: true,
+        }
+    }
+}
+
+impl PeriodicPassBackend for MockPeriodicBackend {
+    fn export_path(&self) -> syn::Path {
+        parse_quote!(rticx::export::periodic)
+    }
+
+    fn tick_hz(&self, _core: u32) -> Option<u64> {
+        self.tick_hz
+    }
+
+    fn generate_now_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block = Box::new(parse_quote!({ mock_now() }));
+        empty_body_fn
+    }
+
+    fn generate_set_compare_fn(
+        &self,
+        _core: u32,
+        irq: &syn::Path,
+        mut empty_body_fn: ItemFn,
+    ) -> ItemFn {
+        empty_body_fn.block = Box::new(parse_quote!({
+            mock_set_compare(#irq, instant);
+        }));
+        empty_body_fn
+    }
+
+    fn release_timer_interrupt(&self, _core: u32) -> Option<syn::Path> {
+        self.release_timer.then(|| parse_quote!(TIMER_RELEASE))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Utility: stringify an ItemMod for codegen assertions
+// ---------------------------------------------------------------------------
+
+/// Convenience: render an `ItemMod` into its token-stream string form.
+pub fn mod_to_string(item_mod: &syn::ItemMod) -> String {
+    item_mod.to_token_stream().to_string()
+}
@@ -0,0 +1,158 @@
+//! Integration tests for the parse phase of `rticx-periodic-pass`.
+
+use quote::quote;
+use rticx_core::parse_utils::Time;
+use rticx_periodic_pass::periodic_pass::parse::ast::TaskKind;
+
+mod common;
+
+use common::{assert_err_contains, parse};
+
+#[test]
+fn parse_periodic_tasks() {
+    let app = common::parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(binds = TIMER0, priority = 3, period = 1000)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self { Sampler }
+            fn exec(&mut self) {}
+        }
+
+        #[sw_task(priority = 2, period = 5.ms())]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = ();
+            fn init(_: ()) -> Self { Logger }
+            fn exec(&mut self, _: ()) {}
+        }
+
+        #[task(binds = UART0, priority = 1)]
+        struct NotPeriodic;
+    })
+    .expect("app parses");
+
+    assert_eq!(app.tasks.len(), 2);
+    let sampler = &app.tasks[0];
+    assert_eq!(sampler.ident, "Sampler");
+    assert_eq!(sampler.kind, TaskKind::Hardware);
+    assert_eq!(sampler.priority, 3);
+    assert_eq!(sampler.period, Time::Plain(1000));
+    let logger = &app.tasks[1];
+    assert_eq!(logger.kind, TaskKind::Software);
+    assert_eq!(logger.period, Time::Nanos(5_000_000));
+}
+
+#[test]
+fn parse_duration_units() {
+    for (period, nanos) in [
+        (quote!(7.ns()), 7),
+        (quote!(7.us()), 7_000),
+        (quote!(7.millis()), 7_000_000),
+        (quote!(7.s()), 7_000_000_000),
+    ] {
+        let app = parse(quote! {
+            #[init]
+            fn init() {}
+
+            #[task(binds = TIMER0, period = #period)]
+            struct Sampler;
+
+            impl RticTask for Sampler {
+                fn init() -> Self { Sampler }
+                fn exec(&mut self) {}
+            }
+        })
+        .expect("app parses");
+        assert_eq!(app.tasks[0].period, Time::Nanos(nanos));
+    }
+}
+
+#[test]
+fn parse_rejects_unknown_unit() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(binds = TIMER0, period = 7.minutes())]
+        struct Sampler;
+    });
+    assert_err_contains(result, "Unknown time unit `minutes`");
+}
+
+#[test]
+fn parse_rejects_zero_period() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(binds = TIMER0, period = 0)]
+        struct Sampler;
+    });
+    assert_err_contains(result, "must be greater than zero");
+}
+
+#[test]
+fn parse_rejects_hw_task_without_binds() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(period = 100)]
+        struct Sampler;
+    });
+    assert_err_contains(result, "must bind the interrupt of the timer");
+}
+
+#[test]
+fn parse_rejects_task_without_exec() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(binds = TIMER0, period = 100)]
+        struct Sampler;
+    });
+    assert_err_contains(result, "has no `exec` function");
+}
+
+#[test]
+fn parse_rejects_sw_task_with_spawn_input() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[sw_task(priority = 1, period = 100)]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Logger }
+            fn exec(&mut self, _: u32) {}
+        }
+    });
+    assert_err_contains(result, "its `SpawnInput` must be `()`");
+}
+
+#[test]
+fn parse_rejects_core_without_init() {
+    let result = parse(quote! {
+        #[init]
+        fn init() {}
+
+        #[task(binds = TIMER1, period = 100, core = 1)]
+        struct Sampler;
+
+        impl RticTask for Sampler {
+            fn init() -> Self { Sampler }
+            fn exec(&mut self) {}
+        }
+    });
+    assert_err_contains(result, "core 1 has no `#[init]` function");
+}
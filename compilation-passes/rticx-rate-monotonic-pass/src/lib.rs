@@ -0,0 +1,8 @@
+// Enable the `no_std` attribute if `no_std` is enabled
+#![cfg_attr(not(feature = "proc-macro"), no_std)]
+
+#[cfg(feature = "proc-macro")]
+pub mod rate_monotonic_pass;
+
+#[cfg(feature = "proc-macro")]
+pub use rate_monotonic_pass::*;
@@ -0,0 +1,29 @@
+//! Utilisation based schedulability tests for rate-monotonic priorities.
+//!
+//! Both tests are sufficient but not necessary: a task set failing them may still meet all its
+//! deadlines, which then has to be shown by a response time analysis.
+
+/// Slack for floating point rounding, so that task sets exactly at a bound are accepted
+const EPSILON: f64 = 1e-9;
+
+/// Liu–Layland bound `n (2^(1/n) - 1)` on the total utilisation of `n` tasks
+pub fn liu_layland_bound(n: usize) -> f64 {
+    if n == 0 {
+        return 1.0;
+    }
+    let n = n as f64;
+    n * (2f64.powf(1.0 / n) - 1.0)
+}
+
+/// Product of `U_i + 1` over all tasks, which the hyperbolic bound requires to be at most `2`
+pub fn hyperbolic_product(utilisations: &[f64]) -> f64 {
+    utilisations.iter().map(|u| u + 1.0).product()
+}
+
+/// Whether the tasks with the given utilisations pass the Liu–Layland or the hyperbolic bound.
+/// The hyperbolic bound accepts every task set the Liu–Layland bound does, and more.
+pub fn schedulable(utilisations: &[f64]) -> bool {
+    let total: f64 = utilisations.iter().sum();
+    total <= liu_layland_bound(utilisations.len()) + EPSILON
+        || hyperbolic_product(utilisations) <= 2.0 + EPSILON
+}
@@ -0,0 +1,131 @@
+pub mod bounds;
+pub mod codegen;
+pub mod parse;
+pub use parse::App;
+
+use codegen::CodeGen;
+use proc_macro2::TokenStream;
+pub use rticx_core::MostUrgent;
+use rticx_core::RticPass;
+use rticx_core::parse_utils::{RticAttr, Time};
+use syn::ItemMod;
+
+pub struct RateMonotonicPass {
+    max_priority: u16,
+    most_urgent: MostUrgent,
+}
+
+impl RateMonotonicPass {
+    #[allow(clippy::new_without_default)]
+    pub fn new(max_priority: u16) -> Self {
+        Self {
+            max_priority,
+            most_urgent: MostUrgent::default(),
+        }
+    }
+
+    /// Set the end of the priority range that is given to the shortest periods
+    pub fn with_most_urgent(mut self, most_urgent: MostUrgent) -> Self {
+        self.most_urgent = most_urgent;
+        self
+    }
+}
+
+impl RticPass for RateMonotonicPass {
+    fn subscribe(&mut self, _info_bus: rticx_core::InfoBus) {}
+    fn run_pass(&self, args: TokenStream, app_mod: ItemMod) -> syn::Result<(TokenStream, ItemMod)> {
+        let params = RticAttr::parse_from_tokens(args.clone())?;
+
+        let mut parsed = App::parse(&params, app_mod)?;
+
+        self.analyze(&mut parsed)?;
+
+        let code = CodeGen::new(parsed).run();
+        Ok((args, code))
+    }
+
+    fn pass_name(&self) -> &str {
+        "rate_monotonic_pass"
+    }
+}
+
+impl RateMonotonicPass {
+    /// Rate-monotonic priority assignment, done separately for every core: tasks with the shortest
+    /// period get the most urgent level and tasks with equal periods share a level. Tasks with a
+    /// `priority` or a deadline keep it. When every periodic task of a core has a `wcet`, the
+    /// core must pass the Liu–Layland or the hyperbolic bound, see [`bounds`].
+    pub fn analyze(&self, app: &mut App) -> syn::Result<()> {
+        let mut cores: Vec<u32> = app.tasks.iter().map(|t| t.core).collect();
+        cores.sort_unstable();
+        cores.dedup();
+
+        for core in cores {
+            let mut tasks: Vec<_> = app
+                .tasks
+                .iter_mut()
+                .filter(|t| t.core == core && t.period.is_some() && !t.keeps_priority)
+                .collect();
+            tasks.sort_by_key(|t| t.period);
+            let (Some(first), Some(last)) = (tasks.first(), tasks.last()) else {
+                continue;
+            };
+
+            // plain numbers and durations can't be compared
+            if first.period.map(Time::is_duration) != last.period.map(Time::is_duration) {
+                return Err(syn::Error::new(
+                    last.period_span,
+                    format!(
+                        "The period of task `{}` is a duration while the period of task `{}` on the same core is a plain number, use the same kind of period for every task of core {core}.",
+                        last.task_struct.ident, first.task_struct.ident
+                    ),
+                ));
+            }
+
+            let mut periods: Vec<_> = tasks.iter().map(|t| t.period).collect();
+            periods.dedup();
+            if periods.len() > self.max_priority as usize {
+                return Err(syn::Error::new(
+                    tasks[tasks.len() - 1].period_span,
+                    format!(
+                        "Core {core} has {} distinct periods but only {} priority levels are available on this platform. Give tasks with similar periods the same period, or set the `priority` of some tasks.",
+                        periods.len(),
+                        self.max_priority
+                    ),
+                ));
+            }
+
+            let utilisations: Option<Vec<f64>> = tasks
+                .iter()
+                .map(|t| Some(t.wcet?.value() as f64 / t.period?.value() as f64))
+                .collect();
+            if let Some(utilisations) = utilisations
+                && !bounds::schedulable(&utilisations)
+            {
+                return Err(syn::Error::new(
+                    tasks[tasks.len() - 1].period_span,
+                    format!(
+                        "The tasks of core {core} have a total utilisation of {:.3}, above the Liu–Layland bound of {:.3} for {} tasks, and fail the hyperbolic bound with a product of {:.3} (at most 2). Reduce the `wcet` of some tasks or lengthen their periods.",
+                        utilisations.iter().sum::<f64>(),
+                        bounds::liu_layland_bound(utilisations.len()),
+                        utilisations.len(),
+                        bounds::hyperbolic_product(&utilisations),
+                    ),
+                ));
+            }
+
+            let levels = periods.len();
+            for task in tasks {
+                let rank = periods
+                    .iter()
+                    .position(|p| *p == task.period)
+                    .unwrap_or_default();
+                let priority = match self.most_urgent {
+                    MostUrgent::Highest => levels - rank,
+                    MostUrgent::Lowest => rank + 1,
+                };
+                task.set_priority(priority as u16);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use proc_macro2::Span;
+use rticx_core::parse_utils::{RticAttr, Time, parse_time};
+use syn::{Expr, ItemStruct, Lit, parse_quote, spanned::Spanned};
+
+/// Arguments that leave the priority of a task to another pass or to the user
+const PRIORITY_ARGS: [&str; 6] = [
+    "priority",
+    "deadline",
+    "deadline_ns",
+    "deadline_us",
+    "deadline_ms",
+    "deadline_s",
+];
+
+#[derive(Debug)]
+pub struct RticTask {
+    pub params: RticAttr,
+    pub attr_idx: usize,
+    pub task_struct: ItemStruct,
+    /// Core the task runs on, `0` unless explicitly set
+    pub core: u32,
+    /// Period or minimum inter-arrival time
+    pub period: Option<Time>,
+    /// Span of the `period` argument, used for error reporting
+    pub period_span: Span,
+    /// Optional worst case execution time, in the same kind of unit as the period
+    pub wcet: Option<Time>,
+    /// The task has a `priority` or a deadline, its priority is left to the user or to another
+    /// pass and it is not part of the rate-monotonic ordering
+    pub keeps_priority: bool,
+    /// Priority computed from the period during analysis
+    pub priority: Option<u16>,
+}
+
+impl RticTask {
+    pub fn from_struct((task_struct, attr_idx): (ItemStruct, usize)) -> syn::Result<Self> {
+        let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
+        let task_name = &task_struct.ident;
+
+        let core = match params.elements.get("core") {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => int.base10_parse()?,
+            _ => 0,
+        };
+
+        let keeps_priority = PRIORITY_ARGS
+            .iter()
+            .any(|arg| params.elements.contains_key(*arg));
+
+        let mut period_span = task_struct.attrs[attr_idx].span();
+        let period = match params.elements.get("period") {
+            Some(expr) => {
+                let period = parse_time("period", expr, None)?;
+                if period.value() == 0 {
+                    return Err(syn::Error::new(
+                        expr.span(),
+                        format!("The period of task `{task_name}` must be greater than zero."),
+                    ));
+                }
+                period_span = expr.span();
+                Some(period)
+            }
+            None => None,
+        };
+
+        let wcet = match params.elements.get("wcet") {
+            Some(expr) => {
+                let wcet = parse_time("wcet", expr, None)?;
+                if period.is_some_and(|p| p.is_duration() != wcet.is_duration()) {
+                    return Err(syn::Error::new(
+                        expr.span(),
+                        format!(
+                            "`wcet` of task `{task_name}` must be given in the same kind of unit as its period, either both durations or both plain numbers."
+                        ),
+                    ));
+                }
+                Some(wcet)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            params,
+            attr_idx,
+            task_struct,
+            core,
+            period,
+            period_span,
+            wcet,
+            keeps_priority,
+            priority: None,
+        })
+    }
+
+    /// Add `priority = N` to the task attribute. The `period` is kept for passes that generate
+    /// the periodic release of the task.
+    pub fn set_priority(&mut self, priority: u16) {
+        let expr: syn::Expr = parse_quote! { #priority };
+        let _ = self.params.elements.insert("priority".into(), expr);
+        self.priority = Some(priority);
+    }
+}
@@ -0,0 +1,203 @@
+//! Integration tests for the analysis phase of `rticx-rate-monotonic-pass`.
+//!
+//! These run `App::parse` followed by `RateMonotonicPass::analyze` and inspect the resulting
+//! `App` (positive scenarios) or assert the expected `syn::Error` (negative scenarios).
+
+use quote::quote;
+use rticx_core::parse_utils::RticAttr;
+use rticx_rate_monotonic_pass::rate_monotonic_pass::{App, MostUrgent, RateMonotonicPass, bounds};
+
+mod common;
+
+use common::{analyze, assert_err_contains, multi_core_args, single_core_args};
+
+/// Priority of the task named `name`
+fn priority_of(app: &App, name: &str) -> Option<u16> {
+    app.tasks
+        .iter()
+        .find(|t| t.task_struct.ident == name)
+        .expect("task exists")
+        .priority
+}
+
+/// Parse and analyze a single-core app with a custom pass
+fn analyze_with(pass: RateMonotonicPass, items: proc_macro2::TokenStream) -> syn::Result<App> {
+    let params = RticAttr::parse_from_tokens(single_core_args()).expect("params parse");
+    let mut parsed = App::parse(&params, common::app_mod(items)).expect("app parse");
+    pass.analyze(&mut parsed)?;
+    Ok(parsed)
+}
+
+// ---------------------------------------------------------------------------
+// Positive scenarios: period-to-priority conversion
+// ---------------------------------------------------------------------------
+
+#[test]
+fn analyze_shortest_period_gets_highest_priority() {
+    let items = quote! {
+        #[task(period = 100)]
+        struct Slow;
+        #[task(period = 10)]
+        struct Fast;
+        #[sw_task(period = 50)]
+        struct Medium;
+    };
+    let app = analyze(single_core_args(), items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "Fast"), Some(3));
+    assert_eq!(priority_of(&app, "Medium"), Some(2));
+    assert_eq!(priority_of(&app, "Slow"), Some(1));
+}
+
+#[test]
+fn analyze_equal_periods_share_a_level() {
+    let items = quote! {
+        #[task(period = 2.ms())]
+        struct A;
+        #[task(period = 2000.us())]
+        struct B;
+        #[task(period = 5.ms())]
+        struct C;
+    };
+    let app = analyze(single_core_args(), items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "A"), Some(2));
+    assert_eq!(priority_of(&app, "B"), Some(2));
+    assert_eq!(priority_of(&app, "C"), Some(1));
+}
+
+#[test]
+fn analyze_most_urgent_lowest_reverses_levels() {
+    let items = quote! {
+        #[task(period = 10)]
+        struct Fast;
+        #[task(period = 100)]
+        struct Slow;
+    };
+    let pass = RateMonotonicPass::new(255).with_most_urgent(MostUrgent::Lowest);
+    let app = analyze_with(pass, items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "Fast"), Some(1));
+    assert_eq!(priority_of(&app, "Slow"), Some(2));
+}
+
+#[test]
+fn analyze_tasks_with_priority_deadline_or_no_period_are_untouched() {
+    let items = quote! {
+        #[task(period = 10)]
+        struct Periodic;
+        #[task(period = 5, priority = 7)]
+        struct Fixed;
+        #[task(period = 5, deadline = 5)]
+        struct Dated;
+        #[task(binds = UART0, priority = 1)]
+        struct Aperiodic;
+    };
+    let app = analyze(single_core_args(), items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "Periodic"), Some(1));
+    assert_eq!(priority_of(&app, "Fixed"), None);
+    assert_eq!(priority_of(&app, "Dated"), None);
+    assert_eq!(priority_of(&app, "Aperiodic"), None);
+}
+
+#[test]
+fn analyze_assigns_priorities_per_core() {
+    let items = quote! {
+        #[task(period = 10, core = 0)]
+        struct A0;
+        #[task(period = 20, core = 0)]
+        struct B0;
+        #[task(period = 30, core = 1)]
+        struct A1;
+    };
+    let app = analyze(multi_core_args(2), items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "A0"), Some(2));
+    assert_eq!(priority_of(&app, "B0"), Some(1));
+    assert_eq!(priority_of(&app, "A1"), Some(1));
+}
+
+#[test]
+fn analyze_accepts_task_set_within_hyperbolic_bound() {
+    // U = 0.6 + 0.25 = 0.85 is above the Liu–Layland bound of 0.828 for 2 tasks, but
+    // (1.6)(1.25) = 2 meets the hyperbolic bound
+    let items = quote! {
+        #[task(period = 10, wcet = 6)]
+        struct A;
+        #[task(period = 20, wcet = 5)]
+        struct B;
+    };
+    let app = analyze(single_core_args(), items).expect("analyze succeeds");
+
+    assert_eq!(priority_of(&app, "A"), Some(2));
+    assert_eq!(priority_of(&app, "B"), Some(1));
+}
+
+#[test]
+fn analyze_skips_bound_check_when_a_wcet_is_missing() {
+    let items = quote! {
+        #[task(period = 10, wcet = 9)]
+        struct A;
+        #[task(period = 20)]
+        struct B;
+    };
+    analyze(single_core_args(), items).expect("analyze succeeds");
+}
+
+#[test]
+fn bounds_match_known_values() {
+    assert!((bounds::liu_layland_bound(1) - 1.0).abs() < 1e-12);
+    assert!((bounds::liu_layland_bound(2) - 0.828_427).abs() < 1e-6);
+    assert!((bounds::liu_layland_bound(3) - 0.779_763).abs() < 1e-6);
+    assert!(bounds::schedulable(&[0.6, 0.25]));
+    assert!(!bounds::schedulable(&[0.6, 0.3]));
+}
+
+// ---------------------------------------------------------------------------
+// Negative scenarios
+// ---------------------------------------------------------------------------
+
+#[test]
+fn analyze_errors_when_bounds_fail() {
+    let items = quote! {
+        #[task(period = 10, wcet = 6)]
+        struct A;
+        #[task(period = 20, wcet = 6)]
+        struct B;
+    };
+    assert_err_contains(
+        analyze(single_core_args(), items),
+        "The tasks of core 0 have a total utilisation of 0.900, above the Liu–Layland bound of 0.828 for 2 tasks, and fail the hyperbolic bound with a product of 2.080",
+    );
+}
+
+#[test]
+fn analyze_errors_when_more_distinct_periods_than_levels() {
+    let items = quote! {
+        #[task(period = 10)]
+        struct A;
+        #[task(period = 20)]
+        struct B;
+        #[task(period = 30)]
+        struct C;
+    };
+    assert_err_contains(
+        analyze_with(RateMonotonicPass::new(2), items),
+        "Core 0 has 3 distinct periods but only 2 priority levels are available on this platform.",
+    );
+}
+
+#[test]
+fn analyze_errors_on_mixed_period_kinds() {
+    let items = quote! {
+        #[task(period = 10)]
+        struct Plain;
+        #[task(period = 10.ms())]
+        struct Duration;
+    };
+    assert_err_contains(
+        analyze(single_core_args(), items),
+        "The period of task `Duration` is a duration while the period of task `Plain` on the same core is a plain number",
+    );
+}
@@ -0,0 +1,61 @@
+//! Integration tests for the codegen phase of `rticx-rate-monotonic-pass`.
+//!
+//! These run the full `RateMonotonicPass::run_pass` pipeline (parse + analyze + codegen) and
+//! verify that the generated `ItemMod` contains the expected sections. Because `RticAttr` stores
+//! attribute elements in a `HashMap`, the emitted `#[task(...)]` element ordering is
+//! non-deterministic, so assertions use individual element fragments (e.g. `priority = 1u16`)
+//! rather than the whole attribute.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use rticx_core::RticPass;
+use rticx_rate_monotonic_pass::rate_monotonic_pass::RateMonotonicPass;
+
+mod common;
+
+use common::{app_mod, assert_section_present, mod_to_string, single_core_args};
+
+/// Run the rate-monotonic pass end-to-end and return the generated module string.
+fn run_pass(args: TokenStream, app_mod: syn::ItemMod) -> String {
+    let (_, module) = RateMonotonicPass::new(255)
+        .run_pass(args, app_mod)
+        .expect("pass succeeds");
+    mod_to_string(&module)
+}
+
+#[test]
+fn codegen_injects_priority_and_keeps_period() {
+    let generated = run_pass(
+        single_core_args(),
+        app_mod(quote! {
+            struct Bar;
+            #[task(period = 10, wcet = 2)]
+            struct Fast;
+            #[sw_task(period = 20)]
+            struct Slow;
+        }),
+    );
+
+    assert_section_present(&generated, quote! { struct Bar ; }, "passthrough struct");
+    assert_section_present(&generated, quote! { struct Fast ; }, "task struct");
+    assert_section_present(&generated, quote! { sw_task }, "sw_task attribute name");
+    assert_section_present(&generated, quote! { priority = 2u16 }, "priority of Fast");
+    assert_section_present(&generated, quote! { priority = 1u16 }, "priority of Slow");
+    // the period stays for passes generating the periodic release
+    assert_section_present(&generated, quote! { period = 10 }, "period of Fast");
+    assert_section_present(&generated, quote! { wcet = 2 }, "wcet of Fast");
+}
+
+#[test]
+fn codegen_leaves_fixed_priority_untouched() {
+    let generated = run_pass(
+        single_core_args(),
+        app_mod(quote! {
+            #[task(binds = UART0, priority = 4)]
+            struct Uart;
+        }),
+    );
+
+    assert_section_present(&generated, quote! { priority = 4 }, "user priority");
+    assert!(!generated.contains("u16"));
+}
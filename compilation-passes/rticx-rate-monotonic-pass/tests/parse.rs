@@ -0,0 +1,103 @@
+//! Integration tests for the parse phase of `rticx-rate-monotonic-pass`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use rticx_core::parse_utils::{RticAttr, Time};
+use rticx_rate_monotonic_pass::rate_monotonic_pass::parse::App;
+
+mod common;
+
+use common::{app_mod, assert_err_contains, single_core_args};
+
+/// Builds a params `RticAttr` from a raw args tokenstream
+fn params(args: &TokenStream) -> RticAttr {
+    RticAttr::parse_from_tokens(args.clone()).expect("params parse")
+}
+
+#[test]
+fn parse_task_with_period() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(period = 10)]
+        struct Task1;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    assert_eq!(parsed.tasks.len(), 1);
+    assert_eq!(parsed.tasks[0].period, Some(Time::Plain(10)));
+    assert_eq!(parsed.tasks[0].wcet, None);
+    assert!(!parsed.tasks[0].keeps_priority);
+}
+
+#[test]
+fn parse_sw_task_with_duration_period_and_wcet() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[sw_task(period = 2.ms(), wcet = 150.us(), core = 1)]
+        struct SwTask1;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    let task = &parsed.tasks[0];
+    assert_eq!(task.period, Some(Time::Nanos(2_000_000)));
+    assert_eq!(task.wcet, Some(Time::Nanos(150_000)));
+    assert_eq!(task.core, 1);
+}
+
+#[test]
+fn parse_task_with_priority_or_deadline_keeps_priority() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(period = 10, priority = 3)]
+        struct Fixed;
+        #[task(period = 10, deadline_us = 5)]
+        struct Dated;
+        #[task(binds = UART0)]
+        struct Aperiodic;
+    });
+    let parsed = App::parse(&params(&args), app).expect("parse");
+
+    assert!(parsed.tasks[0].keeps_priority);
+    assert!(parsed.tasks[1].keeps_priority);
+    assert!(!parsed.tasks[2].keeps_priority);
+    assert_eq!(parsed.tasks[2].period, None);
+}
+
+#[test]
+fn parse_task_zero_period_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(period = 0)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "The period of task `T` must be greater than zero.",
+    );
+}
+
+#[test]
+fn parse_task_wcet_unit_mismatch_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(period = 10.ms(), wcet = 2)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "`wcet` of task `T` must be given in the same kind of unit as its period",
+    );
+}
+
+#[test]
+fn parse_task_non_literal_period_errors() {
+    let args = single_core_args();
+    let app = app_mod(quote! {
+        #[task(period = PERIOD)]
+        struct T;
+    });
+    assert_err_contains(
+        App::parse(&params(&args), app),
+        "`period` must be an integer literal or a duration",
+    );
+}
@@ -1 +1,3 @@
 pub use rticx_spsc::Queue;
+#[cfg(target_has_atomic = "ptr")]
+pub use rticx_spsc::mpsc::Queue as MpscQueue;
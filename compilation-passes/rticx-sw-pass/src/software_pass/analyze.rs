@@ -1,7 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 
 use crate::software_pass::parse::{App, SubApp};
 use proc_macro2::Span;
+use rticx_core::parser::ast::TaskArgs;
+use syn::Item;
 
 #[derive(Clone)]
 pub struct Analysis {
@@ -11,84 +13,84 @@ pub struct Analysis {
 
 impl Analysis {
     pub fn run(app: &App) -> syn::Result<Self> {
+        let core_ceilings = core_ceilings(app)?;
         let sub_analysis = app
             .sub_apps
             .iter()
-            .map(SubAnalysis::analyse_subapp)
+            .map(|sub_app| SubAnalysis::analyse_subapp(sub_app, &core_ceilings))
             .collect::<syn::Result<_>>()?;
         Ok(Self { sub_analysis })
     }
 }
 
+/// Highest priority any task of each core runs at, preemption thresholds and instances included.
+///
+/// The callers of `spawn` can't all be found in the application (macros, function pointers, code
+/// outside of the `#[app]` module, ...), so the spawn queues filled by a core are locked up to
+/// this priority: every task of that core is then excluded, whoever calls `spawn`.
+fn core_ceilings(app: &App) -> syn::Result<HashMap<u32, u16>> {
+    let mut ceilings = HashMap::new();
+    let mut raise = |core: u32, priority: u16| {
+        let ceiling = ceilings.entry(core).or_insert(0);
+        *ceiling = (*ceiling).max(priority);
+    };
+    for sub_app in app.sub_apps.iter() {
+        for task in sub_app.sw_tasks.iter().chain(sub_app.mc_sw_tasks.iter()) {
+            raise(
+                sub_app.core,
+                task.params.priority.max(task.params.threshold),
+            );
+        }
+    }
+    for item in app.rest_of_code.iter() {
+        let Item::Struct(strct) = item else {
+            continue;
+        };
+        for attr in strct
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("task"))
+        {
+            let args = TaskArgs::parse(attr.meta.clone())?;
+            raise(args.core, args.priority.max(args.threshold));
+            for instance in args.instances.iter() {
+                raise(args.core, instance.priority);
+            }
+        }
+    }
+    Ok(ceilings)
+}
+
 /// Per-core/Sub application analysis
 #[derive(Debug, Clone)]
 pub struct SubAnalysis {
     pub core: u32,
     /// Maps every group of software tasks to some priority level
-    /// Tasks are identified by their `Ident` (the name of the task struct)
-    pub tasks_priority_map: HashMap<u16, Vec<(syn::Ident, u32)>>,
+    /// Tasks are identified by their `Ident` (the name of the task struct) and carry the list of cores
+    /// allowed to spawn them
+    pub tasks_priority_map: HashMap<u16, Vec<(syn::Ident, Vec<u32>)>>,
     /// Maps every dispatcher to a priority level
     pub dispatcher_priority_map: HashMap<u16, syn::Path>,
+    /// Maps a (priority group, spawning core) pair to the ceiling of the queues that core fills for
+    /// that group, i.e. the highest priority of the tasks of the spawning core.
+    pub spawn_ceilings: HashMap<(u16, u32), u16>,
 }
 
 impl SubAnalysis {
-    fn analyse_subapp(sub_app: &SubApp) -> syn::Result<Self> {
-        // group sw tasks based on their associated priorities
+    fn analyse_subapp(sub_app: &SubApp, core_ceilings: &HashMap<u32, u16>) -> syn::Result<Self> {
+        // group sw tasks based on their associated priorities.
+        // Core-local and multi-core tasks may share a priority level: every spawning core gets its own
+        // ready queue for that level (see `spawners`), so each queue keeps a single producer core.
         let mut sw_tasks_pgroups: HashMap<u16, Vec<_>> =
             HashMap::with_capacity(sub_app.dispatchers.len());
-        for task in sub_app.sw_tasks.iter() {
+        for task in sub_app.sw_tasks.iter().chain(sub_app.mc_sw_tasks.iter()) {
             let task_prio = task.params.priority;
             sw_tasks_pgroups
                 .entry(task_prio)
                 .or_default()
-                .push((task.name().clone(), sub_app.core /* core local tasks*/));
+                .push((task.name().clone(), task.params.spawn_by.clone()));
         }
 
-        // group multicore sw tasks based on their associated priorities
-        let mut mc_tasks_pgroups: HashMap<u16, Vec<_>> =
-            HashMap::with_capacity(sub_app.dispatchers.len());
-        for task in sub_app.mc_sw_tasks.iter() {
-            let task_prio = task.params.priority;
-            mc_tasks_pgroups
-                .entry(task_prio)
-                .or_default()
-                .push((task.name().clone(), task.params.spawn_by));
-        }
-
-        // ensure that the multi-core tasks do not have overlapping priorities with core local software tasks
-        let sw_tasks_prios = sw_tasks_pgroups.keys().collect::<HashSet<_>>();
-        let mc_tasks_prios = mc_tasks_pgroups.keys().collect::<HashSet<_>>();
-        let disjoint = sw_tasks_prios.is_disjoint(&mc_tasks_prios);
-        if !disjoint {
-            return Err(syn::Error::new(
-                Span::call_site(),
-                format!(
-                    "The priority of some tasks with `spawn_by` argument in core {} have overlapping priority with other core-local software tasks, which is forbidden.",
-                    sub_app.core
-                ),
-            ));
-        }
-
-        // need to further check that multi core tasks in the same priority group must all have the spawn_by index.
-        for priority_group in mc_tasks_pgroups.values() {
-            if priority_group.len() > 1 {
-                let (task_1, spawn_by1) = &priority_group[0];
-                for (task_x, spawn_byx) in priority_group.iter() {
-                    if spawn_by1 != spawn_byx {
-                        return Err(syn::Error::new(
-                            Span::call_site(),
-                            format!(
-                                "{task_1} and {task_x} have the same priority but they are spawned by different cores which is forbidden."
-                            ),
-                        ));
-                    }
-                }
-            }
-        }
-
-        // now we can merge all priority groups together since we know they are disjoint and no overlap exists
-        sw_tasks_pgroups.extend(mc_tasks_pgroups);
-
         // check if the number of dispatchers meets the number of sw task priority groups
         let n_dispatchers = sub_app.dispatchers.len();
         let n_priority_groups = sw_tasks_pgroups.len();
@@ -107,10 +109,44 @@ impl SubAnalysis {
             .map(|p| (p, dispatchers.pop().unwrap()))
             .collect();
 
-        Ok(Self {
+        let mut analysis = Self {
             core: sub_app.core,
             tasks_priority_map: sw_tasks_pgroups,
             dispatcher_priority_map: dispatcher_priorities,
-        })
+            spawn_ceilings: HashMap::new(),
+        };
+        analysis.spawn_ceilings = analysis.compute_spawn_ceilings(core_ceilings);
+        Ok(analysis)
+    }
+
+    /// SRP ceilings of the ready and inputs queues. Every spawning core has its own queues (see
+    /// `spawners`), locked up to the highest priority of the tasks of that core (see
+    /// `core_ceilings`). On the core running the tasks, this includes the dispatcher, which must
+    /// not be preempted by `spawn` while dequeuing inputs that `on_full` policies may rewrite.
+    fn compute_spawn_ceilings(
+        &self,
+        core_ceilings: &HashMap<u32, u16>,
+    ) -> HashMap<(u16, u32), u16> {
+        let mut ceilings = HashMap::new();
+        for priority in self.tasks_priority_map.keys() {
+            for spawner in self.spawners(*priority) {
+                let ceiling = core_ceilings.get(&spawner).copied().unwrap_or(0);
+                ceilings.insert((*priority, spawner), ceiling);
+            }
+        }
+        ceilings
+    }
+
+    /// Sorted list of cores that spawn at least one task of the given priority group.
+    /// The dispatcher of that group drains one ready queue per returned core.
+    pub fn spawners(&self, priority: u16) -> Vec<u32> {
+        let spawners = self
+            .tasks_priority_map
+            .get(&priority)
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, spawn_by)| spawn_by.iter().copied())
+            .collect::<BTreeSet<_>>();
+        spawners.into_iter().collect()
     }
 }
@@ -2,11 +2,13 @@ mod utils;
 
 use crate::SwPassBackend;
 use crate::software_pass::analyze::{Analysis, SubAnalysis};
-use crate::software_pass::parse::ast::SoftwareTask;
-use crate::software_pass::parse::{App, SWT_TRAIT_TY};
+use crate::software_pass::parse::ast::{OverflowPolicy, SoftwareTask};
+use crate::software_pass::parse::spawn_graph::SpawnGraph;
+use crate::software_pass::parse::{App, SWT_TRAIT_TY, SubApp};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
 use rticx_core::parse_utils::RticAttr;
+use rticx_core::{rticx_cell, rticx_functions, rticx_stats, rticx_trace};
 use syn::{ItemMod, LitInt, Path, parse_quote};
 
 /// Compute the name of the core-local pend function for `core`.
@@ -56,15 +58,19 @@ impl<'a> CodeGen<'a> {
                 fn exec(&mut self, input: Self::SpawnInput);
             }
         };
+        let spawn_from_trait_def = self.get_spawn_from_trait();
+        let unreachable_task_warnings = self.get_unreachable_task_warnings();
         let mod_visibility = &self.app.mod_visibility;
         let mod_ident = &self.app.mod_ident;
 
         parse_quote! {
             #mod_visibility mod #mod_ident {
                 #(#rest_of_code)*
+                #unreachable_task_warnings
                 #sub_apps
                 /// RTIC Software task trait
                 #sw_task_trait_def
+                #spawn_from_trait_def
                 /// Core local interrupt pending
                 #local_pend_fns
                 // (optional) Cross Core interrupt pending
@@ -73,6 +79,58 @@ impl<'a> CodeGen<'a> {
         }
     }
 
+    /// Generate the trait behind `spawn_from`, only when some task can be spawned from another core.
+    ///
+    /// Every core token allowed to spawn a task implements the trait for that task, so the inherent
+    /// `spawn_from` only type-checks for the right spawners and picks that spawner's queues.
+    fn get_spawn_from_trait(&self) -> TokenStream {
+        if self
+            .app
+            .sub_apps
+            .iter()
+            .all(|app| app.mc_sw_tasks.is_empty())
+        {
+            return quote!();
+        }
+        let software_task_trait = format_ident!("{SWT_TRAIT_TY}");
+        let spawn_from_trait = utils::spawn_from_trait_ident();
+        quote! {
+            /// Cross-core spawn of task `T` from the implementing core token
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub trait #spawn_from_trait<T: #software_task_trait> {
+                fn spawn_from(self, input: T::SpawnInput) -> Result<(), T::SpawnInput>;
+            }
+        }
+    }
+
+    /// Warn about software tasks that are never spawned from `#[init]` or from a task.
+    ///
+    /// Spawn calls the application makes through macros, function pointers, aliases or from
+    /// outside the `#[app]` module are not found, so the warning is only given for tasks whose
+    /// `spawn_by` is inferred: giving `spawn_by` or `infer_spawn_by = false` silences it.
+    fn get_unreachable_task_warnings(&self) -> TokenStream {
+        let spawn_graph = &self.app.spawn_graph;
+        let warnings = self
+            .app
+            .sub_apps
+            .iter()
+            .flat_map(|sub_app| sub_app.sw_tasks.iter().chain(sub_app.mc_sw_tasks.iter()))
+            .filter(|task| {
+                task.params.infer_spawn_by
+                    && spawn_graph.callers_known(task.name())
+                    && spawn_graph.sites(task.name()).is_empty()
+            })
+            .map(|task| {
+                let message = format!(
+                    "software task `{}` is never spawned by `#[init]` or by any task of the application. Set its `spawn_by` or `infer_spawn_by = false` if it is spawned from code not found by the software pass",
+                    task.name()
+                );
+                rticx_core::errors::warning(task.name().span(), &message)
+            });
+        quote!(#(#warnings)*)
+    }
+
     /// Compute the interrupt type path for the dispatcher on a given core.
     ///
     /// Uses the backend's `custom_interrupt_path` if provided, otherwise falls
@@ -148,11 +206,23 @@ impl<'a> CodeGen<'a> {
     fn generate_subapps(&mut self) -> TokenStream {
         let num_cores = self.app.sub_apps.len();
         let queue_path = self.backend.queue_path();
+        let lock_free_ready_queue = self.backend.ready_queue_path().is_some();
+        let ready_queue_path = self
+            .backend
+            .ready_queue_path()
+            .unwrap_or_else(|| queue_path.clone());
         let apps = self.app.sub_apps.iter_mut();
         let analysis = self.analysis.sub_analysis.iter();
 
         let sub_apps = apps.zip(analysis).map(|(sub_app, sub_analysis)| {
             let pac = &self.app.app_params.pacs[sub_app.core as usize];
+            // generate dispatchers as hardware tasks
+            let dispatcher_tasks = generate_dispatcher_tasks(
+                sub_app,
+                sub_analysis,
+                &ready_queue_path,
+                lock_free_ready_queue,
+            );
             // first merge the multi-core and core local tasks as the same code will be generated for both
             let tasks_iter = sub_app
                 .sw_tasks
@@ -188,19 +258,25 @@ impl<'a> CodeGen<'a> {
                     .dispatcher_priority_map
                     .get(&task.params.priority)
                     .unwrap(); // safe to unwrap
-                let spawn_impl =
-                    task.generate_spawn_api(dispatcher, pac, self.backend, num_cores, &queue_path);
+                let spawn_impl = task.generate_spawn_api(
+                    dispatcher,
+                    sub_analysis,
+                    pac,
+                    self.backend,
+                    num_cores,
+                    &queue_path,
+                );
+                let input_send_check = task.spawn_input_send_check(&self.app.spawn_graph);
 
                 quote! {
                     #reconstructed_task_attr
                     #task_struct
                     #task_impl
                     #spawn_impl
+                    #input_send_check
                 }
             });
 
-            // generate dispatchers as hardware tasks
-            let dispatcher_tasks = generate_dispatcher_tasks(sub_analysis, &queue_path);
             let core_doc = format!(" Core {}", sub_app.core);
             quote! {
                 #[doc = " Software tasks of"]
@@ -223,33 +299,109 @@ impl<'a> CodeGen<'a> {
 /// - an enum type for each group of tasks of the same priority
 /// - a ready queue for each group of tasks of the same priority
 /// - A dispatcher hw task for each priority level
-fn generate_dispatcher_tasks(sub_analysis: &SubAnalysis, queue_path: &Path) -> TokenStream {
+fn generate_dispatcher_tasks(
+    sub_app: &SubApp,
+    sub_analysis: &SubAnalysis,
+    ready_queue_path: &Path,
+    lock_free_ready_queue: bool,
+) -> TokenStream {
     let core = sub_analysis.core;
     let dispatchers = &sub_analysis.dispatcher_priority_map;
     let dispatcher_tasks = sub_analysis.tasks_priority_map.iter().map(|(prio, tasks)| {
         let prio_ty = utils::priority_ty_ident(*prio, core);
 
-        // generate the branches of the match statement for the dispatcher task
-        let dispatch_match_branches = tasks.iter().map(|(task_ident, _)| {
-            let task_static_handle = utils::ident_uppercase(task_ident);
-            let task_inputs_queue = utils::sw_task_inputs_ident(task_ident);
-            let prio_ty = &prio_ty;
-            quote! {
-                #prio_ty::#task_ident => {
-                    let mut input_consumer = #task_inputs_queue.split().1;
-                    let input = input_consumer.dequeue_unchecked();
-                    #task_static_handle.assume_init_mut().exec(input);
-                }
-            }
-        });
+        // one ready queue per spawning core, so that every ready queue keeps a single producer core
+        let (ready_queues, drain_ready_queues): (Vec<_>, Vec<_>) = sub_analysis
+            .spawners(*prio)
+            .into_iter()
+            .map(|spawner| {
+                let spawned_tasks = tasks
+                    .iter()
+                    .filter(|(_, spawn_by)| spawn_by.contains(&spawner))
+                    .map(|(task_ident, _)| task_ident)
+                    .collect::<Vec<_>>();
+
+                // generate the branches of the match statement for the dispatcher task
+                let dispatch_match_branches = spawned_tasks.iter().map(|task_ident| {
+                    let task_static_handle = utils::ident_uppercase(task_ident);
+                    let task_inputs_queue = utils::sw_task_inputs_ident(task_ident, core, spawner);
+                    let prio_ty = &prio_ty;
+                    let task_params = sub_app
+                        .sw_tasks
+                        .iter()
+                        .chain(sub_app.mc_sw_tasks.iter())
+                        .find(|task| task.name() == *task_ident)
+                        .map(|task| &task.params);
+                    // `spawn` may rewrite pending inputs of this task, it must not run while we dequeue
+                    let rewrites_pending_inputs =
+                        task_params.is_some_and(|params| params.on_full.rewrites_pending_inputs());
+                    let dequeue_input = if rewrites_pending_inputs {
+                        let ceiling = sub_analysis.spawn_ceilings[&(*prio, spawner)];
+                        utils::spawn_queues_lock(ceiling, quote!(|| input_consumer.dequeue_unchecked()))
+                    } else {
+                        quote!(input_consumer.dequeue_unchecked())
+                    };
+                    // the task runs at its preemption threshold, above the dispatcher
+                    let exec = rticx_functions::raise_to_threshold(
+                        *prio,
+                        task_params.map_or(*prio, |params| params.threshold),
+                        quote!(#task_static_handle.get_mut().exec(input);),
+                    );
+                    quote! {
+                        #prio_ty::#task_ident => {
+                            let mut input_consumer = #task_inputs_queue.get_mut().split().1;
+                            let input = #dequeue_input;
+                            #exec
+                        }
+                    }
+                });
+                // tasks of this priority that are never spawned from `spawner` never show up in its queue
+                let other_tasks = (spawned_tasks.len() < tasks.len()).then(|| quote!(_ => {}));
+
+                let ready_queue_name = utils::priority_queue_ident(&prio_ty, core, spawner);
+                let ready_queue_size = spawned_tasks.len() + 1; // queue size must always be one more than number of tasks
+                let ready_queue = rticx_cell::static_with_value(
+                    &ready_queue_name,
+                    &quote!(#ready_queue_path<#prio_ty, #ready_queue_size>),
+                    &quote!(#ready_queue_path::new()),
+                );
+                let ready_queue = quote! {
+                    #[doc(hidden)]
+                    #[allow(non_upper_case_globals)]
+                    #ready_queue
+                };
+                let drain_ready_queue = if lock_free_ready_queue {
+                    // the spawners enqueue concurrently through shared references, so the
+                    // dispatcher never forms a `&mut` to the queue either
+                    quote! {
+                        let ready_queue = #ready_queue_name.get();
+                        while let Some(task) = ready_queue.dequeue_shared() {
+                            match task {
+                                #(#dispatch_match_branches)*
+                                #other_tasks
+                            }
+                        }
+                    }
+                } else {
+                    quote! {
+                        let mut ready_consumer = #ready_queue_name.get_mut().split().1;
+                        while let Some(task) = ready_consumer.dequeue() {
+                            match task {
+                                #(#dispatch_match_branches)*
+                                #other_tasks
+                            }
+                        }
+                    }
+                };
+                (ready_queue, drain_ready_queue)
+            })
+            .unzip();
 
-        let ready_queue_name = utils::priority_queue_ident(&prio_ty);
-        let ready_queue_size = tasks.len() + 1; // queue size must always be one more than number of tasks
         let dispatcher_irq_name = dispatchers.get(prio).unwrap(); // safe to unwrap due to guarantees from analysis
         let dispatcher_priority = prio;
         let dispatcher_task_ty = utils::dispatcher_ident(*prio, core);
         let core_nbr = LitInt::new(&core.to_string(), Span::call_site());
-        let tasks = tasks.iter().map(|(ident, _span_by)| ident);
+        let tasks = tasks.iter().map(|(ident, _spawn_by)| ident);
 
         quote! {
             #[derive(Clone, Copy)]
@@ -258,9 +410,7 @@ fn generate_dispatcher_tasks(sub_analysis: &SubAnalysis, queue_path: &Path) -> T
                 #(#tasks,)*
             }
 
-            #[doc(hidden)]
-            #[allow(non_upper_case_globals)]
-            static mut #ready_queue_name: #queue_path<#prio_ty, #ready_queue_size> = #queue_path::new();
+            #(#ready_queues)*
 
             #[doc(hidden)]
             #[task( binds = #dispatcher_irq_name , priority = #dispatcher_priority, core = #core_nbr )]
@@ -274,12 +424,7 @@ fn generate_dispatcher_tasks(sub_analysis: &SubAnalysis, queue_path: &Path) -> T
 
                 fn exec(&mut self) {
                     unsafe {
-                        let mut ready_consumer = #ready_queue_name.split().1;
-                        while let Some(task) = ready_consumer.dequeue() {
-                            match task {
-                                #(#dispatch_match_branches)*
-                            }
-                        }
+                        #(#drain_ready_queues)*
                     }
                 }
             }
@@ -295,77 +440,294 @@ pub const SC_PEND_FN_NAME: &str = "__rticx_local_irq_pend"; // function name for
 pub const MC_PEND_FN_NAME: &str = "__rticx_cross_irq_pend"; // function name for cross-core pending
 
 impl SoftwareTask {
-    /// generate the spawn() function for the task
+    /// generate the spawn() function for the task, and the spawn_from() function if the task can
+    /// be spawned by other cores
     fn generate_spawn_api(
         &self,
         dispatcher_irq_name: &Path,
+        sub_analysis: &SubAnalysis,
         peripheral_crate: &Path,
         backend: &dyn SwPassBackend,
         num_cores: usize,
         queue_path: &Path,
     ) -> TokenStream {
         let task_name = self.name();
-        let task_inputs_queue = utils::sw_task_inputs_ident(task_name);
+        let core = self.params.core;
         let task_trait_name = format_ident!("{}", SWT_TRAIT_TY);
         // get the inputs type. see the RticSwTask trait to understand this and where it comes from.
         let inputs_ty = quote!(<#task_name as #task_trait_name>::SpawnInput);
-        let prio_ty = utils::priority_ty_ident(self.params.priority, self.params.core);
-        let ready_queue_name = utils::priority_queue_ident(&prio_ty);
-
-        let critical_section_fn =
-            format_ident!("{}", rticx_core::rticx_functions::INTERRUPT_FREE_FN);
         let interrupt_ty = backend
-            .custom_interrupt_path(self.params.core)
+            .custom_interrupt_path(core)
             .unwrap_or(parse_quote!(#peripheral_crate::Interrupt));
+        let lock_free_ready_queue = backend.ready_queue_path().is_some();
+
+        // one inputs queue per spawning core
+        let inputs_queues = self.params.spawn_by.iter().map(|spawner| {
+            let task_inputs_queue = utils::sw_task_inputs_ident(task_name, core, *spawner);
+            rticx_cell::static_with_value(
+                &task_inputs_queue,
+                &quote!(#queue_path<#inputs_ty, 2>),
+                &quote!(#queue_path::new()),
+            )
+        });
 
         // spawn for core-local tasks
-        if self.params.core == self.params.spawn_by {
-            let pend_fn = local_pend_fn_ident(self.params.core, num_cores);
+        let local_spawn = self.params.is_spawned_locally().then(|| {
+            let pend_fn = local_pend_fn_ident(core, num_cores);
+            let spawn_body = self.spawn_body(
+                core,
+                sub_analysis,
+                lock_free_ready_queue,
+                quote!(#pend_fn(#interrupt_ty::#dispatcher_irq_name);),
+            );
             quote! {
-                static mut #task_inputs_queue: #queue_path<#inputs_ty, 2> = #queue_path::new();
-
                 impl #task_name {
                     pub fn spawn(input : #inputs_ty) -> Result<(), #inputs_ty> {
-                        let mut inputs_producer = unsafe {#task_inputs_queue.split().0};
-                        let mut ready_producer = unsafe {#ready_queue_name.split().0};
-                        /// need to protect by a critical section because many producers of different priorities can spawn/enqueue this task
-                        #critical_section_fn(|| -> Result<(), #inputs_ty>  {
-                            // enqueue inputs
-                            inputs_producer.enqueue(input)?;
-                            // enqueue task to ready queue
-                            unsafe {ready_producer.enqueue_unchecked(#prio_ty::#task_name)};
-                            // pend dispatcher
-                            #pend_fn(#interrupt_ty::#dispatcher_irq_name);
-                            Ok(())
-                        })
+                        #spawn_body
                     }
                 }
             }
-        }
-        // spawn for cross-core tasks
-        else {
-            let spawner_ty = utils::core_type(self.params.spawn_by);
-            let pend_fn = cross_pend_fn_ident(self.params.core);
+        });
+
+        // spawn for cross-core tasks, one implementation per spawning core token
+        let mut remote_spawners = self.params.remote_spawners().peekable();
+        let cross_spawn = remote_spawners.peek().is_some().then(|| {
+            let spawn_from_trait = utils::spawn_from_trait_ident();
+            let pend_fn = cross_pend_fn_ident(core);
+            let spawn_from_impls = remote_spawners.map(|spawner| {
+                let spawner_ty = utils::core_type(spawner);
+                let spawn_body = self.spawn_body(
+                    spawner,
+                    sub_analysis,
+                    lock_free_ready_queue,
+                    quote!(#pend_fn(#interrupt_ty::#dispatcher_irq_name);),
+                );
+                quote! {
+                    impl #spawn_from_trait<#task_name> for #spawner_ty {
+                        fn spawn_from(self, input : #inputs_ty) -> Result<(), #inputs_ty> {
+                            #spawn_body
+                        }
+                    }
+                }
+            });
+            quote! {
+                impl #task_name {
+                    pub fn spawn_from<S: #spawn_from_trait<#task_name>>(spawner: S, input : #inputs_ty) -> Result<(), #inputs_ty> {
+                        spawner.spawn_from(input)
+                    }
+                }
+                #(#spawn_from_impls)*
+            }
+        });
+
+        // inputs dropped because of the `on_full` policy are counted instead of being reported to the caller
+        // one counter per spawning core, so that cores never race on the same counter
+        let overruns_counter = self.params.on_full.counts_overruns().then(|| {
+            let overruns = self
+                .params
+                .spawn_by
+                .iter()
+                .map(|spawner| utils::sw_task_overruns_ident(task_name, core, *spawner))
+                .collect::<Vec<_>>();
             quote! {
-                static mut #task_inputs_queue: #queue_path<#inputs_ty, 2> = #queue_path::new();
+                #(
+                    #[doc(hidden)]
+                    #[allow(non_upper_case_globals)]
+                    static #overruns: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+                )*
 
                 impl #task_name {
-                    pub fn spawn_from(_spawner: #spawner_ty , input : #inputs_ty) -> Result<(), #inputs_ty> {
-                        let mut inputs_producer = unsafe {#task_inputs_queue.split().0};
-                        let mut ready_producer = unsafe {#ready_queue_name.split().0};
-                        /// need to protect by a critical section because many producers of different priorities can spawn/enqueue this task
-                        #critical_section_fn(|| -> Result<(), #inputs_ty>  {
-                            // enqueue inputs
-                            inputs_producer.enqueue(input)?;
-                            // enqueue task to ready queue
-                            unsafe {ready_producer.enqueue_unchecked(#prio_ty::#task_name)};
+                    /// Number of inputs dropped by `spawn` because the inputs queue of the task was full
+                    pub fn spawn_overruns() -> u32 {
+                        0u32 #(.wrapping_add(#overruns.load(core::sync::atomic::Ordering::Relaxed)))*
+                    }
+                }
+            }
+        });
+
+        quote! {
+            #(#inputs_queues)*
+            #overruns_counter
+            #local_spawn
+            #cross_spawn
+        }
+    }
+
+    /// Compile-time check that the inputs of the task are `Send` when they are spawned from another
+    /// core, or from a context running at another priority than the task.
+    fn spawn_input_send_check(&self, spawn_graph: &SpawnGraph) -> Option<TokenStream> {
+        let task_name = self.name();
+        let sent_from = if let Some(spawner) = self.params.remote_spawners().next() {
+            format!("core {spawner}")
+        } else if !spawn_graph.callers_known(task_name) {
+            "code outside of the tasks".to_string()
+        } else {
+            let site = spawn_graph
+                .sites(task_name)
+                .iter()
+                .find(|site| site.caller_priority != Some(self.params.priority))?;
+            match site.caller_priority {
+                Some(priority) => format!("`{}` of priority {priority}", site.caller),
+                None => format!("`#[init]` function `{}`", site.caller),
+            }
+        };
+        let task_trait_name = format_ident!("{}", SWT_TRAIT_TY);
+        let message = format!(
+            "software task `{task_name}` of priority {} is spawned from {sent_from}, its `SpawnInput` type `{{Self}}` must be `Send`",
+            self.params.priority
+        );
+        Some(rticx_functions::bound_check(
+            &quote!(<#task_name as #task_trait_name>::SpawnInput),
+            "Send",
+            task_name.span(),
+            &message,
+        ))
+    }
+
+    /// Body of a spawn function running on the `spawner` core: enqueue the input and the task in the
+    /// queues owned by that spawner, then pend the dispatcher using `pend_call`. With a
+    /// `lock_free_ready_queue`, only the inputs queue is locked.
+    fn spawn_body(
+        &self,
+        spawner: u32,
+        sub_analysis: &SubAnalysis,
+        lock_free_ready_queue: bool,
+        pend_call: TokenStream,
+    ) -> TokenStream {
+        let task_name = self.name();
+        let core = self.params.core;
+        let task_trait_name = format_ident!("{}", SWT_TRAIT_TY);
+        let inputs_ty = quote!(<#task_name as #task_trait_name>::SpawnInput);
+        let task_inputs_queue = utils::sw_task_inputs_ident(task_name, core, spawner);
+        let prio_ty = utils::priority_ty_ident(self.params.priority, core);
+        let ready_queue_name = utils::priority_queue_ident(&prio_ty, core, spawner);
+        let ceiling = sub_analysis.spawn_ceilings[&(self.params.priority, spawner)];
+
+        // only this core writes the counter, within the same critical section as the queues
+        let overruns = utils::sw_task_overruns_ident(task_name, core, spawner);
+        let count_overrun = quote! {
+            #overruns.store(#overruns.load(core::sync::atomic::Ordering::Relaxed).wrapping_add(1), core::sync::atomic::Ordering::Relaxed);
+        };
+        let (inputs_producer, enqueue_input) = match self.params.on_full {
+            OverflowPolicy::Reject => (
+                Some(
+                    quote!(let mut inputs_producer = unsafe {#task_inputs_queue.get_mut().split().0};),
+                ),
+                quote! {
+                    // enqueue inputs
+                    inputs_producer.enqueue(input)?;
+                },
+            ),
+            OverflowPolicy::DropNewest => (
+                Some(
+                    quote!(let mut inputs_producer = unsafe {#task_inputs_queue.get_mut().split().0};),
+                ),
+                quote! {
+                    // enqueue inputs, or drop the newest input if the queue is full
+                    if inputs_producer.enqueue(input).is_err() {
+                        #count_overrun
+                        return Ok(());
+                    }
+                },
+            ),
+            // the policies below rewrite pending inputs, which is only sound because the dispatcher
+            // dequeues them under the same lock on this same core
+            OverflowPolicy::OverwriteOldest => (
+                None,
+                quote! {
+                    // enqueue inputs, evicting the oldest input if the queue is full
+                    if unsafe { #task_inputs_queue.get_mut().enqueue_overwrite(input) }.is_some() {
+                        // the task is already in the ready queue for the evicted input
+                        #count_overrun
+                        return Ok(());
+                    }
+                },
+            ),
+            OverflowPolicy::Coalesce => (
+                None,
+                quote! {
+                    // if an instance of the task is pending, it will run with the newest input instead
+                    let input = match unsafe { #task_inputs_queue.get_mut().replace_newest(input) } {
+                        Ok(_replaced) => {
+                            #count_overrun
+                            return Ok(());
+                        }
+                        Err(input) => input,
+                    };
+                    // enqueue inputs
+                    unsafe { #task_inputs_queue.get_mut().enqueue(input) }?;
+                },
+            ),
+        };
+
+        // with the tracing hooks of the core pass, trace the spawn before the dispatcher can run
+        let trace_spawn = rticx_trace::ENABLED.then(|| rticx_trace::spawn_hook_call(task_name));
+        // need to lock the queues because many producers of different priorities can spawn/enqueue this task
+        let enqueue = if lock_free_ready_queue {
+            // the ready queue takes concurrent producers: the input is enqueued before the task, so
+            // the dispatcher always finds an input for every task it dequeues
+            let lock_inputs = utils::spawn_queues_lock(
+                ceiling,
+                quote! {
+                    || -> Result<(), #inputs_ty>  {
+                        #enqueue_input
+                        make_ready = true;
+                        Ok(())
+                    }
+                },
+            );
+            quote! {
+                {
+                    // stays false when the input is dropped or merged into a pending one
+                    let mut make_ready = false;
+                    #lock_inputs.map(|()| {
+                        if make_ready {
+                            // enqueue task to ready queue, through a shared reference as the
+                            // other spawners and the dispatcher may be using it
+                            unsafe {#ready_queue_name.get().enqueue_unchecked(#prio_ty::#task_name)};
+                            #trace_spawn
                             // pend dispatcher
-                            #pend_fn(#interrupt_ty::#dispatcher_irq_name);
-                            Ok(())
-                        })
+                            #pend_call
+                        }
+                    })
+                }
+            }
+        } else {
+            utils::spawn_queues_lock(
+                ceiling,
+                quote! {
+                    || -> Result<(), #inputs_ty>  {
+                        #enqueue_input
+                        // enqueue task to ready queue
+                        unsafe {ready_producer.enqueue_unchecked(#prio_ty::#task_name)};
+                        #trace_spawn
+                        // pend dispatcher
+                        #pend_call
+                        Ok(())
                     }
+                },
+            )
+        };
+        // with the runtime statistics of the core pass, count the spawns reporting an error
+        let enqueue = if rticx_stats::ENABLED {
+            let task_stats = rticx_stats::task_stats_ident(task_name);
+            quote! {
+                let result = #enqueue;
+                if result.is_err() {
+                    #task_stats.spawn_failed();
                 }
+                result
             }
+        } else {
+            enqueue
+        };
+        let ready_producer = (!lock_free_ready_queue).then(
+            || quote!(let mut ready_producer = unsafe {#ready_queue_name.get_mut().split().0};),
+        );
+        quote! {
+            #inputs_producer
+            #ready_producer
+            #enqueue
         }
     }
 }
@@ -1,6 +1,6 @@
 use heck::ToSnakeCase;
-use proc_macro2::Span;
-use quote::format_ident;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
 use syn::Ident;
 
 /// used for statics
@@ -22,12 +22,38 @@ pub fn priority_ty_ident(priority: u16, core: u32) -> Ident {
 pub fn dispatcher_ident(priority: u16, core: u32) -> Ident {
     format_ident!("Core{core}Priority{priority}Dispatcher")
 }
-pub fn priority_queue_ident(prio_ty: &Ident) -> Ident {
-    format_ident!("__rticx_internal__{prio_ty}__RQ")
+/// Ready queue of a priority group on `core`, fed by the `spawner` core.
+/// Queues fed from another core carry the spawner in their name.
+pub fn priority_queue_ident(prio_ty: &Ident, core: u32, spawner: u32) -> Ident {
+    if core == spawner {
+        format_ident!("__rticx_internal__{prio_ty}__RQ")
+    } else {
+        format_ident!("__rticx_internal__{prio_ty}__FromCore{spawner}__RQ")
+    }
 }
 
-pub fn sw_task_inputs_ident(task_ident: &Ident) -> Ident {
-    format_ident!("__rticx_internal__{task_ident}__INPUTS")
+/// Inputs queue of a software task running on `core`, fed by the `spawner` core.
+pub fn sw_task_inputs_ident(task_ident: &Ident, core: u32, spawner: u32) -> Ident {
+    if core == spawner {
+        format_ident!("__rticx_internal__{task_ident}__INPUTS")
+    } else {
+        format_ident!("__rticx_internal__{task_ident}__FromCore{spawner}__INPUTS")
+    }
+}
+
+/// Counter of the inputs dropped by the `spawner` core according to the `on_full` policy of a
+/// software task running on `core`. Each counter is only written by its spawner core.
+pub fn sw_task_overruns_ident(task_ident: &Ident, core: u32, spawner: u32) -> Ident {
+    if core == spawner {
+        format_ident!("__rticx_internal__{task_ident}__OVERRUNS")
+    } else {
+        format_ident!("__rticx_internal__{task_ident}__FromCore{spawner}__OVERRUNS")
+    }
+}
+
+/// Hidden trait implemented by every core token that is allowed to spawn a given software task
+pub fn spawn_from_trait_ident() -> Ident {
+    format_ident!("__rticx_internal__SpawnFrom")
 }
 
 /// Type that will be generated in the standard pass for every core
@@ -36,3 +62,9 @@ pub fn sw_task_inputs_ident(task_ident: &Ident) -> Ident {
 pub fn core_type(core: u32) -> Ident {
     format_ident!("__rticx__internal__Core{core}")
 }
+
+/// Runs `closure` with the spawn queues locked under their SRP `ceiling`.
+pub fn spawn_queues_lock(ceiling: u16, closure: TokenStream) -> TokenStream {
+    let lock_fn = format_ident!("{}", rticx_core::rticx_functions::CEILING_LOCK_FN);
+    quote!(#lock_fn(#ceiling, #closure))
+}
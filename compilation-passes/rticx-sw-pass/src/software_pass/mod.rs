@@ -11,6 +11,7 @@ use syn::ItemMod;
 
 pub static INFO_APP: &str = "rticx_sw_pass::App";
 pub static INFO_ANALYSIS: &str = "rticx_sw_pass::Analysis";
+pub static INFO_SPAWN_GRAPH: &str = "rticx_sw_pass::SpawnGraph";
 
 pub struct SoftwarePass {
     backend: Box<dyn SwPassBackend>,
@@ -38,6 +39,10 @@ impl RticPass for SoftwarePass {
         let code = CodeGen::new(parsed.clone(), analysis.clone(), self.backend.as_ref()).run();
         // publish info
         self.info_bus.as_ref().inspect(|b| {
+            b.publish(INFO_SPAWN_GRAPH, parsed.spawn_graph.clone())
+                .unwrap_or_else(|_| {
+                    panic!("no other crate is allowed to publish {INFO_SPAWN_GRAPH}")
+                });
             b.publish(INFO_APP, parsed)
                 .unwrap_or_else(|_| panic!("no other crate is allowed to publish {INFO_APP}"));
             b.publish(INFO_ANALYSIS, analysis)
@@ -64,7 +69,9 @@ pub trait SwPassBackend {
     /// position) and `#queue_path::new()` (expression position).  The
     /// concrete type must support the same API as `rticx_spsc::Queue`:
     /// a const `new()` constructor, `split()` into producer/consumer halves,
-    /// `enqueue` / `dequeue`, and `_unchecked` variants.
+    /// `enqueue` / `dequeue`, and `_unchecked` variants. Tasks using the
+    /// `on_full = overwrite_oldest | coalesce` policies additionally need
+    /// `enqueue_overwrite` and `replace_newest`.
     ///
     /// Typical implementation for a distribution:
     /// ```ignore
@@ -74,6 +81,24 @@ pub trait SwPassBackend {
     /// ```
     fn queue_path(&self) -> syn::Path;
 
+    /// Path to a lock-free multi-producer queue type for the ready queues of the dispatchers.
+    ///
+    /// Defaults to `None`: ready queues use [`queue_path`](SwPassBackend::queue_path), and `spawn`
+    /// fills them in the same critical section as the inputs queues. Targets with compare-and-swap
+    /// atomics can opt into the multi-producer queue re-exported as
+    /// `rticx_sw_pass::export::MpscQueue`. `spawn` then only locks the inputs queue, and enqueues
+    /// the task and pends the dispatcher without masking any task. The queue is only ever used
+    /// through shared references, it must offer a const `new()`, `enqueue_unchecked(&self, _)`
+    /// usable by concurrent producers, and `unsafe dequeue_shared(&self)` for the single consumer:
+    /// ```ignore
+    /// fn ready_queue_path(&self) -> Option<syn::Path> {
+    ///     Some(parse_quote!(rticx_cortex_m::export::MpscQueue))
+    /// }
+    /// ```
+    fn ready_queue_path(&self) -> Option<syn::Path> {
+        None
+    }
+
     /// Body of the core-local interrupt-pending function.
     ///
     /// The software pass generates an empty function for each core and
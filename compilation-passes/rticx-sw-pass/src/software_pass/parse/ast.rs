@@ -118,12 +118,79 @@ impl SoftwareTask {
 #[derive(Debug, Clone)]
 pub struct TaskParams {
     pub priority: u16,
+    /// Preemption threshold the task runs at once dispatched, `priority` by default
+    pub threshold: u16,
     pub core: u32,
-    pub spawn_by: u32,
+    /// Cores allowed to spawn this task, sorted and without duplicates.
+    /// Accepts either a single core (`spawn_by = 1`) or a list (`spawn_by = [0, 1]`).
+    pub spawn_by: Vec<u32>,
+    /// Whether `spawn_by` is inferred from the spawn calls found in the application, and the task
+    /// reported when none is found. Only when `spawn_by` is not given, and unless opted out with
+    /// `infer_spawn_by = false`, see [SpawnGraph](crate::parse::spawn_graph::SpawnGraph).
+    pub infer_spawn_by: bool,
+    /// What `spawn` does when the task inputs queue is full
+    pub on_full: OverflowPolicy,
+}
+
+/// Behaviour of `spawn` when the inputs queue of a software task is full, selected with
+/// `#[sw_task(on_full = ...)]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// `reject` (default): `spawn` returns `Err(input)`
+    #[default]
+    Reject,
+    /// `drop_newest`: the new input is discarded and the overrun counter incremented
+    DropNewest,
+    /// `overwrite_oldest`: the oldest pending input is discarded to make room for the new one,
+    /// giving latest-value mailbox semantics
+    OverwriteOldest,
+    /// `coalesce`: at most one pending instance of the task, whose input is replaced by the newest one
+    Coalesce,
+}
+
+impl OverflowPolicy {
+    fn parse(expr: &Expr) -> syn::Result<Self> {
+        let policy = match expr {
+            Expr::Path(path) => path.path.get_ident().map(|ident| ident.to_string()),
+            _ => None,
+        };
+        match policy.as_deref() {
+            Some("reject") => Ok(Self::Reject),
+            Some("drop_newest") => Ok(Self::DropNewest),
+            Some("overwrite_oldest") => Ok(Self::OverwriteOldest),
+            Some("coalesce") => Ok(Self::Coalesce),
+            _ => Err(syn::Error::new(
+                expr.span(),
+                "`on_full` must be one of `reject`, `drop_newest`, `overwrite_oldest` or `coalesce`.",
+            )),
+        }
+    }
+
+    /// Name of the policy as written in the `on_full` argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::DropNewest => "drop_newest",
+            Self::OverwriteOldest => "overwrite_oldest",
+            Self::Coalesce => "coalesce",
+        }
+    }
+
+    /// Whether `spawn` may drop an input without reporting it to the caller, in which case an
+    /// overrun counter is maintained for the task
+    pub fn counts_overruns(&self) -> bool {
+        *self != Self::Reject
+    }
+
+    /// Whether the policy modifies pending inputs, which requires the producer and the consumer of
+    /// the inputs queue to exclude each other
+    pub fn rewrites_pending_inputs(&self) -> bool {
+        matches!(self, Self::OverwriteOldest | Self::Coalesce)
+    }
 }
 
 impl TaskParams {
-    pub fn from_attr(attr: &RticAttr) -> Self {
+    pub fn from_attr(attr: &RticAttr) -> syn::Result<Self> {
         let mut priority = 0;
         if let Some(Expr::Lit(syn::ExprLit {
             lit: Lit::Int(int), ..
@@ -132,26 +199,83 @@ impl TaskParams {
             priority = int.base10_parse().unwrap_or_default();
         }
 
-        let mut core = 0;
+        let mut threshold = priority;
         if let Some(Expr::Lit(syn::ExprLit {
             lit: Lit::Int(int), ..
-        })) = attr.elements.get("core")
+        })) = attr.elements.get("threshold")
         {
-            core = int.base10_parse().unwrap_or_default();
+            threshold = int.base10_parse().unwrap_or(priority);
         }
 
-        let mut spawn_by = core; // spawn_by is initially set to be the same core, unless the user chooses otherwize
+        let mut core = 0;
         if let Some(Expr::Lit(syn::ExprLit {
             lit: Lit::Int(int), ..
-        })) = attr.elements.get("spawn_by")
+        })) = attr.elements.get("core")
         {
-            spawn_by = int.base10_parse().unwrap_or_default();
+            core = int.base10_parse().unwrap_or_default();
         }
 
-        Self {
+        // spawn_by is initially set to be the same core, unless the user chooses otherwize
+        let mut spawn_by = match attr.elements.get("spawn_by") {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => vec![int.base10_parse().unwrap_or_default()],
+            Some(Expr::Array(array)) => array
+                .elems
+                .iter()
+                .map(|element| match element {
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Int(int), ..
+                    }) => int.base10_parse(),
+                    _ => Err(syn::Error::new(
+                        element.span(),
+                        "`spawn_by` must list core numbers, like `spawn_by = [0, 1]`.",
+                    )),
+                })
+                .collect::<syn::Result<_>>()?,
+            _ => vec![core],
+        };
+        spawn_by.sort_unstable();
+        spawn_by.dedup();
+
+        let infer_spawn_by = match attr.elements.get("infer_spawn_by") {
+            None => true,
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Bool(infer),
+                ..
+            })) => infer.value,
+            Some(expr) => {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    "`infer_spawn_by` must be `true` or `false`.",
+                ));
+            }
+        } && !attr.elements.contains_key("spawn_by");
+
+        let on_full = attr
+            .elements
+            .get("on_full")
+            .map(OverflowPolicy::parse)
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self {
             priority,
+            threshold,
             core,
             spawn_by,
-        }
+            infer_spawn_by,
+            on_full,
+        })
+    }
+
+    /// Whether the task can be spawned by the core it runs on.
+    pub fn is_spawned_locally(&self) -> bool {
+        self.spawn_by.contains(&self.core)
+    }
+
+    /// Cores other than the task's own core that are allowed to spawn it.
+    pub fn remote_spawners(&self) -> impl Iterator<Item = u32> + '_ {
+        self.spawn_by.iter().copied().filter(|c| *c != self.core)
     }
 }
@@ -1,10 +1,12 @@
 use crate::parse::ast::{AppParameters, SoftwareTask, TaskParams};
+use crate::parse::spawn_graph::{SpawnGraph, SpawnKind, SpawnSite};
 use proc_macro2::{Ident, TokenStream};
 use rticx_core::parse_utils::RticAttr;
 use std::collections::HashMap;
-use syn::{Item, ItemImpl, ItemMod, ItemStruct, Type, Visibility};
+use syn::{Item, ItemImpl, ItemMod, ItemStruct, Type, Visibility, spanned::Spanned};
 
 pub mod ast;
+pub mod spawn_graph;
 
 pub const SWT_TRAIT_TY: &str = "RticSwTask";
 
@@ -16,6 +18,7 @@ pub struct SubApp {
     /// Single core/ Core-local software tasks
     pub sw_tasks: Vec<SoftwareTask>,
     /// Multi core/ software tasks to be spawned on this core from other cores
+    /// (possibly in addition to being spawned locally)
     pub mc_sw_tasks: Vec<SoftwareTask>,
 }
 
@@ -29,6 +32,8 @@ pub struct App {
     /// a list of sub-applications, one sub-app per core.
     pub sub_apps: Vec<SubApp>,
     pub rest_of_code: Vec<Item>,
+    /// Which core spawns which software task, as found in the application code.
+    pub spawn_graph: SpawnGraph,
 }
 
 impl App {
@@ -60,20 +65,79 @@ impl App {
         }
 
         let cores = app_params.cores;
+        let mut sw_task_params = Vec::with_capacity(sw_task_structs.len());
+        for (task_struct, attr_idx) in sw_task_structs {
+            let attrs = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
+            let params = TaskParams::from_attr(&attrs)?;
+            sw_task_params.push((task_struct, attr_idx, params));
+        }
+
+        // find out who spawns which task
+        let sw_task_cores = sw_task_params
+            .iter()
+            .map(|(task_struct, _, params)| {
+                (task_struct.ident.clone(), (params.core, params.priority))
+            })
+            .collect();
+        let spawn_graph = SpawnGraph::build(&sw_task_cores, sw_task_impls.values(), &rest_of_code)?;
+
         let mut sw_tasks = HashMap::with_capacity(cores as usize);
         let mut mc_sw_tasks = HashMap::with_capacity(cores as usize);
-        for (task_struct, attr_idx) in sw_task_structs {
+        for (task_struct, attr_idx, mut params) in sw_task_params {
             let task_impl = sw_task_impls.remove(&task_struct.ident);
 
-            let attrs = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx])?;
-            let params = TaskParams::from_attr(&attrs);
+            // infer `spawn_by` from the spawn sites, unless the user provided it or some spawn calls
+            // are made from code whose core is unknown
+            let spawning_cores = spawn_graph.spawning_cores(&task_struct.ident);
+            if params.infer_spawn_by
+                && spawn_graph.callers_known(&task_struct.ident)
+                && !spawning_cores.is_empty()
+            {
+                params.spawn_by = spawning_cores;
+            }
+            if params.spawn_by.is_empty() {
+                return Err(syn::Error::new(
+                    task_struct.attrs[attr_idx].span(),
+                    format!(
+                        "`spawn_by` of task `{}` must list at least one core.",
+                        task_struct.ident
+                    ),
+                ));
+            }
+            if let Some(spawner) = params.spawn_by.iter().find(|c| **c >= cores) {
+                return Err(syn::Error::new(
+                    task_struct.attrs[attr_idx].span(),
+                    format!(
+                        "Task `{}` is spawned by core {spawner}, but the application only has {cores} core(s).",
+                        task_struct.ident
+                    ),
+                ));
+            }
+            if params.on_full.rewrites_pending_inputs() && params.remote_spawners().next().is_some()
+            {
+                return Err(syn::Error::new(
+                    task_struct.attrs[attr_idx].span(),
+                    format!(
+                        "`on_full = {}` of task `{}` requires it to be spawned only from core {}, as pending inputs can't be rewritten while another core dispatches them. Use `reject` or `drop_newest` instead.",
+                        params.on_full.name(),
+                        task_struct.ident,
+                        params.core
+                    ),
+                ));
+            }
+            Self::validate_spawn_sites(
+                &task_struct.ident,
+                &params,
+                spawn_graph.sites(&task_struct.ident),
+            )?;
+
             let task = SoftwareTask {
                 params,
                 task_struct,
                 task_impl,
             };
 
-            if task.params.core == task.params.spawn_by {
+            if task.params.remote_spawners().next().is_none() {
                 sw_tasks
                     .entry(task.params.core)
                     .or_insert(Vec::new())
@@ -107,9 +171,40 @@ impl App {
             app_params,
             sub_apps,
             rest_of_code,
+            spawn_graph,
         })
     }
 
+    /// Ensures every spawn call uses the API matching the core it runs on:
+    /// `spawn` on the core owning the task, `spawn_from` on the other cores listed in `spawn_by`.
+    fn validate_spawn_sites(
+        task: &Ident,
+        params: &TaskParams,
+        sites: &[SpawnSite],
+    ) -> syn::Result<()> {
+        let core = params.core;
+        for site in sites {
+            let caller = &site.caller;
+            let caller_core = site.caller_core;
+            let error = match site.kind {
+                SpawnKind::Spawn if caller_core != core => Some(format!(
+                    "`{task}::spawn` is called by `{caller}` on core {caller_core}, but `{task}` runs on core {core}. Use `{task}::spawn_from(..)` to spawn it from another core."
+                )),
+                SpawnKind::SpawnFrom if caller_core == core => Some(format!(
+                    "`{task}::spawn_from` is called by `{caller}` on core {core}, which already owns `{task}`. Use `{task}::spawn(..)` instead."
+                )),
+                _ if !params.spawn_by.contains(&caller_core) => Some(format!(
+                    "`{task}` is spawned by `{caller}` on core {caller_core}, but core {caller_core} is not listed in its `spawn_by` argument."
+                )),
+                _ => None,
+            };
+            if let Some(error) = error {
+                return Err(syn::Error::new(site.span, error));
+            }
+        }
+        Ok(())
+    }
+
     /// returns the index of the `attr_name` attribute if found in the attribute list of some struct
     fn is_struct_with_attr(strct: &ItemStruct, attr_name: &str) -> Option<usize> {
         for (i, attr) in strct.attrs.iter().enumerate() {
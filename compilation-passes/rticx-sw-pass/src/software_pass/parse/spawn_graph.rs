@@ -0,0 +1,184 @@
+//! Discovery of `X::spawn(..)` and `X::spawn_from(..)` calls in the application.
+//!
+//! The software pass walks the bodies of every context whose core is known (`#[init]` functions,
+//! and `impl` blocks of hardware, idle and software tasks) and records which core spawns which
+//! software task. The result is used to infer `spawn_by`, to reject spawns from the wrong core and
+//! is published on the `InfoBus` for other passes and backends.
+//!
+//! The calls are found syntactically, so the graph is not complete: spawns inside macro
+//! invocations (`defmt::unwrap!(X::spawn(..))`), `X::spawn` used as a function pointer, `use`
+//! aliases of the task type and callers outside of the `#[app]` module are missed. `spawn_by` is
+//! therefore only inferred when not given, and the graph must not be relied on for soundness.
+
+use proc_macro2::Span;
+use rticx_core::parse_utils::RticAttr;
+use rticx_core::parse_utils::spawn_calls::{self, SpawnCall, impl_self_ident};
+use rticx_core::parser::ast::InitTaskArgs;
+use std::collections::{HashMap, HashSet};
+use syn::{Expr, Ident, Item, Lit};
+
+pub use rticx_core::parse_utils::spawn_calls::SpawnKind;
+
+/// A single spawn call found in the application
+#[derive(Debug, Clone)]
+pub struct SpawnSite {
+    /// The task or `#[init]` function containing the call
+    pub caller: Ident,
+    /// The core the caller runs on
+    pub caller_core: u32,
+    /// The priority the caller runs at, `None` for `#[init]` which runs with interrupts disabled
+    pub caller_priority: Option<u16>,
+    pub kind: SpawnKind,
+    /// Span of the call expression, used for error reporting
+    pub span: Span,
+}
+
+/// Maps every software task to the places it is spawned from.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnGraph {
+    pub spawn_sites: HashMap<Ident, Vec<SpawnSite>>,
+    /// Software tasks also spawned from code outside of any task or `#[init]` body (free functions,
+    /// impls of other types, ...). The priority of such callers is unknown.
+    pub untracked_spawns: HashSet<Ident>,
+}
+
+impl SpawnGraph {
+    /// Scan `items` for spawn calls targeting one of the `sw_tasks` (task name -> (core, priority)).
+    ///
+    /// `rest_of_code` hardware/idle task structs are used to learn the core of their `impl` blocks,
+    /// and `sw_task_impls` are scanned on the core of the software task they implement.
+    pub fn build<'a>(
+        sw_tasks: &HashMap<Ident, (u32, u16)>,
+        sw_task_impls: impl Iterator<Item = &'a syn::ItemImpl>,
+        rest_of_code: &'a [Item],
+    ) -> syn::Result<Self> {
+        // learn the core and priority of every task, software tasks included
+        let mut tasks = sw_tasks.clone();
+        for item in rest_of_code {
+            if let Item::Struct(strct) = item {
+                for attr in strct.attrs.iter() {
+                    if attr.path().is_ident("task") {
+                        let attr = RticAttr::parse_from_attr(attr)?;
+                        let priority = int_arg(&attr, "priority")
+                            .unwrap_or_else(rticx_core::default_task_priority);
+                        tasks.insert(
+                            strct.ident.clone(),
+                            (int_arg(&attr, "core").unwrap_or(0), priority),
+                        );
+                    } else if attr.path().is_ident("idle") {
+                        let attr = RticAttr::parse_from_attr(attr)?;
+                        tasks.insert(
+                            strct.ident.clone(),
+                            (int_arg(&attr, "core").unwrap_or(0), 0),
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut graph = Self::default();
+        let rest_of_code_impls = rest_of_code.iter().filter_map(|item| match item {
+            Item::Impl(impl_) => Some(impl_),
+            _ => None,
+        });
+        for impl_ in sw_task_impls.chain(rest_of_code_impls) {
+            let Some(self_ty) = impl_self_ident(impl_) else {
+                continue;
+            };
+            if let Some((core, priority)) = tasks.get(self_ty) {
+                graph.scan(
+                    sw_tasks,
+                    self_ty,
+                    *core,
+                    Some(*priority),
+                    spawn_calls::in_impl(impl_),
+                );
+            }
+        }
+
+        for item in rest_of_code {
+            if let Item::Fn(init) = item
+                && let Some(attr) = init.attrs.iter().find(|attr| attr.path().is_ident("init"))
+            {
+                let core = InitTaskArgs::parse(attr.meta.clone())?.core;
+                graph.scan(
+                    sw_tasks,
+                    &init.sig.ident,
+                    core,
+                    None,
+                    spawn_calls::in_fn(init),
+                );
+            } else if !matches!(item, Item::Impl(impl_) if impl_self_ident(impl_).is_some_and(|ty| tasks.contains_key(ty)))
+            {
+                graph.untracked_spawns.extend(
+                    spawn_calls::in_item(item)
+                        .into_iter()
+                        .map(|call| call.target)
+                        .filter(|task| sw_tasks.contains_key(task)),
+                );
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// The places where `task` is spawned from. Empty if the task is never spawned.
+    pub fn sites(&self, task: &Ident) -> &[SpawnSite] {
+        self.spawn_sites
+            .get(task)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Whether every caller of `task` has been found in the body of a task or an `#[init]` function
+    pub fn callers_known(&self, task: &Ident) -> bool {
+        !self.untracked_spawns.contains(task)
+    }
+
+    /// Sorted list of cores spawning `task`
+    pub fn spawning_cores(&self, task: &Ident) -> Vec<u32> {
+        let mut cores = self
+            .sites(task)
+            .iter()
+            .map(|site| site.caller_core)
+            .collect::<Vec<_>>();
+        cores.sort_unstable();
+        cores.dedup();
+        cores
+    }
+
+    /// Record the spawn `calls` made in the body of `caller` running on `core`.
+    fn scan(
+        &mut self,
+        sw_tasks: &HashMap<Ident, (u32, u16)>,
+        caller: &Ident,
+        core: u32,
+        priority: Option<u16>,
+        calls: Vec<SpawnCall>,
+    ) {
+        for SpawnCall { target, kind, span } in calls {
+            if !sw_tasks.contains_key(&target) {
+                continue;
+            }
+            self.spawn_sites.entry(target).or_default().push(SpawnSite {
+                caller: caller.clone(),
+                caller_core: core,
+                caller_priority: priority,
+                kind,
+                span,
+            });
+        }
+    }
+}
+
+fn int_arg<N: std::str::FromStr>(attr: &RticAttr, name: &str) -> Option<N>
+where
+    N::Err: std::fmt::Display,
+{
+    match attr.elements.get(name) {
+        Some(Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(int), ..
+        })) => int.base10_parse().ok(),
+        _ => None,
+    }
+}
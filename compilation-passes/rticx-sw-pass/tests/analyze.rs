@@ -46,7 +46,7 @@ fn analysis_single_core_one_task_one_dispatcher() {
     let group = &sub.tasks_priority_map[&2];
     assert_eq!(group.len(), 1);
     assert_eq!(group[0].0.to_string(), "Foo");
-    assert_eq!(group[0].1, 0); // core-local task
+    assert_eq!(group[0].1, vec![0]); // core-local task
     assert_eq!(sub.dispatcher_priority_map.len(), 1);
     assert_eq!(
         sub.dispatcher_priority_map[&2]
@@ -196,9 +196,9 @@ fn analysis_multi_core_cross_core_disjoint_prio() {
     let prio3 = &core1.tasks_priority_map[&3];
     assert_eq!(prio3.len(), 1);
     assert_eq!(prio3[0].0.to_string(), "Cross");
-    assert_eq!(prio3[0].1, 0); // spawn_by
+    assert_eq!(prio3[0].1, vec![0]); // spawn_by
     let prio2 = &core1.tasks_priority_map[&2];
-    assert_eq!(prio2[0].1, 1); // core-local on core 1
+    assert_eq!(prio2[0].1, vec![1]); // core-local on core 1
 }
 
 #[test]
@@ -210,14 +210,11 @@ fn analysis_no_tasks_no_dispatchers() {
     assert!(sub.dispatcher_priority_map.is_empty());
 }
 
-// ---------------------------------------------------------------------------
-// Negative scenarios
-// ---------------------------------------------------------------------------
-
 #[test]
-fn analysis_overlapping_local_vs_mc_priority() {
+fn analysis_local_and_mc_tasks_share_priority() {
     // Core 0 has a local task (prio 2) and a cross-core task that runs on
-    // core 0 but is spawned by core 1, also at prio 2 -> overlap.
+    // core 0 but is spawned by core 1, also at prio 2. Each spawner feeds its
+    // own ready queue, so both tasks share one dispatcher.
     let args = common::multi_core_sw_args();
     let items = quote! {
         #[sw_task(priority = 2, core = 0)]
@@ -237,17 +234,17 @@ fn analysis_overlapping_local_vs_mc_priority() {
             fn exec(&mut self, input: u32) {}
         }
     };
-    let result = analyze(args, items);
-    assert_err_contains(
-        result,
-        "overlapping priority with other core-local software tasks",
-    );
+    let analysis = analyze(args, items).expect("analysis succeeds");
+    let core0 = &analysis.sub_analysis[0];
+    assert_eq!(core0.tasks_priority_map.len(), 1);
+    assert_eq!(core0.tasks_priority_map[&2].len(), 2);
+    assert_eq!(core0.spawners(2), vec![0, 1]);
 }
 
 #[test]
 fn analysis_mc_same_prio_diff_spawn_by() {
-    // Two cross-core tasks on core 0, both prio 3, but spawned by different
-    // cores (1 and 2) -> forbidden.
+    // Two cross-core tasks on core 0, both prio 3, spawned by different
+    // cores (1 and 2), plus a logger spawned by every core.
     let args = common::three_core_sw_args();
     let items = quote! {
         #[sw_task(priority = 3, core = 0, spawn_by = 1)]
@@ -266,14 +263,126 @@ fn analysis_mc_same_prio_diff_spawn_by() {
             fn init(_: ()) -> Self { CrossB }
             fn exec(&mut self, input: u32) {}
         }
+        #[sw_task(priority = 3, core = 0, spawn_by = [0, 1, 2])]
+        struct Logger;
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Logger }
+            fn exec(&mut self, input: u32) {}
+        }
     };
-    let result = analyze(args, items);
-    assert_err_contains(
-        result,
-        "have the same priority but they are spawned by different cores",
+    let analysis = analyze(args, items).expect("analysis succeeds");
+    let core0 = &analysis.sub_analysis[0];
+    assert_eq!(core0.tasks_priority_map[&3].len(), 3);
+    assert_eq!(core0.spawners(3), vec![0, 1, 2]);
+    assert!(core0.spawners(4).is_empty());
+}
+
+#[test]
+fn analysis_spawn_ceilings_cover_every_task_of_the_core() {
+    // `Foo` is spawned by `#[init]` and by `Bar` (prio 3), `Baz` only from a free function: the
+    // callers found don't matter, the queues are locked up to the highest priority of the core,
+    // here the preemption threshold of `Tick`.
+    let args: TokenStream = quote!(device = mypac, dispatchers = [IRQ0, IRQ1, IRQ2]);
+    let items = quote! {
+        #[init]
+        fn init() {
+            Foo::spawn(0).unwrap();
+        }
+        #[task(binds = TIM1, priority = 5, threshold = 6)]
+        struct Tick;
+        impl RticTask for Tick {
+            fn init() -> Self { Tick }
+            fn exec(&mut self) {}
+        }
+        #[sw_task(priority = 1)]
+        struct Foo;
+        impl RticSwTask for Foo {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Foo }
+            fn exec(&mut self, input: u32) {}
+        }
+        #[sw_task(priority = 3)]
+        struct Bar;
+        impl RticSwTask for Bar {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Bar }
+            fn exec(&mut self, input: u32) { Foo::spawn(input).ok(); }
+        }
+        #[sw_task(priority = 4)]
+        struct Baz;
+        impl RticSwTask for Baz {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Baz }
+            fn exec(&mut self, input: u32) {}
+        }
+        fn helper() {
+            Baz::spawn(0).ok();
+        }
+    };
+    let analysis = analyze(args, items).expect("analysis succeeds");
+    let sub = &analysis.sub_analysis[0];
+    for priority in [1, 3, 4] {
+        assert_eq!(sub.spawn_ceilings.get(&(priority, 0)), Some(&6));
+    }
+}
+
+#[test]
+fn analysis_spawn_ceilings_per_spawner_core() {
+    // Core 0 spawns `Logger` from idle, core 1 from a prio 2 hw task: each core fills its own
+    // queues, locked up to the highest priority of the spawning core.
+    let args: TokenStream = quote!(
+        device = mypac,
+        cores = 2,
+        dispatchers = [[IRQ0, IRQ2], [IRQ1]]
     );
+    let items = quote! {
+        #[idle(core = 0)]
+        struct Idle0;
+        impl RticIdleTask for Idle0 {
+            fn init() -> Self { Idle0 }
+            fn exec(&mut self) -> ! { loop { Logger::spawn(0).ok(); Latest::spawn(0).ok(); } }
+        }
+        #[task(instances = [(TIM1, 2), (TIM2, 4)], core = 1)]
+        struct Tick;
+        impl RticTask for Tick {
+            fn init() -> Self { Tick }
+            fn exec(&mut self) { Logger::spawn_from(__rticx__internal__Core1, 1).ok(); }
+        }
+        #[sw_task(priority = 1, core = 0)]
+        struct Logger;
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Logger }
+            fn exec(&mut self, input: u32) {}
+        }
+        #[sw_task(priority = 3, core = 0, on_full = coalesce)]
+        struct Latest;
+        impl RticSwTask for Latest {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self { Latest }
+            fn exec(&mut self, input: u32) {}
+        }
+    };
+    let analysis = analyze(args, items).expect("analysis succeeds");
+    let core0 = &analysis.sub_analysis[0];
+    assert_eq!(core0.spawners(1), vec![0, 1]);
+    assert_eq!(core0.spawn_ceilings.get(&(1, 0)), Some(&3));
+    assert_eq!(core0.spawn_ceilings.get(&(1, 1)), Some(&4));
+    // the dispatcher of `Latest` is excluded while `spawn` rewrites its pending inputs
+    assert_eq!(core0.spawn_ceilings.get(&(3, 0)), Some(&3));
 }
 
+// ---------------------------------------------------------------------------
+// Negative scenarios
+// ---------------------------------------------------------------------------
+
 #[test]
 fn analysis_dispatchers_too_few() {
     // Two distinct priorities, but only one dispatcher provided.
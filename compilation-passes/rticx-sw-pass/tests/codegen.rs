@@ -9,7 +9,7 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 use rticx_core::RticPass;
-use rticx_sw_pass::SoftwarePass;
+use rticx_sw_pass::{SoftwarePass, SwPassBackend};
 
 mod common;
 
@@ -95,13 +95,12 @@ fn codegen_expands_single_core_sw_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut __rticx_internal__Foo__INPUTS : rticx :: export :: Queue < < Foo as RticSwTask > :: SpawnInput , 2 > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Foo__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Foo as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Foo__INPUTS" , rticx :: export :: Queue :: new ()) ;
             impl Foo {
                 pub fn spawn (input : < Foo as RticSwTask > :: SpawnInput) -> Result < () , < Foo as RticSwTask > :: SpawnInput > {
-                    let mut inputs_producer = unsafe { __rticx_internal__Foo__INPUTS . split () . 0 } ;
-                    let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__RQ . split () . 0 } ;
-                    /// need to protect by a critical section because many producers of different priorities can spawn/enqueue this task
-                    __rticx_interrupt_free (| | -> Result < () , < Foo as RticSwTask > :: SpawnInput > {
+                    let mut inputs_producer = unsafe { __rticx_internal__Foo__INPUTS . get_mut () . split () . 0 } ;
+                    let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__RQ . get_mut () . split () . 0 } ;
+                    __rticx_ceiling_lock (2u16 , | | -> Result < () , < Foo as RticSwTask > :: SpawnInput > {
                         inputs_producer . enqueue (input) ? ;
                         unsafe { ready_producer . enqueue_unchecked (Core0Prio2Tasks :: Foo) } ;
                         __rticx_local_irq_pend (mypac :: Interrupt :: IRQ0) ;
@@ -123,7 +122,7 @@ fn codegen_expands_single_core_sw_app() {
 
             #[doc (hidden)]
             #[allow (non_upper_case_globals)]
-            static mut __rticx_internal__Core0Prio2Tasks__RQ : rticx :: export :: Queue < Core0Prio2Tasks , 2usize > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Core0Prio2Tasks__RQ : rticx_cell :: RacyCell < rticx :: export :: Queue < Core0Prio2Tasks , 2usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core0Prio2Tasks__RQ" , rticx :: export :: Queue :: new ()) ;
 
             #[doc (hidden)]
             #[task (binds = IRQ0 , priority = 2u16 , core = 0)]
@@ -133,13 +132,13 @@ fn codegen_expands_single_core_sw_app() {
                 fn init () -> Self { Self }
                 fn exec (& mut self) {
                     unsafe {
-                        let mut ready_consumer = __rticx_internal__Core0Prio2Tasks__RQ . split () . 1 ;
+                        let mut ready_consumer = __rticx_internal__Core0Prio2Tasks__RQ . get_mut () . split () . 1 ;
                         while let Some (task) = ready_consumer . dequeue () {
                             match task {
                                 Core0Prio2Tasks :: Foo => {
-                                    let mut input_consumer = __rticx_internal__Foo__INPUTS . split () . 1 ;
+                                    let mut input_consumer = __rticx_internal__Foo__INPUTS . get_mut () . split () . 1 ;
                                     let input = input_consumer . dequeue_unchecked () ;
-                                    FOO . assume_init_mut () . exec (input) ;
+                                    FOO . get_mut () . exec (input) ;
                                 }
                             }
                         }
@@ -221,13 +220,12 @@ fn codegen_expands_multi_core_sw_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut __rticx_internal__Task0__INPUTS : rticx :: export :: Queue < < Task0 as RticSwTask > :: SpawnInput , 2 > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Task0__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Task0 as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Task0__INPUTS" , rticx :: export :: Queue :: new ()) ;
             impl Task0 {
                 pub fn spawn (input : < Task0 as RticSwTask > :: SpawnInput) -> Result < () , < Task0 as RticSwTask > :: SpawnInput > {
-                    let mut inputs_producer = unsafe { __rticx_internal__Task0__INPUTS . split () . 0 } ;
-                    let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__RQ . split () . 0 } ;
-                    /// need to protect by a critical section because many producers of different priorities can spawn/enqueue this task
-                    __rticx_interrupt_free (| | -> Result < () , < Task0 as RticSwTask > :: SpawnInput > {
+                    let mut inputs_producer = unsafe { __rticx_internal__Task0__INPUTS . get_mut () . split () . 0 } ;
+                    let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__RQ . get_mut () . split () . 0 } ;
+                    __rticx_ceiling_lock (2u16 , | | -> Result < () , < Task0 as RticSwTask > :: SpawnInput > {
                         inputs_producer . enqueue (input) ? ;
                         unsafe { ready_producer . enqueue_unchecked (Core0Prio2Tasks :: Task0) } ;
                         __rticx_local_irq_pend_core0 (mypac :: Interrupt :: IRQ0) ;
@@ -248,7 +246,7 @@ fn codegen_expands_multi_core_sw_app() {
 
             #[doc (hidden)]
             #[allow (non_upper_case_globals)]
-            static mut __rticx_internal__Core0Prio2Tasks__RQ : rticx :: export :: Queue < Core0Prio2Tasks , 2usize > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Core0Prio2Tasks__RQ : rticx_cell :: RacyCell < rticx :: export :: Queue < Core0Prio2Tasks , 2usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core0Prio2Tasks__RQ" , rticx :: export :: Queue :: new ()) ;
 
             #[doc (hidden)]
             #[task (binds = IRQ0 , priority = 2u16 , core = 0)]
@@ -263,13 +261,13 @@ fn codegen_expands_multi_core_sw_app() {
                 fn init () -> Self { Self }
                 fn exec (& mut self) {
                     unsafe {
-                        let mut ready_consumer = __rticx_internal__Core0Prio2Tasks__RQ . split () . 1 ;
+                        let mut ready_consumer = __rticx_internal__Core0Prio2Tasks__RQ . get_mut () . split () . 1 ;
                         while let Some (task) = ready_consumer . dequeue () {
                             match task {
                                 Core0Prio2Tasks :: Task0 => {
-                                    let mut input_consumer = __rticx_internal__Task0__INPUTS . split () . 1 ;
+                                    let mut input_consumer = __rticx_internal__Task0__INPUTS . get_mut () . split () . 1 ;
                                     let input = input_consumer . dequeue_unchecked () ;
-                                    TASK0 . assume_init_mut () . exec (input) ;
+                                    TASK0 . get_mut () . exec (input) ;
                                 }
                             }
                         }
@@ -294,13 +292,17 @@ fn codegen_expands_multi_core_sw_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut __rticx_internal__Cross__INPUTS : rticx :: export :: Queue < < Cross as RticSwTask > :: SpawnInput , 2 > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Cross__FromCore0__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Cross as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Cross__FromCore0__INPUTS" , rticx :: export :: Queue :: new ()) ;
             impl Cross {
-                pub fn spawn_from (_spawner : __rticx__internal__Core0 , input : < Cross as RticSwTask > :: SpawnInput) -> Result < () , < Cross as RticSwTask > :: SpawnInput > {
-                    let mut inputs_producer = unsafe { __rticx_internal__Cross__INPUTS . split () . 0 } ;
-                    let mut ready_producer = unsafe { __rticx_internal__Core1Prio3Tasks__RQ . split () . 0 } ;
-                    /// need to protect by a critical section because many producers of different priorities can spawn/enqueue this task
-                    __rticx_interrupt_free (| | -> Result < () , < Cross as RticSwTask > :: SpawnInput > {
+                pub fn spawn_from < S : __rticx_internal__SpawnFrom < Cross > > (spawner : S , input : < Cross as RticSwTask > :: SpawnInput) -> Result < () , < Cross as RticSwTask > :: SpawnInput > {
+                    spawner . spawn_from (input)
+                }
+            }
+            impl __rticx_internal__SpawnFrom < Cross > for __rticx__internal__Core0 {
+                fn spawn_from (self , input : < Cross as RticSwTask > :: SpawnInput) -> Result < () , < Cross as RticSwTask > :: SpawnInput > {
+                    let mut inputs_producer = unsafe { __rticx_internal__Cross__FromCore0__INPUTS . get_mut () . split () . 0 } ;
+                    let mut ready_producer = unsafe { __rticx_internal__Core1Prio3Tasks__FromCore0__RQ . get_mut () . split () . 0 } ;
+                    __rticx_ceiling_lock (2u16 , | | -> Result < () , < Cross as RticSwTask > :: SpawnInput > {
                         inputs_producer . enqueue (input) ? ;
                         unsafe { ready_producer . enqueue_unchecked (Core1Prio3Tasks :: Cross) } ;
                         __rticx_cross_irq_pend_core1 (mypac :: Interrupt :: IRQ1) ;
@@ -321,7 +323,7 @@ fn codegen_expands_multi_core_sw_app() {
 
             #[doc (hidden)]
             #[allow (non_upper_case_globals)]
-            static mut __rticx_internal__Core1Prio3Tasks__RQ : rticx :: export :: Queue < Core1Prio3Tasks , 2usize > = rticx :: export :: Queue :: new () ;
+            static __rticx_internal__Core1Prio3Tasks__FromCore0__RQ : rticx_cell :: RacyCell < rticx :: export :: Queue < Core1Prio3Tasks , 2usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core1Prio3Tasks__FromCore0__RQ" , rticx :: export :: Queue :: new ()) ;
 
             #[doc (hidden)]
             #[task (binds = IRQ1 , priority = 3u16 , core = 1)]
@@ -336,13 +338,13 @@ fn codegen_expands_multi_core_sw_app() {
                 fn init () -> Self { Self }
                 fn exec (& mut self) {
                     unsafe {
-                        let mut ready_consumer = __rticx_internal__Core1Prio3Tasks__RQ . split () . 1 ;
+                        let mut ready_consumer = __rticx_internal__Core1Prio3Tasks__FromCore0__RQ . get_mut () . split () . 1 ;
                         while let Some (task) = ready_consumer . dequeue () {
                             match task {
                                 Core1Prio3Tasks :: Cross => {
-                                    let mut input_consumer = __rticx_internal__Cross__INPUTS . split () . 1 ;
+                                    let mut input_consumer = __rticx_internal__Cross__FromCore0__INPUTS . get_mut () . split () . 1 ;
                                     let input = input_consumer . dequeue_unchecked () ;
-                                    CROSS . assume_init_mut () . exec (input) ;
+                                    CROSS . get_mut () . exec (input) ;
                                 }
                             }
                         }
@@ -353,3 +355,522 @@ fn codegen_expands_multi_core_sw_app() {
         "core1 dispatcher exec",
     );
 }
+
+// ===========================================================================
+// Task spawned from several cores
+// ===========================================================================
+
+#[test]
+fn codegen_expands_multi_spawner_sw_task() {
+    let items = quote! {
+        #[sw_task(priority = 2, core = 0, spawn_by = [0, 1])]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self {
+                Logger
+            }
+            fn exec(&mut self, input: u32) {}
+        }
+
+        #[sw_task(priority = 2, core = 0)]
+        struct Local;
+
+        impl RticSwTask for Local {
+            type InitArgs = ();
+            type SpawnInput = u32;
+            fn init(_: ()) -> Self {
+                Local
+            }
+            fn exec(&mut self, input: u32) {}
+        }
+    };
+    let generated = run_pass(common::multi_core_sw_args(), common::app_mod(items), true);
+
+    // ---- spawn_from trait ----
+    assert_section_present(
+        &generated,
+        quote! {
+            pub trait __rticx_internal__SpawnFrom < T : RticSwTask > {
+                fn spawn_from (self , input : T :: SpawnInput) -> Result < () , T :: SpawnInput > ;
+            }
+        },
+        "spawn_from trait",
+    );
+
+    // ---- one producer queue per spawner ----
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_internal__Logger__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Logger as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Logger__INPUTS" , rticx :: export :: Queue :: new ()) ;
+            static __rticx_internal__Logger__FromCore1__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Logger as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Logger__FromCore1__INPUTS" , rticx :: export :: Queue :: new ()) ;
+        },
+        "per-spawner inputs queues",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            let mut inputs_producer = unsafe { __rticx_internal__Logger__INPUTS . get_mut () . split () . 0 } ;
+            let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__RQ . get_mut () . split () . 0 } ;
+        },
+        "local spawn() queues",
+    );
+    // no task runs on core 1: nothing there preempts the spawner
+    assert_section_present(
+        &generated,
+        quote! {
+            impl __rticx_internal__SpawnFrom < Logger > for __rticx__internal__Core1 {
+                fn spawn_from (self , input : < Logger as RticSwTask > :: SpawnInput) -> Result < () , < Logger as RticSwTask > :: SpawnInput > {
+                    let mut inputs_producer = unsafe { __rticx_internal__Logger__FromCore1__INPUTS . get_mut () . split () . 0 } ;
+                    let mut ready_producer = unsafe { __rticx_internal__Core0Prio2Tasks__FromCore1__RQ . get_mut () . split () . 0 } ;
+                    __rticx_ceiling_lock (0u16 , | | -> Result < () , < Logger as RticSwTask > :: SpawnInput > {
+                        inputs_producer . enqueue (input) ? ;
+                        unsafe { ready_producer . enqueue_unchecked (Core0Prio2Tasks :: Logger) } ;
+                        __rticx_cross_irq_pend_core0 (mypac :: Interrupt :: IRQ0) ;
+                        Ok (())
+                    })
+                }
+            }
+        },
+        "core1 spawn_from() impl",
+    );
+
+    // ---- one ready queue per spawner, drained by the same dispatcher ----
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_internal__Core0Prio2Tasks__RQ : rticx_cell :: RacyCell < rticx :: export :: Queue < Core0Prio2Tasks , 3usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core0Prio2Tasks__RQ" , rticx :: export :: Queue :: new ()) ;
+            #[doc (hidden)]
+            #[allow (non_upper_case_globals)]
+            static __rticx_internal__Core0Prio2Tasks__FromCore1__RQ : rticx_cell :: RacyCell < rticx :: export :: Queue < Core0Prio2Tasks , 2usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core0Prio2Tasks__FromCore1__RQ" , rticx :: export :: Queue :: new ()) ;
+        },
+        "per-spawner ready queues",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            let mut ready_consumer = __rticx_internal__Core0Prio2Tasks__FromCore1__RQ . get_mut () . split () . 1 ;
+            while let Some (task) = ready_consumer . dequeue () {
+                match task {
+                    Core0Prio2Tasks :: Logger => {
+                        let mut input_consumer = __rticx_internal__Logger__FromCore1__INPUTS . get_mut () . split () . 1 ;
+                        let input = input_consumer . dequeue_unchecked () ;
+                        LOGGER . get_mut () . exec (input) ;
+                    }
+                    _ => {}
+                }
+            }
+        },
+        "dispatcher drains core1 ready queue",
+    );
+}
+
+#[test]
+fn codegen_warns_about_never_spawned_tasks() {
+    let items = quote! {
+        #[init]
+        fn init() {
+            let _ = Spawned::spawn(0);
+        }
+
+        #[sw_task(priority = 2)]
+        struct Spawned;
+
+        #[sw_task(priority = 2)]
+        struct Orphan;
+    };
+    let generated = run_pass(common::single_core_sw_args(), common::app_mod(items), false);
+    assert_section_present(
+        &generated,
+        quote! {
+            #[deprecated (note = "software task `Orphan` is never spawned by `#[init]` or by any task of the application. Set its `spawn_by` or `infer_spawn_by = false` if it is spawned from code not found by the software pass")]
+        },
+        "unreachable task warning",
+    );
+    assert!(!generated.contains("software task `Spawned` is never spawned"));
+}
+
+#[test]
+fn codegen_does_not_warn_about_tasks_spawned_elsewhere() {
+    let items = quote! {
+        #[sw_task(priority = 2, spawn_by = 0)]
+        struct Explicit;
+
+        #[sw_task(priority = 2, infer_spawn_by = false)]
+        struct OptedOut;
+
+        #[sw_task(priority = 2)]
+        struct Untracked;
+
+        fn spawn_untracked() {
+            let _ = Untracked::spawn(0);
+        }
+    };
+    let generated = run_pass(common::single_core_sw_args(), common::app_mod(items), false);
+    assert!(!generated.contains("is never spawned"), "{generated}");
+}
+
+#[test]
+fn codegen_checks_spawn_inputs_are_send() {
+    let items = quote! {
+        #[init]
+        fn init() {
+            let _ = FromInit::spawn(0);
+        }
+
+        #[task(binds = UART0, priority = 1)]
+        struct Low;
+
+        impl RticTask for Low {
+            fn exec(&mut self) {
+                let _ = Higher::spawn(0);
+            }
+        }
+
+        #[task(binds = UART1, priority = 2)]
+        struct Same;
+
+        impl RticTask for Same {
+            fn exec(&mut self) {
+                let _ = SamePriority::spawn(0);
+            }
+        }
+
+        #[sw_task(priority = 2)]
+        struct FromInit;
+
+        #[sw_task(priority = 2)]
+        struct Higher;
+
+        #[sw_task(priority = 2)]
+        struct SamePriority;
+    };
+    let generated = run_pass(common::single_core_sw_args(), common::app_mod(items), false);
+    assert_section_present(
+        &generated,
+        quote! {
+            const _ : fn () = | | {
+                #[diagnostic :: on_unimplemented (message = "software task `Higher` of priority 2 is spawned from `Low` of priority 1, its `SpawnInput` type `{Self}` must be `Send`" , label = "not `Send`")]
+                trait BoundCheck { }
+                impl < T : Send + ? Sized > BoundCheck for T { }
+                fn bound_check < T : BoundCheck + ? Sized > () { }
+                bound_check :: < < Higher as RticSwTask > :: SpawnInput > () ;
+            } ;
+        },
+        "inputs Send check",
+    );
+    assert!(generated.contains(
+        "software task `FromInit` of priority 2 is spawned from `#[init]` function `init`"
+    ));
+    assert!(!generated.contains("software task `SamePriority`"));
+
+    // inputs of cross-core tasks are sent between cores
+    let generated = run_pass(
+        common::multi_core_sw_args(),
+        common::multi_core_sw_app_module(),
+        true,
+    );
+    assert!(generated.contains("software task `Cross` of priority 3 is spawned from core 0"));
+}
+
+// ===========================================================================
+// Inputs queue overflow policies
+// ===========================================================================
+
+/// Single-core app with one task per non-default `on_full` policy
+fn overflow_policies_app_module() -> syn::ItemMod {
+    common::app_mod(quote! {
+        #[sw_task(priority = 2, on_full = drop_newest)]
+        struct Dropper;
+
+        #[sw_task(priority = 2, on_full = overwrite_oldest)]
+        struct Mailbox;
+
+        #[sw_task(priority = 2, on_full = coalesce)]
+        struct Coalescer;
+    })
+}
+
+#[test]
+fn codegen_on_full_drop_newest_counts_overruns() {
+    let generated = run_pass(
+        common::single_core_sw_args(),
+        overflow_policies_app_module(),
+        false,
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_internal__Dropper__OVERRUNS : core :: sync :: atomic :: AtomicU32 = core :: sync :: atomic :: AtomicU32 :: new (0) ;
+            impl Dropper {
+                #[doc = r" Number of inputs dropped by `spawn` because the inputs queue of the task was full"]
+                pub fn spawn_overruns () -> u32 {
+                    0u32 . wrapping_add (__rticx_internal__Dropper__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed))
+                }
+            }
+        },
+        "overrun counter and accessor",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            if inputs_producer . enqueue (input) . is_err () {
+                __rticx_internal__Dropper__OVERRUNS . store (__rticx_internal__Dropper__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed) . wrapping_add (1) , core :: sync :: atomic :: Ordering :: Relaxed) ;
+                return Ok (()) ;
+            }
+            unsafe { ready_producer . enqueue_unchecked (Core0Prio2Tasks :: Dropper) } ;
+        },
+        "drop_newest spawn",
+    );
+    // the dispatcher doesn't need a critical section for this policy
+    assert_section_present(
+        &generated,
+        quote! {
+            let mut input_consumer = __rticx_internal__Dropper__INPUTS . get_mut () . split () . 1 ;
+            let input = input_consumer . dequeue_unchecked () ;
+        },
+        "drop_newest dispatch",
+    );
+}
+
+#[test]
+fn codegen_on_full_counts_overruns_per_spawner_core() {
+    let items = quote! {
+        #[sw_task(priority = 2, core = 0, spawn_by = [0, 1], on_full = drop_newest)]
+        struct Logger;
+    };
+    let generated = run_pass(common::multi_core_sw_args(), common::app_mod(items), true);
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn spawn_overruns () -> u32 {
+                0u32
+                    . wrapping_add (__rticx_internal__Logger__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed))
+                    . wrapping_add (__rticx_internal__Logger__FromCore1__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed))
+            }
+        },
+        "sum of the counters of every spawner",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            __rticx_internal__Logger__FromCore1__OVERRUNS . store (__rticx_internal__Logger__FromCore1__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed) . wrapping_add (1) , core :: sync :: atomic :: Ordering :: Relaxed) ;
+        },
+        "core 1 counts its own overruns",
+    );
+}
+
+#[test]
+fn codegen_on_full_overwrite_oldest_and_coalesce() {
+    let generated = run_pass(
+        common::single_core_sw_args(),
+        overflow_policies_app_module(),
+        false,
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            if unsafe { __rticx_internal__Mailbox__INPUTS . get_mut () . enqueue_overwrite (input) } . is_some () {
+                __rticx_internal__Mailbox__OVERRUNS . store (__rticx_internal__Mailbox__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed) . wrapping_add (1) , core :: sync :: atomic :: Ordering :: Relaxed) ;
+                return Ok (()) ;
+            }
+        },
+        "overwrite_oldest spawn",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            let input = match unsafe { __rticx_internal__Coalescer__INPUTS . get_mut () . replace_newest (input) } {
+                Ok (_replaced) => {
+                    __rticx_internal__Coalescer__OVERRUNS . store (__rticx_internal__Coalescer__OVERRUNS . load (core :: sync :: atomic :: Ordering :: Relaxed) . wrapping_add (1) , core :: sync :: atomic :: Ordering :: Relaxed) ;
+                    return Ok (()) ;
+                }
+                Err (input) => input ,
+            } ;
+            unsafe { __rticx_internal__Coalescer__INPUTS . get_mut () . enqueue (input) } ? ;
+        },
+        "coalesce spawn",
+    );
+    // pending inputs are rewritten by spawn: the dispatcher dequeues them under the spawn lock
+    for task in ["Mailbox", "Coalescer"] {
+        let inputs = quote::format_ident!("__rticx_internal__{task}__INPUTS");
+        assert_section_present(
+            &generated,
+            quote! {
+                let mut input_consumer = #inputs . get_mut () . split () . 1 ;
+                let input = __rticx_ceiling_lock (2u16 , | | input_consumer . dequeue_unchecked ()) ;
+            },
+            "locked dispatch",
+        );
+    }
+}
+
+// ===========================================================================
+// SRP locking of the spawn queues
+// ===========================================================================
+
+#[test]
+fn codegen_spawn_locks_queues_at_ceiling() {
+    let items = quote! {
+        #[init]
+        fn init() {
+            let _ = Foo::spawn(0);
+        }
+
+        #[task(binds = TIM1, priority = 4)]
+        struct Tick;
+        impl RticTask for Tick {
+            fn init() -> Self { Tick }
+            fn exec(&mut self) { let _ = Foo::spawn(1); }
+        }
+
+        #[idle]
+        struct Idle;
+        impl RticIdleTask for Idle {
+            fn init() -> Self { Idle }
+            fn exec(&mut self) -> ! { loop { let _ = Latest::spawn(0); } }
+        }
+
+        #[sw_task(priority = 2)]
+        struct Foo;
+
+        #[sw_task(priority = 3, on_full = coalesce)]
+        struct Latest;
+    };
+    let args = quote!(device = mypac, dispatchers = [IRQ0, IRQ1]);
+    let generated = run_pass(args, common::app_mod(items), false);
+
+    // every task of the spawning core is masked while enqueueing, whoever the spawners found are
+    // the dispatcher interrupt is picked in no particular order, so only match the head of the lock
+    let spawn_lock = "__rticx_ceiling_lock (4u16 , | | -> Result < () , < Foo as RticSwTask > :: SpawnInput > { inputs_producer . enqueue (input) ? ;";
+    assert!(
+        generated.contains(spawn_lock),
+        "spawn() is not locked at the core ceiling:\n{generated}"
+    );
+    // pending inputs of `Latest` are rewritten by spawn: its dispatcher locks them too
+    assert_section_present(
+        &generated,
+        quote! {
+            let input = __rticx_ceiling_lock (4u16 , | | input_consumer . dequeue_unchecked ()) ;
+        },
+        "dispatcher locked at the queue ceiling",
+    );
+    assert!(!generated.contains("__rticx_interrupt_free"));
+}
+
+#[test]
+fn codegen_dispatches_tasks_at_their_threshold() {
+    let items = quote! {
+        #[sw_task(priority = 2, threshold = 3)]
+        struct Raised;
+
+        #[sw_task(priority = 2)]
+        struct Plain;
+    };
+    let generated = run_pass(common::single_core_sw_args(), common::app_mod(items), false);
+
+    assert_section_present(
+        &generated,
+        quote! {
+            __rticx_ceiling_lock (3u16 , | | { RAISED . get_mut () . exec (input) ; }) ;
+        },
+        "task executed at its threshold",
+    );
+    // tasks without a threshold run at the dispatcher priority
+    assert_section_present(
+        &generated,
+        quote! {
+            let input = input_consumer . dequeue_unchecked () ;
+            PLAIN . get_mut () . exec (input) ;
+        },
+        "task executed at its priority",
+    );
+    // the threshold reaches the core pass, where the task locks its resources from it
+    assert!(generated.contains("threshold = 3"));
+}
+
+// ===========================================================================
+// Ready queue type
+// ===========================================================================
+
+/// Mock backend opting into the multi-producer queue for ready queues
+struct MpscReadyQueuesBackend(MockSwBackend);
+
+impl SwPassBackend for MpscReadyQueuesBackend {
+    fn queue_path(&self) -> syn::Path {
+        self.0.queue_path()
+    }
+
+    fn ready_queue_path(&self) -> Option<syn::Path> {
+        Some(syn::parse_quote!(rticx::export::MpscQueue))
+    }
+
+    fn generate_local_pend_fn(&self, core: u32, empty_body_fn: syn::ItemFn) -> syn::ItemFn {
+        self.0.generate_local_pend_fn(core, empty_body_fn)
+    }
+
+    fn generate_cross_pend_fn(&self, core: u32, empty_body_fn: syn::ItemFn) -> Option<syn::ItemFn> {
+        self.0.generate_cross_pend_fn(core, empty_body_fn)
+    }
+}
+
+#[test]
+fn codegen_uses_backend_ready_queue_path() {
+    let pass = SoftwarePass::new(MpscReadyQueuesBackend(MockSwBackend { cross: false }));
+    let (_, module) = pass
+        .run_pass(
+            common::single_core_sw_args(),
+            common::single_core_sw_app_module(),
+        )
+        .expect("pass succeeds");
+    let generated = mod_to_string(&module);
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_internal__Core0Prio2Tasks__RQ : rticx_cell :: RacyCell < rticx :: export :: MpscQueue < Core0Prio2Tasks , 2usize > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Core0Prio2Tasks__RQ" , rticx :: export :: MpscQueue :: new ()) ;
+        },
+        "ready queue of the backend type",
+    );
+    // inputs queues keep the default queue type
+    assert_section_present(
+        &generated,
+        quote! {
+            static __rticx_internal__Foo__INPUTS : rticx_cell :: RacyCell < rticx :: export :: Queue < < Foo as RticSwTask > :: SpawnInput , 2 > > = rticx_cell :: RacyCell :: new ("__rticx_internal__Foo__INPUTS" , rticx :: export :: Queue :: new ()) ;
+        },
+        "inputs queue of the default type",
+    );
+    // only the inputs queue is locked, the task is enqueued and the dispatcher pended after it
+    assert_section_present(
+        &generated,
+        quote! {
+            let mut make_ready = false ;
+            __rticx_ceiling_lock (2u16 , | | -> Result < () , < Foo as RticSwTask > :: SpawnInput > {
+                inputs_producer . enqueue (input) ? ;
+                make_ready = true ;
+                Ok (())
+            }) . map (| () | {
+                if make_ready {
+                    unsafe { __rticx_internal__Core0Prio2Tasks__RQ . get () . enqueue_unchecked (Core0Prio2Tasks :: Foo) } ;
+                    __rticx_local_irq_pend(mypac :: Interrupt :: IRQ0) ;
+                }
+            })
+        },
+        "spawn() without lock on the ready queue",
+    );
+    // no `&mut` to the ready queue is formed while the spawners use it
+    assert_section_present(
+        &generated,
+        quote! {
+            let ready_queue = __rticx_internal__Core0Prio2Tasks__RQ . get () ;
+        },
+        "dispatcher taking a shared reference to the ready queue",
+    );
+    assert_section_present(
+        &generated,
+        quote!(while let Some(task) = ready_queue.dequeue_shared()),
+        "dispatcher dequeuing through a shared reference",
+    );
+    assert!(!generated.contains("ready_producer"), "{generated}");
+}
@@ -9,7 +9,7 @@ use quote::ToTokens;
 use quote::quote;
 use rticx_core::parse_utils::RticAttr;
 use rticx_sw_pass::software_pass::parse::App;
-use rticx_sw_pass::software_pass::parse::ast::{AppParameters, TaskParams};
+use rticx_sw_pass::software_pass::parse::ast::{AppParameters, OverflowPolicy, TaskParams};
 
 mod common;
 
@@ -117,27 +117,93 @@ fn sw_task_attr(args: TokenStream) -> RticAttr {
 #[test]
 fn task_params_defaults() {
     let attr = sw_task_attr(quote!());
-    let params = TaskParams::from_attr(&attr);
+    let params = TaskParams::from_attr(&attr).expect("valid params");
     assert_eq!(params.priority, 0);
     assert_eq!(params.core, 0);
-    assert_eq!(params.spawn_by, 0);
+    assert_eq!(params.spawn_by, vec![0]);
 }
 
 #[test]
 fn task_params_explicit_values() {
     let attr = sw_task_attr(quote!(priority = 3, core = 1, spawn_by = 0));
-    let params = TaskParams::from_attr(&attr);
+    let params = TaskParams::from_attr(&attr).expect("valid params");
     assert_eq!(params.priority, 3);
     assert_eq!(params.core, 1);
-    assert_eq!(params.spawn_by, 0);
+    assert_eq!(params.spawn_by, vec![0]);
 }
 
 #[test]
 fn task_params_spawn_by_defaults_to_core() {
     let attr = sw_task_attr(quote!(core = 2));
-    let params = TaskParams::from_attr(&attr);
+    let params = TaskParams::from_attr(&attr).expect("valid params");
     assert_eq!(params.core, 2);
-    assert_eq!(params.spawn_by, 2);
+    assert_eq!(params.spawn_by, vec![2]);
+}
+
+#[test]
+fn task_params_on_full_defaults_to_reject() {
+    let attr = sw_task_attr(quote!(priority = 1));
+    let params = TaskParams::from_attr(&attr).expect("valid params");
+    assert_eq!(params.on_full, OverflowPolicy::Reject);
+    assert!(!params.on_full.counts_overruns());
+}
+
+#[test]
+fn task_params_on_full_policies() {
+    for (policy, expected) in [
+        (quote!(reject), OverflowPolicy::Reject),
+        (quote!(drop_newest), OverflowPolicy::DropNewest),
+        (quote!(overwrite_oldest), OverflowPolicy::OverwriteOldest),
+        (quote!(coalesce), OverflowPolicy::Coalesce),
+    ] {
+        let attr = sw_task_attr(quote!(on_full = #policy));
+        let params = TaskParams::from_attr(&attr).expect("valid params");
+        assert_eq!(params.on_full, expected);
+        assert_eq!(params.on_full.name(), policy.to_string());
+    }
+}
+
+#[test]
+fn task_params_on_full_unknown_policy_errors() {
+    let attr = sw_task_attr(quote!(on_full = drop_oldest));
+    assert_err_contains(
+        TaskParams::from_attr(&attr),
+        "`on_full` must be one of `reject`, `drop_newest`, `overwrite_oldest` or `coalesce`.",
+    );
+}
+
+#[test]
+fn task_params_spawn_by_list_sorted_and_deduplicated() {
+    let attr = sw_task_attr(quote!(core = 0, spawn_by = [2, 0, 2]));
+    let params = TaskParams::from_attr(&attr).expect("valid params");
+    assert_eq!(params.spawn_by, vec![0, 2]);
+    assert!(params.is_spawned_locally());
+    assert_eq!(params.remote_spawners().collect::<Vec<_>>(), vec![2]);
+}
+
+#[test]
+fn task_params_spawn_by_list_rejects_non_integer_cores() {
+    let attr = sw_task_attr(quote!(core = 0, spawn_by = [0, x]));
+    assert_err_contains(
+        TaskParams::from_attr(&attr),
+        "`spawn_by` must list core numbers, like `spawn_by = [0, 1]`.",
+    );
+}
+
+#[test]
+fn task_params_infer_spawn_by_only_without_spawn_by() {
+    let params = TaskParams::from_attr(&sw_task_attr(quote!(core = 0))).expect("valid params");
+    assert!(params.infer_spawn_by);
+    let params =
+        TaskParams::from_attr(&sw_task_attr(quote!(core = 0, spawn_by = 0))).expect("valid params");
+    assert!(!params.infer_spawn_by);
+    let params = TaskParams::from_attr(&sw_task_attr(quote!(core = 0, infer_spawn_by = false)))
+        .expect("valid params");
+    assert!(!params.infer_spawn_by);
+    assert_err_contains(
+        TaskParams::from_attr(&sw_task_attr(quote!(infer_spawn_by = 0))),
+        "`infer_spawn_by` must be `true` or `false`.",
+    );
 }
 
 // ---------------------------------------------------------------------------
@@ -281,6 +347,60 @@ fn parse_partition_local_vs_cross_core() {
     assert!(core0.sw_tasks.iter().all(|t| t.name() != "Cross"));
 }
 
+#[test]
+fn parse_partition_multi_spawner_task_is_cross_core() {
+    // spawned both locally and by core 1 -> needs the cross-core machinery.
+    let items = quote! {
+        #[sw_task(core = 0, spawn_by = [0, 1])]
+        struct Logger;
+
+        impl RticSwTask for Logger {
+            fn exec(&mut self) {}
+        }
+    };
+    let app = parse_app(common::multi_core_args(), items).expect("valid app");
+    let core0 = &app.sub_apps[0];
+    assert!(core0.sw_tasks.is_empty());
+    assert_eq!(core0.mc_sw_tasks.len(), 1);
+    assert_eq!(core0.mc_sw_tasks[0].params.spawn_by, vec![0, 1]);
+}
+
+#[test]
+fn parse_rewriting_policy_on_cross_core_task_errors() {
+    let items = quote! {
+        #[sw_task(core = 0, spawn_by = [0, 1], on_full = coalesce)]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "`on_full = coalesce` of task `Logger` requires it to be spawned only from core 0",
+    );
+}
+
+#[test]
+fn parse_spawn_by_unknown_core_errors() {
+    let items = quote! {
+        #[sw_task(core = 0, spawn_by = [0, 2])]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "is spawned by core 2, but the application only has 2 core(s)",
+    );
+}
+
+#[test]
+fn parse_spawn_by_empty_list_errors() {
+    let items = quote! {
+        #[sw_task(core = 0, spawn_by = [])]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "must list at least one core",
+    );
+}
+
 #[test]
 fn parse_dispatchers_flat_single_core_routed() {
     let args: TokenStream = quote!(device = mypac, dispatchers = [IRQ0, IRQ1]);
@@ -357,3 +477,163 @@ fn parse_multiple_sw_tasks_in_order() {
     assert_eq!(sub.sw_tasks[0].name().to_string(), "Foo");
     assert_eq!(sub.sw_tasks[1].name().to_string(), "Bar");
 }
+
+// ---------------------------------------------------------------------------
+// Block D : spawn graph & `spawn_by` inference (parse/spawn_graph.rs)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn spawn_graph_infers_spawn_by_from_calls() {
+    let items = quote! {
+        #[init(core = 1)]
+        fn init1() {
+            Logger::spawn_from(core1, 0).unwrap();
+        }
+
+        #[task(binds = UART0, core = 0)]
+        struct Uart;
+
+        impl RticTask for Uart {
+            fn exec(&mut self) {
+                let _ = Logger::spawn(1);
+            }
+        }
+
+        #[sw_task(core = 0)]
+        struct Logger;
+    };
+    let app = parse_app(common::multi_core_args(), items).expect("valid app");
+    let logger = &app.sub_apps[0].mc_sw_tasks[0];
+    assert_eq!(logger.params.spawn_by, vec![0, 1]);
+
+    let name = logger.name();
+    let sites = app.spawn_graph.sites(name);
+    assert_eq!(sites.len(), 2);
+    assert!(
+        sites
+            .iter()
+            .any(|site| site.caller == "Uart" && site.caller_core == 0)
+    );
+    assert!(
+        sites
+            .iter()
+            .any(|site| site.caller == "init1" && site.caller_core == 1)
+    );
+}
+
+#[test]
+fn spawn_graph_resolves_self_in_task_impls() {
+    let items = quote! {
+        #[sw_task(core = 1)]
+        struct Periodic;
+
+        impl RticSwTask for Periodic {
+            fn exec(&mut self) {
+                let _ = Self::spawn(());
+            }
+        }
+    };
+    let app = parse_app(common::multi_core_args(), items).expect("valid app");
+    let periodic = &app.sub_apps[1].sw_tasks[0];
+    assert_eq!(periodic.params.spawn_by, vec![1]);
+    assert_eq!(app.spawn_graph.spawning_cores(periodic.name()), vec![1]);
+}
+
+#[test]
+fn spawn_graph_never_spawned_task_keeps_default_spawn_by() {
+    let items = quote! {
+        #[sw_task(core = 1)]
+        struct Orphan;
+    };
+    let app = parse_app(common::multi_core_args(), items).expect("valid app");
+    let orphan = &app.sub_apps[1].sw_tasks[0];
+    assert_eq!(orphan.params.spawn_by, vec![1]);
+    assert!(app.spawn_graph.sites(orphan.name()).is_empty());
+}
+
+#[test]
+fn spawn_graph_does_not_infer_spawn_by_with_untracked_callers() {
+    let items = quote! {
+        #[task(binds = UART0, core = 0)]
+        struct Uart;
+
+        impl RticTask for Uart {
+            fn exec(&mut self) {
+                let _ = Logger::spawn(1);
+            }
+        }
+
+        fn log_from_anywhere() {
+            let _ = Logger::spawn_from(core1, 0);
+        }
+
+        #[sw_task(core = 0, spawn_by = [0, 1])]
+        struct Logger;
+
+        #[sw_task(core = 0)]
+        struct Tracer;
+
+        fn trace_from_anywhere() {
+            let _ = Tracer::spawn(0);
+        }
+    };
+    let app = parse_app(common::multi_core_args(), items).expect("valid app");
+    let tracer = app.sub_apps[0]
+        .sw_tasks
+        .iter()
+        .find(|task| task.name() == "Tracer")
+        .expect("Tracer on core 0");
+    assert!(!app.spawn_graph.callers_known(tracer.name()));
+    assert_eq!(tracer.params.spawn_by, vec![0]);
+}
+
+#[test]
+fn spawn_from_wrong_core_errors() {
+    let items = quote! {
+        #[init(core = 1)]
+        fn init1() {
+            let _ = Logger::spawn(0);
+        }
+
+        #[sw_task(core = 0)]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "`Logger::spawn` is called by `init1` on core 1, but `Logger` runs on core 0",
+    );
+}
+
+#[test]
+fn spawn_from_owning_core_errors() {
+    let items = quote! {
+        #[init]
+        fn init0() {
+            let _ = Logger::spawn_from(core0, 0);
+        }
+
+        #[sw_task(core = 0)]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "Use `Logger::spawn(..)` instead",
+    );
+}
+
+#[test]
+fn spawn_from_core_missing_in_explicit_spawn_by_errors() {
+    let items = quote! {
+        #[init(core = 1)]
+        fn init1() {
+            let _ = Logger::spawn_from(core1, 0);
+        }
+
+        #[sw_task(core = 0, spawn_by = 0)]
+        struct Logger;
+    };
+    assert_err_contains(
+        parse_app(common::multi_core_args(), items),
+        "core 1 is not listed in its `spawn_by` argument",
+    );
+}
@@ -24,7 +24,10 @@ use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 
-use rticx_core::{Analysis, AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
+use rticx_core::{
+    Analysis, AppArgs, CorePassBackend, InitPeripherals, InterruptControl, RticMacroBuilder,
+    SubAnalysis, SubApp,
+};
 #[cfg(feature = "swtasks")]
 use rticx_sw_pass::{SoftwarePass, SwPassBackend};
 use syn::{parse_quote, ItemFn};
@@ -79,7 +82,7 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
     //
     // For a single-core MCU that does not need auto-assignment you can remove the next `bind_pre_core_pass` call.
     #[cfg(feature = "autoassign")]
-    builder.bind_pre_core_pass(rticx_auto_assign::AutoAssignPass);
+    builder.bind_pre_core_pass(rticx_auto_assign::AutoAssignPass::new());
     #[cfg(feature = "swtasks")]
     builder.bind_pre_core_pass(sw_pass);
 
@@ -150,6 +153,35 @@ impl CorePassBackend for Backend {
         None
     }
 
+    /// Peripherals of the processor core given to `#[init]` when it takes
+    /// its context, `fn init(cx: init::Context)`, as `cx.core`.
+    ///
+    /// The PAC `Peripherals` are given as `cx.device` by the default
+    /// `init_device_peripherals`, override it if your PAC has no such
+    /// singleton.
+    ///
+    /// # Contract
+    /// * `take` is evaluated once per core in an `unsafe` block, with
+    ///   interrupts disabled, before `init` and `post_init` run.
+    ///
+    /// # Porting
+    ///
+    /// * **Cortex-M**: `cortex_m::Peripherals`, or `PAC::CorePeripherals`
+    ///   re-exported by svd2rust PACs, taken with `steal()`.
+    /// * **RISC-V**: return `None`, the core registers are CSRs.
+    ///
+    /// Reference: `rticx-cortex-m`, `rticx-rp2040`.
+    fn init_core_peripherals(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+    ) -> Option<InitPeripherals> {
+        // TODO(port): return the core peripherals of your target, for example
+        //   Some(InitPeripherals { ty: parse_quote!(#pac::CorePeripherals),
+        //                          take: parse_quote!(#pac::CorePeripherals::steal()) })
+        None
+    }
+
     /// # SRP-based Resource Locking
     ///
     /// The core pass generates an incomplete `lock` function for each
@@ -180,7 +212,7 @@ impl CorePassBackend for Backend {
     ///     fn lock<R>(&mut self, f: impl FnOnce(&mut Self::ResourceType) -> R) -> R {
     ///         const CEILING: u16 = 3u16; // resource ceiling
     ///         let task_priority = self.task_priority;
-    ///         let resource_ref = unsafe { &mut SHARED.assume_init_mut().resource1 };
+    ///         let resource_ptr = unsafe { core::ptr::addr_of_mut!((*SHARED.as_mut_ptr()).resource1) };
     ///         /* TODO: HARDWARE-SPECIFIC CODE COMES HERE */
     ///     }
     /// }
@@ -270,13 +302,23 @@ impl CorePassBackend for Backend {
     ///
     /// Return `None` to skip wrapping entirely.
     ///
+    /// # Contract
+    /// * When `threshold` is above `task_prio`, `exec()` must run with the
+    ///   system ceiling raised to `threshold`, which
+    ///   `rticx_core::rticx_functions::raise_to_threshold` does with the
+    ///   ceiling lock function.
+    ///
     /// Reference: `rticx-cortex-m` implements execution wrapping for non-armv6m targets
     fn wrap_task_execution(
         &self,
-        _task_prio: u16,
+        task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
-        None
+        // TODO(port): save the priority state before and restore it after, if needed
+        (threshold > task_prio).then(|| {
+            rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call)
+        })
     }
 
     /// Name of the entry function for each core.
@@ -336,6 +378,83 @@ impl CorePassBackend for Backend {
         empty_body_fn
     }
 
+    /// Body of the priority ceiling lock function (optional).
+    ///
+    /// RTIC generates a function like:
+    ///
+    /// ```ignore
+    /// pub fn __rticx_ceiling_lock<F, R>(ceiling: u16, f: F) -> R
+    /// where F: FnOnce() -> R { /* YOU FILL THIS */ }
+    /// ```
+    ///
+    /// The software pass calls it from `spawn` with the highest priority of
+    /// the tasks spawning into the same queues, so that higher priority
+    /// interrupts are not delayed by the enqueue.
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * Never lower the current ceiling, and restore it when `f` returns.
+    ///
+    /// # Porting
+    ///
+    /// Skip this method to keep the default, which calls the critical-section
+    /// function above. Otherwise reuse the primitive of your resource locks:
+    ///
+    /// * **Cortex-M BASEPRI**: `basepri_max` then restore BASEPRI.
+    /// * **RISC-V mintthresh**: raise the threshold then restore it.
+    ///
+    /// Reference: `rticx-cortex-m` uses its BASEPRI `lock` on armv7-m and
+    /// keeps the critical section on armv6-m.
+    fn generate_ceiling_lock_fn(&self, app_args: &AppArgs, mut empty_body_fn: ItemFn) -> ItemFn {
+        let _ = app_args;
+        let fn_body = parse_quote!({
+            // TODO(port): raise the system ceiling to `ceiling` here
+            let _ = ceiling;
+            let r = __rticx_interrupt_free(f);
+            // TODO(port): restore the previous ceiling here
+            r
+        });
+        empty_body_fn.block = Box::new(fn_body);
+        empty_body_fn
+    }
+
+    /// Body of the functions of hardware tasks acting on their interrupt (optional).
+    ///
+    /// RTIC generates `pend()`, `unpend()`, `is_pending() -> bool`, `enable()`
+    /// and `disable()` for every task with `binds = IRQ`, calling this method
+    /// once per function with `control` telling which one.
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * Enabling and disabling must not interfere with the resource locks.
+    /// * Return `None` for the functions your target can't provide, they are
+    ///   then not generated. Targets with an interrupt controller per core
+    ///   return `None` in multicore applications.
+    ///
+    /// # Porting
+    ///
+    /// Skip this method to generate none of the functions. Otherwise:
+    ///
+    /// * **Cortex-M**: `NVIC::pend`, `NVIC::unpend`, `NVIC::is_pending`,
+    ///   `NVIC::unmask` and `NVIC::mask` on `PAC::Interrupt::IRQ`.
+    /// * **Source masking**: return `None` for `Enable` and `Disable`.
+    ///
+    /// Reference: `rticx-cortex-m`.
+    fn generate_interrupt_control_fn(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        // TODO(port): fill `empty_body_fn.block` for the controls of your interrupt controller
+        // Example for Cortex-M, with `irq = quote!(#pac::Interrupt::#binds)`:
+        //   InterruptControl::Pend => parse_quote!({ <your-crate>::export::NVIC::pend(#irq) })
+        let _ = (app_args, app_info, binds, control, empty_body_fn);
+        None
+    }
+
     /// Validation hook called after parsing and analysis, but before
     /// code generation.
     ///
@@ -382,6 +501,18 @@ impl SwPassBackend for SwBackend {
         parse_quote!(distribution_template::export::Queue)
     }
 
+    /// Path to a lock-free queue type for the dispatchers' ready queues (optional).
+    ///
+    /// # Porting
+    /// * Skip this method to use `queue_path` for ready queues too.
+    /// * On targets with compare-and-swap atomics you can return the
+    ///   lock-free multi-producer queue, `your_crate::export::MpscQueue`,
+    ///   so that `spawn` only locks the inputs queue.
+    ///   Do NOT use it on armv6-m, it is not available there.
+    fn ready_queue_path(&self) -> Option<syn::Path> {
+        None
+    }
+
     /// Body of the core-local interrupt-pending function.
     ///
     /// This function is called by `spawn()` to trigger the dispatcher
@@ -11,7 +11,6 @@ mod app {
     use bsp::{
         clic::{Clic, Polarity, Trig},
         embedded_io::Write,
-        fugit::ExtU32,
         mmap::{
             apb_timer::{TIMER0_ADDR, TIMER1_ADDR, TIMER2_ADDR, TIMER3_ADDR},
             CFG_BASE, PERIPH_CLK_DIV_OFS,
@@ -19,7 +18,7 @@ mod app {
         mtimer::{self, MTimer},
         read_u32, riscv, sprint, sprintln,
         tb::signal_pass,
-        timer_group::{Periodic, Timer},
+        timer_group::Timer,
         uart::*,
         write_u32, Interrupt, CPU_FREQ,
     };
@@ -31,52 +30,48 @@ mod app {
     #[cfg_attr(feature = "ufmt", derive(uDebug))]
     #[cfg_attr(not(feature = "ufmt"), derive(Debug))]
     struct TaskDef {
-        // level is specified in RTIC task
+        // level and period are specified in RTIC task
         level: u8,
-        period_ns: u32,
-        duration_ns: u32,
+        period: u32,
+        duration: u32,
     }
 
     const TEST_DURATION: mtimer::Duration = mtimer::Duration::micros(1_000);
 
     impl TaskDef {
-        pub const fn new(level: u8, period_ns: u32, duration_ns: u32) -> Self {
+        pub const fn new(level: u8, period: u32, duration: u32) -> Self {
             Self {
-                period_ns,
-                duration_ns,
+                period,
+                duration,
                 level,
             }
         }
     }
 
-    const TEST_BASE_PERIOD_NS: u32 = 100_000;
+    // Periods and durations are in `mtime` ticks, i.e., CPU cycles with a peripheral clock
+    // divider of 1. The periods must match the `period` of the tasks.
+    const TEST_BASE_PERIOD: u32 = 12_800;
     const TASK0: TaskDef = TaskDef::new(
         1,
-        TEST_BASE_PERIOD_NS / 4,
-        /* 25 ‰) */ TEST_BASE_PERIOD_NS / 40,
+        TEST_BASE_PERIOD / 4,
+        /* 25 ‰) */ TEST_BASE_PERIOD / 40,
     );
     const TASK1: TaskDef = TaskDef::new(
         2,
-        TEST_BASE_PERIOD_NS / 8,
-        /* 12,5 ‰) */ TEST_BASE_PERIOD_NS / 80,
+        TEST_BASE_PERIOD / 8,
+        /* 12,5 ‰) */ TEST_BASE_PERIOD / 80,
     );
     const TASK2: TaskDef = TaskDef::new(
         3,
-        TEST_BASE_PERIOD_NS / 16,
-        /* 5 ‰) */ TEST_BASE_PERIOD_NS / 200,
+        TEST_BASE_PERIOD / 16,
+        /* 5 ‰) */ TEST_BASE_PERIOD / 200,
     );
     const TASK3: TaskDef = TaskDef::new(
         4,
-        TEST_BASE_PERIOD_NS / 32,
-        /* 2,5 ‰) */ TEST_BASE_PERIOD_NS / 400,
+        TEST_BASE_PERIOD / 32,
+        /* 2,5 ‰) */ TEST_BASE_PERIOD / 400,
     );
     const PERIPH_CLK_DIV: u64 = 1;
-    const CYCLES_PER_SEC: u64 = CPU_FREQ as u64 / PERIPH_CLK_DIV;
-    const CYCLES_PER_MS: u64 = CYCLES_PER_SEC / 1_000;
-    const CYCLES_PER_US: u32 = CYCLES_PER_MS as u32 / 1_000;
-    // !!!: this would saturate to zero, so we must not use it. Use `X *
-    // CYCLES_PER_US / 1_000 instead` and verify the output value is not saturated.
-    /* const CYCLES_PER_NS: u64 = CYCLES_PER_US / 1_000; */
 
     static mut TASK0_COUNT: usize = 0;
     static mut TASK1_COUNT: usize = 0;
@@ -115,18 +110,6 @@ mod app {
         // Use mtimer for timeout
         let mut mtimer = MTimer::instance().into_oneshot();
 
-        let timers = &mut [
-            Timer::init::<TIMER0_ADDR>().into_periodic(),
-            Timer::init::<TIMER1_ADDR>().into_periodic(),
-            Timer::init::<TIMER2_ADDR>().into_periodic(),
-            Timer::init::<TIMER3_ADDR>().into_periodic(),
-        ];
-
-        timers[0].set_period(TASK0.period_ns.nanos());
-        timers[1].set_period(TASK1.period_ns.nanos());
-        timers[2].set_period(TASK2.period_ns.nanos());
-        timers[3].set_period(TASK3.period_ns.nanos());
-
         // --- Test critical ---
         unsafe {
             asm!("fence");
@@ -139,19 +122,17 @@ mod app {
         // Test will end when MachineTimer fires
         mtimer.start(TEST_DURATION);
 
-        // Start periodic timers
-        timers.iter_mut().for_each(Periodic::start);
-
+        // The periodic tasks are released from here on, their first job one period later
         Shared {}
     }
 
-    #[task(binds = Timer0Cmp, priority=1, fast)]
+    #[task(binds = Timer0Cmp, priority=1, period = 3200, fast)]
     struct T0 {}
-    #[task(binds = Timer1Cmp, priority=2, fast)]
+    #[task(binds = Timer1Cmp, priority=2, period = 1600, fast)]
     struct T1 {}
-    #[task(binds = Timer2Cmp, priority=3, fast)]
+    #[task(binds = Timer2Cmp, priority=3, period = 800, fast)]
     struct T2 {}
-    #[task(binds = Timer3Cmp, priority=4, fast)]
+    #[task(binds = Timer3Cmp, priority=4, period = 400, fast)]
     struct T3 {}
 
     impl RticTask for T0 {
@@ -166,7 +147,7 @@ mod app {
                     .rept {CNT}
                     nop
                     .endr
-                "#, CNT = const TASK0.duration_ns * CYCLES_PER_US / 1_000);
+                "#, CNT = const TASK0.duration);
             }
         }
     }
@@ -183,7 +164,7 @@ mod app {
                     .rept {CNT}
                     nop
                     .endr
-                "#, CNT = const TASK1.duration_ns * CYCLES_PER_US / 1_000);
+                "#, CNT = const TASK1.duration);
             }
         }
     }
@@ -200,7 +181,7 @@ mod app {
                     .rept {CNT}
                     nop
                     .endr
-                "#, CNT = const TASK2.duration_ns * CYCLES_PER_US / 1_000);
+                "#, CNT = const TASK2.duration);
             }
         }
     }
@@ -217,7 +198,7 @@ mod app {
                     .rept {CNT}
                     nop
                     .endr
-                "#, CNT = const TASK3.duration_ns * CYCLES_PER_US / 1_000);
+                "#, CNT = const TASK3.duration);
             }
         }
     }
@@ -281,17 +262,24 @@ mod app {
                     TASK2_COUNT,
                     TASK3_COUNT
                 );
-                let total_ns_in_task0 = TASK0.duration_ns * TASK0_COUNT as u32;
-                let total_ns_in_task1 = TASK1.duration_ns * TASK1_COUNT as u32;
-                let total_ns_in_task2 = TASK2.duration_ns * TASK2_COUNT as u32;
-                let total_ns_in_task3 = TASK3.duration_ns * TASK3_COUNT as u32;
                 sprintln!(
-                    "Theoretical total duration spent in task workload (ns):\r\n{} | {} | {} | {} = {}",
-                    total_ns_in_task0,
-                    total_ns_in_task1,
-                    total_ns_in_task2,
-                    total_ns_in_task3,
-                    total_ns_in_task0 + total_ns_in_task1 + total_ns_in_task2 + total_ns_in_task3,
+                    "Task overruns:\r\n{} | {} | {} | {}",
+                    T0::overruns(),
+                    T1::overruns(),
+                    T2::overruns(),
+                    T3::overruns()
+                );
+                let total_ticks_in_task0 = TASK0.duration * TASK0_COUNT as u32;
+                let total_ticks_in_task1 = TASK1.duration * TASK1_COUNT as u32;
+                let total_ticks_in_task2 = TASK2.duration * TASK2_COUNT as u32;
+                let total_ticks_in_task3 = TASK3.duration * TASK3_COUNT as u32;
+                sprintln!(
+                    "Theoretical total duration spent in task workload (ticks):\r\n{} | {} | {} | {} = {}",
+                    total_ticks_in_task0,
+                    total_ticks_in_task1,
+                    total_ticks_in_task2,
+                    total_ticks_in_task3,
+                    total_ticks_in_task0 + total_ticks_in_task1 + total_ticks_in_task2 + total_ticks_in_task3,
                 );
 
                 // Make sure serial is done printing before proceeding to the next iteration
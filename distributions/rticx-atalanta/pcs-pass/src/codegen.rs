@@ -12,14 +12,15 @@ impl Codegen {
     }
 
     pub fn run(&mut self) -> syn::ItemMod {
-        // Generate an entry point for each task
-        let trap_entries = self.app.tasks.iter_mut().map(|task| {
-            let interrupt_name = task.binds.clone();
-            if task.fast {
-                quote!(bsp::generate_pcs_trap_entry!(#interrupt_name);)
-            } else {
-                quote!(bsp::generate_nested_trap_entry!(#interrupt_name);)
-            }
+        // Generate an entry point for each interrupt of the tasks
+        let trap_entries = self.app.tasks.iter().flat_map(|task| {
+            task.binds.iter().map(|interrupt_name| {
+                if task.fast {
+                    quote!(bsp::generate_pcs_trap_entry!(#interrupt_name);)
+                } else {
+                    quote!(bsp::generate_nested_trap_entry!(#interrupt_name);)
+                }
+            })
         });
 
         let mod_visibility = &self.app.mod_visibility;
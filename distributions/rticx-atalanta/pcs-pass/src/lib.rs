@@ -2,38 +2,65 @@ mod codegen;
 // mod error;
 mod parse;
 
-use std::cell::RefCell;
+use std::cmp::Reverse;
 
 use codegen::Codegen;
 use parse::App;
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use rticx_core::parse_utils::RticAttr;
-use rticx_core::RticPass;
+use rticx_core::{InfoBus, RticPass};
 use syn::ItemMod;
 
 pub const PCS_ATTR_IDENT: &str = "fast";
+/// App argument opting in to the automatic selection of PCS tasks, e.g. `pcs = priority`
+pub const PCS_APP_ARG: &str = "pcs";
 
-// HACK: pass the list of dispatchers from PCS pass to core pass backend.
-thread_local!(pub static PCS_DISPATCHERS: RefCell<Vec<proc_macro2::Ident>> = const { RefCell::new(vec![]) });
+pub static INFO_PCS_IRQS: &str = "pcs_pass::PcsIrqs";
+
+/// Interrupts whose handlers use a hardware PCS slot, published on the `InfoBus` as
+/// [`INFO_PCS_IRQS`] for the backend to configure the interrupt controller.
+#[derive(Debug, Clone, Default)]
+pub struct PcsIrqs {
+    pub irqs: Vec<Ident>,
+}
+
+/// How the free PCS slots are given out to tasks not marked `fast`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcsSelection {
+    /// `pcs = priority`: tasks with the highest priority first
+    HighestPriority,
+    /// `pcs = deadline`: tasks with the shortest plain `deadline` first, then by priority
+    ShortestDeadline,
+}
 
 pub struct PcsPass {
     max_num_pcs: usize,
+    info_bus: Option<InfoBus>,
 }
 
 impl PcsPass {
     /// Use `max_num_pcs` to specify the number of hardware PCS slots provided by the platform
     pub fn new(max_num_pcs: usize) -> Self {
-        Self { max_num_pcs }
+        Self {
+            max_num_pcs,
+            info_bus: None,
+        }
     }
 }
 
 impl RticPass for PcsPass {
-    fn subscribe(&mut self, _info_bus: rticx_core::InfoBus) {}
+    fn subscribe(&mut self, info_bus: InfoBus) {
+        let _ = self.info_bus.insert(info_bus);
+    }
     fn run_pass(&self, args: TokenStream, app_mod: ItemMod) -> syn::Result<(TokenStream, ItemMod)> {
         let params = RticAttr::parse_from_tokens(args.clone())?;
         let mut parsed = App::parse(&params, app_mod)?;
 
-        self.analyze(&mut parsed);
+        let pcs_irqs = self.analyze(&mut parsed)?;
+        self.info_bus.as_ref().inspect(|b| {
+            b.publish(INFO_PCS_IRQS, pcs_irqs)
+                .unwrap_or_else(|_| panic!("no other crate is allowed to publish {INFO_PCS_IRQS}"))
+        });
 
         let code = Codegen::new(parsed).run();
         Ok((args, code))
@@ -45,33 +72,67 @@ impl RticPass for PcsPass {
 }
 
 impl PcsPass {
-    fn analyze(&self, app: &mut App) {
-        // Partition interrupts into PCS interrupts and non-PCS interrupts
-        let (pcs_irqs, rest_irqs): (Vec<_>, Vec<_>) = app.tasks.iter().partition(|task| task.fast);
-
-        // Limit to maximum number of PCS interrupts supported by hardware
-        if pcs_irqs.len() > self.max_num_pcs {
-            panic!(
-                "Exceeded number of interrupts leveraging PCS for this platform ({}), please reduce the number of accelerated tasks\nFast IRQs: {:?}\nOther IRQs: {:?}",
-                self.max_num_pcs,
-                pcs_irqs
-                    .iter()
-                    .map(|task| format!("{} ({})", task.name, task.binds))
-                    .collect::<Vec<_>>(),
-                rest_irqs
+    /// Check the tasks marked `fast` fit in the PCS slots of the platform and, when the app opts
+    /// in, hand out the remaining slots to the most urgent other tasks
+    fn analyze(&self, app: &mut App) -> syn::Result<PcsIrqs> {
+        let fast: Vec<_> = app.tasks.iter().filter(|task| task.fast).collect();
+
+        // Limit to maximum number of PCS interrupts supported by hardware, each bound interrupt
+        // of a task taking a slot
+        let mut used_slots = 0;
+        for (idx, extra) in fast.iter().enumerate() {
+            used_slots += extra.binds.len();
+            if used_slots > self.max_num_pcs {
+                let taken_by = fast[..idx]
                     .iter()
-                    .map(|task| task.name.clone())
-                    .collect::<Vec<_>>()
-            );
+                    .map(|task| format!("`{}` ({})", task.ident, binds_list(task)))
+                    .collect::<Vec<_>>();
+                return Err(syn::Error::new(
+                    extra.ident.span(),
+                    format!(
+                        "Task `{}` is marked `{PCS_ATTR_IDENT}`, but its interrupts ({}) don't fit in the {} PCS slots of this platform, taken by [{}]. Please reduce the number of accelerated tasks.",
+                        extra.ident,
+                        binds_list(extra),
+                        self.max_num_pcs,
+                        taken_by.join(", ")
+                    ),
+                ));
+            }
         }
+        let mut free_slots = self.max_num_pcs - used_slots;
 
-        let mut pcs_dispatchers = vec![];
-        for task in app.tasks.iter_mut() {
-            if task.fast {
-                // Save bound interrupt for later processing
-                pcs_dispatchers.push(task.binds.clone());
+        if let Some(selection) = app.selection {
+            let mut candidates: Vec<_> = app.tasks.iter_mut().filter(|task| !task.fast).collect();
+            match selection {
+                PcsSelection::HighestPriority => {
+                    candidates.sort_by_key(|task| Reverse(task.priority))
+                }
+                PcsSelection::ShortestDeadline => candidates.sort_by_key(|task| {
+                    (task.deadline.is_none(), task.deadline, Reverse(task.priority))
+                }),
+            }
+            // tasks whose interrupts don't fit in the remaining slots are skipped
+            for task in candidates {
+                if task.binds.len() <= free_slots {
+                    free_slots -= task.binds.len();
+                    task.fast = true;
+                }
             }
         }
-        PCS_DISPATCHERS.replace(pcs_dispatchers);
+
+        // Save bound interrupts for the backend
+        let irqs = app
+            .tasks
+            .iter()
+            .filter(|task| task.fast)
+            .flat_map(|task| task.binds.clone())
+            .collect();
+        Ok(PcsIrqs { irqs })
     }
 }
+
+/// The interrupts of `task`, for error messages
+fn binds_list(task: &parse::ast::RticTask) -> String {
+    let binds: Vec<_> = task.binds.iter().map(ToString::to_string).collect();
+    binds.join(", ")
+}
@@ -1,32 +1,57 @@
 use crate::PCS_ATTR_IDENT;
-use quote::{format_ident, ToTokens};
-use rticx_core::parse_utils::RticAttr;
-use syn::{Ident, ItemStruct};
+use quote::ToTokens;
+use rticx_core::parse_utils::{parse_binds, RticAttr};
+use syn::{spanned::Spanned, Expr, Ident, ItemStruct, Lit, LitInt};
 
 #[derive(Debug)]
 pub struct RticTask {
-    pub name: String,
-    pub binds: Ident,
+    pub ident: Ident,
+    /// Interrupts the task is bound to, several when `binds` lists them
+    pub binds: Vec<Ident>,
     /// User has requested parallel context stacking (PCS) for this line
     pub fast: bool,
+    /// Explicit priority of the task, if any
+    pub priority: Option<u16>,
+    /// Explicit plain `deadline` of the task, if any
+    pub deadline: Option<u64>,
 }
 
 impl RticTask {
     pub fn from_struct((task_struct, attr_idx): (&ItemStruct, usize)) -> syn::Result<Self> {
-        let name = task_struct.ident.to_string();
+        let ident = task_struct.ident.clone();
         let params = RticAttr::parse_from_attr(&task_struct.attrs[attr_idx]).inspect_err(|_e| {
             eprintln!(
                 "An error occurred while parsing: {:?}",
                 task_struct.attrs[attr_idx].to_token_stream().to_string()
             )
         })?;
-        let binds_expr = params
-            .elements
-            .get("binds")
-            .expect("Internal error: any task should always have a bound interrupt");
-        let binds = format_ident!("{}", binds_expr.to_token_stream().to_string());
+        let binds_expr = params.elements.get("binds").ok_or_else(|| {
+            syn::Error::new(
+                task_struct.attrs[attr_idx].span(),
+                format!(
+                    "Task `{ident}` has no bound interrupt, the PCS pass must run after software tasks have been turned into dispatchers."
+                ),
+            )
+        })?;
+        let binds = parse_binds(binds_expr)?;
         let fast = params.elements.contains_key(PCS_ATTR_IDENT);
 
-        Ok(Self { name, binds, fast })
+        let int_arg = |name: &str| match params.elements.get(name) {
+            Some(Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            })) => Some(int),
+            _ => None,
+        };
+        let priority = int_arg("priority").map(LitInt::base10_parse).transpose()?;
+        // durations are left to the deadline pass, only plain deadlines rank tasks here
+        let deadline = int_arg("deadline").map(LitInt::base10_parse).transpose()?;
+
+        Ok(Self {
+            ident,
+            binds,
+            fast,
+            priority,
+            deadline,
+        })
     }
 }
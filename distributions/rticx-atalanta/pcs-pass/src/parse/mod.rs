@@ -1,7 +1,9 @@
 use super::parse::ast::RticTask;
+use crate::{PcsSelection, PCS_APP_ARG};
 use proc_macro2::Ident;
+use quote::ToTokens;
 use rticx_core::parse_utils::RticAttr;
-use syn::{Item, ItemMod, ItemStruct, Visibility};
+use syn::{spanned::Spanned, Item, ItemMod, ItemStruct, Visibility};
 
 pub mod ast;
 
@@ -11,13 +13,31 @@ pub struct App {
     pub mod_ident: Ident,
 
     pub tasks: Vec<RticTask>,
+    /// Automatic selection of PCS tasks requested with `pcs = ..` in the app arguments
+    pub selection: Option<PcsSelection>,
 
     /// Code that remains unchanged by this pass
     pub code: Vec<Item>,
 }
 
 impl App {
-    pub fn parse(_params: &RticAttr, mut app_mod: ItemMod) -> syn::Result<Self> {
+    pub fn parse(params: &RticAttr, mut app_mod: ItemMod) -> syn::Result<Self> {
+        let selection = match params.elements.get(PCS_APP_ARG) {
+            None => None,
+            Some(expr) => match expr.to_token_stream().to_string().as_str() {
+                "priority" => Some(PcsSelection::HighestPriority),
+                "deadline" => Some(PcsSelection::ShortestDeadline),
+                other => {
+                    return Err(syn::Error::new(
+                        expr.span(),
+                        format!(
+                            "Unknown PCS selection `{other}`, expected `{PCS_APP_ARG} = priority` or `{PCS_APP_ARG} = deadline`."
+                        ),
+                    ))
+                }
+            },
+        };
+
         let app_mod_items = app_mod.content.take().unwrap_or_default().1;
         let mut code = Vec::with_capacity(app_mod_items.len());
         let mut tasks = Vec::new();
@@ -40,6 +60,7 @@ impl App {
             mod_ident: app_mod.ident,
             mod_visibility: app_mod.vis,
             tasks,
+            selection,
             code,
         })
     }
@@ -1,23 +1,35 @@
+use std::cell::OnceCell;
+
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 
-use rticx_core::{AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
-use syn::{Path, parse_quote, ItemFn};
+use rticx_core::{
+    AppArgs, CorePassBackend, InfoBus, InterruptControl, RticMacroBuilder, SubAnalysis, SubApp,
+};
+use syn::{Path, parse_quote, parse_quote_spanned, ItemFn};
 
 extern crate proc_macro;
 
-struct AtalantaRtic;
+#[derive(Default)]
+struct AtalantaRtic {
+    info: OnceCell<InfoBus>,
+}
 
 #[cfg(feature = "pcs-pass")]
 use pcs_pass::PcsPass;
+use rticx_periodic_pass::PeriodicPass;
 use rticx_sw_pass::SoftwarePass;
 
 const MIN_TASK_PRIORITY: u16 = 1; // lowest Atalanta priority
 
 #[proc_macro_attribute]
 pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
-    let mut builder = RticMacroBuilder::new(AtalantaRtic);
+    let mut builder = RticMacroBuilder::new(AtalantaRtic::default());
+
+    // periodic tasks are released before the software pass, which then sees their spawn calls
+    let periodic_pass = PeriodicPass::new(PeriodicPassBackend);
+    builder.bind_pre_core_pass(periodic_pass);
 
     // use the standard software pass provided by rticx-sw-pass crate
     let sw_pass = SoftwarePass::new(SwPassBackend);
@@ -29,7 +41,6 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
         const MAX_NUM_PCS: usize = 4;
         let pcs_pass = PcsPass::new(MAX_NUM_PCS);
         builder.bind_pre_core_pass(pcs_pass);
-        println!("--- PCS pass added --- ");
     }
 
     builder.build_rtic_macro(args, input)
@@ -37,6 +48,10 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
 
 // =========================================== Trait implementations ===================================================
 impl CorePassBackend for AtalantaRtic {
+    fn subscribe(&mut self, info_bus: InfoBus) {
+        let _ = self.info.set(info_bus);
+    }
+
     fn default_task_priority(&self) -> u16 {
         MIN_TASK_PRIORITY
     }
@@ -58,11 +73,13 @@ impl CorePassBackend for AtalantaRtic {
             const PCS_FALSE: bool = false;
         });
 
-        let pcs_dispatchers = if cfg!(feature = "pcs-pass") {
-            pcs_pass::PCS_DISPATCHERS.with(|ds| ds.borrow().clone())
-        } else {
-            vec![]
-        };
+        // interrupts given a PCS slot, published by the PCS pass when it is enabled
+        let pcs_irqs = self
+            .info
+            .get()
+            .and_then(|info| info.get::<pcs_pass::PcsIrqs>(pcs_pass::INFO_PCS_IRQS).ok())
+            .unwrap_or_default();
+        let pcs_dispatchers = &pcs_irqs.irqs;
         if !pcs_dispatchers.is_empty() {
             out.extend(quote! {
                 const PCS_TRUE: bool = true;
@@ -149,10 +166,34 @@ impl CorePassBackend for AtalantaRtic {
     /// Customize how the task is dispatched when its bound interrupt is triggered
     fn wrap_task_execution(
         &self,
-        _task_prio: u16,
-        _dispatch_task_call: TokenStream2,
+        task_prio: u16,
+        threshold: u16,
+        dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
-        None
+        // only tasks with a preemption threshold need wrapping
+        (threshold > task_prio).then(|| {
+            rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call)
+        })
+    }
+
+    /// Pend and unpend the interrupts through their CLIC pending bit
+    fn generate_interrupt_control_fn(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        let export_fn = match control {
+            InterruptControl::Pend => format_ident!("pend"),
+            InterruptControl::Unpend => format_ident!("unpend"),
+            _ => return None,
+        };
+        empty_body_fn.block = parse_quote!({
+            rticx_atalanta::export::#export_fn(rticx_atalanta::export::interrupts::#binds)
+        });
+        Some(empty_body_fn)
     }
 
     /// further analysis of parsed user code
@@ -203,3 +244,58 @@ impl rticx_sw_pass::SwPassBackend for SwPassBackend {
         Some(parse_quote!(rticx_atalanta::export::Interrupt))
     }
 }
+
+struct PeriodicPassBackend;
+impl rticx_periodic_pass::PeriodicPassBackend for PeriodicPassBackend {
+    fn export_path(&self) -> Path {
+        parse_quote!(rticx_atalanta::export::periodic)
+    }
+
+    fn start_timer(&self, _core: u32) -> Option<TokenStream2> {
+        Some(quote!(rticx_atalanta::export::periodic::start();))
+    }
+
+    /// Read the low word of `mtime`.
+    fn generate_now_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block = Box::new(parse_quote!({ rticx_atalanta::export::periodic::now() }));
+        empty_body_fn
+    }
+
+    /// Program the `mtime` compare for `MachineTimer`, or APB timer N for `TimerNCmp`.
+    fn generate_set_compare_fn(&self, _core: u32, irq: &Path, mut empty_body_fn: ItemFn) -> ItemFn {
+        let irq_ident = &irq
+            .segments
+            .last()
+            .expect("a path has a last segment")
+            .ident;
+        let irq_name = irq_ident.to_string();
+        let apb_timer = irq_name
+            .strip_prefix("Timer")
+            .and_then(|name| name.strip_suffix("Cmp"))
+            .filter(|n| matches!(*n, "0" | "1" | "2" | "3"));
+        let body = if irq_name == "MachineTimer" {
+            parse_quote!({ rticx_atalanta::export::periodic::set_mtimer_compare(instant) })
+        } else if let Some(n) = apb_timer {
+            let addr = format_ident!("TIMER{n}_ADDR");
+            parse_quote!({
+                rticx_atalanta::export::periodic::set_apb_timer_compare::<
+                    { bsp::mmap::apb_timer::#addr },
+                >(instant)
+            })
+        } else {
+            let error = format!(
+                "Periodic tasks must be bound to `Timer0Cmp` to `Timer3Cmp` or `MachineTimer`, `{irq_name}` has no timer compare."
+            );
+            parse_quote_spanned!(irq_ident.span() => {
+                compile_error!(#error)
+            })
+        };
+        empty_body_fn.block = Box::new(body);
+        empty_body_fn
+    }
+
+    /// Periodic software tasks are released by the `mtime` compare.
+    fn release_timer_interrupt(&self, _core: u32) -> Option<Path> {
+        Some(parse_quote!(MachineTimer))
+    }
+}
@@ -20,6 +20,54 @@ pub use bsp::riscv::interrupt::machine::{
 };
 pub use bsp::Interrupt;
 
+/// Runtime of the periodic tasks pass.
+///
+/// The time base is the low word of `mtime`. Periodic software tasks are released through the
+/// `MachineTimer` compare, hardware tasks bound to `TimerNCmp` through APB timer N. An APB timer
+/// only counts up to its compare value, so it is programmed with the delay until the release and
+/// must count at the rate of `mtime` (peripheral clock divider of 1).
+pub mod periodic {
+    pub use rticx_periodic_pass::export::*;
+
+    use bsp::mtimer::MTimer;
+    use bsp::write_u32;
+
+    // APB timer registers
+    const TIMER_COUNTER_OFS: usize = 0x0;
+    const TIMER_CTRL_OFS: usize = 0x4;
+    const TIMER_CMP_OFS: usize = 0x8;
+    const TIMER_CTRL_ENABLE: u32 = 0b1;
+
+    /// Make sure `mtime` is counting
+    pub fn start() {
+        MTimer::instance().enable();
+    }
+
+    /// Low word of `mtime`
+    #[inline(always)]
+    pub fn now() -> u32 {
+        MTimer::instance().counter() as u32
+    }
+
+    /// Pend `MachineTimer` when the low word of `mtime` reaches `instant`
+    pub fn set_mtimer_compare(instant: u32) {
+        let mut mtimer = MTimer::instance();
+        let now = mtimer.counter();
+        mtimer.set_compare(now + u64::from(instant.wrapping_sub(now as u32)));
+    }
+
+    /// Pend the compare interrupt of the APB timer at `ADDR` when the low word of `mtime` reaches
+    /// `instant`. The timer keeps firing every such delay until it is programmed again, which the
+    /// periodic task does at the start of every job.
+    pub fn set_apb_timer_compare<const ADDR: usize>(instant: u32) {
+        let delay = instant.wrapping_sub(now()).max(1);
+        write_u32(ADDR + TIMER_CTRL_OFS, 0);
+        write_u32(ADDR + TIMER_COUNTER_OFS, 0);
+        write_u32(ADDR + TIMER_CMP_OFS, delay);
+        write_u32(ADDR + TIMER_CTRL_OFS, TIMER_CTRL_ENABLE);
+    }
+}
+
 /// Lock implementation using threshold and global Critical Section (CS)
 ///
 /// # Safety
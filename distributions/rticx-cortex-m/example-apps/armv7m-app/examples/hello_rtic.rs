@@ -7,7 +7,7 @@ use panic_halt as _;
 
 #[rticx_cortex_m::app(device = stm32f0::stm32f0x0, dispatchers = [TIM6])]
 pub mod my_app {
-    use cortex_m::peripheral::{syst::SystClkSource, Peripherals};
+    use cortex_m::peripheral::syst::SystClkSource;
     use cortex_m_semihosting::{debug, hprintln};
 
     /// Number of SysTick-driven spawns after which the example declares success
@@ -23,8 +23,8 @@ pub mod my_app {
     }
 
     #[init]
-    fn system_init() -> Shared {
-        let mut cp = unsafe { Peripherals::steal() };
+    fn system_init(cx: system_init::Context) -> Shared {
+        let mut cp = cx.core;
         cp.SYST.set_clock_source(SystClkSource::Core);
         // Short reload so ticks arrive quickly enough for CI.
         cp.SYST.set_reload(0x1_000);
@@ -2,10 +2,15 @@ use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 
-use rticx_core::{AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
+use rticx_core::{
+    AppArgs, CorePassBackend, InitPeripherals, InterruptControl, RticMacroBuilder, SubAnalysis,
+    SubApp,
+};
+#[cfg(feature = "monitor")]
+use rticx_monitor_pass::{MonitorPass, MonitorPassBackend};
 #[cfg(feature = "swtasks")]
 use rticx_sw_pass::{SoftwarePass, SwPassBackend};
-use syn::{parse_quote, ItemFn, Path};
+use syn::{parse_quote, ImplItemFn, ItemFn, Path};
 
 extern crate proc_macro;
 
@@ -47,6 +52,8 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
 
     #[allow(unused_mut)]
     let mut builder = RticMacroBuilder::new(CortexMRtic);
+    #[cfg(feature = "monitor")]
+    builder.bind_pre_core_pass(MonitorPass::new(MonitorPassBackendImpl)); // time the user tasks only
     #[cfg(feature = "swtasks")]
     builder.bind_pre_core_pass(sw_pass); // run software pass before the core pass
     builder.build_rtic_macro(args, input)
@@ -106,6 +113,15 @@ impl CorePassBackend for CortexMRtic {
             }
         }
 
+        // The runtime statistics and the trace timestamps read the DWT cycle counter, which needs
+        // the trace unit of the DCB
+        if (cfg!(feature = "stats") || cfg!(feature = "trace")) && !cfg!(feature = "armv6m") {
+            stmts.push(quote!(
+                core.DCB.enable_trace();
+                core.DWT.enable_cycle_counter();
+            ));
+        }
+
         // `core::peripheral::Peripherals` handle for SCB/NVIC access at runtime.
         // `post_init` already runs inside a critical section, so stealing is safe.
         Some(quote! {
@@ -116,12 +132,113 @@ impl CorePassBackend for CortexMRtic {
         })
     }
 
+    /// `init::Context` gives the `cortex_m::Peripherals` to `init`. They are stolen again in
+    /// `post_init`, which runs after `init`.
+    fn init_core_peripherals(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+    ) -> Option<InitPeripherals> {
+        Some(InitPeripherals {
+            ty: parse_quote!(rticx_cortex_m::export::Peripherals),
+            take: parse_quote!(rticx_cortex_m::export::Peripherals::steal()),
+        })
+    }
+
     fn populate_idle_loop(&self) -> Option<TokenStream2> {
         Some(quote! {
             rticx_cortex_m::export::wfi();
         })
     }
 
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block = if cfg!(feature = "armv6m") {
+            Box::new(parse_quote!({
+                compile_error!(
+                    "the `stats` feature needs the DWT cycle counter, which armv6-m does not have"
+                )
+            }))
+        } else {
+            Box::new(parse_quote!({ rticx_cortex_m::export::DWT::cycle_count() }))
+        };
+        empty_body_fn
+    }
+
+    /// Read BASEPRI for the `srp-assertions` checks. The source masks of armv6-m are not read, only
+    /// the tracked ceiling is checked there.
+    fn generate_hw_ceiling_fn(
+        &self,
+        app_args: &AppArgs,
+        _core: u32,
+        mut empty_body_fn: ItemFn,
+    ) -> ItemFn {
+        let pac = &app_args.pacs[0];
+        empty_body_fn.block = if cfg!(feature = "armv6m") {
+            Box::new(parse_quote!({ None }))
+        } else {
+            Box::new(parse_quote!({
+                Some(rticx_cortex_m::export::system_ceiling(#pac::NVIC_PRIO_BITS))
+            }))
+        };
+        empty_body_fn
+    }
+
+    fn trace_export_path(&self) -> Option<Path> {
+        Some(parse_quote!(rticx_cortex_m::export::trace))
+    }
+
+    /// External interrupts are controlled through the NVIC, except `enable()`/`disable()` on
+    /// armv6-m where the locks rewrite the NVIC enable registers. Among the exceptions, only the
+    /// pending state of PendSV and SysTick is controlled, through the SCB.
+    fn generate_interrupt_control_fn(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: ImplItemFn,
+    ) -> Option<ImplItemFn> {
+        let pac = &app_args.pacs[app_info.core as usize];
+        let body = if is_exception(binds) {
+            let (pend, unpend, is_pending) = match binds.to_string().as_str() {
+                "PendSV" => ("set_pendsv", "clear_pendsv", "is_pendsv_pending"),
+                "SysTick" => ("set_pendst", "clear_pendst", "is_pendst_pending"),
+                _ => return None,
+            };
+            let scb_fn = match control {
+                InterruptControl::Pend => pend,
+                InterruptControl::Unpend => unpend,
+                InterruptControl::IsPending => is_pending,
+                InterruptControl::Enable | InterruptControl::Disable => return None,
+            };
+            let scb_fn = format_ident!("{scb_fn}");
+            parse_quote!({ rticx_cortex_m::export::SCB::#scb_fn() })
+        } else {
+            let irq = quote!(#pac::Interrupt::#binds);
+            match control {
+                InterruptControl::Pend => {
+                    parse_quote!({ rticx_cortex_m::export::NVIC::pend(#irq) })
+                }
+                InterruptControl::Unpend => {
+                    parse_quote!({ rticx_cortex_m::export::NVIC::unpend(#irq) })
+                }
+                InterruptControl::IsPending => {
+                    parse_quote!({ rticx_cortex_m::export::NVIC::is_pending(#irq) })
+                }
+                _ if cfg!(feature = "armv6m") => return None,
+                // BASEPRI locks don't touch the enable registers
+                InterruptControl::Enable => {
+                    parse_quote!({ unsafe { rticx_cortex_m::export::NVIC::unmask(#irq) } })
+                }
+                InterruptControl::Disable => {
+                    parse_quote!({ rticx_cortex_m::export::NVIC::mask(#irq) })
+                }
+            }
+        };
+        empty_body_fn.block = body;
+        Some(empty_body_fn)
+    }
+
     fn generate_interrupt_free_fn(&self, mut empty_body_fn: ItemFn) -> ItemFn {
         let fn_body = parse_quote! {
             {
@@ -197,6 +314,37 @@ impl CorePassBackend for CortexMRtic {
         }
     }
 
+    fn generate_ceiling_lock_fn(&self, app_args: &AppArgs, mut empty_body_fn: ItemFn) -> ItemFn {
+        // ---- Interrupt source masking (armv6-m: M0/M0+/M23) ----
+        // The masks depend on the priority of the running task, which is unknown here.
+        let fn_body = if cfg!(feature = "armv6m") {
+            parse_quote! {
+                {
+                    let _ = ceiling;
+                    __rticx_interrupt_free(f)
+                }
+            }
+        }
+        // ---- BASEPRI locking (armv7-m and above) ----
+        else {
+            let pac = &app_args.pacs[0];
+            parse_quote! {
+                {
+                    // logical priority 0 is never preempted by the contexts it races with (init/idle)
+                    if ceiling == 0 {
+                        return f();
+                    }
+                    let mut unit = ();
+                    unsafe {
+                        rticx_cortex_m::export::lock(&mut unit as *mut (), ceiling as u8, #pac::NVIC_PRIO_BITS, |_| f())
+                    }
+                }
+            }
+        };
+        empty_body_fn.block = Box::new(fn_body);
+        empty_body_fn
+    }
+
     fn entry_name(&self, _core: u32) -> Ident {
         format_ident!("main")
     }
@@ -207,11 +355,14 @@ impl CorePassBackend for CortexMRtic {
     fn wrap_task_execution(
         &self,
         task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
+        // the ceiling lock raises BASEPRI to the threshold, thresholds are rejected on armv6m
+        let dispatch_task_call = rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call);
         if cfg!(feature = "armv6m") {
-            // No exec wrapping needed in armv6m implementation
-            None
+            // No exec wrapping needed in armv6m implementation, which has no thresholds
+            (threshold > task_prio).then_some(dispatch_task_call)
         } else {
             Some(quote! {
                 rticx_cortex_m::export::run(#task_prio as u8, || { #dispatch_task_call });
@@ -225,10 +376,7 @@ impl CorePassBackend for CortexMRtic {
         _analysis: &rticx_core::Analysis,
     ) -> syn::Result<()> {
         for sub_app in &app.sub_apps {
-            for task in &sub_app.tasks {
-                let Some(binds) = &task.args.binds else {
-                    continue;
-                };
+            for binds in sub_app.tasks.iter().flat_map(|task| &task.args.binds) {
                 let name = binds.to_string();
                 if NON_CONFIGURABLE_EXCEPTIONS.iter().any(|e| name == *e) {
                     return Err(syn::Error::new(
@@ -237,6 +385,21 @@ impl CorePassBackend for CortexMRtic {
                     ));
                 }
             }
+            // without BASEPRI, raising to a threshold would mask every interrupt for the whole job
+            if cfg!(feature = "armv6m")
+                && let Some(task) = sub_app
+                    .tasks
+                    .iter()
+                    .find(|task| task.args.threshold > task.args.priority)
+            {
+                return Err(syn::Error::new(
+                    task.name().span(),
+                    format!(
+                        "`{}` has a preemption threshold, which armv6-m can't run without masking every interrupt for the whole job. Remove `threshold`, or raise the priority of the task instead.",
+                        task.name()
+                    ),
+                ));
+            }
         }
         Ok(())
     }
@@ -253,24 +416,20 @@ fn generate_source_mask_globals(app_args: &AppArgs, app_info: &SubApp) -> Option
     // Cortex-M core exceptions (SysTick, PendSV, SVCall, …) are *not* in the
     // PAC `Interrupt` enum and have no ISER/ICER mask bits, so they must be
     // excluded from the source-mask table.
-    let nvic_tasks: Vec<_> = app_info
+    let nvic_irqs: Vec<_> = app_info
         .tasks
         .iter()
-        .filter(|t| t.args.binds.as_ref().is_some_and(|n| !is_exception(n)))
+        .flat_map(|t| t.args.binds.iter().map(|irq| (irq, t.args.priority)))
+        .filter(|(irq, _)| !is_exception(irq))
         .collect();
-    let irq_list_as_u32 = nvic_tasks.iter().filter_map(|t| {
-        let irq_name = t.args.binds.as_ref()?;
-        Some(quote! { #pac::Interrupt::#irq_name as u32, })
+    let irq_list_as_u32 = nvic_irqs.iter().map(|(irq_name, _)| {
+        quote! { #pac::Interrupt::#irq_name as u32, }
     });
 
     // Group NVIC interrupts by priority level (1..=3) to build one mask per level
     let mut irq_prio_map = [Vec::new(), Vec::new(), Vec::new()];
-    for task in &nvic_tasks {
-        let prio = task.args.priority;
-        if (1..=3).contains(&prio) {
-            let Some(irq_name) = task.args.binds.as_ref() else {
-                continue;
-            };
+    for (irq_name, prio) in &nvic_irqs {
+        if (1..=3).contains(prio) {
             irq_prio_map[(prio - 1) as usize].push(quote! {
                 #pac::Interrupt::#irq_name as u32,
             });
@@ -316,6 +475,12 @@ impl SwPassBackend for SwPassBackendImpl {
         parse_quote!(rticx_cortex_m::export::Queue)
     }
 
+    /// armv7-m and above have compare-and-swap atomics: ready queues take concurrent spawners
+    /// without locking. armv6-m keeps the SPSC queue, filled under the spawn lock.
+    fn ready_queue_path(&self) -> Option<Path> {
+        (!cfg!(feature = "armv6m")).then(|| parse_quote!(rticx_cortex_m::export::MpscQueue))
+    }
+
     /// Core-local interrupt pending: used by `spawn` for software tasks running
     /// on this core.
     fn generate_local_pend_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
@@ -332,3 +497,38 @@ impl SwPassBackend for SwPassBackendImpl {
         None
     }
 }
+
+// =========================================== Monitor pass backend ===========================================
+#[cfg(feature = "monitor")]
+struct MonitorPassBackendImpl;
+
+#[cfg(feature = "monitor")]
+impl MonitorPassBackend for MonitorPassBackendImpl {
+    fn export_path(&self) -> Path {
+        parse_quote!(rticx_cortex_m::export::monitor)
+    }
+
+    /// Enable the DWT cycle counter, which needs the trace unit of the DCB.
+    fn start_counter(&self, _core: u32) -> Option<TokenStream2> {
+        Some(quote! {
+            {
+                let mut core = unsafe { rticx_cortex_m::export::Peripherals::steal() };
+                core.DCB.enable_trace();
+                core.DWT.enable_cycle_counter();
+            }
+        })
+    }
+
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block = if cfg!(feature = "armv6m") {
+            Box::new(parse_quote!({
+                compile_error!(
+                    "the `monitor` feature needs the DWT cycle counter, which armv6-m does not have"
+                )
+            }))
+        } else {
+            Box::new(parse_quote!({ rticx_cortex_m::export::DWT::cycle_count() }))
+        };
+        empty_body_fn
+    }
+}
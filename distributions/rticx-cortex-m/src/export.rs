@@ -6,6 +6,81 @@
 /// Distribution crate must re-export the `export` module from all the used compilation passes
 pub use rticx_sw_pass::export::*;
 
+/// Runtime of the monitor pass
+#[cfg(feature = "monitor")]
+pub use rticx_monitor_pass::export as monitor;
+
+/// Tracing runtime, with a recorder timestamped by the DWT cycle counter
+#[cfg(feature = "trace")]
+pub mod trace {
+    pub use rticx_trace::*;
+
+    /// Number of records kept by [`RingTracer`]
+    pub const CAPACITY: usize = 512;
+
+    static RECORDS: RingBuffer<CAPACITY> = RingBuffer::new();
+
+    /// Tracer recording the events in memory, the oldest ones are overwritten.
+    ///
+    /// Select it with `#[app(..., tracer = rticx_cortex_m::export::trace::RingTracer)]`, then send
+    /// the records to the host with [`RingTracer::dump`] and convert them with `rticx-trace-decode`.
+    pub struct RingTracer;
+
+    impl RingTracer {
+        /// Pass the kept records to `f`, oldest first, encoded for the trace decoder.
+        ///
+        /// Interrupts are disabled while the records are read.
+        pub fn dump(mut f: impl FnMut([u8; Record::SIZE])) {
+            cortex_m::interrupt::free(|_| unsafe {
+                RECORDS.records().for_each(|record| f(record.to_bytes()));
+            });
+        }
+
+        /// Number of records lost because the buffer was full
+        #[must_use]
+        pub fn overwritten() -> usize {
+            cortex_m::interrupt::free(|_| unsafe { RECORDS.overwritten() })
+        }
+
+        /// Drop all the records
+        pub fn clear() {
+            cortex_m::interrupt::free(|_| unsafe { RECORDS.clear() });
+        }
+    }
+
+    #[inline(always)]
+    fn record(kind: EventKind, id: u16) {
+        #[cfg(not(feature = "armv6m"))]
+        let timestamp = cortex_m::peripheral::DWT::cycle_count();
+        // armv6-m has no cycle counter, only the order of the events is kept
+        #[cfg(feature = "armv6m")]
+        let timestamp = 0;
+        cortex_m::interrupt::free(|_| unsafe { RECORDS.push(Record::new(kind, id, timestamp)) });
+    }
+
+    impl Tracer for RingTracer {
+        fn task_enter(task: u16) {
+            record(EventKind::TaskEnter, task);
+        }
+
+        fn task_exit(task: u16) {
+            record(EventKind::TaskExit, task);
+        }
+
+        fn lock_acquire(resource: u16) {
+            record(EventKind::LockAcquire, resource);
+        }
+
+        fn lock_release(resource: u16) {
+            record(EventKind::LockRelease, resource);
+        }
+
+        fn spawn(task: u16) {
+            record(EventKind::Spawn, task);
+        }
+    }
+}
+
 /// Exports required by the core pass and by generated code
 pub use cortex_m::interrupt::InterruptNumber; // a trait that abstracts an interrupt type
 pub use cortex_m::{
@@ -22,6 +97,17 @@ pub const fn cortex_logical2hw(logical: u8, nvic_prio_bits: u8) -> u8 {
     ((1 << nvic_prio_bits) - logical) << (8 - nvic_prio_bits)
 }
 
+/// Inverse of [cortex_logical2hw], a BASEPRI of `0` masks nothing
+#[inline]
+#[must_use]
+pub const fn cortex_hw2logical(hw: u8, nvic_prio_bits: u8) -> u8 {
+    if hw == 0 {
+        0
+    } else {
+        (1 << nvic_prio_bits) - (hw >> (8 - nvic_prio_bits))
+    }
+}
+
 /// Sets the given `interrupt` as pending
 ///
 /// Convenience wrapper around [`NVIC::pend`](cortex_m::peripheral::struct.NVIC.html)
@@ -61,6 +147,17 @@ mod basepri {
         }
     }
 
+    /// Logical priority masked by the locks, read by the `srp-assertions` checks: the maximum one
+    /// inside a global critical section, the BASEPRI one otherwise.
+    #[inline]
+    pub fn system_ceiling(nvic_prio_bits: u8) -> u16 {
+        if cortex_m::register::primask::read().is_inactive() {
+            1 << nvic_prio_bits
+        } else {
+            super::cortex_hw2logical(basepri::read(), nvic_prio_bits) as u16
+        }
+    }
+
     /// Lock implementation using BASEPRI and a global critical section (CS).
     ///
     /// # Safety
@@ -3,7 +3,9 @@ use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 
 use syn::{parse_quote, ItemFn, Path};
-use rticx_core::{AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
+use rticx_core::{
+    AppArgs, CorePassBackend, InterruptControl, RticMacroBuilder, SubAnalysis, SubApp,
+};
 
 extern crate proc_macro;
 
@@ -11,11 +13,13 @@ struct HippoRtic;
 
 #[cfg(feature = "deadline-pass")]
 use rticx_deadline_pass::{DeadlineToPriorityPass /* DeadlineToPriorityPassImpl */};
+#[cfg(feature = "rate-monotonic-pass")]
+use rticx_rate_monotonic_pass::RateMonotonicPass;
 
 use rticx_sw_pass::SoftwarePass;
 
 const MIN_TASK_PRIORITY: u16 = 0; // lowest hippo priority
-#[cfg(feature = "deadline-pass")]
+#[cfg(any(feature = "deadline-pass", feature = "rate-monotonic-pass"))]
 const MAX_TASK_PRIORITY: u16 = 3; // highest hippo priority
 
 #[proc_macro_attribute]
@@ -25,13 +29,15 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
     // use the standard deadline to priority pass provided bp the rticx-deadline-pass crate
     #[cfg(feature = "deadline-pass")]
     let deadline_pass = DeadlineToPriorityPass::new(MAX_TASK_PRIORITY);
+    // periodic tasks without a deadline get rate-monotonic priorities
+    #[cfg(feature = "rate-monotonic-pass")]
+    let rate_monotonic_pass = RateMonotonicPass::new(MAX_TASK_PRIORITY);
 
     let mut builder = RticMacroBuilder::new(HippoRtic);
     #[cfg(feature = "deadline-pass")]
-    {
-        builder.bind_pre_core_pass(deadline_pass); // run deadline to priority pass first
-        println!("--- deadline pass added --- ");
-    }
+    builder.bind_pre_core_pass(deadline_pass); // run deadline to priority pass first
+    #[cfg(feature = "rate-monotonic-pass")]
+    builder.bind_pre_core_pass(rate_monotonic_pass);
     builder.bind_pre_core_pass(sw_pass); // run software pass second
     builder.build_rtic_macro(args, input)
 }
@@ -121,10 +127,33 @@ impl CorePassBackend for HippoRtic {
     /// Customize how the task is dispatched when its bound interrupt is triggered (save baspri before and restore after executing the task)
     fn wrap_task_execution(
         &self,
-        _task_prio: u16,
-        _dispatch_task_call: TokenStream2,
+        task_prio: u16,
+        threshold: u16,
+        dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
-        None
+        // only tasks with a preemption threshold need wrapping
+        (threshold > task_prio).then(|| {
+            rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call)
+        })
+    }
+
+    /// Only pending is provided, through the `Interrupt` trait of hippomenes-core: the export of
+    /// this distribution doesn't clear nor mask single interrupts
+    fn generate_interrupt_control_fn(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        if control != InterruptControl::Pend {
+            return None;
+        }
+        empty_body_fn.block = parse_quote!({
+            rticx_hippo::export::pend(rticx_hippo::export::interrupts::#binds)
+        });
+        Some(empty_body_fn)
     }
 
     /// further analysis of parsed user code
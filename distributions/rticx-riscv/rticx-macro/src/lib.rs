@@ -5,8 +5,11 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 
 use rticx_core::{
-    Analysis, App, AppArgs, CorePassBackend, InfoBus, RticMacroBuilder, SubAnalysis, SubApp,
+    Analysis, App, AppArgs, CorePassBackend, InfoBus, InterruptControl, RticMacroBuilder,
+    SubAnalysis, SubApp,
 };
+#[cfg(feature = "monitor")]
+use rticx_monitor_pass::{MonitorPass, MonitorPassBackend};
 #[cfg(feature = "swtasks")]
 use rticx_sw_pass::{SoftwarePass, SwPassBackend};
 #[cfg(feature = "swtasks")]
@@ -28,6 +31,8 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
 
     #[allow(unused_mut)]
     let mut builder = RticMacroBuilder::new(BackendImpl::default());
+    #[cfg(feature = "monitor")]
+    builder.bind_pre_core_pass(MonitorPass::new(MonitorBackendImpl)); // time the user tasks only
     #[cfg(feature = "swtasks")]
     builder.bind_pre_core_pass(sw_pass);
     builder.build_rtic_macro(args, input)
@@ -229,13 +234,54 @@ impl CorePassBackend for BackendImpl {
     fn wrap_task_execution(
         &self,
         task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
+        // the ceiling lock raises the interrupt threshold to the preemption threshold of the task
+        let dispatch_task_call = rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call);
         Some(quote! {
             rticx_riscv::export::run(#task_prio as u8, || { #dispatch_task_call });
         })
     }
 
+    // ---- interrupt control of hardware tasks ---------------------------------
+    //
+    // The SLIC pends its software interrupts.  The ESP32 targets can only pend
+    // and unpend the `FROM_CPU_INTR{0..3}` software interrupts, peripheral
+    // interrupts are requested by their peripheral.  Enabling and disabling
+    // is not provided: the CPU interrupt of each source is assigned in
+    // `post_init`.
+    fn generate_interrupt_control_fn(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+        binds: &syn::Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        if cfg!(feature = "slic") {
+            if control != InterruptControl::Pend {
+                return None;
+            }
+            empty_body_fn.block = parse_quote!({
+                rticx_riscv::export::pend(slic::SoftwareInterrupt::#binds)
+            });
+            return Some(empty_body_fn);
+        }
+        if !binds.to_string().starts_with("FROM_CPU_INTR") {
+            return None;
+        }
+        let export_fn = match control {
+            InterruptControl::Pend => format_ident!("pend"),
+            InterruptControl::Unpend => format_ident!("unpend"),
+            _ => return None,
+        };
+        empty_body_fn.block = parse_quote!({
+            rticx_riscv::export::#export_fn(rticx_riscv::export::Interrupt::#binds)
+        });
+        Some(empty_body_fn)
+    }
+
     // ---- validation: dispatcher names for ESP targets ------------------------
     //
     // ESP32-C3 and ESP32-C6 only support `FROM_CPU_INTR{0..3}` as software
@@ -316,3 +362,24 @@ impl SwPassBackend for SwBackendImpl {
         None
     }
 }
+
+// ============================================================================
+// Monitor pass backend
+// ============================================================================
+
+#[cfg(feature = "monitor")]
+struct MonitorBackendImpl;
+
+#[cfg(feature = "monitor")]
+impl MonitorPassBackend for MonitorBackendImpl {
+    fn export_path(&self) -> syn::Path {
+        parse_quote!(rticx_riscv::export::monitor)
+    }
+
+    /// `mcycle` counts from reset, its lower 32 bits are enough for the monitor.
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: ItemFn) -> ItemFn {
+        empty_body_fn.block =
+            Box::new(parse_quote!({ rticx_riscv::export::mcycle::read() as u32 }));
+        empty_body_fn
+    }
+}
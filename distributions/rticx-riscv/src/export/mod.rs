@@ -9,6 +9,10 @@
 // queues and task inputs by the software-tasks pass.
 pub use rticx_sw_pass::export::*;
 
+/// Runtime of the monitor pass
+#[cfg(feature = "monitor")]
+pub use rticx_monitor_pass::export as monitor;
+
 // ============================================================================
 // Generic SLIC exports
 // ============================================================================
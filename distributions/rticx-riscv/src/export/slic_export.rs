@@ -5,6 +5,10 @@
 // Upstream: `upstream/exports/slic.rs`.
 pub use riscv_slic::{self, InterruptNumber, codegen, lock, pend, run, set_priority};
 
+/// Cycle counter read by the monitor pass
+#[cfg(feature = "monitor")]
+pub use riscv::register::mcycle;
+
 pub mod interrupt {
     #[inline]
     pub fn disable() {
@@ -27,6 +27,9 @@ compile_error!(
     "rticx-riscv: either `mecall-backend` or `clint-backend` must be enabled when `slic` is enabled"
 );
 
+#[cfg(all(feature = "monitor", not(feature = "slic")))]
+compile_error!("rticx-riscv: the `monitor` feature is only available on the `slic` target");
+
 #[cfg(all(feature = "mecall-backend", feature = "clint-backend"))]
 compile_error!(
     "rticx-riscv: the `mecall-backend` and `clint-backend` features are mutually exclusive"
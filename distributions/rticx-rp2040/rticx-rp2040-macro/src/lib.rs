@@ -3,7 +3,10 @@ use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 #[cfg(feature = "autoassign")]
 use rticx_auto_assign::AutoAssignPass;
-use rticx_core::{AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
+use rticx_core::{
+    AppArgs, CorePassBackend, InitPeripherals, InterruptControl, RticMacroBuilder, SubAnalysis,
+    SubApp,
+};
 #[cfg(feature = "swtasks")]
 use syn::Path;
 use syn::{ItemFn, parse_quote};
@@ -27,7 +30,7 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
     #[allow(unused_mut)]
     let mut builder = RticMacroBuilder::new(Rp2040Rtic);
     #[cfg(feature = "autoassign")]
-    builder.bind_pre_core_pass(AutoAssignPass); // run auto-assign pass first
+    builder.bind_pre_core_pass(AutoAssignPass::new()); // run auto-assign pass first
     #[cfg(feature = "swtasks")]
     builder.bind_pre_core_pass(sw_pass); // run software-pass second
     builder.build_rtic_macro(args, input)
@@ -38,6 +41,17 @@ impl CorePassBackend for Rp2040Rtic {
     fn default_task_priority(&self) -> u16 {
         MIN_TASK_PRIORITY
     }
+    fn init_core_peripherals(
+        &self,
+        app_args: &AppArgs,
+        sub_app: &SubApp,
+    ) -> Option<InitPeripherals> {
+        let peripheral_crate = &app_args.pacs[sub_app.core as usize];
+        Some(InitPeripherals {
+            ty: parse_quote!(#peripheral_crate::CorePeripherals),
+            take: parse_quote!(#peripheral_crate::CorePeripherals::steal()),
+        })
+    }
     fn post_init(
         &self,
         app_args: &AppArgs,
@@ -112,21 +126,21 @@ impl CorePassBackend for Rp2040Rtic {
         let peripheral_crate = &app_args.pacs[app_info.core as usize];
 
         // irq names from hadware tasks
-        let irq_list_as_u32 = app_info.tasks.iter().filter_map(|t| {
-            let irq_name = t.args.binds.as_ref()?;
-            Some(quote! { #peripheral_crate::Interrupt::#irq_name as u32, })
+        let irq_list_as_u32 = app_info.tasks.iter().flat_map(|t| {
+            t.args.binds.iter().map(|irq_name| {
+                quote! { #peripheral_crate::Interrupt::#irq_name as u32, }
+            })
         });
 
         let mut irq_prio_map = [Vec::new(), Vec::new(), Vec::new()];
         for hw_task in app_info.tasks.iter() {
             let prio = hw_task.args.priority;
             if (1..=3).contains(&prio) {
-                let Some(irq_name) = hw_task.args.binds.as_ref() else {
-                    continue;
-                };
-                irq_prio_map[(prio - 1) as usize].push(quote! {
-                    #peripheral_crate::Interrupt::#irq_name as u32,
-                })
+                for irq_name in &hw_task.args.binds {
+                    irq_prio_map[(prio - 1) as usize].push(quote! {
+                        #peripheral_crate::Interrupt::#irq_name as u32,
+                    })
+                }
             }
         }
 
@@ -176,6 +190,35 @@ impl CorePassBackend for Rp2040Rtic {
         completed_lock_fn
     }
 
+    /// Control the interrupts through the NVIC. Each core only reaches its own NVIC, so nothing is
+    /// generated for multicore applications.
+    fn generate_interrupt_control_fn(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        if app_args.cores > 1 {
+            return None;
+        }
+        let peripheral_crate = &app_args.pacs[app_info.core as usize];
+        let irq = quote!(#peripheral_crate::Interrupt::#binds);
+        empty_body_fn.block = match control {
+            InterruptControl::Pend => parse_quote!({ rticx_rp2040::export::NVIC::pend(#irq) }),
+            InterruptControl::Unpend => parse_quote!({ rticx_rp2040::export::NVIC::unpend(#irq) }),
+            InterruptControl::IsPending => {
+                parse_quote!({ rticx_rp2040::export::NVIC::is_pending(#irq) })
+            }
+            InterruptControl::Enable => {
+                parse_quote!({ unsafe { rticx_rp2040::export::NVIC::unmask(#irq) } })
+            }
+            InterruptControl::Disable => parse_quote!({ rticx_rp2040::export::NVIC::mask(#irq) }),
+        };
+        Some(empty_body_fn)
+    }
+
     fn entry_name(&self, core: u32) -> Ident {
         match core {
             0 => format_ident!("main"),
@@ -185,10 +228,18 @@ impl CorePassBackend for Rp2040Rtic {
 
     fn wrap_task_execution(
         &self,
-        _task_prio: u16,
-        _dispatch_task_call: TokenStream2,
+        task_prio: u16,
+        threshold: u16,
+        dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
-        None
+        // only tasks with a preemption threshold need wrapping
+        (threshold > task_prio).then(|| {
+            rticx_core::rticx_functions::raise_to_threshold(
+                task_prio,
+                threshold,
+                dispatch_task_call,
+            )
+        })
     }
 
     fn pre_codegen_validation(
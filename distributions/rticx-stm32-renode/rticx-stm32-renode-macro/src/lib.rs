@@ -3,7 +3,10 @@ use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use syn::{parse_quote, ItemFn, Path};
 use rticx_auto_assign::AutoAssignPass;
-use rticx_core::{AppArgs, CorePassBackend, RticMacroBuilder, SubAnalysis, SubApp};
+use rticx_core::{
+    AppArgs, CorePassBackend, InitPeripherals, InterruptControl, RticMacroBuilder, SubAnalysis,
+    SubApp,
+};
 
 extern crate proc_macro;
 
@@ -19,7 +22,7 @@ pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
     let sw_pass = SoftwarePass::new(SwPassBackendImpl);
 
     let mut builder = RticMacroBuilder::new(RenodeRtic);
-    builder.bind_pre_core_pass(AutoAssignPass); // run auto-assign first
+    builder.bind_pre_core_pass(AutoAssignPass::new()); // run auto-assign first
     builder.bind_pre_core_pass(sw_pass); // run software pass second
     builder.build_rtic_macro(args, input)
 }
@@ -29,6 +32,17 @@ impl CorePassBackend for RenodeRtic {
     fn default_task_priority(&self) -> u16 {
         MIN_TASK_PRIORITY
     }
+    fn init_core_peripherals(
+        &self,
+        app_args: &AppArgs,
+        sub_app: &SubApp,
+    ) -> Option<InitPeripherals> {
+        let peripheral_crate = &app_args.pacs[sub_app.core as usize];
+        Some(InitPeripherals {
+            ty: parse_quote!(#peripheral_crate::CorePeripherals),
+            take: parse_quote!(#peripheral_crate::CorePeripherals::steal()),
+        })
+    }
     fn post_init(
         &self,
         app_args: &AppArgs,
@@ -118,6 +132,54 @@ impl CorePassBackend for RenodeRtic {
         completed_lock_fn
     }
 
+    /// Read BASEPRI for the `srp-assertions` checks
+    fn generate_hw_ceiling_fn(
+        &self,
+        _app_args: &AppArgs,
+        _core: u32,
+        mut empty_body_fn: ItemFn,
+    ) -> ItemFn {
+        empty_body_fn.block = Box::new(parse_quote!({
+            Some(rticx_stm32_renode::export::system_ceiling(NVIC_PRIO_BITS))
+        }));
+        empty_body_fn
+    }
+
+    /// Control the interrupts through the NVIC. Each core only reaches its own NVIC, so nothing is
+    /// generated for multicore applications.
+    fn generate_interrupt_control_fn(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        if app_args.cores > 1 {
+            return None;
+        }
+        let peripheral_crate = &app_args.pacs[app_info.core as usize];
+        let irq = quote!(#peripheral_crate::Interrupt::#binds);
+        empty_body_fn.block = match control {
+            InterruptControl::Pend => {
+                parse_quote!({ rticx_stm32_renode::export::NVIC::pend(#irq) })
+            }
+            InterruptControl::Unpend => {
+                parse_quote!({ rticx_stm32_renode::export::NVIC::unpend(#irq) })
+            }
+            InterruptControl::IsPending => {
+                parse_quote!({ rticx_stm32_renode::export::NVIC::is_pending(#irq) })
+            }
+            InterruptControl::Enable => {
+                parse_quote!({ unsafe { rticx_stm32_renode::export::NVIC::unmask(#irq) } })
+            }
+            InterruptControl::Disable => {
+                parse_quote!({ rticx_stm32_renode::export::NVIC::mask(#irq) })
+            }
+        };
+        Some(empty_body_fn)
+    }
+
     fn entry_name(&self, _core: u32) -> Ident {
         // same entry name for both cores.
         // two main() functions will be generated but both will be guarded by #[cfg(core = "X")]
@@ -129,8 +191,11 @@ impl CorePassBackend for RenodeRtic {
     fn wrap_task_execution(
         &self,
         task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
+        // the ceiling lock raises BASEPRI to the threshold
+        let dispatch_task_call = rticx_core::rticx_functions::raise_to_threshold(task_prio, threshold, dispatch_task_call);
         Some(quote! {
             rticx_stm32_renode::export::run(#task_prio as u8, || {#dispatch_task_call});
         })
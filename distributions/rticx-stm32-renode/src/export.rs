@@ -25,6 +25,28 @@ pub const fn cortex_logical2hw(logical: u8, nvic_prio_bits: u8) -> u8 {
     ((1 << nvic_prio_bits) - logical) << (8 - nvic_prio_bits)
 }
 
+/// Inverse of [cortex_logical2hw], a BASEPRI of `0` masks nothing
+#[inline]
+#[must_use]
+pub const fn cortex_hw2logical(hw: u8, nvic_prio_bits: u8) -> u8 {
+    if hw == 0 {
+        0
+    } else {
+        (1 << nvic_prio_bits) - (hw >> (8 - nvic_prio_bits))
+    }
+}
+
+/// Logical priority masked by the locks, read by the `srp-assertions` checks: the maximum one
+/// inside a global critical section, the BASEPRI one otherwise
+#[inline]
+pub fn system_ceiling(nvic_prio_bits: u8) -> u16 {
+    if cortex_m::register::primask::read().is_inactive() {
+        1 << nvic_prio_bits
+    } else {
+        cortex_hw2logical(basepri::read(), nvic_prio_bits) as u16
+    }
+}
+
 // TODO: need to think how to abstract this
 #[inline(always)]
 pub fn run<F>(priority: u8, f: F)
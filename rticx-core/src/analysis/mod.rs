@@ -1,29 +1,47 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use proc_macro2::Span;
-use syn::Ident;
 use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, Ident, Item, Lit, Type};
 
 use crate::App;
 use crate::parser::SubApp;
-use crate::parser::ast::{HardwareTask, SharedResources};
+use crate::parser::ast::{
+    HardwareTask, IdleTask, ResourcePath, ResourceProjection, RticTask, SharedPart,
+    SharedResources, TaskArgs,
+};
 use heck::ToSnakeCase;
 #[derive(Debug, Clone)]
 pub struct Analysis {
     pub sub_analysis: Vec<SubAnalysis>,
     pub task_traits: HashSet<syn::Ident>,
+    /// compact identifiers of the tasks and shared resources, used by the tracing hooks
+    pub trace_ids: TraceIds,
+    /// `#[readonly]` resources read by reference by each task (and idle task), by task name
+    pub readonly_access: HashMap<Ident, Vec<ReadonlyAccess>>,
 }
 
 impl Analysis {
-    /// - updates resource ceilings
+    /// - updates resource ceilings and the priorities accessing them
     /// - collects and structure key information about the user application to be used during code generation
     /// - collect the task traits
+    /// - assign the trace identifiers of the tasks and shared resources
+    /// - resolve the read-only resources accessed by reference
     pub fn run(parsed_app: &mut App) -> syn::Result<Self> {
         // update resource ceilings
         for app in parsed_app.sub_apps.iter_mut() {
-            update_resource_priorities(app.shared.as_mut(), &app.tasks)?;
+            update_resource_priorities(app.shared.as_mut(), &app.tasks, app.idle.as_ref())?;
+            update_part_priorities(
+                app.shared.as_mut(),
+                &app.tasks,
+                app.idle.as_ref(),
+                &parsed_app.other_code,
+            )?;
         }
 
+        // resolve the read-only resources
+        let readonly_access = resolve_readonly_access(parsed_app)?;
+
         // collect and structure key information about the user application to be used during code generation
         let sub_analysis = parsed_app
             .sub_apps
@@ -41,9 +59,13 @@ impl Analysis {
             }
         }
 
+        let trace_ids = TraceIds::assign(parsed_app)?;
+
         Ok(Self {
             sub_analysis,
             task_traits,
+            trace_ids,
+            readonly_access,
         })
     }
 }
@@ -54,16 +76,37 @@ pub struct SubAnalysis {
     pub used_irqs: Vec<(syn::Ident, u16)>,
     // tasks requiring some late local resource initialization.
     pub late_resource_tasks: Vec<LateResourceTask>,
+    // tasks with a preemption threshold above their priority, and the tasks they block
+    pub preemption_thresholds: Vec<PreemptionThreshold>,
 }
 
 impl SubAnalysis {
     pub fn run(app: &SubApp) -> syn::Result<Self> {
-        // hw interrupts bound to hardware tasks
-        let used_interrupts = app
-            .tasks
-            .iter()
-            .filter_map(|t| Some((t.args.binds.clone()?, t.args.priority)))
-            .collect();
+        let preemption_thresholds = PreemptionThreshold::collect(app)?;
+
+        // hw interrupts bound to hardware tasks, all of them for tasks bound to several interrupts
+        let mut used_interrupts: Vec<(Ident, u16)> = Vec::new();
+        for task in &app.tasks {
+            for irq in &task.args.binds {
+                if used_interrupts.iter().any(|(used, _)| used == irq) {
+                    // the first task listing it, which may be this one
+                    let other = app
+                        .tasks
+                        .iter()
+                        .find(|other| other.args.binds.contains(irq))
+                        .unwrap_or(task);
+                    return Err(syn::Error::new(
+                        irq.span(),
+                        format!(
+                            "The interrupt `{irq}` of `{}` is already bound to `{}`, an interrupt runs a single task.",
+                            task.name(),
+                            other.name()
+                        ),
+                    ));
+                }
+                used_interrupts.push((irq.clone(), task.args.priority));
+            }
+        }
 
         let user_initializable_tasks = app
             .tasks
@@ -72,7 +115,8 @@ impl SubAnalysis {
             .filter_map(|t| {
                 if t.user_initializable {
                     Some(LateResourceTask {
-                        task_name: t.task_struct.ident.clone(),
+                        task_name: t.instance_name(),
+                        task_ty: t.name().clone(),
                     })
                 } else {
                     None
@@ -83,22 +127,156 @@ impl SubAnalysis {
         Ok(Self {
             used_irqs: used_interrupts,
             late_resource_tasks: user_initializable_tasks,
+            preemption_thresholds,
         })
     }
 }
 
+/// A task running above its priority once dispatched, see [TaskArgs::threshold](crate::parser::ast::TaskArgs::threshold).
+/// The task is never preempted by the tasks of a priority up to its threshold, which saves context
+/// switches and stack, but these tasks are blocked for up to the whole execution of the task.
+#[derive(Debug, Clone)]
+pub struct PreemptionThreshold {
+    pub task: Ident,
+    pub priority: u16,
+    pub threshold: u16,
+    /// Tasks of the same core with a priority in `priority + 1..=threshold`
+    pub blocked_tasks: Vec<Ident>,
+}
+
+impl PreemptionThreshold {
+    fn collect(app: &SubApp) -> syn::Result<Vec<Self>> {
+        if let Some(idle) = app
+            .idle
+            .as_ref()
+            .filter(|idle| idle.args.threshold != idle.args.priority)
+        {
+            return Err(syn::Error::new(
+                idle.task_struct.span(),
+                format!(
+                    "The idle task `{}` can't have a preemption threshold.",
+                    idle.name()
+                ),
+            ));
+        }
+        let mut thresholds = Vec::new();
+        for task in app.tasks.iter() {
+            let TaskArgs {
+                priority,
+                threshold,
+                ..
+            } = task.args;
+            if threshold < priority {
+                return Err(syn::Error::new(
+                    task.task_struct.span(),
+                    format!(
+                        "The threshold {threshold} of `{}` is below its priority {priority}, a task can't run below the priority it is dispatched at.",
+                        task.name()
+                    ),
+                ));
+            }
+            if threshold == priority {
+                continue;
+            }
+            let blocked_tasks = app
+                .tasks
+                .iter()
+                .filter(|other| (priority + 1..=threshold).contains(&other.args.priority))
+                .map(RticTask::instance_name)
+                .collect();
+            thresholds.push(Self {
+                task: task.instance_name(),
+                priority,
+                threshold,
+                blocked_tasks,
+            });
+        }
+        Ok(thresholds)
+    }
+}
+
+/// A task or shared resource identified in traces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceId {
+    pub id: u16,
+    pub ident: Ident,
+    pub core: u32,
+    /// priority of a task, or ceiling of a resource
+    pub priority: u16,
+}
+
+/// Identifiers of the tasks and shared resources in traces, numbered from `0` in declaration order
+/// across all the cores. The idle tasks are not traced.
+#[derive(Debug, Clone, Default)]
+pub struct TraceIds {
+    pub tasks: Vec<TraceId>,
+    pub resources: Vec<TraceId>,
+}
+
+impl TraceIds {
+    fn assign(app: &App) -> syn::Result<Self> {
+        let mut ids = Self::default();
+        for sub_app in app.sub_apps.iter() {
+            for task in sub_app.tasks.iter() {
+                let id = next_trace_id(&ids.tasks, task.name())?;
+                ids.tasks.push(TraceId {
+                    id,
+                    ident: task.instance_name(),
+                    core: sub_app.core,
+                    priority: task.args.priority,
+                });
+            }
+            for resource in sub_app.shared.iter().flat_map(|shared| &shared.resources) {
+                let id = next_trace_id(&ids.resources, &resource.ident)?;
+                ids.resources.push(TraceId {
+                    id,
+                    ident: resource.ident.clone(),
+                    core: sub_app.core,
+                    priority: resource.priority,
+                });
+            }
+        }
+        Ok(ids)
+    }
+
+    pub fn task(&self, task: &Ident) -> Option<u16> {
+        self.tasks.iter().find(|t| &t.ident == task).map(|t| t.id)
+    }
+
+    /// Shared resources are declared per core, so their names may be reused by other cores
+    pub fn resource(&self, core: u32, resource: &Ident) -> Option<u16> {
+        self.resources
+            .iter()
+            .find(|r| r.core == core && &r.ident == resource)
+            .map(|r| r.id)
+    }
+}
+
+fn next_trace_id(assigned: &[TraceId], ident: &Ident) -> syn::Result<u16> {
+    u16::try_from(assigned.len()).map_err(|_| {
+        syn::Error::new(
+            ident.span(),
+            format!("Too many items to trace, `{ident}` can't be given a 16-bit identifier."),
+        )
+    })
+}
+
+/// A task locks its resources at its threshold, the priority it runs at once dispatched, so the
+/// ceiling of a resource is the highest threshold of the tasks locking it
 fn update_resource_priorities(
     shared: Option<&mut SharedResources>,
     hw_tasks: &[HardwareTask],
+    idle: Option<&IdleTask>,
 ) -> syn::Result<()> {
     let Some(shared) = shared else { return Ok(()) };
     for task in hw_tasks.iter() {
-        let task_priority = task.args.priority;
+        let task_priority = task.args.threshold;
         for resource_ident in task.args.shared.iter() {
             if let Some(shared_element) = shared.get_field_mut(resource_ident) {
                 if shared_element.priority < task_priority {
                     shared_element.priority = task_priority
                 }
+                shared_element.accessed_by.insert(task_priority);
             } else {
                 return Err(syn::Error::new(
                     task.task_struct.span(),
@@ -110,12 +288,262 @@ fn update_resource_priorities(
             }
         }
     }
+    // the idle task runs below every task, it doesn't raise the ceilings
+    for resource_ident in idle.iter().flat_map(|idle| idle.args.shared.iter()) {
+        if let Some(shared_element) = shared.get_field_mut(resource_ident) {
+            shared_element.accessed_by.insert(0);
+        }
+    }
+    Ok(())
+}
+
+/// Resolves the parts of resources locked on their own, like `channels[0]` or `motor.state`, and
+/// computes their ceilings. Disjoint parts don't block each other: the ceiling of a part only
+/// accounts for the tasks locking the whole resource, the part itself, a part containing it or a
+/// part it contains. The whole resource contains every part, its ceiling accounts for all of them.
+fn update_part_priorities(
+    shared: Option<&mut SharedResources>,
+    hw_tasks: &[HardwareTask],
+    idle: Option<&IdleTask>,
+    items: &[Item],
+) -> syn::Result<()> {
+    let Some(shared) = shared else { return Ok(()) };
+    let shared_ident = shared.strct.ident.clone();
+    let tasks = hw_tasks
+        .iter()
+        .map(|task| (task, task.args.threshold))
+        .chain(idle.map(|idle| (idle, 0)));
+    for (task, task_priority) in tasks {
+        for path in task.args.shared_parts.iter() {
+            // the part is named like a resource in the `shared()` API and its proxy type
+            let part_ident = path.ident();
+            if shared.get_field(&part_ident).is_some() {
+                return Err(syn::Error::new(
+                    task.task_struct.span(),
+                    format!(
+                        "The resource part `{path}` is named `{part_ident}`, like a field of `{shared_ident}`. Rename the field."
+                    ),
+                ));
+            }
+            let Some(shared_element) = shared.get_field_mut(&path.resource) else {
+                return Err(syn::Error::new(
+                    task.task_struct.span(),
+                    format!(
+                        "The resource `{}` was not found in `{shared_ident}`",
+                        path.resource
+                    ),
+                ));
+            };
+            if let Some(part) = shared_element.parts.iter_mut().find(|p| &p.path == path) {
+                part.accessed_by.insert(task_priority);
+            } else {
+                let ty = resolve_part_type(&shared_element.ty, path, items)
+                    .map_err(|message| syn::Error::new(task.task_struct.span(), message))?;
+                shared_element.parts.push(SharedPart {
+                    path: path.clone(),
+                    ty,
+                    priority: 0,
+                    accessed_by: BTreeSet::from([task_priority]),
+                });
+            }
+        }
+    }
+
+    for shared_element in shared.resources.iter_mut() {
+        // ceiling of the tasks locking the whole resource
+        let whole_ceiling = shared_element.priority;
+        let ceilings: Vec<u16> = shared_element
+            .parts
+            .iter()
+            .map(|part| {
+                shared_element
+                    .parts
+                    .iter()
+                    .filter(|other| other.path.overlaps(&part.path))
+                    .flat_map(|other| other.accessed_by.iter().copied())
+                    .fold(whole_ceiling, u16::max)
+            })
+            .collect();
+        for (part, ceiling) in shared_element.parts.iter_mut().zip(ceilings) {
+            part.priority = ceiling;
+            shared_element.priority = shared_element.priority.max(ceiling);
+            shared_element
+                .accessed_by
+                .extend(part.accessed_by.iter().copied());
+        }
+    }
     Ok(())
 }
 
+/// Type of the part `path` of a resource of type `resource_ty`. Arrays are indexed through their
+/// `[T; N]` type, and fields are looked up in the structs declared in the app module.
+fn resolve_part_type(
+    resource_ty: &Type,
+    path: &ResourcePath,
+    items: &[Item],
+) -> Result<Type, String> {
+    let mut ty = resource_ty.clone();
+    let mut prefix = path.resource.to_string();
+    for projection in path.projections.iter() {
+        ty = match (projection, &ty) {
+            (ResourceProjection::Index(index), Type::Array(array)) => {
+                let len = match &array.len {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(len), ..
+                    }) => len.base10_parse::<usize>().ok(),
+                    _ => None,
+                };
+                if len.is_some_and(|len| *index >= len) {
+                    return Err(format!(
+                        "The resource part `{path}` is out of the bounds of `{prefix}`."
+                    ));
+                }
+                array.elem.as_ref().clone()
+            }
+            (ResourceProjection::Index(_), _) => {
+                return Err(format!(
+                    "The resource part `{path}` can't be resolved, `{prefix}` is not an array `[T; N]`."
+                ));
+            }
+            (ResourceProjection::Field(field), ty) => {
+                let strct = match ty {
+                    Type::Path(ty) => ty.path.segments.last().and_then(|segment| {
+                        items.iter().find_map(|item| match item {
+                            Item::Struct(strct) if strct.ident == segment.ident => Some(strct),
+                            _ => None,
+                        })
+                    }),
+                    _ => None,
+                };
+                strct
+                    .filter(|strct| strct.generics.params.is_empty())
+                    .and_then(|strct| {
+                        strct
+                            .fields
+                            .iter()
+                            .find(|f| f.ident.as_ref() == Some(field))
+                    })
+                    .map(|f| f.ty.clone())
+                    .ok_or_else(|| format!(
+                        "The resource part `{path}` can't be resolved, the type of `{prefix}` must be a struct without generics declared in the app module, with a field `{field}`."
+                    ))?
+            }
+        };
+        match projection {
+            ResourceProjection::Field(field) => prefix.push_str(&format!(".{field}")),
+            ResourceProjection::Index(index) => prefix.push_str(&format!("[{index}]")),
+        }
+    }
+    Ok(ty)
+}
+
+/// A `#[readonly]` shared resource read by reference by a task
+#[derive(Debug, Clone)]
+pub struct ReadonlyAccess {
+    pub ident: Ident,
+    pub ty: syn::Type,
+    /// Static holding the `#[shared]` struct declaring the resource
+    pub shared_static: Ident,
+}
+
+/// Resolves the resources listed by reference (`&resource`) by the tasks, and rejects the tasks
+/// locking a read-only resource. A task reads the read-only resources of its own core, or of core
+/// 0, which is initialized before the other cores are started.
+fn resolve_readonly_access(app: &App) -> syn::Result<HashMap<Ident, Vec<ReadonlyAccess>>> {
+    let shared_of = |core: u32| {
+        app.sub_apps
+            .iter()
+            .find(|sub_app| sub_app.core == core)
+            .and_then(|sub_app| sub_app.shared.as_ref())
+    };
+    let mut readonly_access = HashMap::new();
+    for sub_app in app.sub_apps.iter() {
+        let tasks = sub_app.tasks.iter().chain(sub_app.idle.iter());
+        for task in tasks {
+            let task_name = task.name();
+            let shared = sub_app.shared.as_ref();
+            for resource_ident in task.args.shared.iter() {
+                if shared
+                    .and_then(|shared| shared.get_field(resource_ident))
+                    .is_some_and(|resource| resource.readonly)
+                {
+                    return Err(syn::Error::new(
+                        task.task_struct.span(),
+                        format!(
+                            "The resource `{resource_ident}` is `#[readonly]` and can't be locked, `{task_name}` must list it as `&{resource_ident}`."
+                        ),
+                    ));
+                }
+            }
+
+            for path in task.args.shared_parts.iter() {
+                if shared
+                    .and_then(|shared| shared.get_field(&path.resource))
+                    .is_some_and(|resource| resource.readonly)
+                {
+                    return Err(syn::Error::new(
+                        task.task_struct.span(),
+                        format!(
+                            "The resource `{}` is `#[readonly]` and its part `{path}` can't be locked, `{task_name}` must list it as `&{}`.",
+                            path.resource, path.resource
+                        ),
+                    ));
+                }
+            }
+
+            let mut accesses = Vec::new();
+            for resource_ident in task.args.readonly.iter() {
+                let (declaring, resource) = [sub_app.core, 0]
+                    .into_iter()
+                    .filter_map(shared_of)
+                    .find_map(|shared| Some((shared, shared.get_field(resource_ident)?)))
+                    .ok_or_else(|| readonly_not_found(app, task, resource_ident))?;
+                if !resource.readonly {
+                    return Err(syn::Error::new(
+                        task.task_struct.span(),
+                        format!(
+                            "`{task_name}` lists `&{resource_ident}`, but only `#[readonly]` resources can be read by reference, list it as `{resource_ident}` to lock it."
+                        ),
+                    ));
+                }
+                accesses.push(ReadonlyAccess {
+                    ident: resource.ident.clone(),
+                    ty: resource.ty.clone(),
+                    shared_static: declaring.name_uppercase(),
+                });
+            }
+            readonly_access.insert(task_name.clone(), accesses);
+        }
+    }
+    Ok(readonly_access)
+}
+
+fn readonly_not_found(app: &App, task: &RticTask, resource_ident: &Ident) -> syn::Error {
+    let other_core = app.sub_apps.iter().find(|sub_app| {
+        sub_app
+            .shared
+            .as_ref()
+            .is_some_and(|shared| shared.get_field(resource_ident).is_some())
+    });
+    let message = match other_core {
+        Some(other) => format!(
+            "The resource `{resource_ident}` of core {} can't be read by `{}` on core {}, only the read-only resources of the same core or of core 0 can.",
+            other.core,
+            task.name(),
+            task.args.core
+        ),
+        None => {
+            format!("The resource `{resource_ident}` was not found in the `#[shared]` structs.")
+        }
+    };
+    syn::Error::new(task.task_struct.span(), message)
+}
+
 #[derive(Debug, Clone)]
 pub struct LateResourceTask {
+    /// Name of the task, see [RticTask::instance_name]
     pub task_name: Ident,
+    pub task_ty: Ident,
 }
 impl LateResourceTask {
     /// By convention, this method is used to generate the name of the static task instance
@@ -1,5 +1,53 @@
 use super::*;
 
+/// Operation on the interrupt a hardware task is bound to, see
+/// [CorePassBackend::generate_interrupt_control_fn].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptControl {
+    /// `pend()`: request the interrupt, as its peripheral would.
+    Pend,
+    /// `unpend()`: clear a pending request of the interrupt.
+    Unpend,
+    /// `is_pending() -> bool`: whether the interrupt is pending.
+    IsPending,
+    /// `enable()`: unmask the interrupt.
+    Enable,
+    /// `disable()`: mask the interrupt, requests stay pending until it is enabled again.
+    Disable,
+}
+
+impl InterruptControl {
+    pub const ALL: [Self; 5] = [
+        Self::Pend,
+        Self::Unpend,
+        Self::IsPending,
+        Self::Enable,
+        Self::Disable,
+    ];
+
+    /// Name of the associated function of the task.
+    pub fn fn_name(self) -> &'static str {
+        match self {
+            Self::Pend => "pend",
+            Self::Unpend => "unpend",
+            Self::IsPending => "is_pending",
+            Self::Enable => "enable",
+            Self::Disable => "disable",
+        }
+    }
+}
+
+/// Peripherals given to `#[init]` by its context, see [CorePassBackend::init_device_peripherals]
+/// and [CorePassBackend::init_core_peripherals].
+#[derive(Debug, Clone)]
+pub struct InitPeripherals {
+    /// Type of the peripherals, like `PAC::Peripherals`.
+    pub ty: syn::Type,
+    /// Expression taking the peripherals, like `PAC::Peripherals::steal()`, evaluated in an
+    /// `unsafe` block with interrupts disabled, right before `init` runs.
+    pub take: syn::Expr,
+}
+
 /// Interface for providing the low-level hardware bindings specific for a target(s) (A.k.a The Backend) to be used during code generation phase
 /// of the **Core Compilation Pass*.
 pub trait CorePassBackend {
@@ -68,7 +116,7 @@ pub trait CorePassBackend {
     ///     fn lock<R>(&mut self, f: impl FnOnce(&mut Self::ResourceType) -> R) -> R {
     ///         const CEILING: u16 = 3u16; // resource ceiling
     ///         let task_priority = self.task_priority;
-    ///         let resource_ref = unsafe { &mut SHARED.assume_init_mut().resource1 };
+    ///         let resource_ptr = unsafe { core::ptr::addr_of_mut!((*SHARED.as_mut_ptr()).resource1) };
     ///         /* TODO: HARDWARE-SPECIFIC CODE COMES HERE */
     ///     }
     /// }
@@ -154,10 +202,19 @@ pub trait CorePassBackend {
     ///
     /// Return `None` to skip wrapping entirely.
     ///
+    /// # Contract
+    /// * `threshold` is the preemption threshold of the task, at least
+    ///   `task_prio`. When it is above `task_prio`, `exec()` must run with
+    ///   the system ceiling raised to `threshold` (BASEPRI, `mintthresh`,
+    ///   CLIC level, ...), and restored afterwards.
+    ///   [rticx_functions::raise_to_threshold] does it with the ceiling
+    ///   lock function ([Self::generate_ceiling_lock_fn]).
+    ///
     /// Reference: `rticx-cortex-m` implements execution wrapping for non-armv6m targets
     fn wrap_task_execution(
         &self,
         task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2>;
 
@@ -242,6 +299,245 @@ pub trait CorePassBackend {
     /// Reference: `rticx-cortex-m` uses `1`. `rticx-hippo` uses `15`.
     fn default_task_priority(&self) -> u16;
 
+    /// Body of the ceiling lock function.
+    ///
+    /// RTIC generates a function like:
+    ///
+    /// ```ignore
+    /// pub fn __rticx_ceiling_lock<F, R>(ceiling: u16, f: F) -> R
+    /// where F: FnOnce() -> R { /* YOU FILL THIS */ }
+    /// ```
+    ///
+    /// It runs `f` with the system ceiling raised to (at least) the logical
+    /// priority `ceiling`, so that only tasks of higher priority can preempt `f`.
+    /// Compilation passes use it instead of the global critical section when they
+    /// know the priorities of every context accessing some data, e.g. the software
+    /// pass protects its spawn queues with it.
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * The ceiling must never be lowered: if the current ceiling is already
+    ///   higher than `ceiling`, just run `f`.
+    /// * The previous ceiling must be restored when `f` returns.
+    ///
+    /// # Porting
+    ///
+    /// The default implementation falls back to the global critical section,
+    /// which is always correct but masks every interrupt.
+    ///
+    /// * **Cortex-M BASEPRI**: save BASEPRI, raise it with `basepri_max`, restore.
+    /// * **RISC-V mintthresh**: save the threshold, raise it, restore.
+    /// * **Source masking**: keep the default, the masks depend on the running
+    ///   task priority which is not known here.
+    ///
+    /// Reference: `rticx-cortex-m` uses its BASEPRI `lock` primitive on armv7-m.
+    fn generate_ceiling_lock_fn(
+        &self,
+        _app_args: &AppArgs,
+        mut empty_body_fn: syn::ItemFn,
+    ) -> syn::ItemFn {
+        let interrupt_free = quote::format_ident!("{}", rticx_functions::INTERRUPT_FREE_FN);
+        empty_body_fn.block = Box::new(syn::parse_quote! {
+            {
+                let _ = ceiling;
+                #interrupt_free(f)
+            }
+        });
+        empty_body_fn
+    }
+
+    /// Body of the function reading the cycle counter of `core`, used by
+    /// the runtime statistics of the `stats` feature.
+    ///
+    /// RTIC generates a function like:
+    ///
+    /// ```ignore
+    /// pub fn __rticx_stats_cycles_core0() -> u32 { /* YOU FILL THIS */ }
+    /// ```
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * The counter must count up and wrap around at `u32::MAX`.
+    /// * It must already run when `post_init` returns, start it there if needed.
+    ///
+    /// # Porting
+    ///
+    /// The default implementation always returns `0`: activations and spawn
+    /// failures are still counted, but every cycle statistic stays `0`.
+    ///
+    /// * **Cortex-M (armv7-m)**: `DWT::cycle_count()`.
+    /// * **RISC-V**: the `mcycle` CSR.
+    ///
+    /// Reference: `rticx-cortex-m` reads the DWT cycle counter.
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: syn::ItemFn) -> syn::ItemFn {
+        empty_body_fn.block = Box::new(syn::parse_quote!({ 0 }));
+        empty_body_fn
+    }
+
+    /// Body of the function reading the ceiling currently set by the locks
+    /// of `core`, used by the checks of the `srp-assertions` feature.
+    ///
+    /// RTIC generates a function like:
+    ///
+    /// ```ignore
+    /// pub fn __rticx_srp_hw_ceiling_core0() -> Option<u16> { /* YOU FILL THIS */ }
+    /// ```
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * Return the highest logical priority masked by the lock mechanism,
+    ///   `0` when nothing is masked. The priority of the running interrupt
+    ///   is not part of it.
+    /// * Locks must raise it to at least their ceiling, and locks and
+    ///   [Self::wrap_task_execution] must restore it to its previous value.
+    ///
+    /// # Porting
+    ///
+    /// The default implementation returns `None`: the checks then only
+    /// cover the ceiling tracked by the generated code, not the hardware.
+    ///
+    /// * **Cortex-M (armv7-m)**: convert `BASEPRI` back to a logical
+    ///   priority, the maximum one when `PRIMASK` masks all interrupts.
+    /// * **RISC-V with a threshold register**: read the threshold.
+    ///
+    /// Reference: `rticx-cortex-m` reads `BASEPRI` and `PRIMASK`.
+    fn generate_hw_ceiling_fn(
+        &self,
+        _app_args: &AppArgs,
+        _core: u32,
+        mut empty_body_fn: syn::ItemFn,
+    ) -> syn::ItemFn {
+        empty_body_fn.block = Box::new(syn::parse_quote!({ None }));
+        empty_body_fn
+    }
+
+    /// Body of an associated function of a hardware task acting on the
+    /// interrupt the task is bound to.
+    ///
+    /// For every task with `binds = IRQ`, RTIC generates the functions
+    ///
+    /// ```ignore
+    /// impl UartTask {
+    ///     pub fn pend() { /* YOU FILL THIS */ }
+    ///     pub fn unpend() { /* YOU FILL THIS */ }
+    ///     pub fn is_pending() -> bool { /* YOU FILL THIS */ }
+    ///     pub fn enable() { /* YOU FILL THIS */ }
+    ///     pub fn disable() { /* YOU FILL THIS */ }
+    /// }
+    /// ```
+    ///
+    /// This method is called once per function, `control` tells which one.
+    ///
+    /// # Contract
+    /// * Do NOT change the function signature of `empty_body_fn`.
+    /// * `binds` is the interrupt of the task as written by the user, handled
+    ///   by the core `app_info.core`. The functions can be called from any
+    ///   task of any core, and from `#[init]` (where `post_init` runs after
+    ///   them, and unmasks the interrupt again). Targets with an interrupt
+    ///   controller per core, not reachable from the others, must not provide
+    ///   them in multicore applications.
+    /// * Enabling and disabling must not interfere with the locks: targets
+    ///   whose locks mask interrupt sources must not provide them.
+    /// * Return `None` for the functions the target can't provide, they are
+    ///   then not generated.
+    ///
+    /// # Porting
+    ///
+    /// The default implementation returns `None`: no function is generated.
+    ///
+    /// * **Cortex-M**: `NVIC::pend`, `NVIC::unpend`, `NVIC::is_pending`,
+    ///   `NVIC::unmask` and `NVIC::mask`.
+    /// * **Source masking**: no `enable()`/`disable()`, the locks rewrite
+    ///   the enable registers.
+    /// * **RISC-V CLIC**: the pending and enable bits of the interrupt.
+    ///
+    /// Reference: `rticx-cortex-m` uses the NVIC and only pends exceptions.
+    fn generate_interrupt_control_fn(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+        _binds: &Ident,
+        _control: InterruptControl,
+        _empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        None
+    }
+
+    /// Peripherals of the device given to `#[init]` by its context, the
+    /// `device` field of `init::Context`.
+    ///
+    /// `#[init]` can take a context, `fn init(cx: init::Context)`, holding the
+    /// peripherals of its core so that the application doesn't steal them.
+    ///
+    /// # Contract
+    /// * `take` runs once per core, before `init` and [Self::post_init]. The
+    ///   backend may still steal the peripherals it configures in `post_init`.
+    /// * On multicore targets, each core gets the peripherals of its PAC,
+    ///   `app_args.pacs[app_info.core as usize]`.
+    ///
+    /// # Porting
+    ///
+    /// The default implementation returns the `Peripherals` of the svd2rust PAC
+    /// of the core, taken with `Peripherals::steal()`. Return `None` if the PAC
+    /// has no such singleton, the context then has no `device` field.
+    ///
+    /// Reference: every distribution keeps the default.
+    fn init_device_peripherals(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+    ) -> Option<InitPeripherals> {
+        let pac = &app_args.pacs[app_info.core as usize];
+        Some(InitPeripherals {
+            ty: syn::parse_quote!(#pac::Peripherals),
+            take: syn::parse_quote!(#pac::Peripherals::steal()),
+        })
+    }
+
+    /// Peripherals of the processor core given to `#[init]` by its context,
+    /// the `core` field of `init::Context`.
+    ///
+    /// # Contract
+    /// * `take` runs once per core, before `init` and [Self::post_init].
+    ///
+    /// # Porting
+    ///
+    /// The default implementation returns `None`: the context has no `core`
+    /// field.
+    ///
+    /// * **Cortex-M**: `cortex_m::Peripherals`, taken with `steal()`.
+    /// * **RISC-V**: keep the default, the core registers are CSRs.
+    ///
+    /// Reference: `rticx-cortex-m` re-exports `cortex_m::Peripherals`, `rticx-rp2040` and
+    /// `rticx-stm32-renode` use the `CorePeripherals` of the PAC.
+    fn init_core_peripherals(
+        &self,
+        _app_args: &AppArgs,
+        _app_info: &SubApp,
+    ) -> Option<InitPeripherals> {
+        None
+    }
+
+    /// Path to the re-exported `rticx-trace` crate, needed by the `trace`
+    /// feature.
+    ///
+    /// The generated tracing hooks call the `Tracer` given by the `tracer`
+    /// argument of the application, or `NoTracer` by default, through this
+    /// path.
+    ///
+    /// # Porting
+    ///
+    /// Return `None` (the default) if the distribution does not support
+    /// tracing, building an application with the `trace` feature then fails.
+    /// Distributions can also export a recorder implementing `Tracer` with
+    /// an `rticx_trace::RingBuffer` and a timestamp source of the target.
+    ///
+    /// Reference: `rticx-cortex-m` returns `rticx_cortex_m::export::trace`,
+    /// which provides a `RingTracer` timestamped with the DWT cycle counter.
+    fn trace_export_path(&self) -> Option<syn::Path> {
+        None
+    }
+
     /// Attribute macros to add to the entry point.
     ///
     /// Used to annotate the runtime entry point for bare metal applications.
@@ -1,39 +1,171 @@
+use heck::ToSnakeCase;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{ImplItem, ImplItemFn, parse_quote};
+use quote::{format_ident, quote};
+use syn::{Ident, ImplItem, ImplItemFn, ItemImpl, parse_quote};
 
-use crate::rticx_functions;
+use crate::rticx_traits::INTERRUPT_SOURCE_TY;
 use crate::{
-    CorePassBackend,
+    AppArgs, CorePassBackend, InterruptControl, SubApp,
+    analysis::ReadonlyAccess,
     codegen::utils,
-    parser::ast::{HardwareTask, RticTask, SharedResources},
+    parser::ast::{HardwareTask, RticTask, SharedResources, instance_ident},
 };
+use crate::{rticx_cell, rticx_functions, rticx_srp, rticx_stats, rticx_trace};
 
 impl RticTask {
     /// Generates task definition, Context struct, resource proxies and binds task to appropriate interrupt
-    pub fn generate_task_def(&self, shared_resources: Option<&SharedResources>) -> TokenStream2 {
+    pub fn generate_task_def(
+        &self,
+        shared_resources: Option<&SharedResources>,
+        readonly: &[ReadonlyAccess],
+    ) -> TokenStream2 {
         let task_ty = &self.task_struct.ident;
         let task_static_handle = &self.name_uppercase();
+        let task_static = rticx_cell::uninit_static(task_static_handle, task_ty);
+        let task_stats = rticx_stats::ENABLED.then(|| self.generate_stats_static());
+        if !self.defines_type() {
+            // the other instances only have their own static
+            return quote! {
+                #task_static
+                #task_stats
+            };
+        }
         let task_struct = &self.task_struct;
-        let task_impl = &self.struct_impl;
+        let task_impl = self.instrumented_impl();
         let task_trait_check = rticx_functions::trait_check_call_for(self);
+        let task_stats_api = rticx_stats::ENABLED.then(|| self.generate_stats_api());
+        let instance_priority = self.instance.is_some().then(|| {
+            let priority_static = self.instance_priority_static();
+            rticx_cell::static_with_value(&priority_static, &quote!(u16), &quote!(0))
+        });
 
         let task_prio_impl = self.generate_priority_func();
-        let shared_mod = shared_resources.map(|shared| shared.generate_shared_for_task(self));
+        let shared_mod = self.generate_shared_access(shared_resources, readonly);
         let current_current_fn = self.generate_current_core_fn();
         quote! {
-            static mut #task_static_handle: core::mem::MaybeUninit<#task_ty> = core::mem::MaybeUninit::uninit();
+            #task_static
             #task_struct
             #task_trait_check
 
             #task_impl
 
+            #instance_priority
             #task_prio_impl
             #shared_mod
             #current_current_fn
+            #task_stats
+            #task_stats_api
+        }
+    }
+
+    /// Static recording the priority of the running instance of a task type declaring several, as
+    /// they share the `shared()` API
+    pub(crate) fn instance_priority_static(&self) -> Ident {
+        let task_ty = self.name().to_string().to_snake_case().to_uppercase();
+        format_ident!("__RTICX_{task_ty}_PRIORITY")
+    }
+
+    /// Statistics of the task
+    fn generate_stats_static(&self) -> TokenStream2 {
+        let task_name = self.instance_name();
+        let task_stats = rticx_stats::task_stats_ident(&task_name);
+        let task_name = task_name.to_string();
+        let stats_mod = format_ident!("{}", rticx_stats::STATS_MOD);
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #task_stats: #stats_mod::TaskStats = #stats_mod::TaskStats::new(#task_name);
         }
     }
 
+    /// The `stats()` API of the task type to read the statistics, of the instance given as an
+    /// `InterruptSource` for the task types declaring several
+    fn generate_stats_api(&self) -> TokenStream2 {
+        let task_ty = self.name();
+        let stats_mod = format_ident!("{}", rticx_stats::STATS_MOD);
+        let Some(_) = self.instance else {
+            let task_stats = rticx_stats::task_stats_ident(task_ty);
+            return quote! {
+                impl #task_ty {
+                    /// Runtime statistics of this task
+                    pub fn stats() -> &'static #stats_mod::TaskStats {
+                        &#task_stats
+                    }
+                }
+            };
+        };
+        let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
+        let binds = self.type_binds();
+        let tasks_stats = binds
+            .iter()
+            .map(|irq| rticx_stats::task_stats_ident(&instance_ident(task_ty, irq)));
+        quote! {
+            impl #task_ty {
+                /// Runtime statistics of the instance bound to `source`
+                pub fn stats(source: #interrupt_source) -> &'static #stats_mod::TaskStats {
+                    #[allow(unreachable_patterns)]
+                    match source {
+                        #(#interrupt_source::#binds => &#tasks_stats,)*
+                        _ => panic!("no instance of the task is bound to this interrupt"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Statements checking, accounting and tracing a job of the task until the end of the enclosing
+    /// scope, `None` without the `srp-assertions`, `stats` and `trace` features
+    fn job_instrumentation(&self) -> Option<TokenStream2> {
+        if !rticx_srp::ENABLED && !rticx_stats::ENABLED && !rticx_trace::ENABLED {
+            return None;
+        }
+        let srp_task = rticx_srp::ENABLED.then(|| {
+            let bound = !self.args.binds.is_empty();
+            rticx_srp::task_check_stmt(self.args.core, &self.instance_name(), &self.args, bound)
+        });
+        let stats_job = rticx_stats::ENABLED.then(|| {
+            let task_stats = rticx_stats::task_stats_ident(&self.instance_name());
+            let core_stats = rticx_stats::core_stats_ident(self.args.core);
+            quote! {
+                let __rticx_stats_job = #core_stats.job(&#task_stats);
+            }
+        });
+        // declared last so the task exit is traced before the statistics are updated
+        let trace_job =
+            rticx_trace::ENABLED.then(|| rticx_trace::task_hook_stmt(&self.instance_name()));
+        Some(quote! {
+            #srp_task
+            #stats_job
+            #trace_job
+        })
+    }
+
+    /// The trait implementation of the task. With the `srp-assertions`, `stats` or `trace` feature,
+    /// the `exec` function of tasks not bound to an interrupt is instrumented, as they are called by
+    /// other tasks (e.g. the dispatchers of the software pass). Bound tasks are instrumented in their
+    /// interrupt handler instead, which also covers tasks implemented outside of the application.
+    fn instrumented_impl(&self) -> Option<ItemImpl> {
+        let mut task_impl = self.struct_impl.clone()?;
+        let Some(instrumentation) = self
+            .job_instrumentation()
+            .filter(|_| self.args.binds.is_empty())
+        else {
+            return Some(task_impl);
+        };
+        task_impl.items.iter_mut().for_each(|item| {
+            if let ImplItem::Fn(f) = item
+                && f.sig.ident == "exec"
+            {
+                let stmts = &f.block.stmts;
+                f.block = parse_quote!({
+                    #instrumentation
+                    #(#stmts)*
+                });
+            }
+        });
+        Some(task_impl)
+    }
+
     pub fn task_init_call(&self) -> Option<TokenStream2> {
         if self.user_initializable {
             // it is user responsibility to initialize task, and this is enforced at compiler time
@@ -44,13 +176,32 @@ impl RticTask {
         Some(quote! { #task_static_handle.write(#task_ty::init(())); })
     }
 
+    /// The `priority()` of the task type, of the instance given as an `InterruptSource` for the
+    /// task types declaring several
     fn generate_priority_func(&self) -> TokenStream2 {
         let task_ty = self.name();
-        let task_prio = self.args.priority;
+        if self.instance.is_none() {
+            let task_prio = self.args.priority;
+            return quote! {
+                impl #task_ty {
+                    pub const fn priority() -> u16 {
+                        #task_prio
+                    }
+                }
+            };
+        }
+        let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
+        let binds = self.args.instances.iter().map(|i| &i.binds);
+        let priorities = self.args.instances.iter().map(|i| i.priority);
         quote! {
             impl #task_ty {
-                pub const fn priority() -> u16 {
-                    #task_prio
+                /// Priority of the instance bound to `source`
+                pub const fn priority(source: #interrupt_source) -> u16 {
+                    #[allow(unreachable_patterns)]
+                    match source {
+                        #(#interrupt_source::#binds => #priorities,)*
+                        _ => panic!("no instance of the task is bound to this interrupt"),
+                    }
                 }
             }
         }
@@ -77,22 +228,156 @@ impl HardwareTask {
     ) -> Option<TokenStream2> {
         let task_attrs = implementation.task_attrs();
         let task_static_handle = &self.name_uppercase();
-        let task_irq_handler = &self.args.binds.clone()?;
+        let binds = &self.args.binds;
+        if binds.is_empty() {
+            return None;
+        }
+        let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
+        // checked outside of the wrapping, which must restore the hardware ceiling
+        let srp_handler = rticx_srp::ENABLED
+            .then(|| rticx_srp::handler_check_stmt(self.args.core, &self.instance_name()));
 
-        let default_task_dispatch_call = quote! {
-            unsafe {#task_static_handle.assume_init_mut().exec()};
-        };
+        // one handler per interrupt, all of them running the same task instance
+        let handlers = binds.iter().map(|task_irq_handler| {
+            let mut default_task_dispatch_call = if binds.len() > 1 {
+                quote! {
+                    unsafe {#task_static_handle.get_mut().exec_from(#interrupt_source::#task_irq_handler)};
+                }
+            } else if self.instance.is_some() {
+                // restored once the job ends, for the instance it preempted
+                let priority_static = self.instance_priority_static();
+                let priority = self.args.priority;
+                quote! {
+                    unsafe {
+                        let __rticx_preempted_priority = #priority_static.as_mut_ptr().replace(#priority);
+                        #task_static_handle.get_mut().exec();
+                        #priority_static.as_mut_ptr().write(__rticx_preempted_priority);
+                    }
+                }
+            } else {
+                quote! {
+                    unsafe {#task_static_handle.get_mut().exec()};
+                }
+            };
+            if let Some(instrumentation) = self.job_instrumentation() {
+                default_task_dispatch_call = quote! {
+                    {
+                        #instrumentation
+                        #default_task_dispatch_call
+                    }
+                };
+            }
+
+            let task_dispatch_call = implementation
+                .wrap_task_execution(
+                    self.args.priority,
+                    self.args.threshold,
+                    default_task_dispatch_call.clone(),
+                )
+                .unwrap_or(default_task_dispatch_call);
 
-        let task_dispatch_call = implementation
-            .wrap_task_execution(self.args.priority, default_task_dispatch_call.clone())
-            .unwrap_or(default_task_dispatch_call);
+            quote! {
+                #[allow(non_snake_case)]
+                #[unsafe(no_mangle)]
+                #(#task_attrs)*
+                fn #task_irq_handler() {
+                    #srp_handler
+                    #task_dispatch_call
+                }
+            }
+        });
+        Some(quote!(#(#handlers)*))
+    }
 
+    /// Generates the functions of the task acting on the interrupt it is bound to: `pend()`,
+    /// `unpend()`, `is_pending()`, `enable()` and `disable()`, those provided by the backend. Tasks
+    /// bound to several interrupts take the interrupt to act on as an `InterruptSource` argument.
+    pub fn generate_interrupt_control(
+        &self,
+        implementation: &dyn CorePassBackend,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+    ) -> Option<TokenStream2> {
+        if !self.defines_type() {
+            return None;
+        }
+        let binds = self.type_binds();
+        let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
+        let (target, input) = match binds.as_slice() {
+            [] => return None,
+            [irq] if !self.takes_interrupt_source() => (format!("`{irq}`"), quote!()),
+            binds => {
+                let binds = binds.iter().map(|irq| format!("`{irq}`"));
+                let target = format!("`source`, one of {}", binds.collect::<Vec<_>>().join(", "));
+                (target, quote!(source: #interrupt_source))
+            }
+        };
+        let control_fns = InterruptControl::ALL.into_iter().filter_map(|control| {
+            let fn_ident = format_ident!("{}", control.fn_name());
+            let (doc, output) = match control {
+                InterruptControl::Pend => (
+                    format!(
+                        " Pends {target}, the task then runs as if its peripheral requested it"
+                    ),
+                    quote!(),
+                ),
+                InterruptControl::Unpend => {
+                    (format!(" Clears a pending request of {target}"), quote!())
+                }
+                InterruptControl::IsPending => {
+                    (format!(" Whether {target} is pending"), quote!(-> bool))
+                }
+                InterruptControl::Enable => (
+                    format!(" Unmasks {target}, its pending requests then run the task"),
+                    quote!(),
+                ),
+                InterruptControl::Disable => (
+                    format!(" Masks {target}, its requests stay pending until it is enabled again"),
+                    quote!(),
+                ),
+            };
+            // one body per interrupt, the function is only generated if the backend provides all
+            let bodies = binds
+                .iter()
+                .map(|irq| {
+                    let empty_body_fn = parse_quote! {
+                        #[doc = #doc]
+                        #[inline]
+                        pub fn #fn_ident() #output {
+                            // IMPLEMENTOR RESPONSIBILITY: act on the interrupt the task is bound to
+                        }
+                    };
+                    implementation.generate_interrupt_control_fn(
+                        app_args,
+                        app_info,
+                        irq,
+                        control,
+                        empty_body_fn,
+                    )
+                })
+                .collect::<Option<Vec<ImplItemFn>>>()?;
+            match bodies.as_slice() {
+                [control_fn] if !self.takes_interrupt_source() => Some(control_fn.clone()),
+                control_fns => {
+                    let mut control_fn = control_fns[0].clone();
+                    let bodies = control_fns.iter().map(|f| &f.block);
+                    control_fn.sig.inputs.push(parse_quote!(#input));
+                    // the interrupts of the other tasks bound to several interrupts are ignored
+                    control_fn.block = parse_quote!({
+                        #[allow(unreachable_patterns)]
+                        match source {
+                            #(#interrupt_source::#binds => #bodies)*
+                            _ => Default::default(),
+                        }
+                    });
+                    Some(control_fn)
+                }
+            }
+        });
+        let task_ty = self.name();
         Some(quote! {
-            #[allow(non_snake_case)]
-            #[unsafe(no_mangle)]
-            #(#task_attrs)*
-            fn #task_irq_handler() {
-                #task_dispatch_call
+            impl #task_ty {
+                #(#control_fns)*
             }
         })
     }
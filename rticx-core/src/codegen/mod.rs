@@ -1,15 +1,20 @@
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{ToTokens, format_ident, quote};
 use task_init::{generate_late_init_tasks_struct, generate_late_tasks_init_calls};
 
-use crate::CorePassBackend;
-use crate::analysis::Analysis;
+use crate::analysis::{Analysis, ReadonlyAccess};
 use crate::parser::ast::{RticTask, SharedResources};
-use crate::parser::{App, ast::IdleTask};
+use crate::parser::{App, SubApp, ast::IdleTask};
+use crate::rticx_cell::get_rticx_cell_mod;
 use crate::rticx_functions::{
-    INTERRUPT_FREE_FN, generate_task_traits_check_functions, get_interrupt_free_fn,
+    INTERRUPT_FREE_FN, generate_task_traits_check_functions, get_ceiling_lock_fn,
+    get_interrupt_free_fn,
 };
+use crate::rticx_srp::{self, get_hw_ceiling_fn, get_rticx_srp_mod};
+use crate::rticx_stats::{self, get_cycles_fn, get_rticx_stats_mod};
+use crate::rticx_trace::{self, get_rticx_trace_mod};
 use crate::rticx_traits::get_rticx_traits_mod;
+use crate::{AppArgs, CorePassBackend, InitPeripherals};
 
 pub mod hw_task;
 pub mod shared_resources;
@@ -44,9 +49,41 @@ impl<'a> CodeGen<'a> {
         let user_includes = &app.user_includes;
         let user_code = &app.other_code;
         let interrupt_free_fn = get_interrupt_free_fn(implementation);
-
-        // traits
-        let rticx_traits_mod = get_rticx_traits_mod();
+        let ceiling_lock_fn = get_ceiling_lock_fn(implementation, &app.args);
+
+        // traits, with the interrupts of the tasks bound to several interrupts and of the task
+        // instances of any core
+        let mut interrupt_sources: Vec<Ident> = Vec::new();
+        let tasks = app.sub_apps.iter().flat_map(|app| &app.tasks);
+        for task in tasks.filter(|task| task.takes_interrupt_source()) {
+            for irq in task.type_binds() {
+                if !interrupt_sources.contains(irq) {
+                    interrupt_sources.push(irq.clone());
+                }
+            }
+        }
+        let rticx_traits_mod = get_rticx_traits_mod(&interrupt_sources);
+
+        // cell of the statics
+        let rticx_cell_mod = get_rticx_cell_mod();
+
+        // SRP checks
+        let rticx_srp_mod = rticx_srp::ENABLED.then(get_rticx_srp_mod);
+
+        // runtime statistics
+        let rticx_stats_mod = rticx_stats::ENABLED.then(get_rticx_stats_mod);
+
+        // tracing hooks
+        let rticx_trace_mod = rticx_trace::ENABLED
+            .then(|| implementation.trace_export_path())
+            .flatten()
+            .map(|trace_path| {
+                get_rticx_trace_mod(
+                    &trace_path,
+                    app.args.tracer.as_ref(),
+                    &self.analysis.trace_ids,
+                )
+            });
 
         // sub_apps
         let sub_apps = self.generate_sub_apps();
@@ -63,9 +100,15 @@ impl<'a> CodeGen<'a> {
                 #(#user_includes)*
                 // ==================================== rticx traits ====================================
                 #rticx_traits_mod
+                #rticx_cell_mod
+                #rticx_srp_mod
+                #rticx_stats_mod
+                #rticx_trace_mod
                 // ================================== rticx functions ===================================
                 /// critical section function
                 #interrupt_free_fn
+                /// priority ceiling lock function
+                #ceiling_lock_fn
                 // ==================================== User code ======================================
                 #(#user_code)*
 
@@ -78,6 +121,14 @@ impl<'a> CodeGen<'a> {
         }
     }
 
+    /// The read-only resources read by `task`
+    fn readonly_access(&self, task: &RticTask) -> &[ReadonlyAccess] {
+        self.analysis
+            .readonly_access
+            .get(task.name())
+            .map_or(&[], Vec::as_slice)
+    }
+
     fn generate_sub_apps(&self) -> TokenStream2 {
         let implementation = self.implementation;
         let iter = self
@@ -93,10 +144,17 @@ impl<'a> CodeGen<'a> {
             let def_init_task = &app.init.body;
             let init_task = &app.init.ident;
             let late_init_struct = generate_late_init_tasks_struct(&analysis.late_resource_tasks);
+            let (def_init_context, init_context) = if app.init.context {
+                let (def, context) = generate_init_context(implementation, args, app);
+                (Some(def), Some(context))
+            } else {
+                (None, None)
+            };
 
             // idle
             let def_idle_task = app.idle.as_ref().map(|idle| {
-                let idle_task = idle.generate_task_def(app.shared.as_ref());
+                let idle_task =
+                    idle.generate_task_def(app.shared.as_ref(), self.readonly_access(idle));
                 Some(idle_task)
             });
 
@@ -104,16 +162,19 @@ impl<'a> CodeGen<'a> {
                 generate_idle_call(app.idle.as_ref(), implementation.populate_idle_loop());
 
             // tasks
-            let tasks_def = app
-                .tasks
-                .iter()
-                .map(|task| task.generate_task_def(app.shared.as_ref()));
+            let tasks_def = app.tasks.iter().map(|task| {
+                task.generate_task_def(app.shared.as_ref(), self.readonly_access(task))
+            });
             let task_init_calls = app.tasks.iter().filter_map(RticTask::task_init_call);
 
             let hw_tasks_binds = app
                 .tasks
                 .iter()
                 .filter_map(|t| t.generate_hw_task_to_irq_binding(implementation));
+            let hw_tasks_interrupt_control = app
+                .tasks
+                .iter()
+                .filter_map(|t| t.generate_interrupt_control(implementation, args, app));
 
             // shared resources
             let shared = app.shared.as_ref();
@@ -135,15 +196,15 @@ impl<'a> CodeGen<'a> {
                 let task_inits_ty = &s.ident;
                 let shared_resource_ty = shared
                     .map(|s| s.strct.ident.to_token_stream())
-                    .unwrap_or(quote!("()"));
+                    .unwrap_or(quote!(()));
                 quote! {
-                    let (__shared_resources, #tasks_initializer) : (#shared_resource_ty, #task_inits_ty) = #init_task(); // call to init and get shared and local resources inits
+                    let (__shared_resources, #tasks_initializer) : (#shared_resource_ty, #task_inits_ty) = #init_task(#init_context); // call to init and get shared and local resources inits
                     #(unsafe {#shared_resources_handle.write(__shared_resources);})* // init shared resources
                     #user_task_late_inits
                 }
             } else {
                 quote! {
-                    let shared_resources = #init_task();  // call to init and get shared resources init
+                    let shared_resources = #init_task(#init_context);  // call to init and get shared resources init
                     #(unsafe {#shared_resources_handle.write(shared_resources);})* // init shared resources
                 }
             };
@@ -157,13 +218,29 @@ impl<'a> CodeGen<'a> {
 
             let def_core_type = generate_core_type(app.core);
 
+            // runtime statistics, measured from the end of the initialization
+            let (def_core_stats, start_stats_window) = if rticx_stats::ENABLED {
+                let core_stats = rticx_stats::core_stats_ident(app.core);
+                (
+                    Some(generate_core_stats(implementation, app.core, &app.tasks)),
+                    Some(quote!(#core_stats.reset_window();)),
+                )
+            } else {
+                (None, None)
+            };
+
+            // SRP state of the core
+            let def_core_srp =
+                rticx_srp::ENABLED.then(|| generate_core_srp(implementation, args, app.core));
+
             let doc = format!(" # CORE {}", app.core);
             let entry_of = format!(" # Entry of CORE {}", app.core);
             quote! {
                 #[doc = #doc]
-                // define static mut shared resources
+                // define the static shared resources
                 #def_shared
                 // init task
+                #def_init_context
                 #def_init_task
                 // idle task
                 #def_idle_task
@@ -171,10 +248,15 @@ impl<'a> CodeGen<'a> {
                 #(#tasks_def)*
                 // bind hw tasks to interrupts
                 #(#hw_tasks_binds)*
+                #(#hw_tasks_interrupt_control)*
                 // proxies for accessing the shared resources
                 #resource_proxies
                 // unique type for the specific sub-app/core
                 #def_core_type
+                // SRP state of the core
+                #def_core_srp
+                // runtime statistics of the core
+                #def_core_stats
                 // Computed priority Masks
                 #priority_masks
                 /// Type representing tasks that need explicit user initialization
@@ -194,6 +276,8 @@ impl<'a> CodeGen<'a> {
 
                         // post initialization code
                         #post_init
+
+                        #start_stats_window
                     });
 
                     #call_idle_task
@@ -214,7 +298,7 @@ fn generate_idle_call(idle: Option<&IdleTask>, wfi: Option<TokenStream2>) -> Tok
             quote! {
                 unsafe {
                     #idle_instance_name.write(#idle_ty::init(()));
-                    #idle_instance_name.assume_init_mut().exec();
+                    #idle_instance_name.get_mut().exec();
                 }
 
             }
@@ -222,7 +306,7 @@ fn generate_idle_call(idle: Option<&IdleTask>, wfi: Option<TokenStream2>) -> Tok
             let idle_instance_name = &idle.name_uppercase();
             quote! {
                 unsafe {
-                    #idle_instance_name.assume_init_mut().exec();
+                    #idle_instance_name.get_mut().exec();
                 }
             }
         }
@@ -258,6 +342,112 @@ fn generate_core_type(core: u32) -> TokenStream2 {
     }
 }
 
+/// Generates the `init::Context` module of some core and the expression building it
+fn generate_init_context(
+    implementation: &dyn CorePassBackend,
+    app_args: &AppArgs,
+    app: &SubApp,
+) -> (TokenStream2, TokenStream2) {
+    let init_ident = &app.init.ident;
+    let core_ty = utils::core_type(app.core);
+    let device = implementation.init_device_peripherals(app_args, app);
+    let core = implementation.init_core_peripherals(app_args, app);
+
+    let device_field = device.as_ref().map(|InitPeripherals { ty, .. }| {
+        quote! {
+            /// Peripherals of the device
+            pub device: #ty,
+        }
+    });
+    let core_field = core.as_ref().map(|InitPeripherals { ty, .. }| {
+        quote! {
+            /// Peripherals of the processor core
+            pub core: #ty,
+        }
+    });
+    let device_init = device
+        .as_ref()
+        .map(|InitPeripherals { take, .. }| quote!(device: unsafe { #take },));
+    let core_init = core
+        .as_ref()
+        .map(|InitPeripherals { take, .. }| quote!(core: unsafe { #take },));
+
+    let doc = format!(" Context of `{init_ident}`, on core {}", app.core);
+    let def = quote! {
+        pub mod #init_ident {
+            use super::*;
+            #[doc = #doc]
+            pub struct Context {
+                #device_field
+                #core_field
+                /// Token of the core running `init`
+                pub core_token: #core_ty,
+            }
+        }
+    };
+    let context = quote! {
+        #init_ident::Context {
+            #device_init
+            #core_init
+            core_token: unsafe { #core_ty::new() },
+        }
+    };
+    (def, context)
+}
+
+/// Generates the hardware ceiling function and the tracked SRP state of some core
+fn generate_core_srp(
+    implementation: &dyn CorePassBackend,
+    app_args: &AppArgs,
+    core: u32,
+) -> TokenStream2 {
+    let core_srp = rticx_srp::core_srp_ident(core);
+    let hw_ceiling_fn_ident = rticx_srp::hw_ceiling_fn_ident(core);
+    let hw_ceiling_fn = get_hw_ceiling_fn(implementation, app_args, core);
+    let srp_mod = format_ident!("{}", rticx_srp::SRP_MOD);
+
+    quote! {
+        #hw_ceiling_fn
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #core_srp: #srp_mod::SystemCeiling = #srp_mod::SystemCeiling::new(#hw_ceiling_fn_ident);
+    }
+}
+
+/// Generates the cycle counter function and the statistics of some core, and the `stats()` API of
+/// the core type to read them
+fn generate_core_stats(
+    implementation: &dyn CorePassBackend,
+    core: u32,
+    tasks: &[RticTask],
+) -> TokenStream2 {
+    let core_ty = utils::core_type(core);
+    let core_stats = rticx_stats::core_stats_ident(core);
+    let cycles_fn_ident = rticx_stats::cycles_fn_ident(core);
+    let cycles_fn = get_cycles_fn(implementation, core);
+    let stats_mod = format_ident!("{}", rticx_stats::STATS_MOD);
+    let tasks_stats = tasks
+        .iter()
+        .map(|task| rticx_stats::task_stats_ident(&task.instance_name()));
+
+    quote! {
+        #cycles_fn
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #core_stats: #stats_mod::CoreStats =
+            #stats_mod::CoreStats::new(&[#(&#tasks_stats),*], #cycles_fn_ident);
+
+        impl #core_ty {
+            /// Runtime statistics of this core and its tasks
+            pub fn stats(&self) -> &'static #stats_mod::CoreStats {
+                &#core_stats
+            }
+        }
+    }
+}
+
 /// This will generate the `use path::to::pac as _` statement.
 /// This is usually needed as the PAC needs to be imported as it defines the vector table
 fn generate_use_pac_statement(app: &App) -> TokenStream2 {
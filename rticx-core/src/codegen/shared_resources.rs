@@ -1,8 +1,12 @@
+use heck::ToSnakeCase;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
+use syn::spanned::Spanned;
 
+use crate::analysis::ReadonlyAccess;
 use crate::parser::ast::{RticTask, SharedResources};
-use crate::rticx_functions::get_resource_proxy_lock_fn;
+use crate::rticx_cell;
+use crate::rticx_functions::{bound_check, get_resource_proxy_lock_fn};
 use crate::rticx_traits::MUTEX_TY;
 use crate::{AppArgs, CorePassBackend, SubApp};
 
@@ -10,11 +14,33 @@ impl SharedResources {
     pub fn generate_shared_resources_def(&self) -> TokenStream2 {
         let shared_struct = &self.strct;
         let resources_ty = &shared_struct.ident;
-        let static_instance_name = &self.name_uppercase();
+        let shared_static = rticx_cell::uninit_static(&self.name_uppercase(), resources_ty);
+        // the resources locked by tasks of different priorities are sent between them, and the
+        // read-only ones are shared by all the tasks
+        let bound_checks = self.resources.iter().filter_map(|r| {
+            let ident = &r.ident;
+            let (bound, message) = if r.readonly {
+                let message = format!(
+                    "read-only resource `{ident}` is read by reference from any task, its type `{{Self}}` must be `Sync`"
+                );
+                ("Sync", message)
+            } else if r.accessed_by.len() > 1 {
+                let priorities = r.accessed_by.iter().map(u16::to_string).collect::<Vec<_>>();
+                let message = format!(
+                    "shared resource `{ident}` is locked by tasks of priorities {}, its type `{{Self}}` must be `Send`",
+                    priorities.join(", ")
+                );
+                ("Send", message)
+            } else {
+                return None;
+            };
+            Some(bound_check(&r.ty, bound, r.ty.span(), &message))
+        });
 
         quote! {
-            static mut #static_instance_name: core::mem::MaybeUninit<#resources_ty> = core::mem::MaybeUninit::uninit();
+            #shared_static
             #shared_struct
+            #(#bound_checks)*
         }
     }
 
@@ -24,84 +50,143 @@ impl SharedResources {
         app_params: &AppArgs,
         app_info: &SubApp,
     ) -> TokenStream2 {
-        let static_mut_shared_resources = self.name_uppercase();
-        let proxies = self.resources.iter().map(|element| {
-            let element_name = &element.ident;
-            let element_ty = &element.ty;
-            let proxy_name = utils::get_proxy_name(element_name);
-            let mutex_ty = format_ident!("{}", MUTEX_TY);
-
-            // generate the implementation of lock function, using external implementation
-            let impl_lock_fn = get_resource_proxy_lock_fn(
-                implementor,
-                app_params,
-                app_info,
-                element,
-                &static_mut_shared_resources,
-            );
-
-            quote! {
-                // Resource proxy for `#element_name`
-                pub struct #proxy_name {
-                    #[doc(hidden)]
-                    task_priority: u16,
-                }
+        let shared_resources_static = self.name_uppercase();
+        let mutex_ty = format_ident!("{}", MUTEX_TY);
+        // a proxy for each resource, and for each of its parts locked on their own
+        let proxies = self
+            .resources
+            .iter()
+            .filter(|r| !r.readonly)
+            .flat_map(|element| {
+                std::iter::once((element, None))
+                    .chain(element.parts.iter().map(move |part| (element, Some(part))))
+            })
+            .map(|(element, part)| {
+                let (element_name, element_ty) = match part {
+                    Some(part) => (part.path.ident(), &part.ty),
+                    None => (element.ident.clone(), &element.ty),
+                };
+                let proxy_name = utils::get_proxy_name(&element_name);
+
+                // generate the implementation of lock function, using external implementation
+                let impl_lock_fn = get_resource_proxy_lock_fn(
+                    implementor,
+                    app_params,
+                    app_info,
+                    element,
+                    part,
+                    &shared_resources_static,
+                );
+
+                quote! {
+                    // Resource proxy for `#element_name`
+                    pub struct #proxy_name {
+                        #[doc(hidden)]
+                        task_priority: u16,
+                    }
 
-                impl #proxy_name {
-                    #[inline(always)]
-                    pub fn new(task_priority: u16) -> Self {
-                        Self { task_priority }
+                    impl #proxy_name {
+                        #[inline(always)]
+                        pub fn new(task_priority: u16) -> Self {
+                            Self { task_priority }
+                        }
                     }
-                }
 
-                impl #mutex_ty for #proxy_name {
-                    type ResourceType = #element_ty;
-                    #impl_lock_fn
+                    impl #mutex_ty for #proxy_name {
+                        type ResourceType = #element_ty;
+                        #impl_lock_fn
+                    }
                 }
-            }
-        });
+            });
         quote! {
             #(#proxies)*
         }
     }
+}
 
-    pub fn generate_shared_for_task(&self, task: &RticTask) -> TokenStream2 {
-        let task_resources_idents = &task.args.shared;
-        if task_resources_idents.is_empty() {
+impl RticTask {
+    /// Generates the `shared()` API of the task: proxies locking its shared resources, and
+    /// references to the read-only ones
+    pub fn generate_shared_access(
+        &self,
+        shared_resources: Option<&SharedResources>,
+        readonly: &[ReadonlyAccess],
+    ) -> TokenStream2 {
+        let task_resources_idents = &self.args.shared;
+        let task_parts = &self.args.shared_parts;
+        if task_resources_idents.is_empty() && task_parts.is_empty() && readonly.is_empty() {
             return quote!();
         }
 
         // generate `field_name : proxy_type` to use for populating struct body
-        let field_and_proxytype = task_resources_idents.iter().filter_map(|resource_ident| {
-            if let Some(resource) = self.get_field(resource_ident) {
-                let ident = &resource.ident;
-                let proxy_type = utils::get_proxy_name(ident);
-                Some(quote! {#ident: #proxy_type})
-            } else {
-                None
+        let part_idents = task_parts
+            .iter()
+            .filter(|path| {
+                shared_resources.is_some_and(|shared| shared.get_field(&path.resource).is_some())
+            })
+            .map(|path| path.ident());
+        let field_and_proxytype = task_resources_idents
+            .iter()
+            .filter_map(|resource_ident| {
+                Some(shared_resources?.get_field(resource_ident)?.ident.clone())
+            })
+            .chain(part_idents)
+            .map(|ident| {
+                let proxy_type = utils::get_proxy_name(&ident);
+                quote! {#ident: #proxy_type}
+            });
+        let field_and_proxytype2 = field_and_proxytype.clone();
+
+        // read-only resources are initialized before any task runs and never written again
+        let readonly_fields = readonly.iter().map(|resource| {
+            let ident = &resource.ident;
+            let ty = &resource.ty;
+            quote! {#ident: &'static #ty}
+        });
+        let readonly_inits = readonly.iter().map(|resource| {
+            let ident = &resource.ident;
+            let shared_static = &resource.shared_static;
+            quote! {
+                #ident: unsafe { &(*#shared_static.as_mut_ptr()).#ident }
             }
         });
-        let field_and_proxytype2 = field_and_proxytype.clone();
 
         // TODO: replace `shared(&self)` with individual `shared_resource_name(&self) -> proxy_type`
         // to avoid constructing the whole shared structure only for one resource access.
 
-        let task_ty = task.name();
-        let task_prio = task.args.priority;
+        let task_ty = self.name();
         let task_shared_resources_struct =
-            format_ident!("__{}_shared_resources", task.name_snakecase());
+            format_ident!("__{}_shared_resources", task_ty.to_string().to_snake_case());
+        // the proxies lock from the threshold the task runs at, that of the running instance for
+        // the task types declaring several
+        let new_shared_resources = match self.instance {
+            Some(_) => {
+                let priority_static = self.instance_priority_static();
+                quote! {
+                    let task_priority = unsafe { *#priority_static.as_mut_ptr() };
+                    #task_shared_resources_struct::new(task_priority)
+                }
+            }
+            None => {
+                let task_prio = self.args.threshold;
+                quote! {
+                    const TASK_PRIORITY: u16 = #task_prio;
+                    #task_shared_resources_struct::new(TASK_PRIORITY)
+                }
+            }
+        };
         quote! {
             // Shared resources access through shared() API for `#task_ty`
             impl #task_ty {
                 pub fn shared(&self) -> #task_shared_resources_struct {
-                    const TASK_PRIORITY: u16 = #task_prio;
-                    #task_shared_resources_struct::new(TASK_PRIORITY)
+                    #new_shared_resources
                 }
             }
 
             // internal struct for `#task_ty` resource proxies
             pub struct #task_shared_resources_struct {
                 #(pub #field_and_proxytype ,)*
+                #(pub #readonly_fields ,)*
             }
 
             impl #task_shared_resources_struct {
@@ -109,6 +194,7 @@ impl SharedResources {
                 pub fn new(priority: u16) -> Self {
                     Self {
                         #(#field_and_proxytype2::new(priority) ,)*
+                        #(#readonly_inits ,)*
                     }
                 }
             }
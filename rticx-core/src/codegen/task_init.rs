@@ -10,7 +10,7 @@ pub fn generate_late_init_tasks_struct(tasks: &[LateResourceTask]) -> Option<Ite
     }
     let struct_fields = tasks.iter().map(|t| {
         let field_name = t.name_snakecase();
-        let field_ty = &t.task_name;
+        let field_ty = &t.task_ty;
         quote! {pub #field_name: #field_ty,}
     });
     Some(parse_quote! {
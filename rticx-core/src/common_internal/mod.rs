@@ -1,4 +1,8 @@
 //! Provide RTIC internal definitions used both by `parse` and `codegen`
 
+pub mod rticx_cell;
 pub mod rticx_functions;
+pub mod rticx_srp;
+pub mod rticx_stats;
+pub mod rticx_trace;
 pub mod rticx_traits;
@@ -0,0 +1,134 @@
+//! Cell holding the statics of the application.
+//!
+//! The tasks, the `#[shared]` structs and the queues of the compilation passes are placed in
+//! `static` items of type `rticx_cell::RacyCell`, instead of `static mut` items referenced directly
+//! by the generated code. The cell documents the invariants the generated code upholds, and tracks
+//! in debug builds whether it was initialized to catch uses before `#[init]` returns.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, format_ident, quote};
+use syn::Ident;
+
+pub const CELL_MOD: &str = "rticx_cell";
+
+/// Definition of the `static` named `ident` holding a `ty`, written later by the generated code
+pub fn uninit_static(ident: &Ident, ty: &impl ToTokens) -> TokenStream2 {
+    let cell_mod = format_ident!("{CELL_MOD}");
+    let name = ident.to_string();
+    quote! {
+        static #ident: #cell_mod::RacyCell<#ty> = #cell_mod::RacyCell::uninit(#name);
+    }
+}
+
+/// Definition of the `static` named `ident` holding a `ty` initialized with the constant `value`
+pub fn static_with_value(ident: &Ident, ty: &impl ToTokens, value: &impl ToTokens) -> TokenStream2 {
+    let cell_mod = format_ident!("{CELL_MOD}");
+    let name = ident.to_string();
+    quote! {
+        static #ident: #cell_mod::RacyCell<#ty> = #cell_mod::RacyCell::new(#name, #value);
+    }
+}
+
+pub(crate) fn get_rticx_cell_mod() -> TokenStream2 {
+    let cell_mod = format_ident!("{CELL_MOD}");
+    quote! {
+        /// Module defining the cell holding the statics of the application
+        #[doc(hidden)]
+        pub mod #cell_mod {
+            use core::cell::UnsafeCell;
+            use core::mem::MaybeUninit;
+            #[cfg(debug_assertions)]
+            use core::sync::atomic::{AtomicBool, Ordering};
+
+            /// A static of the application: a task, a `#[shared]` struct or a queue.
+            ///
+            /// The generated code upholds the invariants the compiler can't check:
+            /// * a cell created with [RacyCell::uninit] is written once, by the entry of its core
+            ///   with interrupts disabled, before the tasks using it can run,
+            /// * mutable references to the value, or to a field of a `#[shared]` struct, are only
+            ///   created by the task owning the value, or under the lock of a resource,
+            /// * shared references are only created to `#[readonly]` resources, which are never
+            ///   written after `#[init]`, and to values synchronizing their accesses themselves,
+            ///   like lock-free queues.
+            ///
+            /// In debug builds, reading a cell before it is written panics.
+            pub struct RacyCell<T> {
+                value: UnsafeCell<MaybeUninit<T>>,
+                #[cfg(debug_assertions)]
+                name: &'static str,
+                #[cfg(debug_assertions)]
+                initialized: AtomicBool,
+            }
+
+            // SAFETY: accesses follow the invariants above, and the values shared between priorities
+            // or cores are checked to be `Send` or `Sync`
+            unsafe impl<T> Sync for RacyCell<T> {}
+
+            impl<T> RacyCell<T> {
+                /// Cell written later with [RacyCell::write]
+                pub const fn uninit(name: &'static str) -> Self {
+                    let _ = name;
+                    Self {
+                        value: UnsafeCell::new(MaybeUninit::uninit()),
+                        #[cfg(debug_assertions)]
+                        name,
+                        #[cfg(debug_assertions)]
+                        initialized: AtomicBool::new(false),
+                    }
+                }
+
+                /// Cell holding `value` from the start
+                pub const fn new(name: &'static str, value: T) -> Self {
+                    let _ = name;
+                    Self {
+                        value: UnsafeCell::new(MaybeUninit::new(value)),
+                        #[cfg(debug_assertions)]
+                        name,
+                        #[cfg(debug_assertions)]
+                        initialized: AtomicBool::new(true),
+                    }
+                }
+
+                /// Initialize the cell, without dropping a previous value
+                ///
+                /// # Safety
+                /// No reference to the value may be alive.
+                #[inline(always)]
+                pub unsafe fn write(&self, value: T) {
+                    unsafe { (*self.value.get()).write(value) };
+                    #[cfg(debug_assertions)]
+                    self.initialized.store(true, Ordering::Release);
+                }
+
+                /// Pointer to the value, to access it, or one of its fields, without a reference
+                /// to the whole value
+                #[inline(always)]
+                pub fn as_mut_ptr(&self) -> *mut T {
+                    #[cfg(debug_assertions)]
+                    assert!(
+                        self.initialized.load(Ordering::Acquire),
+                        "`{}` was used before its initialization",
+                        self.name
+                    );
+                    self.value.get().cast()
+                }
+
+                /// # Safety
+                /// No mutable reference to the value may be alive while the returned one is.
+                #[inline(always)]
+                pub unsafe fn get(&self) -> &T {
+                    unsafe { &*self.as_mut_ptr() }
+                }
+
+                /// # Safety
+                /// The value must not be accessed through another reference while the returned one
+                /// is alive.
+                #[inline(always)]
+                #[allow(clippy::mut_from_ref)]
+                pub unsafe fn get_mut(&self) -> &mut T {
+                    unsafe { &mut *self.as_mut_ptr() }
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use heck::ToSnakeCase;
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{ImplItemFn, ItemFn, parse_quote};
 
 use crate::{
     Analysis, AppArgs, CorePassBackend, SubApp,
-    parser::ast::{RticTask, SharedElement},
+    parser::ast::{RticTask, SharedElement, SharedPart},
+    rticx_srp, rticx_trace,
 };
 
 pub const INTERRUPT_FREE_FN: &str = "__rticx_interrupt_free";
@@ -24,29 +25,93 @@ pub(crate) fn get_interrupt_free_fn(implementor: &dyn CorePassBackend) -> ItemFn
     // TODO: we should validate if the implementor has kept the correct function signature by comparing it to the initial signature
 }
 
+pub const CEILING_LOCK_FN: &str = "__rticx_ceiling_lock";
+
+pub(crate) fn get_ceiling_lock_fn(implementor: &dyn CorePassBackend, app_args: &AppArgs) -> ItemFn {
+    let fn_ident = format_ident!("{CEILING_LOCK_FN}");
+    let ceiling_lock_fn = parse_quote! {
+        #[inline]
+        pub fn #fn_ident<F, R>(ceiling: u16, f: F) -> R
+        where F: FnOnce() -> R,
+        {
+           // IMPLEMENTOR RESPONSIBILITY: run `f` with the system ceiling raised to at least `ceiling`
+        }
+    };
+    implementor.generate_ceiling_lock_fn(app_args, ceiling_lock_fn)
+}
+
+/// Runs `dispatch_task_call`, the job of a task of priority `task_prio`, with the system ceiling
+/// raised to the preemption `threshold` of the task through the ceiling lock function. Unchanged
+/// when the task has no threshold above its priority.
+pub fn raise_to_threshold(
+    task_prio: u16,
+    threshold: u16,
+    dispatch_task_call: TokenStream,
+) -> TokenStream {
+    if threshold <= task_prio {
+        return dispatch_task_call;
+    }
+    let ceiling_lock = format_ident!("{CEILING_LOCK_FN}");
+    quote! {
+        #ceiling_lock(#threshold, || { #dispatch_task_call });
+    }
+}
+
 pub(crate) fn get_resource_proxy_lock_fn(
     implementor: &dyn CorePassBackend,
     app_params: &AppArgs,
     app_info: &SubApp,
     resource: &SharedElement,
-    static_mut_shared_resources: &syn::Ident,
+    part: Option<&SharedPart>,
+    shared_resources_static: &syn::Ident,
 ) -> ImplItemFn {
-    let ceiling = resource.priority;
     let resource_ident = &resource.ident;
+    // a part of the resource is locked with its own ceiling, and traced as the whole resource
+    let (ceiling, place, name) = match part {
+        Some(part) => (
+            part.priority,
+            part.path.to_token_stream(),
+            part.path.to_string(),
+        ),
+        None => (
+            resource.priority,
+            resource_ident.to_token_stream(),
+            resource_ident.to_string(),
+        ),
+    };
     let lock_fn = parse_quote! {
         fn lock<R>(&mut self, f: impl FnOnce(&mut Self::ResourceType) -> R) -> R {
             // `self` refers to the resource proxy struct
 
             const CEILING: u16 = #ceiling; // resource priority ceiling
             let task_priority = self.task_priority; // running task priority
-            let resource_ptr = unsafe { // get a mut pointer to the resource
-                &mut #static_mut_shared_resources.assume_init_mut().#resource_ident
-            } as *mut _;
+            let resource_ptr = unsafe { // get a mut pointer to the resource, without borrowing the read-only ones
+                core::ptr::addr_of_mut!((*#shared_resources_static.as_mut_ptr()).#place)
+            };
             // IMPLEMENTOR RESPONSIBILITY: continue lock implementation here
             // call for example <distro>::export::lock(resource_ptr, task_priority, ...., f)
         }
     };
-    implementor.generate_resource_proxy_lock_impl(app_params, app_info, lock_fn)
+    let mut lock_fn = implementor.generate_resource_proxy_lock_impl(app_params, app_info, lock_fn);
+    if rticx_srp::ENABLED {
+        // check the ceiling around the lock and inside the critical section
+        let lock_check = rticx_srp::lock_check_stmts(app_info.core, &name, ceiling);
+        let stmts = &lock_fn.block.stmts;
+        lock_fn.block = parse_quote!({
+            #lock_check
+            #(#stmts)*
+        });
+    }
+    if rticx_trace::ENABLED {
+        // trace the critical section, inside the raised ceiling
+        let lock_hook = rticx_trace::lock_hook_stmt(app_info.core, resource_ident);
+        let stmts = &lock_fn.block.stmts;
+        lock_fn.block = parse_quote!({
+            #lock_hook
+            #(#stmts)*
+        });
+    }
+    lock_fn
     // TODO: we should validate if the implementor has kept the correct function signature by comparing it to the initial signature
 }
 
@@ -82,3 +147,19 @@ pub(crate) fn generate_task_traits_check_functions(analysis: &Analysis) -> Token
         }
     }
 }
+
+/// Compile-time check that `ty` implements the auto trait `bound` (`Send` or `Sync`), reported at
+/// `span` with `message`. `{Self}` in the message is replaced by the type.
+pub fn bound_check(ty: &impl ToTokens, bound: &str, span: Span, message: &str) -> TokenStream {
+    let bound = format_ident!("{bound}");
+    let label = format!("not `{bound}`");
+    quote_spanned! {span=>
+        const _: fn() = || {
+            #[diagnostic::on_unimplemented(message = #message, label = #label)]
+            trait BoundCheck {}
+            impl<T: #bound + ?Sized> BoundCheck for T {}
+            fn bound_check<T: BoundCheck + ?Sized>() {}
+            bound_check::<#ty>();
+        };
+    }
+}
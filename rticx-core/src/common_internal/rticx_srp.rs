@@ -0,0 +1,266 @@
+//! SRP invariant checks, generated when the `srp-assertions` feature is enabled.
+//!
+//! Every core tracks the priority of its running task and its system ceiling (the highest ceiling
+//! of the locked resources). The interrupt handlers, the `exec` functions of the tasks called by
+//! other tasks and the resource proxies assert that
+//! * a task only preempts when its priority is above both the running priority and the system
+//!   ceiling, and tasks called by other tasks run at their own priority, then at their threshold,
+//! * a proxy is only used at the priority of its task and locks a ceiling at least as high,
+//! * the system ceiling is restored when a lock is released and when a task finishes.
+//!
+//! When the backend can read the ceiling it sets in hardware
+//! (see [CorePassBackend::generate_hw_ceiling_fn]), the checks also verify that it is raised to
+//! the ceiling of every lock, and restored after every lock and every task handler.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemFn, parse_quote};
+
+use crate::{AppArgs, CorePassBackend, parser::ast::TaskArgs};
+
+/// Whether the SRP checks are generated
+pub const ENABLED: bool = cfg!(feature = "srp-assertions");
+
+pub const SRP_MOD: &str = "rticx_srp";
+
+/// Static holding the SRP state of `core`, of type `rticx_srp::SystemCeiling`
+pub(crate) fn core_srp_ident(core: u32) -> Ident {
+    format_ident!("__rticx_srp_core{core}")
+}
+
+/// Function reading the hardware ceiling of `core`
+pub(crate) fn hw_ceiling_fn_ident(core: u32) -> Ident {
+    format_ident!("__rticx_srp_hw_ceiling_core{core}")
+}
+
+pub(crate) fn get_hw_ceiling_fn(
+    implementor: &dyn CorePassBackend,
+    app_args: &AppArgs,
+    core: u32,
+) -> ItemFn {
+    let fn_ident = hw_ceiling_fn_ident(core);
+    let hw_ceiling_fn = parse_quote! {
+        #[doc(hidden)]
+        #[inline]
+        pub fn #fn_ident() -> Option<u16> {
+           // IMPLEMENTOR RESPONSIBILITY: read the logical priority masked by the locks of this core
+        }
+    };
+    implementor.generate_hw_ceiling_fn(app_args, core, hw_ceiling_fn)
+}
+
+/// Statement checking a job of `task` of some `core` until the end of the enclosing scope. Bound
+/// tasks preempt the running task, other tasks are called by a task of their own priority. The job
+/// then runs at the threshold of the task.
+pub(crate) fn task_check_stmt(
+    core: u32,
+    task: &Ident,
+    args: &TaskArgs,
+    bound: bool,
+) -> TokenStream2 {
+    let core_srp = core_srp_ident(core);
+    let task_name = task.to_string();
+    let TaskArgs {
+        priority,
+        threshold,
+        ..
+    } = args;
+    quote! {
+        let __rticx_srp_task = #core_srp.task(#task_name, #priority, #threshold, #bound);
+    }
+}
+
+/// Statement checking that the interrupt handler of `task` restores the hardware ceiling
+pub(crate) fn handler_check_stmt(core: u32, task: &Ident) -> TokenStream2 {
+    let core_srp = core_srp_ident(core);
+    let task_name = task.to_string();
+    quote! {
+        let __rticx_srp_handler = #core_srp.handler(#task_name);
+    }
+}
+
+/// Statements checking a `lock` of the proxy of `resource`, wrapping its closure `f` to check the
+/// raised ceiling inside the critical section
+pub(crate) fn lock_check_stmts(core: u32, resource_name: &str, ceiling: u16) -> TokenStream2 {
+    let core_srp = core_srp_ident(core);
+    quote! {
+        let __rticx_srp_lock = #core_srp.lock(#resource_name, self.task_priority, #ceiling);
+        let f = |resource: &mut Self::ResourceType| {
+            let __rticx_srp_raised = __rticx_srp_lock.raise();
+            f(resource)
+        };
+    }
+}
+
+pub(crate) fn get_rticx_srp_mod() -> TokenStream2 {
+    let srp_mod = format_ident!("{SRP_MOD}");
+    quote! {
+        /// Module defining the SRP checks of the locks and tasks
+        #[doc(hidden)]
+        pub mod #srp_mod {
+            use core::sync::atomic::{AtomicU16, Ordering};
+
+            /// SRP state of a core. Plain loads and stores: the jobs of a core are nested, and
+            /// every job restores the state it changed before finishing.
+            pub struct SystemCeiling {
+                running: AtomicU16,
+                ceiling: AtomicU16,
+                hw_ceiling: fn() -> Option<u16>,
+            }
+
+            impl SystemCeiling {
+                pub const fn new(hw_ceiling: fn() -> Option<u16>) -> Self {
+                    Self {
+                        running: AtomicU16::new(0),
+                        ceiling: AtomicU16::new(0),
+                        hw_ceiling,
+                    }
+                }
+
+                pub fn task(&'static self, task: &'static str, priority: u16, threshold: u16, preempts: bool) -> Task {
+                    let running = self.running.load(Ordering::Relaxed);
+                    let ceiling = self.ceiling.load(Ordering::Relaxed);
+                    assert!(
+                        priority > ceiling,
+                        "SRP violation: task `{task}` of priority {priority} started with the system ceiling at {ceiling}"
+                    );
+                    if preempts {
+                        assert!(
+                            priority > running,
+                            "SRP violation: task `{task}` of priority {priority} preempted a task of priority {running}"
+                        );
+                    } else {
+                        assert!(
+                            priority == running,
+                            "SRP violation: task `{task}` of priority {priority} was run at priority {running}"
+                        );
+                    }
+                    if let Some(hw_ceiling) = (self.hw_ceiling)().filter(|_| threshold > priority) {
+                        assert!(
+                            hw_ceiling >= threshold,
+                            "SRP violation: task `{task}` of threshold {threshold} runs with the hardware ceiling at {hw_ceiling}"
+                        );
+                    }
+                    self.running.store(threshold, Ordering::Relaxed);
+                    Task { core: self, task, running, ceiling }
+                }
+
+                pub fn handler(&'static self, task: &'static str) -> Handler {
+                    Handler { core: self, task, hw_ceiling: (self.hw_ceiling)() }
+                }
+
+                pub fn lock(&'static self, resource: &'static str, task_priority: u16, ceiling: u16) -> Lock {
+                    let running = self.running.load(Ordering::Relaxed);
+                    assert!(
+                        task_priority == running,
+                        "SRP violation: resource `{resource}` locked through the proxy of a task of priority {task_priority} at priority {running}"
+                    );
+                    assert!(
+                        ceiling >= task_priority,
+                        "SRP violation: resource `{resource}` has a ceiling of {ceiling}, below the priority {task_priority} of the task locking it"
+                    );
+                    Lock { core: self, resource, ceiling, hw_ceiling: (self.hw_ceiling)() }
+                }
+            }
+
+            /// A running job of a task, checked when dropped
+            pub struct Task {
+                core: &'static SystemCeiling,
+                task: &'static str,
+                running: u16,
+                ceiling: u16,
+            }
+
+            impl Drop for Task {
+                fn drop(&mut self) {
+                    let ceiling = self.core.ceiling.load(Ordering::Relaxed);
+                    assert!(
+                        ceiling == self.ceiling,
+                        "SRP violation: task `{}` finished with the system ceiling at {ceiling} instead of {}",
+                        self.task,
+                        self.ceiling
+                    );
+                    self.core.running.store(self.running, Ordering::Relaxed);
+                }
+            }
+
+            /// An interrupt handler, checking when dropped that the hardware ceiling was restored
+            pub struct Handler {
+                core: &'static SystemCeiling,
+                task: &'static str,
+                hw_ceiling: Option<u16>,
+            }
+
+            impl Drop for Handler {
+                fn drop(&mut self) {
+                    let hw_ceiling = (self.core.hw_ceiling)();
+                    assert!(
+                        hw_ceiling == self.hw_ceiling,
+                        "SRP violation: the handler of task `{}` left the hardware ceiling at {hw_ceiling:?} instead of {:?}",
+                        self.task,
+                        self.hw_ceiling
+                    );
+                }
+            }
+
+            /// A lock of a resource, checking when dropped that the hardware ceiling was restored
+            pub struct Lock {
+                core: &'static SystemCeiling,
+                resource: &'static str,
+                ceiling: u16,
+                hw_ceiling: Option<u16>,
+            }
+
+            impl Lock {
+                /// Enter the critical section, the system ceiling is lowered when the result is dropped
+                pub fn raise(&self) -> Raised<'_> {
+                    if let Some(hw_ceiling) = (self.core.hw_ceiling)() {
+                        assert!(
+                            hw_ceiling >= self.ceiling,
+                            "SRP violation: resource `{}` locked with the hardware ceiling at {hw_ceiling} instead of {}",
+                            self.resource,
+                            self.ceiling
+                        );
+                    }
+                    let previous = self.core.ceiling.load(Ordering::Relaxed);
+                    let ceiling = previous.max(self.ceiling);
+                    self.core.ceiling.store(ceiling, Ordering::Relaxed);
+                    Raised { lock: self, previous, ceiling }
+                }
+            }
+
+            impl Drop for Lock {
+                fn drop(&mut self) {
+                    let hw_ceiling = (self.core.hw_ceiling)();
+                    assert!(
+                        hw_ceiling == self.hw_ceiling,
+                        "SRP violation: the lock of resource `{}` left the hardware ceiling at {hw_ceiling:?} instead of {:?}",
+                        self.resource,
+                        self.hw_ceiling
+                    );
+                }
+            }
+
+            /// The critical section of a lock
+            pub struct Raised<'a> {
+                lock: &'a Lock,
+                previous: u16,
+                ceiling: u16,
+            }
+
+            impl Drop for Raised<'_> {
+                fn drop(&mut self) {
+                    let core = self.lock.core;
+                    let ceiling = core.ceiling.load(Ordering::Relaxed);
+                    assert!(
+                        ceiling == self.ceiling,
+                        "SRP violation: the critical section of resource `{}` ended with the system ceiling at {ceiling} instead of {}",
+                        self.lock.resource,
+                        self.ceiling
+                    );
+                    core.ceiling.store(self.previous, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Runtime statistics of the tasks and cores, generated when the `stats` feature is enabled.
+//!
+//! Every core counts the activations and the longest execution of its tasks, and the time it spends
+//! running tasks, using a cycle counter provided by the backend
+//! (see [CorePassBackend::generate_cycles_fn]). The statistics are read through the generated
+//! `Task::stats()` and `current_core().stats()` APIs.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemFn, parse_quote};
+
+use crate::CorePassBackend;
+
+/// Whether the statistics are generated, compilation passes use it to report their own events
+pub const ENABLED: bool = cfg!(feature = "stats");
+
+pub const STATS_MOD: &str = "rticx_stats";
+
+/// Static holding the statistics of `task`, of type `rticx_stats::TaskStats`
+pub fn task_stats_ident(task: &Ident) -> Ident {
+    format_ident!("__rticx_stats_{task}")
+}
+
+/// Static holding the statistics of `core`, of type `rticx_stats::CoreStats`
+pub(crate) fn core_stats_ident(core: u32) -> Ident {
+    format_ident!("__rticx_stats_core{core}")
+}
+
+/// Function reading the cycle counter of `core`
+pub(crate) fn cycles_fn_ident(core: u32) -> Ident {
+    format_ident!("__rticx_stats_cycles_core{core}")
+}
+
+pub(crate) fn get_cycles_fn(implementor: &dyn CorePassBackend, core: u32) -> ItemFn {
+    let fn_ident = cycles_fn_ident(core);
+    let cycles_fn = parse_quote! {
+        #[doc(hidden)]
+        #[inline]
+        pub fn #fn_ident() -> u32 {
+           // IMPLEMENTOR RESPONSIBILITY: read a free running 32-bit cycle counter of this core
+        }
+    };
+    implementor.generate_cycles_fn(core, cycles_fn)
+}
+
+pub(crate) fn get_rticx_stats_mod() -> TokenStream2 {
+    let stats_mod = format_ident!("{STATS_MOD}");
+    quote! {
+        /// Module defining the runtime statistics of the tasks and cores
+        pub mod #stats_mod {
+            use core::sync::atomic::{AtomicU32, Ordering};
+
+            // plain loads and stores: the jobs of a core are nested, so an update preempted by
+            // another job is never interleaved with it, even on targets without compare-and-swap
+            fn add(counter: &AtomicU32, value: u32) {
+                counter.store(counter.load(Ordering::Relaxed).wrapping_add(value), Ordering::Relaxed);
+            }
+
+            /// Statistics of a task
+            pub struct TaskStats {
+                name: &'static str,
+                activations: AtomicU32,
+                max_cycles: AtomicU32,
+                spawn_failures: AtomicU32,
+            }
+
+            impl TaskStats {
+                pub const fn new(name: &'static str) -> Self {
+                    Self {
+                        name,
+                        activations: AtomicU32::new(0),
+                        max_cycles: AtomicU32::new(0),
+                        spawn_failures: AtomicU32::new(0),
+                    }
+                }
+
+                /// Name of the task
+                pub fn name(&self) -> &'static str {
+                    self.name
+                }
+
+                /// Number of finished jobs
+                pub fn activations(&self) -> u32 {
+                    self.activations.load(Ordering::Relaxed)
+                }
+
+                /// Longest job in cycles, preemptions included
+                pub fn max_cycles(&self) -> u32 {
+                    self.max_cycles.load(Ordering::Relaxed)
+                }
+
+                /// Number of `spawn` calls that returned an error because the task could not be queued
+                pub fn spawn_failures(&self) -> u32 {
+                    self.spawn_failures.load(Ordering::Relaxed)
+                }
+
+                #[doc(hidden)]
+                pub fn spawn_failed(&self) {
+                    add(&self.spawn_failures, 1);
+                }
+            }
+
+            /// Statistics of a core, measured over a window starting at the end of `#[init]` or at
+            /// the last [CoreStats::reset_window]
+            pub struct CoreStats {
+                tasks: &'static [&'static TaskStats],
+                now: fn() -> u32,
+                nesting: AtomicU32,
+                busy_start: AtomicU32,
+                busy: AtomicU32,
+                window_start: AtomicU32,
+            }
+
+            impl CoreStats {
+                pub const fn new(tasks: &'static [&'static TaskStats], now: fn() -> u32) -> Self {
+                    Self {
+                        tasks,
+                        now,
+                        nesting: AtomicU32::new(0),
+                        busy_start: AtomicU32::new(0),
+                        busy: AtomicU32::new(0),
+                        window_start: AtomicU32::new(0),
+                    }
+                }
+
+                /// Statistics of the tasks of the core
+                pub fn tasks(&self) -> &'static [&'static TaskStats] {
+                    self.tasks
+                }
+
+                /// Length of the window in cycles. The window must stay shorter than `2^32` cycles.
+                pub fn window_cycles(&self) -> u32 {
+                    (self.now)().wrapping_sub(self.window_start.load(Ordering::Relaxed))
+                }
+
+                /// Cycles spent running tasks in the window, the running jobs are not counted yet
+                pub fn busy_cycles(&self) -> u32 {
+                    self.busy.load(Ordering::Relaxed)
+                }
+
+                /// Cycles spent in the idle loop in the window
+                pub fn idle_cycles(&self) -> u32 {
+                    self.window_cycles().saturating_sub(self.busy_cycles())
+                }
+
+                /// Share of the window spent running tasks, in per mille
+                pub fn cpu_load_permille(&self) -> u32 {
+                    let window = self.window_cycles().max(1) as u64;
+                    (self.busy_cycles() as u64 * 1000 / window).min(1000) as u32
+                }
+
+                /// Start a new measurement window
+                pub fn reset_window(&self) {
+                    let now = (self.now)();
+                    self.window_start.store(now, Ordering::Relaxed);
+                    self.busy.store(0, Ordering::Relaxed);
+                    // the running jobs are counted from now on
+                    self.busy_start.store(now, Ordering::Relaxed);
+                }
+
+                #[doc(hidden)]
+                pub fn job(&'static self, task: &'static TaskStats) -> Job {
+                    let start = (self.now)();
+                    let nesting = self.nesting.load(Ordering::Relaxed);
+                    if nesting == 0 {
+                        self.busy_start.store(start, Ordering::Relaxed);
+                    }
+                    self.nesting.store(nesting + 1, Ordering::Relaxed);
+                    Job { core: self, task, start }
+                }
+            }
+
+            /// A running job, finished when dropped so early returns from `exec` are accounted for
+            #[doc(hidden)]
+            pub struct Job {
+                core: &'static CoreStats,
+                task: &'static TaskStats,
+                start: u32,
+            }
+
+            impl Drop for Job {
+                fn drop(&mut self) {
+                    let end = (self.core.now)();
+                    let cycles = end.wrapping_sub(self.start);
+                    let task = self.task;
+                    add(&task.activations, 1);
+                    task.max_cycles.store(task.max_cycles().max(cycles), Ordering::Relaxed);
+
+                    let nesting = self.core.nesting.load(Ordering::Relaxed) - 1;
+                    if nesting == 0 {
+                        // the busy time is stored before leaving the outermost job, a job preempting
+                        // this update is counted as nested
+                        let busy_start = self.core.busy_start.load(Ordering::Relaxed);
+                        add(&self.core.busy, end.wrapping_sub(busy_start));
+                    }
+                    self.core.nesting.store(nesting, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
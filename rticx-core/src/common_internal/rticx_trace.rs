@@ -0,0 +1,184 @@
+//! Tracing hooks, generated when the `trace` feature is enabled.
+//!
+//! The hardware task handlers, the `exec` functions of the tasks called by other tasks (e.g. by
+//! the dispatchers of the software pass), the resource proxies and the spawn functions call the
+//! hooks of the generated `rticx_trace` module with the identifiers assigned by the analysis
+//! ([TraceIds](crate::analysis::TraceIds)). The hooks forward the events to the `Tracer` of the
+//! application, given by its `tracer` argument.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Ident, Path};
+
+use crate::analysis::{TraceId, TraceIds};
+
+/// Whether the tracing hooks are generated, compilation passes use it to report their own events
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+pub const TRACE_MOD: &str = "rticx_trace";
+
+/// Generated alias of the tracer type of the application, declared in the application module so
+/// the `tracer` argument is resolved in the scope of the user code
+const TRACER_ALIAS: &str = "__rticx_Tracer";
+
+/// Path to the constant holding the trace identifier of `task`, of type `u16`
+pub fn task_id_path(task: &Ident) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    quote!(#trace_mod::task_ids::#task)
+}
+
+/// Path to the constant holding the trace identifier of the shared resource `resource` of `core`
+pub(crate) fn resource_id_path(core: u32, resource: &Ident) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    let core_mod = format_ident!("core{core}");
+    quote!(#trace_mod::resource_ids::#core_mod::#resource)
+}
+
+/// Call of the `spawn` hook for `task`
+pub fn spawn_hook_call(task: &Ident) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    let task_id = task_id_path(task);
+    quote!(#trace_mod::spawn(#task_id);)
+}
+
+/// Statement tracing a job of `task` until the end of the enclosing scope
+pub(crate) fn task_hook_stmt(task: &Ident) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    let task_id = task_id_path(task);
+    quote! {
+        let __rticx_trace_job = #trace_mod::task_enter(#task_id);
+    }
+}
+
+/// Statement wrapping the closure `f` of a `lock` function to trace the critical section
+pub(crate) fn lock_hook_stmt(core: u32, resource: &Ident) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    let resource_id = resource_id_path(core, resource);
+    quote! {
+        let f = move |resource: &mut Self::ResourceType| {
+            #trace_mod::lock_acquire(#resource_id);
+            let r = f(resource);
+            #trace_mod::lock_release(#resource_id);
+            r
+        };
+    }
+}
+
+pub(crate) fn get_rticx_trace_mod(
+    trace_path: &Path,
+    tracer: Option<&Path>,
+    ids: &TraceIds,
+) -> TokenStream2 {
+    let trace_mod = format_ident!("{TRACE_MOD}");
+    let tracer_alias = format_ident!("{TRACER_ALIAS}");
+    let tracer = tracer.map_or_else(|| quote!(#trace_path::NoTracer), |tracer| quote!(#tracer));
+    let id_const = |TraceId { id, ident, .. }: &TraceId| quote!(pub const #ident: u16 = #id;);
+    let task_ids = ids.tasks.iter().map(id_const);
+    let mut cores: Vec<u32> = ids.resources.iter().map(|r| r.core).collect();
+    cores.dedup();
+    let resource_ids = cores.into_iter().map(|core| {
+        let core_mod = format_ident!("core{core}");
+        let ids = ids
+            .resources
+            .iter()
+            .filter(|r| r.core == core)
+            .map(id_const);
+        quote! {
+            pub mod #core_mod {
+                #(#ids)*
+            }
+        }
+    });
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        type #tracer_alias = #tracer;
+
+        /// Module defining the tracing hooks of the application
+        pub mod #trace_mod {
+            use #trace_path::Tracer;
+
+            type AppTracer = super::#tracer_alias;
+
+            /// Identifiers of the tasks in traces
+            #[allow(non_upper_case_globals)]
+            pub mod task_ids {
+                #(#task_ids)*
+            }
+
+            /// Identifiers of the shared resources in traces, per core
+            #[allow(non_upper_case_globals)]
+            pub mod resource_ids {
+                #(#resource_ids)*
+            }
+
+            /// Running job of a task, finished when dropped so early returns from `exec` are traced
+            #[doc(hidden)]
+            pub struct Job(u16);
+
+            impl Drop for Job {
+                #[inline(always)]
+                fn drop(&mut self) {
+                    AppTracer::task_exit(self.0);
+                }
+            }
+
+            #[doc(hidden)]
+            #[inline(always)]
+            pub fn task_enter(task: u16) -> Job {
+                AppTracer::task_enter(task);
+                Job(task)
+            }
+
+            #[doc(hidden)]
+            #[inline(always)]
+            pub fn lock_acquire(resource: u16) {
+                AppTracer::lock_acquire(resource);
+            }
+
+            #[doc(hidden)]
+            #[inline(always)]
+            pub fn lock_release(resource: u16) {
+                AppTracer::lock_release(resource);
+            }
+
+            #[doc(hidden)]
+            #[inline(always)]
+            pub fn spawn(task: u16) {
+                AppTracer::spawn(task);
+            }
+        }
+    }
+}
+
+/// The application report: the identifiers, names and cores of the traced tasks and resources, read
+/// by the trace decoder of `rticx-trace`
+pub fn report_json(ids: &TraceIds) -> String {
+    let entries = |ids: &[TraceId], priority: &str| {
+        ids.iter()
+            .map(|t| {
+                format!(
+                    "    {{ \"id\": {}, \"name\": \"{}\", \"core\": {}, \"{priority}\": {} }}",
+                    t.id, t.ident, t.core, t.priority
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n")
+    };
+    format!(
+        "{{\n  \"tasks\": [\n{}\n  ],\n  \"resources\": [\n{}\n  ]\n}}\n",
+        entries(&ids.tasks, "priority"),
+        entries(&ids.resources, "ceiling")
+    )
+}
+
+/// Write the application report to `target/rticx/<binary>.trace.json` in the project root
+pub(crate) fn write_report(ids: &TraceIds) -> std::io::Result<()> {
+    let name = std::env::var("CARGO_BIN_NAME")
+        .or_else(|_| std::env::var("CARGO_CRATE_NAME"))
+        .unwrap_or_else(|_| "app".to_string());
+    let dir = project_root::get_project_root()?.join("target/rticx");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(format!("{name}.trace.json")), report_json(ids))
+}
@@ -1,5 +1,6 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
+use syn::Ident;
 
 pub const HWT_TRAIT_TY: &str = "RticTask";
 pub const SWT_TRAIT_TY: &str = "RticSwTask"; // FIXME: add a backend trait method to provide a list of additional traits that define task types instead of this wrong way of borrowing from sw pass implicitly !
@@ -7,8 +8,12 @@ pub const IDLE_TRAIT_TY: &str = "RticIdleTask";
 
 pub const MUTEX_TY: &str = "RticMutex";
 
-pub(crate) fn get_rticx_traits_mod() -> TokenStream2 {
+/// Enum of the interrupts of the tasks bound to several interrupts and of the task instances
+pub const INTERRUPT_SOURCE_TY: &str = "InterruptSource";
+
+pub(crate) fn get_rticx_traits_mod(interrupt_sources: &[Ident]) -> TokenStream2 {
     let hw_task_trait = hw_task_trait();
+    let interrupt_source = interrupt_source_enum(interrupt_sources);
     let idle_trait = idle_task_trait();
     let mutex_trait = mutex_trait();
     quote! {
@@ -16,6 +21,7 @@ pub(crate) fn get_rticx_traits_mod() -> TokenStream2 {
         pub use rticx_traits::*;
         pub mod rticx_traits {
             #hw_task_trait
+            #interrupt_source
             #idle_trait
             #mutex_trait
         }
@@ -24,6 +30,7 @@ pub(crate) fn get_rticx_traits_mod() -> TokenStream2 {
 
 fn hw_task_trait() -> TokenStream2 {
     let hw_task = format_ident!("{HWT_TRAIT_TY}");
+    let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
     quote! {
         /// Trait for a hardware task
         pub trait #hw_task {
@@ -33,6 +40,25 @@ fn hw_task_trait() -> TokenStream2 {
             fn init(args: Self::InitArgs) -> Self;
             /// Function to be bound to a HW Interrupt
             fn exec(&mut self);
+            /// Function bound to several HW Interrupts, `source` being the one that triggered the
+            /// job. Runs [Self::exec] by default
+            fn exec_from(&mut self, source: #interrupt_source) {
+                let _ = source;
+                self.exec()
+            }
+        }
+    }
+}
+
+fn interrupt_source_enum(interrupt_sources: &[Ident]) -> TokenStream2 {
+    let interrupt_source = format_ident!("{INTERRUPT_SOURCE_TY}");
+    quote! {
+        /// Interrupts of the tasks bound to several interrupts, telling [RticTask::exec_from]
+        /// which one triggered the job, and of the task instances
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum #interrupt_source {
+            #(#interrupt_sources,)*
         }
     }
 }
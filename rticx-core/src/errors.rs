@@ -10,6 +10,44 @@ pub enum ParseError {
 
     #[error("The value passed to the `device` argument must be a path to a PAC crate.")]
     DeviceNotPath,
+
+    #[error(
+        "The value passed to the `tracer` argument must be a path to a type implementing `Tracer`."
+    )]
+    TracerNotPath,
+
+    #[error(
+        "The `tracer` argument needs the `trace` feature of `rticx-core`, enable it in the distribution."
+    )]
+    TracingDisabled,
+
+    #[error(
+        "Shared resources are locked through their proxy, list them without `&mut`, or use `&` for `#[readonly]` resources."
+    )]
+    SharedMutReference,
+
+    #[error("Read-only access only works on whole resources, list them as `&resource`.")]
+    ReadonlyPart,
+
+    #[error(
+        "Parts of shared resources are listed as a path of named fields and literal indices, like `channels[0]` or `motor.state`."
+    )]
+    ResourcePath,
+
+    #[error(
+        "`{0}` overlaps `{1}`, already listed in `shared`. List each resource once, either whole or as disjoint parts."
+    )]
+    SharedOverlap(String, String),
+
+    #[error(
+        "`binds` takes the name of an interrupt, or a list of interrupts like `[EXTI0, EXTI1]`."
+    )]
+    Binds,
+
+    #[error(
+        "`instances` takes a list of interrupts and priorities like `[(UART0, 2), (UART1, 3)]`, which replace the `binds`, `priority` and `threshold` of the task."
+    )]
+    Instances,
 }
 impl ParseError {
     pub fn to_syn(&self, span: proc_macro2::Span) -> syn::Error {
@@ -26,3 +64,18 @@ pub enum Error {
     #[error("The target type is incorrect for entry '{0}'")]
     InvalidTargetType(String),
 }
+
+/// Builds an item that makes rustc emit `message` as a warning pointing at `span`.
+///
+/// Proc-macros can't emit warnings on stable, so this goes through the `deprecated` lint instead:
+/// the returned tokens must be placed where an item is allowed (e.g. inside the app module).
+pub fn warning(span: proc_macro2::Span, message: &str) -> proc_macro2::TokenStream {
+    quote::quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #message)]
+            #[allow(non_upper_case_globals)]
+            const rticx_warning: () = ();
+            rticx_warning
+        };
+    }
+}
@@ -7,11 +7,15 @@ use std::sync::atomic::Ordering;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use syn::{ItemMod, parse_macro_input};
 
+pub use common_internal::rticx_cell;
 pub use common_internal::rticx_functions;
+pub use common_internal::rticx_srp;
+pub use common_internal::rticx_stats;
+pub use common_internal::rticx_trace;
 pub use common_internal::rticx_traits;
 
 pub use analysis::{Analysis, SubAnalysis};
-pub use backend::CorePassBackend;
+pub use backend::{CorePassBackend, InitPeripherals, InterruptControl};
 use codegen::CodeGen;
 pub use parser::ast::AppArgs;
 pub use parser::{App, SubApp};
@@ -31,6 +35,23 @@ pub mod parser;
 
 static DEFAULT_TASK_PRIORITY: AtomicU16 = AtomicU16::new(0);
 
+/// Priority given to tasks declared without a `priority` argument, as provided by
+/// [CorePassBackend::default_task_priority]. It is set before any compilation pass runs.
+pub fn default_task_priority() -> u16 {
+    DEFAULT_TASK_PRIORITY.load(Ordering::Relaxed)
+}
+
+/// Which end of the priority range the hardware considers the most urgent, for the passes deriving
+/// priorities from timing arguments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MostUrgent {
+    /// The highest level preempts the others, as for RTICX logical priorities
+    #[default]
+    Highest,
+    /// Level `1` preempts the others
+    Lowest,
+}
+
 /// A trait that allows defining a **Compilation Pass**.
 ///
 /// A **Compilation Pass** can be thought of as a (partial) proc-macro that expands parts of the user application
@@ -101,6 +122,14 @@ impl RticMacroBuilder {
         // init statics
         DEFAULT_TASK_PRIORITY.store(self.core.default_task_priority(), Ordering::Relaxed);
 
+        if rticx_trace::ENABLED && self.core.trace_export_path().is_none() {
+            return syn::Error::new(
+                app_mod.ident.span(),
+                "The `trace` feature of `rticx-core` is enabled, but this distribution does not support tracing.",
+            )
+            .to_compile_error();
+        }
+
         let mut args = args;
         let mut app_mod = app_mod;
 
@@ -154,7 +183,17 @@ impl RticMacroBuilder {
             return e.to_compile_error();
         }
 
-        let code = CodeGen::new(self.core.as_ref(), &parsed_app, &analysis).run();
+        let mut code = CodeGen::new(self.core.as_ref(), &parsed_app, &analysis).run();
+
+        // the trace decoder reads the names of the tasks and resources from the application report
+        if rticx_trace::ENABLED
+            && let Err(e) = rticx_trace::write_report(&analysis.trace_ids)
+        {
+            code.extend(errors::warning(
+                parsed_app.app_name.span(),
+                &format!("The application report for the trace decoder could not be written: {e}"),
+            ));
+        }
 
         #[cfg(feature = "debug_expand")]
         if let Ok(binary_name) = std::env::var("CARGO_BIN_NAME")
@@ -11,7 +11,7 @@ use syn::{Ident, parse_quote};
 use crate::analysis::SubAnalysis;
 use crate::parser::SubApp;
 use crate::parser::ast::AppArgs;
-use crate::{Analysis, App, CorePassBackend};
+use crate::{Analysis, App, CorePassBackend, InitPeripherals, InterruptControl, rticx_functions};
 
 /// A no-op backend used for testing the parser, analysis, and codegen pieces of `rticx-core`.
 ///
@@ -57,10 +57,15 @@ impl CorePassBackend for MockCoreBackend {
 
     fn wrap_task_execution(
         &self,
-        _task_prio: u16,
+        task_prio: u16,
+        threshold: u16,
         dispatch_task_call: TokenStream2,
     ) -> Option<TokenStream2> {
-        Some(dispatch_task_call)
+        Some(rticx_functions::raise_to_threshold(
+            task_prio,
+            threshold,
+            dispatch_task_call,
+        ))
     }
 
     fn entry_name(&self, core: u32) -> Ident {
@@ -85,6 +90,69 @@ impl CorePassBackend for MockCoreBackend {
         empty_body_fn
     }
 
+    fn generate_cycles_fn(&self, _core: u32, mut empty_body_fn: syn::ItemFn) -> syn::ItemFn {
+        empty_body_fn.block = parse_quote! {
+            {
+                // mock backend: deterministic counter, advanced by 10 cycles on every read
+                static CYCLES: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+                let now = CYCLES.load(core::sync::atomic::Ordering::Relaxed);
+                CYCLES.store(now.wrapping_add(10), core::sync::atomic::Ordering::Relaxed);
+                now
+            }
+        };
+        empty_body_fn
+    }
+
+    fn generate_hw_ceiling_fn(
+        &self,
+        _app_args: &AppArgs,
+        _core: u32,
+        mut empty_body_fn: syn::ItemFn,
+    ) -> syn::ItemFn {
+        empty_body_fn.block = parse_quote! {
+            {
+                // mock backend: no hardware ceiling, only the tracked one is checked
+                None
+            }
+        };
+        empty_body_fn
+    }
+
+    fn generate_interrupt_control_fn(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+        binds: &Ident,
+        control: InterruptControl,
+        mut empty_body_fn: syn::ImplItemFn,
+    ) -> Option<syn::ImplItemFn> {
+        let pac = &app_args.pacs[app_info.core as usize];
+        let mock_fn = format_ident!("mock_{}", control.fn_name());
+        empty_body_fn.block = parse_quote! {
+            {
+                // mock backend: interrupt controller
+                #mock_fn(#pac::Interrupt::#binds)
+            }
+        };
+        Some(empty_body_fn)
+    }
+
+    fn init_core_peripherals(
+        &self,
+        app_args: &AppArgs,
+        app_info: &SubApp,
+    ) -> Option<InitPeripherals> {
+        let pac = &app_args.pacs[app_info.core as usize];
+        Some(InitPeripherals {
+            ty: parse_quote!(#pac::CorePeripherals),
+            take: parse_quote!(#pac::CorePeripherals::steal()),
+        })
+    }
+
+    fn trace_export_path(&self) -> Option<syn::Path> {
+        Some(parse_quote!(rticx_trace))
+    }
+
     fn pre_codegen_validation(&self, _app: &App, _analysis: &Analysis) -> syn::Result<()> {
         Ok(())
     }
@@ -3,7 +3,14 @@
 use proc_macro2::{Punct, Spacing, TokenStream as TokenStream2};
 use quote::{ToTokens, TokenStreamExt, format_ident, quote};
 use std::collections::HashMap;
-use syn::{Attribute, Meta, parse::Parser, parse_quote};
+use syn::{Attribute, Expr, Ident, Meta, parse::Parser, parse_quote, spanned::Spanned};
+
+use crate::errors::ParseError;
+
+pub mod spawn_calls;
+mod time;
+
+pub use time::{Time, parse_time};
 
 #[derive(Debug, Clone)]
 pub struct RticAttr {
@@ -80,3 +87,19 @@ impl ToTokens for RticAttr {
         tokens.append_all(attribue.to_token_stream())
     }
 }
+
+/// Parse the value of the `binds` argument of a task: an interrupt, `binds = EXTI0`, or a list of
+/// interrupts, `binds = [EXTI0, EXTI1]`
+pub fn parse_binds(expr: &Expr) -> syn::Result<Vec<Ident>> {
+    let interrupt = |expr: &Expr| match expr {
+        Expr::Path(path) => path.path.get_ident().cloned(),
+        _ => None,
+    };
+    let binds = match expr {
+        Expr::Array(array) => array.elems.iter().map(interrupt).collect(),
+        expr => interrupt(expr).map(|irq| vec![irq]),
+    };
+    binds
+        .filter(|binds| !binds.is_empty())
+        .ok_or_else(|| ParseError::Binds.to_syn(expr.span()))
+}
@@ -0,0 +1,102 @@
+//! Discovery of `X::spawn(..)` and `X::spawn_from(..)` calls in task and `#[init]` bodies.
+//!
+//! The calls are found syntactically: spawns inside macro invocations (`defmt::unwrap!(X::spawn(..))`),
+//! `X::spawn` used as a function pointer and `use` aliases of the task type are missed.
+
+use proc_macro2::Span;
+use syn::{Expr, ExprCall, Ident, Item, ItemFn, ItemImpl, Type, spanned::Spanned, visit::Visit};
+
+/// Which spawn API was called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnKind {
+    /// `X::spawn(..)`, called on the core owning `X`
+    Spawn,
+    /// `X::spawn_from(..)`, called from another core
+    SpawnFrom,
+}
+
+/// A spawn call of the software task `target`
+#[derive(Debug, Clone)]
+pub struct SpawnCall {
+    pub target: Ident,
+    pub kind: SpawnKind,
+    /// Span of the call expression, used for error reporting
+    pub span: Span,
+}
+
+/// Collects the spawn calls made in `impl_`, resolving `Self` to the implementor.
+pub fn in_impl(impl_: &ItemImpl) -> Vec<SpawnCall> {
+    let mut visitor = SpawnCallVisitor {
+        self_ty: impl_self_ident(impl_),
+        calls: Vec::new(),
+    };
+    visitor.visit_item_impl(impl_);
+    visitor.calls
+}
+
+/// Collects the spawn calls made in the function `f`, e.g. an `#[init]` function.
+pub fn in_fn(f: &ItemFn) -> Vec<SpawnCall> {
+    let mut visitor = SpawnCallVisitor {
+        self_ty: None,
+        calls: Vec::new(),
+    };
+    visitor.visit_item_fn(f);
+    visitor.calls
+}
+
+/// Collects the spawn calls made anywhere in `item`.
+pub fn in_item(item: &Item) -> Vec<SpawnCall> {
+    match item {
+        Item::Impl(impl_) => in_impl(impl_),
+        item => {
+            let mut visitor = SpawnCallVisitor {
+                self_ty: None,
+                calls: Vec::new(),
+            };
+            visitor.visit_item(item);
+            visitor.calls
+        }
+    }
+}
+
+/// The type an `impl` block is written for, when it is a plain identifier
+pub fn impl_self_ident(impl_: &ItemImpl) -> Option<&Ident> {
+    match impl_.self_ty.as_ref() {
+        Type::Path(self_ty) => self_ty.path.get_ident(),
+        _ => None,
+    }
+}
+
+struct SpawnCallVisitor<'a> {
+    self_ty: Option<&'a Ident>,
+    calls: Vec<SpawnCall>,
+}
+
+impl<'ast> Visit<'ast> for SpawnCallVisitor<'_> {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func) = call.func.as_ref()
+            && func.qself.is_none()
+            && func.path.segments.len() >= 2
+        {
+            let segments = &func.path.segments;
+            let kind = match segments[segments.len() - 1].ident.to_string().as_str() {
+                "spawn" => Some(SpawnKind::Spawn),
+                "spawn_from" => Some(SpawnKind::SpawnFrom),
+                _ => None,
+            };
+            let target = &segments[segments.len() - 2].ident;
+            let target = match self.self_ty {
+                Some(self_ty) if target == "Self" => self_ty,
+                _ => target,
+            };
+            if let Some(kind) = kind {
+                self.calls.push(SpawnCall {
+                    target: target.clone(),
+                    kind,
+                    span: call.span(),
+                });
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
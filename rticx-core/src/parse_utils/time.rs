@@ -0,0 +1,85 @@
+//! Timing arguments of tasks (`deadline`, `period`, `wcet`, `budget`, ...), shared by the passes
+//! deriving priorities or timers from them.
+
+use syn::{Expr, Lit, spanned::Spanned};
+
+/// A deadline, period or execution time of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Time {
+    /// A plain number, in a unit chosen by the application or the platform (cycles, timer ticks)
+    Plain(u64),
+    /// A duration, in nanoseconds
+    Nanos(u64),
+}
+
+impl Time {
+    pub fn is_duration(self) -> bool {
+        matches!(self, Time::Nanos(_))
+    }
+
+    /// The value, in nanoseconds for durations
+    pub fn value(self) -> u64 {
+        match self {
+            Time::Plain(value) | Time::Nanos(value) => value,
+        }
+    }
+}
+
+/// Parses the value of the timing argument `arg`: an integer literal, or a duration such as
+/// `500.us()`. `unit` is the unit implied by the argument name (e.g. `deadline_us`), in
+/// nanoseconds, if any: the value is then an integer literal in that unit.
+pub fn parse_time(arg: &str, expr: &Expr, unit: Option<u64>) -> syn::Result<Time> {
+    let (value, unit) = match (expr, unit) {
+        (
+            Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            }),
+            unit,
+        ) => (int.base10_parse()?, unit),
+        (Expr::MethodCall(call), None) if call.args.is_empty() => {
+            let Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(int), ..
+            }) = call.receiver.as_ref()
+            else {
+                return Err(syn::Error::new(
+                    call.receiver.span(),
+                    "Durations must start with an integer literal, e.g. `500.us()`.",
+                ));
+            };
+            let unit = match call.method.to_string().as_str() {
+                "ns" | "nanos" => 1,
+                "us" | "micros" => 1_000,
+                "ms" | "millis" => 1_000_000,
+                "s" | "secs" => 1_000_000_000,
+                method => {
+                    return Err(syn::Error::new(
+                        call.method.span(),
+                        format!(
+                            "Unknown time unit `{method}`, expected one of `ns`, `us`, `ms` or `s`."
+                        ),
+                    ));
+                }
+            };
+            (int.base10_parse()?, Some(unit))
+        }
+        (_, None) => {
+            return Err(syn::Error::new(
+                expr.span(),
+                format!("`{arg}` must be an integer literal or a duration such as `500.us()`."),
+            ));
+        }
+        (_, Some(_)) => {
+            return Err(syn::Error::new(
+                expr.span(),
+                format!("`{arg}` must be an integer literal."),
+            ));
+        }
+    };
+
+    match unit {
+        None => Ok(Time::Plain(value)),
+        Some(unit) => u64::checked_mul(value, unit)
+            .map(Time::Nanos)
+            .ok_or_else(|| syn::Error::new(expr.span(), "The duration is too long.")),
+    }
+}
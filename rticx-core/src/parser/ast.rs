@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::atomic::Ordering;
 
 use heck::ToSnakeCase;
@@ -9,7 +10,10 @@ use syn::{
 };
 
 use crate::{
-    DEFAULT_TASK_PRIORITY, errors::ParseError, parse_utils::RticAttr, rticx_traits::HWT_TRAIT_TY,
+    DEFAULT_TASK_PRIORITY,
+    errors::ParseError,
+    parse_utils::{RticAttr, parse_binds},
+    rticx_traits::HWT_TRAIT_TY,
 };
 
 #[derive(Debug, Clone)]
@@ -17,6 +21,8 @@ pub struct InitTask {
     pub args: InitTaskArgs,
     pub ident: Ident,
     pub body: ItemFn,
+    /// Whether `init` takes its context, `fn init(cx: init::Context)`
+    pub context: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -52,12 +58,24 @@ impl InitTaskArgs {
 
 #[derive(Debug, Clone)]
 pub struct TaskArgs {
-    /// Interrupt handler name
-    pub binds: Option<syn::Ident>,
+    /// Interrupt handler names, a single one as `binds = UART` or a list as `binds = [EXTI0, EXTI1]`.
+    /// Empty for tasks not bound to an interrupt
+    pub binds: Vec<syn::Ident>,
     pub priority: u16,
+    /// Preemption threshold: the priority the task runs at once dispatched, so that the tasks of a
+    /// priority up to the threshold don't preempt it. Defaults to `priority`
+    pub threshold: u16,
     /// Shared resources, stored as a list of [identifiers](`proc_macro2::Ident`)
     pub shared: Vec<Ident>,
+    /// Read-only shared resources, listed by reference (`&resource`) in `shared`
+    pub readonly: Vec<Ident>,
+    /// Parts of shared resources locked on their own, listed as `channels[0]` or `motor.state` in
+    /// `shared`
+    pub shared_parts: Vec<ResourcePath>,
     pub core: u32,
+    /// Instances of the task type, each bound to its own interrupt at its own priority, declared as
+    /// `instances = [(UART0, 2), (UART1, 3)]`. Empty for a single task
+    pub instances: Vec<TaskInstance>,
     // tells whether a task is native to this compilation pass or if another compilation pass handles its trait implementation
     pub task_trait: Ident,
 }
@@ -65,32 +83,43 @@ pub struct TaskArgs {
 impl TaskArgs {
     pub fn parse(args: Meta) -> syn::Result<Self> {
         let Meta::List(args) = args else {
+            let priority = DEFAULT_TASK_PRIORITY.load(Ordering::Relaxed);
             return Ok(TaskArgs {
-                binds: None,
-                priority: DEFAULT_TASK_PRIORITY.load(Ordering::Relaxed),
+                binds: Vec::new(),
+                priority,
+                threshold: priority,
                 shared: Default::default(),
+                readonly: Default::default(),
+                shared_parts: Default::default(),
                 core: 0,
+                instances: Vec::new(),
                 task_trait: format_ident!("{HWT_TRAIT_TY}"),
             });
         };
 
-        let mut binds: Option<syn::Path> = None;
+        let mut binds: Option<Expr> = None;
         let mut task_trait: Option<Ident> = None;
         let mut priority: Option<LitInt> = None;
+        let mut threshold: Option<LitInt> = None;
         let mut shared: Option<ExprArray> = None;
         let mut core: Option<LitInt> = None;
+        let mut instances: Option<ExprArray> = None;
 
         syn::meta::parser(|meta| {
             if meta.path.is_ident("binds") {
                 binds = Some(meta.value()?.parse()?);
             } else if meta.path.is_ident("priority") {
                 priority = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("threshold") {
+                threshold = Some(meta.value()?.parse()?);
             } else if meta.path.is_ident("shared") {
                 shared = Some(meta.value()?.parse()?);
             } else if meta.path.is_ident("core") {
                 core = Some(meta.value()?.parse()?);
             } else if meta.path.is_ident("task_trait") {
                 task_trait = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("instances") {
+                instances = Some(meta.value()?.parse()?);
             } else {
                 // this is needed to advance the values iterator
                 let _: syn::Result<Expr> = meta
@@ -109,34 +138,261 @@ impl TaskArgs {
             );
         })?;
 
-        let binds = binds.map(|i| Ident::new(&i.to_token_stream().to_string(), Span::call_site()));
+        // the instances replace the interrupt and priority of the task
+        let instances = match instances {
+            Some(instances) if binds.is_some() || priority.is_some() || threshold.is_some() => {
+                return Err(ParseError::Instances.to_syn(instances.span()));
+            }
+            Some(instances) => TaskInstance::parse_list(&instances)?,
+            None => Vec::new(),
+        };
+
+        let binds = binds
+            .map(|binds| parse_binds(&binds))
+            .transpose()?
+            .unwrap_or_default();
 
         let priority = priority
             .and_then(|p| p.base10_parse().ok())
             .unwrap_or(DEFAULT_TASK_PRIORITY.load(Ordering::Relaxed));
+        let threshold = threshold
+            .map(|t| t.base10_parse())
+            .transpose()?
+            .unwrap_or(priority);
 
         let core = core
             .and_then(|core| core.base10_parse().ok())
             .unwrap_or_default();
         let task_trait = task_trait.unwrap_or(format_ident!("{HWT_TRAIT_TY}"));
 
+        let mut readonly = Vec::new();
+        let mut shared_parts = Vec::new();
         let shared = shared
             .map(|expr| {
-                expr.elems
-                    .into_iter()
-                    .map(|elem| Ident::new(&elem.to_token_stream().to_string(), Span::call_site()))
-                    .collect()
+                let mut shared = Vec::new();
+                // every entry as a path, to reject those overlapping a previous one
+                let mut listed: Vec<ResourcePath> = Vec::new();
+                for elem in expr.elems {
+                    let span = elem.span();
+                    let path = match elem {
+                        Expr::Reference(reference) if reference.mutability.is_some() => {
+                            return Err(ParseError::SharedMutReference.to_syn(reference.span()));
+                        }
+                        Expr::Reference(reference) => {
+                            let ident = match &*reference.expr {
+                                Expr::Path(path) => path.path.get_ident(),
+                                _ => None,
+                            }
+                            .ok_or_else(|| {
+                                ParseError::ReadonlyPart.to_syn(reference.expr.span())
+                            })?;
+                            readonly.push(ident.clone());
+                            ResourcePath::whole(ident.clone())
+                        }
+                        elem @ (Expr::Field(_) | Expr::Index(_)) => {
+                            let path = ResourcePath::parse(&elem)?;
+                            shared_parts.push(path.clone());
+                            path
+                        }
+                        elem => {
+                            let ident =
+                                Ident::new(&elem.to_token_stream().to_string(), Span::call_site());
+                            shared.push(ident.clone());
+                            ResourcePath::whole(ident)
+                        }
+                    };
+                    // two proxies to the same memory would hand out two `&mut` to it
+                    if let Some(previous) = listed.iter().find(|other| other.overlaps(&path)) {
+                        return Err(ParseError::SharedOverlap(
+                            path.to_string(),
+                            previous.to_string(),
+                        )
+                        .to_syn(span));
+                    }
+                    listed.push(path);
+                }
+                Ok(shared)
             })
+            .transpose()?
             .unwrap_or_default();
 
         Ok(Self {
             binds,
             priority,
+            threshold,
             shared,
+            readonly,
+            shared_parts,
             core,
+            instances,
             task_trait,
         })
     }
+
+    /// Arguments of the instance `idx` of the task type, bound to its interrupt and running at its
+    /// priority
+    pub fn instance_args(&self, idx: usize) -> Self {
+        let instance = &self.instances[idx];
+        Self {
+            binds: vec![instance.binds.clone()],
+            priority: instance.priority,
+            threshold: instance.priority,
+            ..self.clone()
+        }
+    }
+}
+
+/// Instance of a task type declaring several, see [TaskArgs::instances]
+#[derive(Debug, Clone)]
+pub struct TaskInstance {
+    /// Interrupt of the instance, which also names it
+    pub binds: Ident,
+    pub priority: u16,
+}
+
+impl TaskInstance {
+    fn parse_list(instances: &ExprArray) -> syn::Result<Vec<Self>> {
+        if instances.elems.is_empty() {
+            return Err(ParseError::Instances.to_syn(instances.span()));
+        }
+        instances
+            .elems
+            .iter()
+            .map(|instance| {
+                let Expr::Tuple(tuple) = instance else {
+                    return Err(ParseError::Instances.to_syn(instance.span()));
+                };
+                let elems: Vec<_> = tuple.elems.iter().collect();
+                match elems.as_slice() {
+                    [
+                        Expr::Path(binds),
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Int(priority),
+                            ..
+                        }),
+                    ] => Ok(Self {
+                        binds: binds
+                            .path
+                            .get_ident()
+                            .cloned()
+                            .ok_or_else(|| ParseError::Instances.to_syn(binds.span()))?,
+                        priority: priority.base10_parse()?,
+                    }),
+                    _ => Err(ParseError::Instances.to_syn(tuple.span())),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Step from a shared resource to one of its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceProjection {
+    /// Named field of a struct, `.field`
+    Field(Ident),
+    /// Element of an array, `[index]`
+    Index(usize),
+}
+
+/// Part of a shared resource, like `channels[0]` or `motor.state`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePath {
+    pub resource: Ident,
+    pub projections: Vec<ResourceProjection>,
+}
+
+impl ResourcePath {
+    /// Path of a whole resource
+    pub fn whole(resource: Ident) -> Self {
+        Self {
+            resource,
+            projections: Vec::new(),
+        }
+    }
+
+    fn parse(expr: &Expr) -> syn::Result<Self> {
+        match expr {
+            Expr::Path(path) => {
+                let resource = path
+                    .path
+                    .get_ident()
+                    .ok_or_else(|| ParseError::ResourcePath.to_syn(path.span()))?;
+                Ok(Self::whole(resource.clone()))
+            }
+            Expr::Field(field) => {
+                let syn::Member::Named(ident) = &field.member else {
+                    return Err(ParseError::ResourcePath.to_syn(field.member.span()));
+                };
+                let mut path = Self::parse(&field.base)?;
+                path.projections
+                    .push(ResourceProjection::Field(ident.clone()));
+                Ok(path)
+            }
+            Expr::Index(index) => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }) = index.index.as_ref()
+                else {
+                    return Err(ParseError::ResourcePath.to_syn(index.index.span()));
+                };
+                let mut path = Self::parse(&index.expr)?;
+                path.projections
+                    .push(ResourceProjection::Index(lit.base10_parse()?));
+                Ok(path)
+            }
+            expr => Err(ParseError::ResourcePath.to_syn(expr.span())),
+        }
+    }
+
+    /// Name of the part in the `shared()` API of the tasks, like `channels_0` or `motor_state`
+    pub fn ident(&self) -> Ident {
+        let mut name = self.resource.to_string();
+        for projection in self.projections.iter() {
+            match projection {
+                ResourceProjection::Field(field) => name.push_str(&format!("_{field}")),
+                ResourceProjection::Index(index) => name.push_str(&format!("_{index}")),
+            }
+        }
+        format_ident!("{name}")
+    }
+
+    /// Whether the parts have memory in common, one containing the other
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.resource == other.resource
+            && self
+                .projections
+                .iter()
+                .zip(other.projections.iter())
+                .all(|(a, b)| a == b)
+    }
+}
+
+impl std::fmt::Display for ResourcePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.resource)?;
+        for projection in self.projections.iter() {
+            match projection {
+                ResourceProjection::Field(field) => write!(f, ".{field}")?,
+                ResourceProjection::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToTokens for ResourcePath {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.resource.to_tokens(tokens);
+        for projection in self.projections.iter() {
+            match projection {
+                ResourceProjection::Field(field) => quote::quote!(.#field).to_tokens(tokens),
+                ResourceProjection::Index(index) => {
+                    let index = proc_macro2::Literal::usize_unsuffixed(*index);
+                    quote::quote!([#index]).to_tokens(tokens)
+                }
+            }
+        }
+    }
 }
 
 /// Alias for hardware task
@@ -151,18 +407,50 @@ pub struct RticTask {
     pub task_struct: ItemStruct,
     pub struct_impl: Option<ItemImpl>,
     pub user_initializable: bool, // whether user should manually initialize this task during init
+    /// Index of the instance in [TaskArgs::instances], for the task types declaring several. The
+    /// arguments are then those of the instance
+    pub instance: Option<usize>,
 }
 
 impl RticTask {
+    /// Name of the task type
     pub fn name(&self) -> &Ident {
         &self.task_struct.ident
     }
 
+    /// Name of the task, the type followed by the interrupt of the instance for the task types
+    /// declaring several, like `UartTask_UART0`
+    pub fn instance_name(&self) -> Ident {
+        match self.instance {
+            Some(idx) => instance_ident(self.name(), &self.args.instances[idx].binds),
+            None => self.name().clone(),
+        }
+    }
+
+    /// Whether the task defines the items of its type: the struct, its implementation and API.
+    /// Instances other than the first one only define their own statics and interrupt handler
+    pub fn defines_type(&self) -> bool {
+        self.instance.is_none_or(|idx| idx == 0)
+    }
+
+    /// Whether the functions of the task type act on one of its interrupts, given to them as an
+    /// `InterruptSource`: tasks bound to several interrupts and task types declaring instances
+    pub fn takes_interrupt_source(&self) -> bool {
+        self.args.binds.len() > 1 || self.instance.is_some()
+    }
+
+    /// The interrupts of the task type, those of all its instances for task types declaring several
+    pub fn type_binds(&self) -> Vec<&Ident> {
+        match self.instance {
+            Some(_) => self.args.instances.iter().map(|i| &i.binds).collect(),
+            None => self.args.binds.iter().collect(),
+        }
+    }
+
     /// By convention, this method is used to generate the name of the static task instance
     pub fn name_uppercase(&self) -> Ident {
         let name = self
-            .task_struct
-            .ident
+            .instance_name()
             .to_string()
             .to_snake_case()
             .to_uppercase();
@@ -170,16 +458,39 @@ impl RticTask {
     }
 
     pub fn name_snakecase(&self) -> Ident {
-        let name = self.task_struct.ident.to_string().to_snake_case();
+        let name = self.instance_name().to_string().to_snake_case();
         Ident::new(&name, Span::call_site())
     }
 }
 
+/// Name of the instance of the task type `task_ty` bound to `binds`
+pub fn instance_ident(task_ty: &Ident, binds: &Ident) -> Ident {
+    format_ident!("{task_ty}_{binds}")
+}
+
 #[derive(Debug, Clone)]
 pub struct SharedElement {
     pub ident: Ident,
     pub ty: syn::Type,
     pub priority: u16,
+    /// Marked `#[readonly]`: initialized by `#[init]`, then only read by reference without locks
+    pub readonly: bool,
+    /// Thresholds of the tasks locking the resource, the idle task counting as 0. Set by the analysis
+    pub accessed_by: BTreeSet<u16>,
+    /// Parts of the resource locked on their own, with their own ceilings. Set by the analysis
+    pub parts: Vec<SharedPart>,
+}
+
+/// Part of a shared resource locked on its own by some tasks
+#[derive(Debug, Clone)]
+pub struct SharedPart {
+    pub path: ResourcePath,
+    pub ty: syn::Type,
+    /// Ceiling of the part: the highest threshold of the tasks locking the part, a part containing
+    /// it or a part it contains
+    pub priority: u16,
+    /// Thresholds of the tasks locking the part, the idle task counting as 0
+    pub accessed_by: BTreeSet<u16>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -244,6 +555,8 @@ pub struct AppArgs {
     // path to peripheral crate
     pub pacs: Vec<syn::Path>,
     pub cores: u32,
+    // type implementing `Tracer`, receiving the tracing events when the `trace` feature is enabled
+    pub tracer: Option<syn::Path>,
 }
 
 impl AppArgs {
@@ -294,6 +607,20 @@ impl AppArgs {
             _ => return Err(ParseError::DeviceNotPath.to_syn(args_span)),
         };
 
-        Ok(Self { pacs, cores })
+        // parse the tracer
+        let tracer = match args.elements.remove("tracer") {
+            Some(_) if !crate::rticx_trace::ENABLED => {
+                return Err(ParseError::TracingDisabled.to_syn(args_span));
+            }
+            Some(Expr::Path(tracer)) => Some(tracer.path),
+            Some(_) => return Err(ParseError::TracerNotPath.to_syn(args_span)),
+            None => None,
+        };
+
+        Ok(Self {
+            pacs,
+            cores,
+            tracer,
+        })
     }
 }
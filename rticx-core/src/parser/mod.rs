@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use proc_macro2::Span;
-use quote::format_ident;
-use syn::{Ident, Item, ItemFn, ItemImpl, ItemStruct, ItemUse, Type, spanned::Spanned};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    FnArg, Ident, Item, ItemFn, ItemImpl, ItemStruct, ItemUse, ReturnType, Type, spanned::Spanned,
+};
 
 use ast::*;
 
@@ -96,6 +98,9 @@ impl App {
                 tasks: tasks.remove(&core).unwrap_or_default(),
             })
         }
+        for app in sub_apps.iter() {
+            Self::check_init_return(app)?;
+        }
 
         Ok(Self {
             app_name: module.ident,
@@ -158,14 +163,27 @@ impl App {
                 let args = SharedResourcesArgs::parse(attr.meta)?;
                 let parsed_elements = strct
                     .fields
-                    .iter()
-                    .map(|f| SharedElement {
-                        ident: f
-                            .ident
-                            .clone()
-                            .expect("unnamed struct is not supported for shared resources"),
-                        ty: f.ty.clone(),
-                        priority: 0,
+                    .iter_mut()
+                    .map(|f| {
+                        // remove the #[readonly] attribute
+                        let readonly = f
+                            .attrs
+                            .iter()
+                            .position(|attr| attr.path().is_ident("readonly"));
+                        if let Some(attr_idx) = readonly {
+                            f.attrs.remove(attr_idx);
+                        }
+                        SharedElement {
+                            ident: f
+                                .ident
+                                .clone()
+                                .expect("unnamed struct is not supported for shared resources"),
+                            ty: f.ty.clone(),
+                            priority: 0,
+                            readonly: readonly.is_some(),
+                            accessed_by: BTreeSet::new(),
+                            parts: Vec::new(),
+                        }
                     })
                     .collect();
                 Ok((
@@ -196,14 +214,22 @@ impl App {
             let struct_impl = task_impls.get(&task_struct.ident.to_string());
 
             let tasks = out.entry(args.core).or_insert_with(Vec::new);
-            let mut task = RticTask {
-                args,
-                task_struct,
-                struct_impl: struct_impl.cloned(),
-                user_initializable: false, //initially this is false.
+            // a task for each instance of the type, sharing its definition
+            let instances = match args.instances.len() {
+                0 => vec![None],
+                n => (0..n).map(Some).collect(),
             };
-            task.adjust_task_impl_initialization()?; // adjust the init method and args type of the task trait implementation
-            tasks.push(task);
+            for instance in instances {
+                let mut task = RticTask {
+                    args: instance.map_or_else(|| args.clone(), |idx| args.instance_args(idx)),
+                    task_struct: task_struct.clone(),
+                    struct_impl: struct_impl.cloned(),
+                    user_initializable: false, //initially this is false.
+                    instance,
+                };
+                task.adjust_task_impl_initialization()?; // adjust the init method and args type of the task trait implementation
+                tasks.push(task);
+            }
         }
         Ok(out)
     }
@@ -228,6 +254,7 @@ impl App {
                     task_struct: idle_struct,
                     struct_impl: struct_impl.cloned(),
                     user_initializable: false,
+                    instance: None,
                 };
                 task.adjust_task_impl_initialization()?; // adjust the init method and args type of the task trait implementation
                 Ok((core, task))
@@ -235,6 +262,112 @@ impl App {
             .collect::<Result<HashMap<_, _>, syn::Error>>()
     }
 
+    /// `init` takes either nothing or its context, `fn init(cx: init::Context)`
+    fn init_takes_context(init_fn: &ItemFn) -> syn::Result<bool> {
+        let init_ident = &init_fn.sig.ident;
+        let inputs = init_fn.sig.inputs.iter().collect::<Vec<_>>();
+        match inputs[..] {
+            [] => Ok(false),
+            [FnArg::Typed(input)] => {
+                let expected = quote!(#init_ident::Context).to_string();
+                if input.ty.to_token_stream().to_string() == expected {
+                    Ok(true)
+                } else {
+                    Err(syn::Error::new(
+                        input.ty.span(),
+                        format!(
+                            "The context of `{init_ident}` must be of type `{init_ident}::Context`."
+                        ),
+                    ))
+                }
+            }
+            _ => Err(syn::Error::new(
+                init_fn.sig.inputs.span(),
+                format!(
+                    "`{init_ident}` takes either no argument or its context, `cx: {init_ident}::Context`."
+                ),
+            )),
+        }
+    }
+
+    /// Checks the return type of `init` against the shared resources and the
+    /// tasks initialized by the user on its core:
+    /// - `(Shared, TaskInits)` (or `((), TaskInits)`) when some task is initialized by the user
+    /// - `Shared` otherwise
+    /// - nothing when there is neither
+    ///
+    /// Types are compared by the last segment of their path, so `app::Shared` or `self::Shared`
+    /// are accepted as well.
+    fn check_init_return(app: &SubApp) -> syn::Result<()> {
+        let init_fn = &app.init.body;
+        let shared = app.shared.as_ref().map(|s| &s.strct.ident);
+        let late_tasks = app
+            .tasks
+            .iter()
+            .chain(app.idle.iter())
+            .any(|t| t.user_initializable);
+        let task_inits = format_ident!("TaskInits");
+
+        let matches = match (&init_fn.sig.output, late_tasks) {
+            (ReturnType::Default, false) => shared.is_none(),
+            (ReturnType::Default, true) => false,
+            (ReturnType::Type(_, ty), false) => Self::names_type(ty, shared),
+            (ReturnType::Type(_, ty), true) => match Self::peel_type(ty) {
+                Type::Tuple(tuple) => match tuple.elems.iter().collect::<Vec<_>>()[..] {
+                    [first, second] => {
+                        Self::names_type(first, shared)
+                            && Self::names_type(second, Some(&task_inits))
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+        };
+        if matches {
+            return Ok(());
+        }
+        let expected = match (shared, late_tasks) {
+            (Some(shared), true) => quote!((#shared, TaskInits)),
+            (None, true) => quote!(((), TaskInits)),
+            (Some(shared), false) => quote!(#shared),
+            (None, false) => quote!(()),
+        };
+        let found = match &init_fn.sig.output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => ty.to_token_stream(),
+        };
+        Err(syn::Error::new(
+            init_fn.sig.output.span(),
+            format!(
+                "Expected the return type of `{}` to be `{expected}`, found `{found}`.",
+                init_fn.sig.ident
+            ),
+        ))
+    }
+
+    /// `ty` without the parentheses around it
+    fn peel_type(ty: &Type) -> &Type {
+        match ty {
+            Type::Paren(paren) => Self::peel_type(&paren.elem),
+            Type::Group(group) => Self::peel_type(&group.elem),
+            ty => ty,
+        }
+    }
+
+    /// Whether `ty` is the type named `ident` (whatever path leads to it), or `()` for `None`
+    fn names_type(ty: &Type, ident: Option<&Ident>) -> bool {
+        match (Self::peel_type(ty), ident) {
+            (Type::Path(path), Some(ident)) => {
+                path.qself.is_none()
+                    && path.path.segments.last().is_some_and(|segment| {
+                        segment.ident == *ident && segment.arguments.is_none()
+                    })
+            }
+            (Type::Tuple(tuple), None) => tuple.elems.is_empty(),
+            _ => false,
+        }
+    }
+
     fn construct_inits(
         inits: Vec<(ItemFn, usize)>,
         module_span: Span,
@@ -248,17 +381,7 @@ impl App {
             inits
                 .into_iter()
                 .map(|(mut init_fn, init_attr_idx)| {
-                    // // check return type
-                    // let expected_ret = format!("-> {}", shared_resources.strct.ident);
-                    // let found_ret = format!("{}", init_fn.sig.output.to_token_stream());
-                    // if found_ret != expected_ret {
-                    //     return Err(syn::Error::new(
-                    //         init_fn.span(),
-                    //         format!(
-                    //             "Expected function return type to be {expected_ret}, found {found_ret}."
-                    //         ),
-                    //     ));
-                    // }
+                    let context = Self::init_takes_context(&init_fn)?;
 
                     // remove the [#init]
                     let attr = init_fn.attrs.remove(init_attr_idx);
@@ -269,6 +392,7 @@ impl App {
                             args,
                             ident: init_fn.sig.ident.clone(),
                             body: init_fn,
+                            context,
                         },
                     ))
                 })
@@ -1,3 +1,5 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
 use rticx_core::analysis::Analysis;
 use rticx_core::parser::App;
 
@@ -64,6 +66,10 @@ fn analysis_computes_max_resource_priority() {
     let shared = app.sub_apps[0].shared.as_ref().unwrap();
     let counter = shared.get_field(&quote::format_ident!("counter")).unwrap();
     assert_eq!(counter.priority, 5);
+    assert_eq!(
+        counter.accessed_by.iter().copied().collect::<Vec<_>>(),
+        [2, 5]
+    );
 }
 
 #[test]
@@ -108,8 +114,8 @@ fn analysis_collects_late_resource_tasks() {
             }
 
             #[init]
-            fn init() -> Shared {
-                Shared { counter: 0 }
+            fn init() -> (Shared, TaskInits) {
+                unimplemented!()
             }
 
             #[task(binds = UART, priority = 2)]
@@ -152,8 +158,8 @@ fn analysis_collects_task_traits() {
             }
 
             #[init]
-            fn init() -> Shared {
-                Shared { counter: 0 }
+            fn init() -> (Shared, TaskInits) {
+                unimplemented!()
             }
 
             #[task(binds = UART, priority = 2, task_trait = CustomTrait)]
@@ -171,3 +177,479 @@ fn analysis_collects_task_traits() {
     let analysis = Analysis::run(&mut app).expect("analysis succeeds");
     assert!(analysis.task_traits.iter().any(|t| t == "CustomTrait"));
 }
+
+#[test]
+fn analysis_assigns_trace_ids() {
+    let args = common::multi_core_app_args();
+    let module = common::multi_core_app_module();
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+
+    let ids = &analysis.trace_ids;
+    let tasks: Vec<_> = ids
+        .tasks
+        .iter()
+        .map(|t| (t.id, t.ident.to_string(), t.core))
+        .collect();
+    assert_eq!(
+        tasks,
+        [
+            (0, "UartTask0".to_string(), 0),
+            (1, "UartTask1".to_string(), 1)
+        ]
+    );
+    assert_eq!(ids.task(&quote::format_ident!("UartTask1")), Some(1));
+    assert_eq!(ids.resources.len(), 2);
+    assert_eq!(ids.resources[1].core, 1);
+
+    let report = rticx_core::rticx_trace::report_json(ids);
+    assert!(report.contains(r#"{ "id": 1, "name": "UartTask1", "core": 1, "priority": 3 }"#));
+    assert!(report.contains(r#""ceiling""#));
+}
+
+fn readonly_app_module(
+    core0_task_shared: TokenStream,
+    core1_task_shared: TokenStream,
+) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            #[shared(core = 0)]
+            struct Shared0 {
+                pub counter: u32,
+                #[readonly]
+                pub config: u32,
+            }
+
+            #[shared(core = 1)]
+            struct Shared1 {
+                #[readonly]
+                pub calibration: u32,
+            }
+
+            #[init(core = 0)]
+            fn init0() -> (Shared0, TaskInits) {
+                unimplemented!()
+            }
+
+            #[init(core = 1)]
+            fn init1() -> (Shared1, TaskInits) {
+                unimplemented!()
+            }
+
+            #[task(binds = UART0, priority = 2, core = 0, shared = #core0_task_shared)]
+            struct UartTask0;
+
+            #[task(binds = UART1, priority = 2, core = 1, shared = #core1_task_shared)]
+            struct UartTask1;
+        }
+    }
+}
+
+#[test]
+fn analysis_resolves_readonly_resources() {
+    let args = common::multi_core_app_args();
+    let module = readonly_app_module(quote!([counter, &config]), quote!([&config, &calibration]));
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+
+    let access = |task: &str| {
+        analysis.readonly_access[&format_ident!("{task}")]
+            .iter()
+            .map(|r| format!("{}.{}", r.shared_static, r.ident))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(access("UartTask0"), ["SHARED0.config"]);
+    // core 1 reads its own resources and those of core 0
+    assert_eq!(
+        access("UartTask1"),
+        ["SHARED0.config", "SHARED1.calibration"]
+    );
+
+    // read-only resources are never locked, their ceiling is not raised
+    let shared = app.sub_apps[0].shared.as_ref().unwrap();
+    assert_eq!(
+        shared.get_field(&format_ident!("config")).unwrap().priority,
+        0
+    );
+}
+
+#[test]
+fn analysis_rejects_invalid_readonly_access() {
+    let analysis_error = |core0_task_shared, core1_task_shared| {
+        let args = common::multi_core_app_args();
+        let module = readonly_app_module(core0_task_shared, core1_task_shared);
+        let mut app = App::parse(args, module).expect("valid app");
+        Analysis::run(&mut app)
+            .expect_err("invalid access should fail")
+            .to_string()
+    };
+
+    // locking a read-only resource
+    let err = analysis_error(quote!([config]), quote!([]));
+    assert!(err.contains("`config` is `#[readonly]`"), "{err}");
+    // reading a resource that is not read-only
+    let err = analysis_error(quote!([&counter]), quote!([]));
+    assert!(err.contains("only `#[readonly]` resources"), "{err}");
+    // core 1 may not be initialized when the tasks of core 0 run
+    let err = analysis_error(quote!([&calibration]), quote!([]));
+    assert!(err.contains("of core 1"), "{err}");
+}
+
+fn resource_parts_app_module(extra_task: TokenStream) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            pub struct Channel {
+                pub rx: u8,
+                pub tx: u8,
+            }
+
+            pub struct Motor {
+                pub state: u8,
+                pub speed: u16,
+            }
+
+            #[shared]
+            struct Shared {
+                pub channels: [Channel; 2],
+                pub motor: Motor,
+                #[readonly]
+                pub config: [u8; 2],
+            }
+
+            #[init]
+            fn init() -> (Shared, TaskInits) {
+                todo!()
+            }
+
+            #[task(binds = CH0, priority = 1, shared = [channels[0]])]
+            struct Channel0;
+
+            #[task(binds = CH1, priority = 3, shared = [channels[1].rx])]
+            struct Channel1Rx;
+
+            #[task(binds = CONTROL, priority = 2, shared = [channels[1], motor.state])]
+            struct Control;
+
+            #[task(binds = MONITOR, priority = 4, shared = [motor])]
+            struct Monitor;
+
+            #extra_task
+        }
+    }
+}
+
+#[test]
+fn analysis_computes_resource_part_ceilings() {
+    let args = common::single_core_app_args();
+    let mut app = App::parse(args, resource_parts_app_module(quote!())).expect("valid app");
+    let _ = Analysis::run(&mut app).expect("analysis succeeds");
+
+    let shared = app.sub_apps[0].shared.as_ref().unwrap();
+    let ceilings = |resource: &str| {
+        let resource = shared.get_field(&format_ident!("{resource}")).unwrap();
+        let parts = resource
+            .parts
+            .iter()
+            .map(|part| {
+                let ty = &part.ty;
+                (
+                    part.path.to_string(),
+                    quote!(#ty).to_string(),
+                    part.priority,
+                )
+            })
+            .collect::<Vec<_>>();
+        (resource.priority, parts)
+    };
+
+    // `channels[0]` is disjoint from `channels[1]`, its ISR is not blocked by the others
+    assert_eq!(
+        ceilings("channels"),
+        (
+            3,
+            vec![
+                ("channels[0]".to_string(), "Channel".to_string(), 1),
+                ("channels[1].rx".to_string(), "u8".to_string(), 3),
+                ("channels[1]".to_string(), "Channel".to_string(), 3),
+            ]
+        )
+    );
+    // the whole `motor` is locked at priority 4, so are its parts
+    assert_eq!(
+        ceilings("motor"),
+        (4, vec![("motor.state".to_string(), "u8".to_string(), 4)])
+    );
+}
+
+#[test]
+fn analysis_rejects_invalid_resource_parts() {
+    let analysis_error = |shared: TokenStream| {
+        let args = common::single_core_app_args();
+        let module = resource_parts_app_module(quote! {
+            #[task(binds = OTHER, priority = 1, shared = #shared)]
+            struct Other;
+        });
+        let mut app = App::parse(args, module).expect("valid app");
+        Analysis::run(&mut app)
+            .expect_err("invalid part should fail")
+            .to_string()
+    };
+
+    let err = analysis_error(quote!([channels[2]]));
+    assert!(err.contains("out of the bounds of `channels`"), "{err}");
+    let err = analysis_error(quote!([motor[0]]));
+    assert!(err.contains("`motor` is not an array"), "{err}");
+    let err = analysis_error(quote!([channels[0].torque]));
+    assert!(err.contains("with a field `torque`"), "{err}");
+    let err = analysis_error(quote!([config[0]]));
+    assert!(err.contains("`config` is `#[readonly]`"), "{err}");
+    let err = analysis_error(quote!([missing[0]]));
+    assert!(err.contains("`missing` was not found"), "{err}");
+}
+
+#[test]
+fn analysis_rejects_part_named_like_a_resource() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub channels: [u8; 2],
+                pub channels_0: u8,
+            }
+
+            #[init]
+            fn init() -> (Shared, TaskInits) {
+                todo!()
+            }
+
+            #[task(binds = CH0, priority = 1, shared = [channels[0]])]
+            struct Channel0;
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let err = Analysis::run(&mut app)
+        .expect_err("colliding part should fail")
+        .to_string();
+    assert!(
+        err.contains("`channels[0]` is named `channels_0`, like a field of `Shared`"),
+        "{err}"
+    );
+}
+
+fn threshold_app_module(idle_args: TokenStream) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> (Shared, TaskInits) {
+                unimplemented!()
+            }
+
+            #[task(binds = UART, priority = 2, threshold = 4, shared = [counter])]
+            struct UartTask;
+
+            #[task(binds = TIMER, priority = 3, shared = [counter])]
+            struct TimerTask;
+
+            #[task(binds = ADC, priority = 4)]
+            struct AdcTask;
+
+            #[task(binds = DMA, priority = 5)]
+            struct DmaTask;
+
+            #[idle #idle_args]
+            struct Idle;
+        }
+    }
+}
+
+#[test]
+fn analysis_accounts_for_preemption_thresholds() {
+    let args = common::single_core_app_args();
+    let mut app = App::parse(args, threshold_app_module(quote!())).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+
+    // `UartTask` locks `counter` from its threshold
+    let shared = app.sub_apps[0].shared.as_ref().unwrap();
+    let counter = shared.get_field(&format_ident!("counter")).unwrap();
+    assert_eq!(counter.priority, 4);
+    assert_eq!(
+        counter.accessed_by.iter().copied().collect::<Vec<_>>(),
+        [3, 4]
+    );
+
+    let thresholds = &analysis.sub_analysis[0].preemption_thresholds;
+    assert_eq!(thresholds.len(), 1);
+    assert_eq!(thresholds[0].task.to_string(), "UartTask");
+    assert_eq!((thresholds[0].priority, thresholds[0].threshold), (2, 4));
+    let blocked: Vec<_> = thresholds[0]
+        .blocked_tasks
+        .iter()
+        .map(|task| task.to_string())
+        .collect();
+    assert_eq!(blocked, ["TimerTask", "AdcTask"]);
+}
+
+#[test]
+fn analysis_rejects_invalid_thresholds() {
+    let args = common::single_core_app_args();
+    let mut app =
+        App::parse(args, threshold_app_module(quote!((threshold = 2)))).expect("valid app");
+    let err = Analysis::run(&mut app).expect_err("idle threshold should fail");
+    assert!(
+        err.to_string()
+            .contains("can't have a preemption threshold"),
+        "{err}"
+    );
+
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() -> ((), TaskInits) { unimplemented!() }
+
+            #[task(binds = UART, priority = 3, threshold = 2)]
+            struct UartTask;
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let err = Analysis::run(&mut app).expect_err("threshold below priority should fail");
+    assert!(err.to_string().contains("below its priority 3"), "{err}");
+}
+
+fn multi_bound_app_module(extra_task: TokenStream) -> syn::ItemMod {
+    syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() {}
+
+            #[task(binds = [EXTI0, EXTI1, EXTI2], priority = 2)]
+            struct ButtonTask;
+
+            impl RticTask for ButtonTask {
+                type InitArgs = ();
+                fn init(_: ()) -> Self { ButtonTask }
+                fn exec(&mut self) {}
+            }
+
+            #extra_task
+        }
+    }
+}
+
+#[test]
+fn analysis_collects_every_interrupt_of_multi_bound_tasks() {
+    let args: TokenStream = quote!(device = mypac);
+    let mut app = App::parse(args, multi_bound_app_module(quote!())).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let used_irqs: Vec<_> = analysis.sub_analysis[0]
+        .used_irqs
+        .iter()
+        .map(|(irq, prio)| (irq.to_string(), *prio))
+        .collect();
+    assert_eq!(
+        used_irqs,
+        [
+            ("EXTI0".to_string(), 2),
+            ("EXTI1".to_string(), 2),
+            ("EXTI2".to_string(), 2)
+        ]
+    );
+}
+
+#[test]
+fn analysis_rejects_interrupts_bound_twice() {
+    let args: TokenStream = quote!(device = mypac);
+    let module = multi_bound_app_module(quote! {
+        #[task(binds = EXTI1, priority = 3)]
+        struct OtherTask;
+
+        impl RticTask for OtherTask {
+            type InitArgs = ();
+            fn init(_: ()) -> Self { OtherTask }
+            fn exec(&mut self) {}
+        }
+    });
+    let mut app = App::parse(args, module).expect("valid app");
+    let err = Analysis::run(&mut app).expect_err("interrupt bound twice should fail");
+    assert!(err.to_string().contains("`EXTI1` of `OtherTask`"));
+    assert!(err.to_string().contains("bound to `ButtonTask`"));
+
+    let args: TokenStream = quote!(device = mypac);
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() -> ((), TaskInits) { unimplemented!() }
+
+            #[task(binds = [EXTI0, EXTI0])]
+            struct ButtonTask;
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let err = Analysis::run(&mut app).expect_err("interrupt listed twice should fail");
+    assert!(err.to_string().contains("`EXTI0` of `ButtonTask`"));
+}
+
+#[test]
+fn analysis_runs_task_instances_at_their_priority() {
+    let args: TokenStream = quote!(device = mypac);
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> (Shared, TaskInits) {
+                todo!()
+            }
+
+            #[task(instances = [(UART0, 2), (UART1, 4)], shared = [counter])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                type InitArgs = u8;
+                fn init(_: u8) -> Self { UartTask }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+
+    let sub_analysis = &analysis.sub_analysis[0];
+    let used_irqs: Vec<_> = sub_analysis
+        .used_irqs
+        .iter()
+        .map(|(irq, prio)| (irq.to_string(), *prio))
+        .collect();
+    assert_eq!(
+        used_irqs,
+        [("UART0".to_string(), 2), ("UART1".to_string(), 4)]
+    );
+    // the ceiling accounts for the priority of every instance
+    let shared = app.sub_apps[0].shared.as_ref().expect("shared resources");
+    let counter = shared
+        .get_field(&format_ident!("counter"))
+        .expect("counter");
+    assert_eq!(counter.priority, 4);
+    // each instance is initialized with its own arguments
+    let late: Vec<_> = sub_analysis
+        .late_resource_tasks
+        .iter()
+        .map(|t| (t.name_snakecase().to_string(), t.task_ty.to_string()))
+        .collect();
+    assert_eq!(
+        late,
+        [
+            ("uart_task_uart0".to_string(), "UartTask".to_string()),
+            ("uart_task_uart1".to_string(), "UartTask".to_string())
+        ]
+    );
+}
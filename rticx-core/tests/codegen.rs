@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use rticx_core::analysis::Analysis;
 use rticx_core::codegen::CodeGen;
 use rticx_core::mock_backend::MockCoreBackend;
@@ -28,6 +28,88 @@ fn assert_section_present(generated: &str, expected: TokenStream, label: &str) {
     );
 }
 
+/// Expected call of a hardware task `exec` in its interrupt handler, checking, accounting and
+/// tracing the job of the task when the SRP checks, the runtime statistics and the tracing hooks are
+/// enabled
+fn expected_dispatch_call(task_handle: &str, task: &str, priority: u16, core: u32) -> TokenStream {
+    let task_handle = format_ident!("{task_handle}");
+    let call = quote! { unsafe { #task_handle . get_mut () . exec () } ; };
+    let srp = rticx_core::rticx_srp::ENABLED;
+    let stats = rticx_core::rticx_stats::ENABLED;
+    let trace = rticx_core::rticx_trace::ENABLED;
+    if !srp && !stats && !trace {
+        return call;
+    }
+    let task_ident = format_ident!("{task}");
+    let task_stats = format_ident!("__rticx_stats_{task}");
+    let core_stats = format_ident!("__rticx_stats_core{core}");
+    let core_srp = format_ident!("__rticx_srp_core{core}");
+    let srp_handler =
+        srp.then(|| quote! { let __rticx_srp_handler = #core_srp . handler (#task) ; });
+    let srp_task = srp
+        .then(|| quote! { let __rticx_srp_task = #core_srp . task (#task , #priority , #priority , true) ; });
+    let stats_job =
+        stats.then(|| quote! { let __rticx_stats_job = #core_stats . job (& #task_stats) ; });
+    let trace_job = trace.then(|| {
+        quote! { let __rticx_trace_job = rticx_trace :: task_enter (rticx_trace :: task_ids :: #task_ident) ; }
+    });
+    quote! {
+        #srp_handler
+        {
+            #srp_task
+            #stats_job
+            #trace_job
+            #call
+        }
+    }
+}
+
+/// Expected wrapping of the closure of a `lock` function when the tracing hooks or the SRP checks
+/// are enabled
+fn expected_lock_hook(core: u32, resource: &str, ceiling: u16) -> Option<TokenStream> {
+    expected_part_lock_hook(core, resource, resource, ceiling)
+}
+
+/// Same as [expected_lock_hook], for the `part` of `resource` locked on its own. The lock is traced
+/// as the whole resource.
+fn expected_part_lock_hook(
+    core: u32,
+    resource: &str,
+    part: &str,
+    ceiling: u16,
+) -> Option<TokenStream> {
+    let core_srp = format_ident!("__rticx_srp_core{core}");
+    let core = format_ident!("core{core}");
+    let resource_name = part;
+    let resource = format_ident!("{resource}");
+    let trace = rticx_core::rticx_trace::ENABLED.then(|| {
+        quote! {
+            let f = move | resource : & mut Self :: ResourceType | {
+                rticx_trace :: lock_acquire (rticx_trace :: resource_ids :: #core :: #resource) ;
+                let r = f (resource) ;
+                rticx_trace :: lock_release (rticx_trace :: resource_ids :: #core :: #resource) ;
+                r
+            } ;
+        }
+    });
+    let srp = rticx_core::rticx_srp::ENABLED.then(|| {
+        quote! {
+            let __rticx_srp_lock = #core_srp . lock (#resource_name , self . task_priority , #ceiling) ;
+            let f = | resource : & mut Self :: ResourceType | {
+                let __rticx_srp_raised = __rticx_srp_lock . raise () ;
+                f (resource)
+            } ;
+        }
+    });
+    (trace.is_some() || srp.is_some()).then(|| quote! { #trace #srp })
+}
+
+/// Expected start of the statistics window at the end of the initialization of `core`
+fn expected_stats_window(core: u32) -> Option<TokenStream> {
+    let core_stats = format_ident!("__rticx_stats_core{core}");
+    rticx_core::rticx_stats::ENABLED.then(|| quote! { #core_stats . reset_window () ; })
+}
+
 #[test]
 fn codegen_expands_single_core_app() {
     let args = common::single_core_app_args();
@@ -75,11 +157,23 @@ fn codegen_expands_single_core_app() {
         "interrupt-free function signature",
     );
 
+    // ---- ceiling lock function, falling back to the critical section by default ----
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn __rticx_ceiling_lock < F , R > (ceiling : u16 , f : F) -> R where F : FnOnce () -> R , {
+                let _ = ceiling ;
+                __rticx_interrupt_free (f)
+            }
+        },
+        "ceiling lock function",
+    );
+
     // ---- shared resources definition for core 0 ----
     assert_section_present(
         &generated,
         quote! {
-            static mut SHARED : core :: mem :: MaybeUninit < Shared > = core :: mem :: MaybeUninit :: uninit () ;
+            static SHARED : rticx_cell :: RacyCell < Shared > = rticx_cell :: RacyCell :: uninit ("SHARED") ;
             struct Shared { pub counter : u32 , }
         },
         "shared resources definition",
@@ -89,7 +183,7 @@ fn codegen_expands_single_core_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut UART_TASK : core :: mem :: MaybeUninit < UartTask > = core :: mem :: MaybeUninit :: uninit () ;
+            static UART_TASK : rticx_cell :: RacyCell < UartTask > = rticx_cell :: RacyCell :: uninit ("UART_TASK") ;
             struct UartTask ;
         },
         "task static + struct",
@@ -139,12 +233,14 @@ fn codegen_expands_single_core_app() {
         quote! { pub struct __counter_mutex { # [doc (hidden)] task_priority : u16 , } },
         "resource proxy struct",
     );
+    let lock_hook = expected_lock_hook(0, "counter", 2);
     assert_section_present(
         &generated,
         quote! {
             impl RticMutex for __counter_mutex {
                 type ResourceType = u32 ;
                 fn lock < R > (& mut self , f : impl FnOnce (& mut Self :: ResourceType) -> R) -> R {
+                    #lock_hook
                     f (unsafe { & mut * resource_ptr })
                 }
             }
@@ -163,13 +259,14 @@ fn codegen_expands_single_core_app() {
     );
 
     // ---- hardware-task to interrupt binding ----
+    let dispatch_uart = expected_dispatch_call("UART_TASK", "UartTask", 2, 0);
     assert_section_present(
         &generated,
         quote! {
             # [allow (non_snake_case)]
             # [unsafe (no_mangle)]
             fn UART () {
-                unsafe { UART_TASK . assume_init_mut () . exec () } ;
+                #dispatch_uart
             }
         },
         "hw task to irq binding",
@@ -194,6 +291,7 @@ fn codegen_expands_single_core_app() {
     );
 
     // ---- entry point ----
+    let stats_window_0 = expected_stats_window(0);
     assert_section_present(
         &generated,
         quote! {
@@ -203,10 +301,11 @@ fn codegen_expands_single_core_app() {
                     let shared_resources = init () ;
                     unsafe { SHARED . write (shared_resources) ; }
                     unsafe { UART_TASK . write (UartTask :: init (())) ; }
+                    #stats_window_0
                 }) ;
                 unsafe {
                     IDLE . write (Idle :: init (())) ;
-                    IDLE . assume_init_mut () . exec () ;
+                    IDLE . get_mut () . exec () ;
                 }
             }
         },
@@ -231,7 +330,7 @@ fn codegen_expands_single_core_app() {
     );
     assert_section_present(
         &generated,
-        quote! { static mut IDLE : core :: mem :: MaybeUninit < Idle > = core :: mem :: MaybeUninit :: uninit () ; struct Idle ; },
+        quote! { static IDLE : rticx_cell :: RacyCell < Idle > = rticx_cell :: RacyCell :: uninit ("IDLE") ; struct Idle ; },
         "idle task definition",
     );
 }
@@ -276,7 +375,7 @@ fn codegen_expands_multi_core_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut SHARED0 : core :: mem :: MaybeUninit < Shared0 > = core :: mem :: MaybeUninit :: uninit () ;
+            static SHARED0 : rticx_cell :: RacyCell < Shared0 > = rticx_cell :: RacyCell :: uninit ("SHARED0") ;
             struct Shared0 { pub counter : u32 , }
         },
         "core0 shared resources definition",
@@ -284,7 +383,7 @@ fn codegen_expands_multi_core_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut UART_TASK0 : core :: mem :: MaybeUninit < UartTask0 > = core :: mem :: MaybeUninit :: uninit () ;
+            static UART_TASK0 : rticx_cell :: RacyCell < UartTask0 > = rticx_cell :: RacyCell :: uninit ("UART_TASK0") ;
             struct UartTask0 ;
         },
         "core0 task static + struct",
@@ -304,13 +403,14 @@ fn codegen_expands_multi_core_app() {
         quote! { impl UartTask0 { pub const fn current_core () -> __rticx__internal__Core0 { unsafe { __rticx__internal__Core0 :: new () } } } },
         "core0 task current_core function",
     );
+    let dispatch_uart0 = expected_dispatch_call("UART_TASK0", "UartTask0", 2, 0);
     assert_section_present(
         &generated,
         quote! {
             # [allow (non_snake_case)]
             # [unsafe (no_mangle)]
             fn UART0 () {
-                unsafe { UART_TASK0 . assume_init_mut () . exec () } ;
+                #dispatch_uart0
             }
         },
         "core0 hw task to irq binding",
@@ -325,6 +425,7 @@ fn codegen_expands_multi_core_app() {
         quote! { pub use core0_type_mod :: __rticx__internal__Core0 ; },
         "core0 type re-export",
     );
+    let stats_window_0 = expected_stats_window(0);
     assert_section_present(
         &generated,
         quote! {
@@ -334,10 +435,11 @@ fn codegen_expands_multi_core_app() {
                     let shared_resources = init0 () ;
                     unsafe { SHARED0 . write (shared_resources) ; }
                     unsafe { UART_TASK0 . write (UartTask0 :: init (())) ; }
+                    #stats_window_0
                 }) ;
                 unsafe {
                     IDLE0 . write (Idle0 :: init (())) ;
-                    IDLE0 . assume_init_mut () . exec () ;
+                    IDLE0 . get_mut () . exec () ;
                 }
             }
         },
@@ -348,7 +450,7 @@ fn codegen_expands_multi_core_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut SHARED1 : core :: mem :: MaybeUninit < Shared1 > = core :: mem :: MaybeUninit :: uninit () ;
+            static SHARED1 : rticx_cell :: RacyCell < Shared1 > = rticx_cell :: RacyCell :: uninit ("SHARED1") ;
             struct Shared1 { pub counter : u32 , }
         },
         "core1 shared resources definition",
@@ -356,7 +458,7 @@ fn codegen_expands_multi_core_app() {
     assert_section_present(
         &generated,
         quote! {
-            static mut UART_TASK1 : core :: mem :: MaybeUninit < UartTask1 > = core :: mem :: MaybeUninit :: uninit () ;
+            static UART_TASK1 : rticx_cell :: RacyCell < UartTask1 > = rticx_cell :: RacyCell :: uninit ("UART_TASK1") ;
             struct UartTask1 ;
         },
         "core1 task static + struct",
@@ -376,13 +478,14 @@ fn codegen_expands_multi_core_app() {
         quote! { impl UartTask1 { pub const fn current_core () -> __rticx__internal__Core1 { unsafe { __rticx__internal__Core1 :: new () } } } },
         "core1 task current_core function",
     );
+    let dispatch_uart1 = expected_dispatch_call("UART_TASK1", "UartTask1", 3, 1);
     assert_section_present(
         &generated,
         quote! {
             # [allow (non_snake_case)]
             # [unsafe (no_mangle)]
             fn UART1 () {
-                unsafe { UART_TASK1 . assume_init_mut () . exec () } ;
+                #dispatch_uart1
             }
         },
         "core1 hw task to irq binding",
@@ -393,6 +496,7 @@ fn codegen_expands_multi_core_app() {
         "core1 type re-export",
     );
     // The second entry point uses the `main_1` suffix produced by `MockCoreBackend::entry_name`.
+    let stats_window_1 = expected_stats_window(1);
     assert_section_present(
         &generated,
         quote! {
@@ -402,10 +506,11 @@ fn codegen_expands_multi_core_app() {
                     let shared_resources = init1 () ;
                     unsafe { SHARED1 . write (shared_resources) ; }
                     unsafe { UART_TASK1 . write (UartTask1 :: init (())) ; }
+                    #stats_window_1
                 }) ;
                 unsafe {
                     IDLE1 . write (Idle1 :: init (())) ;
-                    IDLE1 . assume_init_mut () . exec () ;
+                    IDLE1 . get_mut () . exec () ;
                 }
             }
         },
@@ -425,3 +530,800 @@ fn codegen_expands_multi_core_app() {
         "implements_rtic_idle_task check fn",
     );
 }
+
+#[test]
+fn codegen_generates_readonly_resource_access() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+                #[readonly]
+                pub config: [u8; 4],
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0, config: [0; 4] }
+            }
+
+            #[task(binds = UART, priority = 2, shared = [counter, &config])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! { struct Shared { pub counter : u32 , pub config : [u8 ; 4] , } },
+        "shared struct without the readonly marker",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            const _ : fn () = || {
+                #[diagnostic :: on_unimplemented (message = "read-only resource `config` is read by reference from any task, its type `{Self}` must be `Sync`" , label = "not `Sync`")]
+                trait BoundCheck { }
+                impl < T : Sync + ? Sized > BoundCheck for T { }
+                fn bound_check < T : BoundCheck + ? Sized > () { }
+                bound_check :: < [u8 ; 4] > () ;
+            } ;
+        },
+        "Sync check of the read-only resource",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub struct __uart_task_shared_resources {
+                pub counter : __counter_mutex ,
+                pub config : & 'static [u8 ; 4] ,
+            }
+        },
+        "task shared resources struct",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            Self {
+                counter : __counter_mutex :: new (priority) ,
+                config : unsafe { & (* SHARED . as_mut_ptr ()) . config } ,
+            }
+        },
+        "read-only resource reference",
+    );
+    assert!(
+        !generated.contains("__config_mutex"),
+        "read-only resources have no lock proxy"
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn codegen_generates_runtime_stats() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() {}
+
+            #[task(binds = UART, priority = 2)]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            // not bound to an interrupt, e.g. a software task called by a dispatcher
+            #[task(priority = 1)]
+            struct Worker;
+
+            impl RticTask for Worker {
+                fn init() -> Self {
+                    Worker
+                }
+                fn exec(&mut self) {
+                    work();
+                }
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! { pub mod rticx_stats },
+        "rticx_stats module",
+    );
+    assert_section_present(
+        &generated,
+        quote! { static __rticx_stats_Worker : rticx_stats :: TaskStats = rticx_stats :: TaskStats :: new ("Worker") ; },
+        "task statistics",
+    );
+    assert_section_present(
+        &generated,
+        quote! { impl Worker { # [doc = r" Runtime statistics of this task"] pub fn stats () -> & 'static rticx_stats :: TaskStats { & __rticx_stats_Worker } } },
+        "task stats API",
+    );
+    assert_section_present(
+        &generated,
+        quote! { let __rticx_stats_job = __rticx_stats_core0 . job (& __rticx_stats_Worker) ; },
+        "unbound task exec instrumentation",
+    );
+    assert_section_present(
+        &generated,
+        expected_dispatch_call("UART_TASK", "UartTask", 2, 0),
+        "bound task handler instrumentation",
+    );
+    assert_section_present(
+        &generated,
+        quote! { static __rticx_stats_core0 : rticx_stats :: CoreStats = rticx_stats :: CoreStats :: new (& [& __rticx_stats_UartTask , & __rticx_stats_Worker] , __rticx_stats_cycles_core0) ; },
+        "core statistics",
+    );
+    assert_section_present(
+        &generated,
+        quote! { impl __rticx__internal__Core0 { # [doc = r" Runtime statistics of this core and its tasks"] pub fn stats (& self) -> & 'static rticx_stats :: CoreStats { & __rticx_stats_core0 } } },
+        "core stats API",
+    );
+    assert_section_present(
+        &generated,
+        quote! { pub fn __rticx_stats_cycles_core0 () -> u32 },
+        "cycle counter function",
+    );
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn codegen_generates_tracing_hooks() {
+    let args = quote!(device = mypac, tracer = crate::Recorder);
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0 }
+            }
+
+            #[task(binds = UART, priority = 2, shared = [counter])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[task(priority = 1)]
+            struct Worker;
+
+            impl RticTask for Worker {
+                fn init() -> Self {
+                    Worker
+                }
+                fn exec(&mut self) {
+                    work();
+                }
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! { type __rticx_Tracer = crate :: Recorder ; },
+        "tracer of the application",
+    );
+    assert_section_present(
+        &generated,
+        quote! { pub mod rticx_trace },
+        "rticx_trace module",
+    );
+    assert_section_present(
+        &generated,
+        quote! { pub mod task_ids { pub const UartTask : u16 = 0u16 ; pub const Worker : u16 = 1u16 ; } },
+        "task identifiers",
+    );
+    assert_section_present(
+        &generated,
+        quote! { pub mod resource_ids { pub mod core0 { pub const counter : u16 = 0u16 ; } } },
+        "resource identifiers",
+    );
+    assert_section_present(
+        &generated,
+        quote! { let __rticx_trace_job = rticx_trace :: task_enter (rticx_trace :: task_ids :: Worker) ; },
+        "unbound task exec instrumentation",
+    );
+    assert_section_present(
+        &generated,
+        expected_dispatch_call("UART_TASK", "UartTask", 2, 0),
+        "bound task handler instrumentation",
+    );
+    assert_section_present(
+        &generated,
+        expected_lock_hook(0, "counter", 2).expect("tracing is enabled"),
+        "lock instrumentation",
+    );
+}
+
+#[cfg(feature = "srp-assertions")]
+#[test]
+fn codegen_generates_srp_checks() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0 }
+            }
+
+            #[task(binds = UART, priority = 2, shared = [counter])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[task(priority = 3, shared = [counter])]
+            struct Worker;
+
+            impl RticTask for Worker {
+                fn init() -> Self {
+                    Worker
+                }
+                fn exec(&mut self) {
+                    work();
+                }
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(&generated, quote! { pub mod rticx_srp }, "rticx_srp module");
+    assert_section_present(
+        &generated,
+        quote! { pub fn __rticx_srp_hw_ceiling_core0 () -> Option < u16 > },
+        "hardware ceiling function",
+    );
+    assert_section_present(
+        &generated,
+        quote! { static __rticx_srp_core0 : rticx_srp :: SystemCeiling = rticx_srp :: SystemCeiling :: new (__rticx_srp_hw_ceiling_core0) ; },
+        "core SRP state",
+    );
+    assert_section_present(
+        &generated,
+        quote! { let __rticx_srp_task = __rticx_srp_core0 . task ("Worker" , 3u16 , 3u16 , false) ; },
+        "unbound task exec check",
+    );
+    assert_section_present(
+        &generated,
+        expected_dispatch_call("UART_TASK", "UartTask", 2, 0),
+        "bound task handler check",
+    );
+    // the ceiling of `counter` is the highest priority of the tasks sharing it
+    assert_section_present(
+        &generated,
+        expected_lock_hook(0, "counter", 3).expect("SRP checks are enabled"),
+        "lock check",
+    );
+}
+
+#[test]
+fn codegen_checks_resources_locked_by_several_priorities_are_send() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+                pub buffer: [u8; 4],
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0, buffer: [0; 4] }
+            }
+
+            #[task(binds = UART, priority = 2, shared = [counter, buffer])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[idle(shared = [counter])]
+            struct Idle;
+
+            impl RticIdleTask for Idle {
+                fn init() -> Self {
+                    Idle
+                }
+                fn exec(&mut self) -> ! {
+                    loop {}
+                }
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! {
+            const _ : fn () = || {
+                #[diagnostic :: on_unimplemented (message = "shared resource `counter` is locked by tasks of priorities 0, 2, its type `{Self}` must be `Send`" , label = "not `Send`")]
+                trait BoundCheck { }
+                impl < T : Send + ? Sized > BoundCheck for T { }
+                fn bound_check < T : BoundCheck + ? Sized > () { }
+                bound_check :: < u32 > () ;
+            } ;
+        },
+        "Send check of the resource shared with idle",
+    );
+    // only locked at priority 2
+    assert!(!generated.contains("shared resource `buffer`"));
+}
+
+#[test]
+fn codegen_generates_resource_part_proxies() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            pub struct Buffer {
+                pub data: [u8; 8],
+                pub len: usize,
+            }
+
+            #[shared]
+            struct Shared {
+                pub channels: [Buffer; 2],
+            }
+
+            #[init]
+            fn init() -> Shared {
+                todo!()
+            }
+
+            #[task(binds = CH0, priority = 1, shared = [channels[0]])]
+            struct Channel0;
+
+            impl RticTask for Channel0 {
+                fn init() -> Self {
+                    Channel0
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[task(binds = CH1, priority = 2, shared = [channels[1].len])]
+            struct Channel1;
+
+            impl RticTask for Channel1 {
+                fn init() -> Self {
+                    Channel1
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert!(
+        generated.contains("impl RticMutex for __channels_0_mutex { type ResourceType = Buffer ;"),
+        "proxy of an array element"
+    );
+    assert!(
+        generated
+            .contains("impl RticMutex for __channels_1_len_mutex { type ResourceType = usize ;"),
+        "proxy of a field of an array element"
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub struct __channel0_shared_resources {
+                pub channels_0 : __channels_0_mutex ,
+            }
+        },
+        "task shared resources struct",
+    );
+    // each channel only blocks its own task
+    if let Some(lock_hook) = expected_part_lock_hook(0, "channels", "channels[0]", 1) {
+        assert_section_present(&generated, lock_hook, "lock of an array element");
+    }
+    if let Some(lock_hook) = expected_part_lock_hook(0, "channels", "channels[1].len", 2) {
+        assert_section_present(&generated, lock_hook, "lock of a field of an array element");
+    }
+}
+
+#[test]
+fn codegen_runs_tasks_at_their_threshold() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0 }
+            }
+
+            #[task(binds = UART, priority = 2, threshold = 4, shared = [counter])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[task(binds = TIMER, priority = 3, shared = [counter])]
+            struct TimerTask;
+
+            impl RticTask for TimerTask {
+                fn init() -> Self {
+                    TimerTask
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert!(
+        generated.contains("__rticx_ceiling_lock (4u16 , || {"),
+        "the handler raises the system ceiling to the threshold"
+    );
+    assert!(
+        !generated.contains("__rticx_ceiling_lock (3u16"),
+        "tasks without a threshold run at their priority"
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn shared (& self) -> __uart_task_shared_resources {
+                const TASK_PRIORITY : u16 = 4u16 ;
+                __uart_task_shared_resources :: new (TASK_PRIORITY)
+            }
+        },
+        "proxies locking from the threshold",
+    );
+    if rticx_core::rticx_srp::ENABLED {
+        assert_section_present(
+            &generated,
+            quote! { let __rticx_srp_task = __rticx_srp_core0 . task ("UartTask" , 2u16 , 4u16 , true) ; },
+            "job checked at its threshold",
+        );
+    }
+}
+
+#[test]
+fn codegen_generates_interrupt_control_of_bound_tasks() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() {}
+
+            #[task(binds = UART, priority = 2)]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self {
+                    UartTask
+                }
+                fn exec(&mut self) {}
+            }
+
+            #[task(priority = 1)]
+            struct Unbound;
+
+            impl RticTask for Unbound {
+                fn init() -> Self {
+                    Unbound
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! {
+            impl UartTask {
+                #[doc = " Pends `UART`, the task then runs as if its peripheral requested it"]
+                #[inline]
+                pub fn pend () { mock_pend (mypac :: Interrupt :: UART) }
+                #[doc = " Clears a pending request of `UART`"]
+                #[inline]
+                pub fn unpend () { mock_unpend (mypac :: Interrupt :: UART) }
+                #[doc = " Whether `UART` is pending"]
+                #[inline]
+                pub fn is_pending () -> bool { mock_is_pending (mypac :: Interrupt :: UART) }
+                #[doc = " Unmasks `UART`, its pending requests then run the task"]
+                #[inline]
+                pub fn enable () { mock_enable (mypac :: Interrupt :: UART) }
+                #[doc = " Masks `UART`, its requests stay pending until it is enabled again"]
+                #[inline]
+                pub fn disable () { mock_disable (mypac :: Interrupt :: UART) }
+            }
+        },
+        "interrupt control of the bound task",
+    );
+    // tasks without an interrupt have nothing to pend
+    assert_eq!(generated.matches("pub fn pend ()").count(), 1);
+}
+
+#[test]
+fn codegen_dispatches_multi_bound_tasks_from_each_interrupt() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() {}
+
+            #[task(binds = [EXTI0, EXTI1], priority = 2)]
+            struct ButtonTask;
+
+            impl RticTask for ButtonTask {
+                fn init() -> Self {
+                    ButtonTask
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! {
+            # [allow (non_camel_case_types)]
+            # [derive (Debug , Clone , Copy , PartialEq , Eq)]
+            pub enum InterruptSource { EXTI0 , EXTI1 , }
+        },
+        "interrupt source enum",
+    );
+    // one handler per interrupt, both running the same task instance
+    for irq in ["EXTI0", "EXTI1"] {
+        let irq = format_ident!("{irq}");
+        assert_section_present(
+            &generated,
+            quote! { # [unsafe (no_mangle)] fn #irq () },
+            "handler of each interrupt",
+        );
+        assert_section_present(
+            &generated,
+            quote! { unsafe { BUTTON_TASK . get_mut () . exec_from (InterruptSource :: #irq) } ; },
+            "task executed with its interrupt source",
+        );
+    }
+    assert_section_present(
+        &generated,
+        quote! {
+            #[doc = " Pends `source`, one of `EXTI0`, `EXTI1`, the task then runs as if its peripheral requested it"]
+            #[inline]
+            pub fn pend (source : InterruptSource) {
+                # [allow (unreachable_patterns)]
+                match source {
+                    InterruptSource :: EXTI0 => { mock_pend (mypac :: Interrupt :: EXTI0) }
+                    InterruptSource :: EXTI1 => { mock_pend (mypac :: Interrupt :: EXTI1) }
+                    _ => Default :: default () ,
+                }
+            }
+        },
+        "interrupt control of the given source",
+    );
+}
+
+#[test]
+fn codegen_expands_task_instances() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> (Shared, TaskInits) {
+                todo!()
+            }
+
+            #[task(instances = [(UART0, 2), (UART1, 3)], shared = [counter])]
+            struct UartTask {
+                port: u8,
+            }
+
+            impl RticTask for UartTask {
+                type InitArgs = u8;
+                fn init(port: u8) -> Self {
+                    UartTask { port }
+                }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    // the type is defined once, each instance has its own static
+    assert_eq!(generated.matches("struct UartTask {").count(), 1);
+    for instance in ["UART_TASK_UART0", "UART_TASK_UART1"] {
+        let instance = format_ident!("{instance}");
+        assert_section_present(
+            &generated,
+            rticx_core::rticx_cell::uninit_static(&instance, &quote!(UartTask)),
+            "static of each instance",
+        );
+    }
+    assert_section_present(
+        &generated,
+        quote! {
+            pub struct TaskInits {
+                pub uart_task_uart0 : UartTask ,
+                pub uart_task_uart1 : UartTask ,
+            }
+        },
+        "instances initialized with their own arguments",
+    );
+
+    // the handler of an instance records its priority for the `shared()` API
+    assert_section_present(
+        &generated,
+        quote! {
+            unsafe {
+                let __rticx_preempted_priority = __RTICX_UART_TASK_PRIORITY . as_mut_ptr () . replace (3u16) ;
+                UART_TASK_UART1 . get_mut () . exec () ;
+                __RTICX_UART_TASK_PRIORITY . as_mut_ptr () . write (__rticx_preempted_priority) ;
+            }
+        },
+        "instance handler",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub fn shared (& self) -> __uart_task_shared_resources {
+                let task_priority = unsafe { * __RTICX_UART_TASK_PRIORITY . as_mut_ptr () } ;
+                __uart_task_shared_resources :: new (task_priority)
+            }
+        },
+        "shared() API of the running instance",
+    );
+
+    // the functions of the type take the instance
+    assert_section_present(
+        &generated,
+        quote! { pub enum InterruptSource { UART0 , UART1 , } },
+        "interrupt source enum",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            pub const fn priority (source : InterruptSource) -> u16 {
+                # [allow (unreachable_patterns)]
+                match source {
+                    InterruptSource :: UART0 => 2u16 ,
+                    InterruptSource :: UART1 => 3u16 ,
+                    _ => panic ! ("no instance of the task is bound to this interrupt") ,
+                }
+            }
+        },
+        "priority of each instance",
+    );
+    assert_section_present(
+        &generated,
+        quote! { pub fn pend (source : InterruptSource) },
+        "interrupt control of each instance",
+    );
+    assert_eq!(generated.matches("pub fn pend (").count(), 1);
+}
+
+#[test]
+fn codegen_passes_the_context_to_init() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init(cx: init::Context) -> Shared {
+                Shared { counter: 0 }
+            }
+        }
+    };
+    let mut app = App::parse(args, module).expect("valid app");
+    let analysis = Analysis::run(&mut app).expect("analysis succeeds");
+    let tokens = CodeGen::new(&MockCoreBackend, &app, &analysis).run();
+    let generated = tokens.to_string();
+
+    assert_section_present(
+        &generated,
+        quote! {
+            pub mod init {
+                use super::*;
+                #[doc = " Context of `init`, on core 0"]
+                pub struct Context {
+                    #[doc = r" Peripherals of the device"]
+                    pub device: mypac::Peripherals,
+                    #[doc = r" Peripherals of the processor core"]
+                    pub core: mypac::CorePeripherals,
+                    #[doc = r" Token of the core running `init`"]
+                    pub core_token: __rticx__internal__Core0,
+                }
+            }
+        },
+        "init context",
+    );
+    assert_section_present(
+        &generated,
+        quote! {
+            let shared_resources = init(init::Context {
+                device: unsafe { mypac::Peripherals::steal() },
+                core: unsafe { mypac::CorePeripherals::steal() },
+                core_token: unsafe { __rticx__internal__Core0::new() },
+            });
+        },
+        "init called with its context",
+    );
+}
@@ -34,6 +34,22 @@ fn parse_app_args_missing_device_fails() {
     assert!(err.to_string().contains("device"));
 }
 
+#[test]
+fn parse_app_args_with_tracer() {
+    let args: TokenStream = quote!(device = mypac, tracer = crate::MyTracer);
+    let parsed = AppArgs::parse(args);
+    if rticx_core::rticx_trace::ENABLED {
+        let tracer = parsed
+            .expect("valid app args")
+            .tracer
+            .expect("tracer is set");
+        assert_eq!(tracer.to_token_stream().to_string(), "crate :: MyTracer");
+    } else {
+        let err = parsed.expect_err("tracer needs the trace feature");
+        assert!(err.to_string().contains("`trace` feature"));
+    }
+}
+
 #[test]
 fn parse_single_core_app() {
     let args = common::single_core_app_args();
@@ -126,10 +142,7 @@ fn parse_task_args_default_values() {
     use rticx_core::parser::ast::TaskArgs;
     let meta: syn::Meta = syn::parse_quote!(task(binds = UART, priority = 2, shared = [counter]));
     let args = TaskArgs::parse(meta).expect("valid task args");
-    assert_eq!(
-        args.binds.as_ref().map(|i| i.to_string()),
-        Some("UART".to_string())
-    );
+    assert_eq!(args.binds, ["UART"]);
     assert_eq!(args.priority, 2);
     assert_eq!(args.shared.len(), 1);
     assert_eq!(args.shared[0].to_string(), "counter");
@@ -137,6 +150,235 @@ fn parse_task_args_default_values() {
     assert_eq!(args.task_trait.to_string(), "RticTask");
 }
 
+#[test]
+fn parse_task_args_with_readonly_resources() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, shared = [counter, &config]));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    assert_eq!(args.shared.len(), 1);
+    assert_eq!(args.shared[0].to_string(), "counter");
+    assert_eq!(args.readonly.len(), 1);
+    assert_eq!(args.readonly[0].to_string(), "config");
+
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, shared = [&mut counter]));
+    let err = TaskArgs::parse(meta).expect_err("`&mut` access should fail");
+    assert!(err.to_string().contains("`&mut`"));
+
+    for meta in [
+        syn::parse_quote!(task(binds = UART, shared = [&channels[0]])),
+        syn::parse_quote!(task(binds = UART, shared = [&motor.state])),
+    ] {
+        let err = TaskArgs::parse(meta).expect_err("read-only part should fail");
+        assert!(err.to_string().contains("whole resources"), "{err}");
+    }
+}
+
+#[test]
+fn parse_task_args_with_threshold() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, priority = 2, threshold = 4));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    assert_eq!((args.priority, args.threshold), (2, 4));
+
+    // without a threshold, the task runs at its priority
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, priority = 2));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    assert_eq!(args.threshold, 2);
+}
+
+#[test]
+fn parse_task_args_with_several_binds() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(binds = [EXTI0, EXTI1, EXTI2], priority = 2));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    assert_eq!(args.binds, ["EXTI0", "EXTI1", "EXTI2"]);
+
+    let meta: syn::Meta = syn::parse_quote!(task(binds = []));
+    let err = TaskArgs::parse(meta).expect_err("empty list of interrupts should fail");
+    assert!(err.to_string().contains("`binds`"));
+
+    let meta: syn::Meta = syn::parse_quote!(task(binds = [EXTI0, 1]));
+    let err = TaskArgs::parse(meta).expect_err("non-interrupt should fail");
+    assert!(err.to_string().contains("`binds`"));
+}
+
+#[test]
+fn parse_task_args_with_instances() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(instances = [(UART0, 2), (UART1, 3)]));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    let instances: Vec<_> = args
+        .instances
+        .iter()
+        .map(|i| (i.binds.to_string(), i.priority))
+        .collect();
+    assert_eq!(
+        instances,
+        [("UART0".to_string(), 2), ("UART1".to_string(), 3)]
+    );
+    let instance = args.instance_args(1);
+    assert_eq!(instance.binds, ["UART1"]);
+    assert_eq!((instance.priority, instance.threshold), (3, 3));
+
+    for meta in [
+        syn::parse_quote!(task(instances = [])),
+        syn::parse_quote!(task(instances = [UART0])),
+        syn::parse_quote!(task(instances = [(UART0, 2, 3)])),
+        syn::parse_quote!(task(binds = UART0, instances = [(UART1, 2)])),
+        syn::parse_quote!(task(priority = 2, instances = [(UART1, 2)])),
+    ] {
+        let err = TaskArgs::parse(meta).expect_err("invalid instances should fail");
+        assert!(err.to_string().contains("`instances`"));
+    }
+}
+
+#[test]
+fn parse_task_instances() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() {}
+
+            #[task(instances = [(UART0, 2), (UART1, 3)])]
+            struct UartTask;
+
+            impl RticTask for UartTask {
+                fn init() -> Self { UartTask }
+                fn exec(&mut self) {}
+            }
+        }
+    };
+    let app = App::parse(args, module).expect("valid app");
+    let tasks: Vec<_> = app.sub_apps[0]
+        .tasks
+        .iter()
+        .map(|task| {
+            (
+                task.instance_name().to_string(),
+                task.name_uppercase().to_string(),
+                task.args.priority,
+                task.defines_type(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        tasks,
+        [
+            (
+                "UartTask_UART0".to_string(),
+                "UART_TASK_UART0".to_string(),
+                2,
+                true
+            ),
+            (
+                "UartTask_UART1".to_string(),
+                "UART_TASK_UART1".to_string(),
+                3,
+                false
+            ),
+        ]
+    );
+    // all the instances share the task type
+    assert!(
+        app.sub_apps[0]
+            .tasks
+            .iter()
+            .all(|task| task.name() == "UartTask")
+    );
+}
+
+#[test]
+fn parse_task_args_with_resource_parts() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(
+        binds = UART,
+        shared = [counter, channels[0], motor.state.mode]
+    ));
+    let args = TaskArgs::parse(meta).expect("valid task args");
+    assert_eq!(args.shared.len(), 1);
+    let parts: Vec<_> = args
+        .shared_parts
+        .iter()
+        .map(|part| (part.to_string(), part.ident().to_string()))
+        .collect();
+    assert_eq!(
+        parts,
+        [
+            ("channels[0]".to_string(), "channels_0".to_string()),
+            (
+                "motor.state.mode".to_string(),
+                "motor_state_mode".to_string()
+            ),
+        ]
+    );
+    assert!(args.shared_parts[0].overlaps(&args.shared_parts[0]));
+    assert!(!args.shared_parts[0].overlaps(&args.shared_parts[1]));
+
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, shared = [channels[i]]));
+    let err = TaskArgs::parse(meta).expect_err("non-literal index should fail");
+    assert!(err.to_string().contains("literal indices"));
+}
+
+#[test]
+fn parse_rejects_overlapping_resource_parts() {
+    use rticx_core::parser::ast::TaskArgs;
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, shared = [channels, channels[0]]));
+    let err = TaskArgs::parse(meta).expect_err("part of a listed resource should fail");
+    assert!(
+        err.to_string()
+            .contains("`channels[0]` overlaps `channels`"),
+        "{err}"
+    );
+
+    let meta: syn::Meta =
+        syn::parse_quote!(task(binds = UART, shared = [motor.state, motor.state.x]));
+    let err = TaskArgs::parse(meta).expect_err("part of a listed part should fail");
+    assert!(
+        err.to_string()
+            .contains("`motor.state.x` overlaps `motor.state`"),
+        "{err}"
+    );
+
+    let meta: syn::Meta = syn::parse_quote!(task(binds = UART, shared = [counter, counter]));
+    let err = TaskArgs::parse(meta).expect_err("resource listed twice should fail");
+    assert!(
+        err.to_string().contains("`counter` overlaps `counter`"),
+        "{err}"
+    );
+
+    // disjoint parts of the same resource are fine
+    let meta: syn::Meta =
+        syn::parse_quote!(task(binds = UART, shared = [channels[0], channels[1].rx]));
+    TaskArgs::parse(meta).expect("disjoint parts are valid");
+}
+
+#[test]
+fn parse_readonly_shared_resources() {
+    let args = common::single_core_app_args();
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+                #[readonly]
+                pub config: [u8; 4],
+            }
+
+            #[init]
+            fn init() -> Shared {
+                Shared { counter: 0, config: [0; 4] }
+            }
+        }
+    };
+    let app = App::parse(args, module).expect("valid app");
+    let shared = app.sub_apps[0].shared.as_ref().expect("shared resources");
+    let readonly: Vec<_> = shared.resources.iter().map(|r| r.readonly).collect();
+    assert_eq!(readonly, [false, true]);
+    // the marker is removed from the generated struct
+    assert!(shared.strct.fields.iter().all(|f| f.attrs.is_empty()));
+}
+
 #[test]
 fn parse_task_args_with_core_and_trait() {
     use rticx_core::parser::ast::TaskArgs;
@@ -158,7 +400,7 @@ fn parse_task_args_defaults() {
     // mock backend value of 1 because the full builder sets the static.
     let meta: syn::Meta = syn::parse_quote!(task);
     let args = TaskArgs::parse(meta).expect("valid task args");
-    assert!(args.binds.is_none());
+    assert!(args.binds.is_empty());
     assert_eq!(args.core, 0);
     assert_eq!(args.task_trait.to_string(), "RticTask");
     // priority defaults to whatever DEFAULT_TASK_PRIORITY currently is; this test is only
@@ -206,8 +448,8 @@ fn task_marks_custom_init_args_as_user_initializable() {
             }
 
             #[init]
-            fn init() -> Shared {
-                Shared { counter: 0 }
+            fn init() -> (Shared, TaskInits) {
+                unimplemented!()
             }
 
             #[task(binds = UART, priority = 2)]
@@ -226,3 +468,124 @@ fn task_marks_custom_init_args_as_user_initializable() {
     assert!(task.user_initializable);
     assert!(task.task_init_call().is_none());
 }
+
+#[test]
+fn parse_init_with_context() {
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init(cx: init::Context) {}
+        }
+    };
+    let app = App::parse(quote::quote!(device = mypac), module).expect("valid app");
+    assert!(app.sub_apps[0].init.context);
+
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init(cx: idle::Context) {}
+        }
+    };
+    let err = App::parse(quote::quote!(device = mypac), module).expect_err("wrong context");
+    assert!(err.to_string().contains("`init::Context`"), "{err}");
+
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init(cx: init::Context, extra: u32) {}
+        }
+    };
+    let err = App::parse(quote::quote!(device = mypac), module).expect_err("two arguments");
+    assert!(err.to_string().contains("either no argument"), "{err}");
+}
+
+#[test]
+fn parse_checks_the_init_return_type() {
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() {}
+        }
+    };
+    let err = App::parse(quote::quote!(device = mypac), module).expect_err("missing Shared");
+    assert!(
+        err.to_string()
+            .contains("Expected the return type of `init` to be `Shared`"),
+        "{err}"
+    );
+
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[init]
+            fn init() -> Shared {
+                todo!()
+            }
+
+            #[task(binds = UART, priority = 2)]
+            struct UartTask;
+        }
+    };
+    let err = App::parse(quote::quote!(device = mypac), module).expect_err("missing TaskInits");
+    assert!(err.to_string().contains("TaskInits"), "{err}");
+}
+
+#[test]
+fn parse_accepts_path_qualified_init_return_types() {
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> (self::Shared, app::TaskInits) {
+                todo!()
+            }
+
+            #[task(binds = UART, priority = 2)]
+            struct UartTask;
+        }
+    };
+    assert!(App::parse(quote::quote!(device = mypac), module).is_ok());
+
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> app::Shared {
+                todo!()
+            }
+        }
+    };
+    assert!(App::parse(quote::quote!(device = mypac), module).is_ok());
+
+    let module: syn::ItemMod = syn::parse_quote! {
+        mod app {
+            #[shared]
+            struct Shared {
+                pub counter: u32,
+            }
+
+            #[init]
+            fn init() -> app::Other {
+                todo!()
+            }
+        }
+    };
+    let err = App::parse(quote::quote!(device = mypac), module).expect_err("wrong type");
+    assert!(
+        err.to_string()
+            .contains("Expected the return type of `init` to be `Shared`, found `app :: Other`"),
+        "{err}"
+    );
+}
@@ -0,0 +1,119 @@
+//! Compares the lock-free MPSC queue with the SPSC queue serialised by a lock, which is what the
+//! software tasks pass does today with its critical section.
+//!
+//! Run with `cargo bench`. The uncontended scenario measures one enqueue + dequeue pair on a single
+//! thread, which is the cost `spawn` and the dispatcher pay on a microcontroller. The contended
+//! scenarios have several threads enqueuing `ITEMS` elements each while one consumer drains the
+//! queue, they need a host with more than one CPU. Figures are the mean time per element.
+
+use std::hint::black_box;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rticx_spsc::Queue as SpscQueue;
+use rticx_spsc::mpsc::Queue as MpscQueue;
+
+const ITEMS: usize = 200_000;
+const DEPTH: usize = 16;
+const RUNS: u32 = 5;
+
+fn mpsc_uncontended(_: usize) -> Duration {
+    let mut q: MpscQueue<usize, DEPTH> = MpscQueue::new();
+    let start = Instant::now();
+    for i in 0..ITEMS {
+        let _ = q.enqueue(black_box(i));
+        black_box(q.dequeue());
+    }
+    start.elapsed()
+}
+
+fn spsc_locked_uncontended(_: usize) -> Duration {
+    let q: Mutex<SpscQueue<usize, { DEPTH + 1 }>> = Mutex::new(SpscQueue::new());
+    let start = Instant::now();
+    for i in 0..ITEMS {
+        let _ = q.lock().unwrap().enqueue(black_box(i));
+        black_box(q.lock().unwrap().dequeue());
+    }
+    start.elapsed()
+}
+
+fn mpsc(producers: usize) -> Duration {
+    let mut q: MpscQueue<usize, DEPTH> = MpscQueue::new();
+    let (producer, mut consumer) = q.split();
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..producers {
+            let mut producer = producer;
+            s.spawn(move || {
+                for i in 0..ITEMS {
+                    let mut item = i;
+                    while let Err(rejected) = producer.enqueue(item) {
+                        item = rejected;
+                        std::hint::spin_loop();
+                    }
+                }
+            });
+        }
+        let mut received = 0;
+        while received < producers * ITEMS {
+            if let Some(item) = consumer.dequeue() {
+                black_box(item);
+                received += 1;
+            }
+        }
+    });
+    start.elapsed()
+}
+
+fn spsc_locked(producers: usize) -> Duration {
+    // the lock stands in for `__rticx_interrupt_free`: producers and consumer are serialised
+    let q: Mutex<SpscQueue<usize, { DEPTH + 1 }>> = Mutex::new(SpscQueue::new());
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..producers {
+            let q = &q;
+            s.spawn(move || {
+                for i in 0..ITEMS {
+                    let mut item = i;
+                    while let Err(rejected) = q.lock().unwrap().enqueue(item) {
+                        item = rejected;
+                        std::hint::spin_loop();
+                    }
+                }
+            });
+        }
+        let mut received = 0;
+        while received < producers * ITEMS {
+            if let Some(item) = q.lock().unwrap().dequeue() {
+                black_box(item);
+                received += 1;
+            }
+        }
+    });
+    start.elapsed()
+}
+
+fn report(name: &str, producers: usize, bench: fn(usize) -> Duration) {
+    let best = (0..RUNS)
+        .map(|_| bench(producers))
+        .min()
+        .unwrap_or_default();
+    let per_item = best.as_nanos() as f64 / (producers * ITEMS) as f64;
+    println!("{name:<12} producers={producers:<2} {per_item:>8.1} ns/element");
+}
+
+fn main() {
+    report("mpsc", 1, mpsc_uncontended);
+    report("spsc+lock", 1, spsc_locked_uncontended);
+
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+    if cpus < 2 {
+        println!("skipping the contended scenarios, this host has a single CPU");
+        return;
+    }
+    for producers in [1, 2, 4, 8].into_iter().filter(|p| *p < cpus) {
+        report("mpsc", producers, mpsc);
+        report("spsc+lock", producers, spsc_locked);
+    }
+}
@@ -2,6 +2,9 @@
 
 use core::mem::MaybeUninit;
 
+#[cfg(target_has_atomic = "ptr")]
+pub mod mpsc;
+
 pub struct Queue<T: Clone, const DEPTH: usize> {
     buffer: [MaybeUninit<T>; DEPTH],
     read_idx: usize,
@@ -69,6 +72,43 @@ impl<T: Clone, const DEPTH: usize> Queue<T, DEPTH> {
         data
     }
 
+    /// Returns `true` if the queue holds no element
+    pub fn is_empty(&self) -> bool {
+        self.write_idx % DEPTH == self.read_idx % DEPTH
+    }
+
+    /// Returns `true` if the next `enqueue` would fail
+    pub fn is_full(&self) -> bool {
+        (self.write_idx + 1) % DEPTH == self.read_idx % DEPTH
+    }
+
+    /// Adds `data` to the end of the queue. If the queue is full, the oldest element is removed
+    /// first to make room for it and is returned.
+    ///
+    /// Unlike [`Producer::enqueue`], this moves the read index as well: the caller must make sure
+    /// the consumer does not run concurrently (e.g. both sides access the queue in a critical section).
+    pub fn enqueue_overwrite(&mut self, data: T) -> Option<T> {
+        let evicted = if self.is_full() { self.dequeue() } else { None };
+        // there is room for one more element now
+        unsafe { self.enqueue_unchecked(data) };
+        evicted
+    }
+
+    /// Replaces the most recently enqueued element with `data`, returning the replaced element.
+    /// Returns `Err(data)` if the queue is empty.
+    ///
+    /// The replaced slot may be the one the consumer is about to read: the caller must make sure
+    /// the consumer does not run concurrently (e.g. both sides access the queue in a critical section).
+    pub fn replace_newest(&mut self, data: T) -> Result<T, T> {
+        if self.is_empty() {
+            return Err(data);
+        }
+        let newest = &mut self.buffer[self.write_idx.wrapping_sub(1) % DEPTH];
+        let replaced = unsafe { newest.assume_init_read() };
+        newest.write(data);
+        Ok(replaced)
+    }
+
     // Splits a queue into producer and consumer endpoints
     pub fn split(&mut self) -> (Producer<'_, T, DEPTH>, Consumer<'_, T, DEPTH>) {
         let self1 = unsafe { (self as *mut Queue<T, DEPTH>).as_mut().unwrap() };
@@ -177,4 +217,38 @@ mod tests {
         assert_eq!(q.dequeue(), Some(9));
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn test_enqueue_overwrite() {
+        let mut q: Queue<u32, 3> = Queue::new();
+        assert!(q.is_empty());
+        assert_eq!(q.enqueue_overwrite(1), None);
+        assert_eq!(q.enqueue_overwrite(2), None);
+        assert!(q.is_full());
+        assert_eq!(q.enqueue_overwrite(3), Some(1)); // the oldest element is evicted
+        assert_eq!(q.enqueue_overwrite(4), Some(2));
+        assert_eq!(q.dequeue(), Some(3));
+        assert_eq!(q.dequeue(), Some(4));
+        assert_eq!(q.dequeue(), None);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn test_replace_newest() {
+        let mut q: Queue<u32, 3> = Queue::new();
+        assert_eq!(q.replace_newest(1), Err(1));
+        assert!(q.enqueue(1).is_ok());
+        assert!(q.enqueue(2).is_ok());
+        assert_eq!(q.replace_newest(3), Ok(2));
+        assert_eq!(q.dequeue(), Some(1));
+        assert_eq!(q.replace_newest(4), Ok(3));
+        assert_eq!(q.dequeue(), Some(4));
+        assert_eq!(q.dequeue(), None);
+        // the newest element wraps around the end of the buffer
+        for i in 5..8 {
+            assert!(q.enqueue(i).is_ok());
+            assert_eq!(q.replace_newest(i * 10), Ok(i));
+            assert_eq!(q.dequeue(), Some(i * 10));
+        }
+    }
 }
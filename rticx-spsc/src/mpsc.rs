@@ -0,0 +1,292 @@
+//! Bounded multi-producer single-consumer queue.
+//!
+//! Producers claim a slot with a compare-and-swap on the tail index, so several contexts (tasks of
+//! different priorities, or different cores) can enqueue without a critical section. Indices carry
+//! a lap counter next to the slot index, which protects the CAS from the ABA problem: a stale tail
+//! only matches again after the counter wraps around, i.e. after `usize::MAX / DEPTH` laps.
+//!
+//! Nobody ever waits for a preempted context, so the queue can be used from interrupt handlers: a
+//! producer preempted between claiming a slot and filling it makes the consumer see the queue as
+//! empty from that slot on, and the other producers see it as full once they wrap around to that
+//! slot. The element becomes visible once that `enqueue` returns; the software tasks pass pends the
+//! dispatcher after every enqueue, so it never misses it.
+//!
+//! Only available on targets with compare-and-swap atomics (e.g. not on armv6-m).
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering, fence};
+
+struct Slot<T> {
+    /// `tail` position for which the slot is free, or that position + 1 once it is filled
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Bounded MPSC queue holding up to `DEPTH` elements.
+///
+/// It offers the same `new()`, `split()`, `enqueue` and `dequeue` API as [`crate::Queue`], so it
+/// can be used as the ready queue type of the software tasks pass. Unlike [`crate::Queue`], all
+/// `DEPTH` slots are usable, and `enqueue` only needs a shared reference.
+pub struct Queue<T, const DEPTH: usize> {
+    buffer: [Slot<T>; DEPTH],
+    /// next position to dequeue, only written by the consumer
+    head: AtomicUsize,
+    /// next position to enqueue, claimed by the producers
+    tail: AtomicUsize,
+}
+
+// SAFETY: producers only get shared access to the queue, and the single consumer is enforced by
+// `split`/`dequeue` taking `&mut self`
+unsafe impl<T: Send, const DEPTH: usize> Sync for Queue<T, DEPTH> {}
+
+impl<T, const DEPTH: usize> Queue<T, DEPTH> {
+    /// Positions are `lap | index`, `ONE_LAP` is the increment of the lap part
+    const ONE_LAP: usize = (DEPTH + 1).next_power_of_two();
+
+    #[inline(always)]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        assert!(DEPTH > 0, "the queue must have at least one slot");
+        let mut buffer = [const {
+            Slot {
+                stamp: AtomicUsize::new(0),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            }
+        }; DEPTH];
+        // slot `i` is free for the position `i` of the first lap
+        let mut i = 0;
+        while i < DEPTH {
+            buffer[i].stamp = AtomicUsize::new(i);
+            i += 1;
+        }
+        Queue {
+            buffer,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Position following `pos`
+    #[inline(always)]
+    fn next(pos: usize) -> usize {
+        let index = pos & (Self::ONE_LAP - 1);
+        if index + 1 < DEPTH {
+            pos + 1
+        } else {
+            (pos & !(Self::ONE_LAP - 1)).wrapping_add(Self::ONE_LAP)
+        }
+    }
+
+    /// Adds `data` to the end of the queue. Can be called concurrently by any number of producers.
+    pub fn enqueue(&self, data: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[tail & (Self::ONE_LAP - 1)];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            if stamp == tail {
+                // the slot is free, try to claim it
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    Self::next(tail),
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(data) };
+                        slot.stamp.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if (tail.wrapping_sub(stamp) as isize) > 0 {
+                // the slot still holds the element of the previous lap, or a preempted producer
+                // claimed it and has not written it yet: the queue is full either way. Never wait
+                // for that producer, it may run at a lower priority than us.
+                fence(Ordering::SeqCst);
+                let head = self.head.load(Ordering::Acquire);
+                if head.wrapping_add(Self::ONE_LAP) == tail {
+                    return Err(data);
+                }
+                tail = self.tail.load(Ordering::Relaxed);
+            } else {
+                // another producer claimed this slot, so the tail moved on since we read it
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Adds an `item` to the end of the queue, without reporting if it's full
+    ///
+    /// # Safety
+    ///
+    /// If the queue is full `data` is leaked (its destructor won't run). The caller must make
+    /// sure there is room for it, e.g. by bounding the number of elements in flight.
+    pub unsafe fn enqueue_unchecked(&self, data: T) {
+        if let Err(data) = self.enqueue(data) {
+            core::mem::forget(data);
+        }
+    }
+
+    /// Removes the element in the front of the queue. Returns `None` if the queue is empty, or if
+    /// the front element is still being written by a preempted producer.
+    pub fn dequeue(&mut self) -> Option<T> {
+        // SAFETY: `&mut self` guarantees a single consumer
+        unsafe { self.dequeue_shared() }
+    }
+
+    /// Returns the item in the front of the queue, without checking if there is something in the
+    /// queue
+    ///
+    /// # Safety
+    ///
+    /// If the queue is empty (or its front element is not fully enqueued yet) this is undefined
+    /// behavior.
+    pub unsafe fn dequeue_unchecked(&mut self) -> T {
+        unsafe { self.dequeue().unwrap_unchecked() }
+    }
+
+    /// Removes the element in the front of the queue through a shared reference, for a consumer
+    /// running concurrently with producers that also hold one.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called concurrently with itself.
+    pub unsafe fn dequeue_shared(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        let slot = &self.buffer[head & (Self::ONE_LAP - 1)];
+        if slot.stamp.load(Ordering::Acquire) != head.wrapping_add(1) {
+            return None;
+        }
+        let data = unsafe { (*slot.value.get()).assume_init_read() };
+        // free the slot before moving the head: a producer preempting us in between then finds
+        // the slot free, instead of spinning on a full queue until we resume
+        slot.stamp
+            .store(head.wrapping_add(Self::ONE_LAP), Ordering::Release);
+        self.head.store(Self::next(head), Ordering::Release);
+        Some(data)
+    }
+
+    /// Returns `true` if the queue holds no element
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+
+    // Splits a queue into producer and consumer endpoints
+    pub fn split(&mut self) -> (Producer<'_, T, DEPTH>, Consumer<'_, T, DEPTH>) {
+        let q: &Self = self;
+        (Producer { q }, Consumer { q })
+    }
+}
+
+impl<T, const DEPTH: usize> Drop for Queue<T, DEPTH> {
+    fn drop(&mut self) {
+        while self.dequeue().is_some() {}
+    }
+}
+
+/// Producer endpoint. It can be copied and shared with every context enqueueing to the queue.
+pub struct Producer<'a, T, const DEPTH: usize> {
+    q: &'a Queue<T, DEPTH>,
+}
+
+impl<T, const DEPTH: usize> Clone for Producer<'_, T, DEPTH> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const DEPTH: usize> Copy for Producer<'_, T, DEPTH> {}
+
+impl<'a, T, const DEPTH: usize> Producer<'a, T, DEPTH> {
+    pub fn enqueue(&mut self, data: T) -> Result<(), T> {
+        self.q.enqueue(data)
+    }
+
+    /// Adds an `item` to the end of the queue, without reporting if it's full
+    ///
+    /// # Safety
+    ///
+    /// See [`Queue::enqueue_unchecked`].
+    pub unsafe fn enqueue_unchecked(&mut self, data: T) {
+        unsafe { self.q.enqueue_unchecked(data) }
+    }
+}
+
+/// Consumer endpoint, there is only one per `split`.
+pub struct Consumer<'a, T, const DEPTH: usize> {
+    q: &'a Queue<T, DEPTH>,
+}
+
+// SAFETY: the consumer is unique, moving it to another thread keeps a single consumer
+unsafe impl<T: Send, const DEPTH: usize> Send for Consumer<'_, T, DEPTH> {}
+
+impl<'a, T, const DEPTH: usize> Consumer<'a, T, DEPTH> {
+    pub fn dequeue(&mut self) -> Option<T> {
+        // SAFETY: `split` hands out a single consumer
+        unsafe { self.q.dequeue_shared() }
+    }
+
+    /// Returns the item in the front of the queue, without checking if there is something in the
+    /// queue
+    ///
+    /// # Safety
+    ///
+    /// See [`Queue::dequeue_unchecked`].
+    pub unsafe fn dequeue_unchecked(&mut self) -> T {
+        unsafe { self.dequeue().unwrap_unchecked() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+
+    #[test]
+    fn test_mpsc_queue() {
+        let mut q: Queue<u32, 3> = Queue::new();
+        assert!(q.is_empty());
+        assert!(q.enqueue(1).is_ok());
+        assert!(q.enqueue(2).is_ok());
+        assert!(q.enqueue(3).is_ok()); // every slot is usable
+        assert_eq!(q.enqueue(4), Err(4));
+        assert_eq!(q.dequeue(), Some(1));
+        assert!(q.enqueue(4).is_ok());
+        assert_eq!(q.dequeue(), Some(2));
+        assert_eq!(q.dequeue(), Some(3));
+        assert_eq!(q.dequeue(), Some(4));
+        assert_eq!(q.dequeue(), None);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn test_mpsc_split_laps() {
+        let mut q: Queue<u32, 2> = Queue::new();
+        let (mut p1, mut c) = q.split();
+        let mut p2 = p1;
+        // go around the buffer several times
+        for i in 0..10 {
+            assert!(p1.enqueue(2 * i).is_ok());
+            assert!(p2.enqueue(2 * i + 1).is_ok());
+            assert!(p1.enqueue(0).is_err());
+            assert_eq!(c.dequeue(), Some(2 * i));
+            assert_eq!(c.dequeue(), Some(2 * i + 1));
+            assert_eq!(c.dequeue(), None);
+        }
+    }
+
+    #[test]
+    fn test_mpsc_shared_reference() {
+        let q: Queue<u32, 2> = Queue::new();
+        let (producer, consumer) = (&q, &q);
+        unsafe {
+            producer.enqueue_unchecked(1);
+            producer.enqueue_unchecked(2);
+            producer.enqueue_unchecked(3); // full, leaked
+            assert_eq!(consumer.dequeue_shared(), Some(1));
+            assert_eq!(consumer.dequeue_shared(), Some(2));
+            assert_eq!(consumer.dequeue_shared(), None);
+        }
+    }
+}
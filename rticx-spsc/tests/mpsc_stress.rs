@@ -0,0 +1,91 @@
+//! Host stress tests of the MPSC queue: many threads hammer a small queue while a single consumer
+//! drains it, then we check that every element arrived exactly once and in per-producer order.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use rticx_spsc::mpsc::Queue;
+
+/// Run `producers` threads each enqueuing `per_producer` elements, retrying while the queue is full,
+/// and return the elements in the order the consumer got them.
+fn run<const DEPTH: usize>(producers: usize, per_producer: usize) -> Vec<(usize, usize)> {
+    let mut q: Queue<(usize, usize), DEPTH> = Queue::new();
+    let (producer, mut consumer) = q.split();
+    let total = producers * per_producer;
+    let mut received = Vec::with_capacity(total);
+
+    thread::scope(|s| {
+        for id in 0..producers {
+            let mut producer = producer;
+            s.spawn(move || {
+                for seq in 0..per_producer {
+                    let mut item = (id, seq);
+                    while let Err(rejected) = producer.enqueue(item) {
+                        item = rejected;
+                        thread::yield_now();
+                    }
+                }
+            });
+        }
+        while received.len() < total {
+            match consumer.dequeue() {
+                Some(item) => received.push(item),
+                None => thread::yield_now(),
+            }
+        }
+    });
+    assert!(q.is_empty());
+    received
+}
+
+fn check(received: &[(usize, usize)], producers: usize, per_producer: usize) {
+    assert_eq!(received.len(), producers * per_producer);
+    let mut next_seq = vec![0; producers];
+    for &(id, seq) in received {
+        assert_eq!(
+            seq, next_seq[id],
+            "producer {id} lost or reordered an element"
+        );
+        next_seq[id] += 1;
+    }
+    assert!(next_seq.iter().all(|n| *n == per_producer));
+}
+
+#[test]
+fn mpsc_stress_many_producers() {
+    let received = run::<8>(8, 1_000);
+    check(&received, 8, 1_000);
+}
+
+#[test]
+fn mpsc_stress_tiny_queue() {
+    // with two slots, producers constantly hit a full queue and wrap around laps
+    let received = run::<2>(4, 500);
+    check(&received, 4, 500);
+}
+
+#[test]
+fn mpsc_stress_non_power_of_two_depth() {
+    let received = run::<5>(3, 1_000);
+    check(&received, 3, 1_000);
+}
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn mpsc_drops_pending_elements() {
+    let q: Queue<Counted, 4> = Queue::new();
+    for _ in 0..3 {
+        assert!(q.enqueue(Counted).is_ok());
+    }
+    drop(q);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+}
@@ -0,0 +1,54 @@
+//! Converts a trace dump to Chrome trace / Perfetto JSON, written to the standard output.
+//!
+//! ```text
+//! rticx-trace-decode <report.json> <dump.bin> [--hz <counter frequency>]
+//! ```
+
+use std::process::ExitCode;
+
+use rticx_trace::decode::{Report, records_from_bytes, to_chrome_trace};
+
+const USAGE: &str = "usage: rticx-trace-decode <report.json> <dump.bin> [--hz <counter frequency>]";
+
+fn run() -> Result<String, String> {
+    let mut args = std::env::args().skip(1);
+    let mut paths = Vec::new();
+    let mut hz = None;
+    while let Some(arg) = args.next() {
+        if arg == "--hz" {
+            let value = args.next().ok_or(USAGE)?;
+            hz = Some(
+                value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|hz| *hz > 0)
+                    .ok_or_else(|| format!("invalid counter frequency `{value}`"))?,
+            );
+        } else {
+            paths.push(arg);
+        }
+    }
+    let [report_path, dump_path] = paths.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+
+    let report = std::fs::read_to_string(report_path)
+        .map_err(|e| format!("cannot read `{report_path}`: {e}"))?;
+    let report = Report::from_json(&report)?;
+    let dump = std::fs::read(dump_path).map_err(|e| format!("cannot read `{dump_path}`: {e}"))?;
+    let records = records_from_bytes(&dump)?;
+    Ok(to_chrome_trace(&records, &report, hz))
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(trace) => {
+            println!("{trace}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
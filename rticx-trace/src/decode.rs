@@ -0,0 +1,236 @@
+//! Conversion of trace dumps to the Chrome trace event format, which Perfetto and
+//! `chrome://tracing` open.
+//!
+//! Every core is shown as a thread. Task jobs and locks are nested slices, and spawns are instant
+//! events on the core of the spawned task.
+
+use serde_json::{Value, json};
+
+use crate::{EventKind, Record};
+
+/// A task or shared resource of the application report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub id: u16,
+    pub name: String,
+    pub core: u32,
+}
+
+/// Names of the traced tasks and resources, read from the application report written by
+/// `rticx-core`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub tasks: Vec<Entry>,
+    pub resources: Vec<Entry>,
+}
+
+impl Report {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let report: Value =
+            serde_json::from_str(json).map_err(|e| format!("invalid report: {e}"))?;
+        let entries = |key: &str| -> Result<Vec<Entry>, String> {
+            let Some(entries) = report.get(key).and_then(Value::as_array) else {
+                return Err(format!("the report has no `{key}` array"));
+            };
+            entries
+                .iter()
+                .map(|entry| {
+                    let field = |name: &str| {
+                        entry
+                            .get(name)
+                            .ok_or_else(|| format!("an entry of `{key}` has no `{name}`"))
+                    };
+                    let number = |name: &str| {
+                        field(name)?.as_u64().ok_or_else(|| {
+                            format!("`{name}` of an entry of `{key}` is not a number")
+                        })
+                    };
+                    Ok(Entry {
+                        id: u16::try_from(number("id")?)
+                            .map_err(|_| format!("an identifier of `{key}` is too large"))?,
+                        name: field("name")?
+                            .as_str()
+                            .ok_or_else(|| format!("a name of `{key}` is not a string"))?
+                            .to_string(),
+                        core: u32::try_from(number("core")?)
+                            .map_err(|_| format!("a core of `{key}` is too large"))?,
+                    })
+                })
+                .collect()
+        };
+        Ok(Self {
+            tasks: entries("tasks")?,
+            resources: entries("resources")?,
+        })
+    }
+
+    fn task(&self, id: u16) -> Option<&Entry> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    fn resource(&self, id: u16) -> Option<&Entry> {
+        self.resources.iter().find(|resource| resource.id == id)
+    }
+}
+
+/// Decode a dump made of encoded [Record]s, oldest first
+pub fn records_from_bytes(dump: &[u8]) -> Result<Vec<Record>, String> {
+    if !dump.len().is_multiple_of(Record::SIZE) {
+        return Err(format!(
+            "the dump is {} bytes long, which is not a multiple of the record size ({})",
+            dump.len(),
+            Record::SIZE
+        ));
+    }
+    dump.chunks_exact(Record::SIZE)
+        .enumerate()
+        .map(|(idx, bytes)| {
+            let bytes = bytes.try_into().expect("chunks have the record size");
+            Record::from_bytes(bytes)
+                .ok_or_else(|| format!("record {idx} has an unknown event kind {}", bytes[0]))
+        })
+        .collect()
+}
+
+/// Chrome trace JSON of `records`. Timestamps are converted to microseconds when the counter
+/// frequency `hz` is given, and shown as is otherwise. The 32-bit counter may wrap around between
+/// two records, but not more than once.
+pub fn to_chrome_trace(records: &[Record], report: &Report, hz: Option<u64>) -> String {
+    let mut events = Vec::new();
+
+    // name the cores
+    let mut cores: Vec<u32> = report
+        .tasks
+        .iter()
+        .chain(&report.resources)
+        .map(|entry| entry.core)
+        .collect();
+    cores.sort_unstable();
+    cores.dedup();
+    for core in cores {
+        events.push(json!({
+            "name": "thread_name",
+            "ph": "M",
+            "pid": 0,
+            "tid": core,
+            "args": { "name": format!("core {core}") },
+        }));
+    }
+
+    let mut wraps = 0u64;
+    let mut previous = None;
+    for record in records {
+        if previous.is_some_and(|previous| record.timestamp < previous) {
+            wraps += 1;
+        }
+        previous = Some(record.timestamp);
+        let cycles = (wraps << 32) + u64::from(record.timestamp);
+        let ts = match hz {
+            Some(hz) => cycles as f64 * 1_000_000.0 / hz as f64,
+            None => cycles as f64,
+        };
+
+        let (entry, fallback) = match record.kind {
+            EventKind::TaskEnter | EventKind::TaskExit | EventKind::Spawn => {
+                (report.task(record.id), "task")
+            }
+            EventKind::LockAcquire | EventKind::LockRelease => {
+                (report.resource(record.id), "resource")
+            }
+        };
+        let name = entry.map_or_else(
+            || format!("{fallback} {}", record.id),
+            |entry| entry.name.clone(),
+        );
+        let core = entry.map_or(0, |entry| entry.core);
+
+        let (name, cat, ph) = match record.kind {
+            EventKind::TaskEnter => (name, "task", "B"),
+            EventKind::TaskExit => (name, "task", "E"),
+            EventKind::LockAcquire => (format!("lock {name}"), "lock", "B"),
+            EventKind::LockRelease => (format!("lock {name}"), "lock", "E"),
+            EventKind::Spawn => (format!("spawn {name}"), "spawn", "i"),
+        };
+        let mut event = json!({
+            "name": name,
+            "cat": cat,
+            "ph": ph,
+            "ts": ts,
+            "pid": 0,
+            "tid": core,
+        });
+        if record.kind == EventKind::Spawn {
+            event["s"] = json!("t");
+        }
+        events.push(event);
+    }
+
+    json!({ "traceEvents": events }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"{
+        "tasks": [
+            { "id": 0, "name": "Uart", "core": 0, "priority": 2 },
+            { "id": 1, "name": "Worker", "core": 0, "priority": 1 }
+        ],
+        "resources": [
+            { "id": 0, "name": "counter", "core": 0, "ceiling": 2 }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_report() {
+        let report = Report::from_json(REPORT).unwrap();
+        assert_eq!(report.tasks.len(), 2);
+        assert_eq!(
+            report.resources[0],
+            Entry {
+                id: 0,
+                name: "counter".into(),
+                core: 0
+            }
+        );
+        assert!(Report::from_json(r#"{ "tasks": [] }"#).is_err());
+    }
+
+    #[test]
+    fn decodes_dump() {
+        let records = [
+            Record::new(EventKind::TaskEnter, 1, 10),
+            Record::new(EventKind::TaskExit, 1, 20),
+        ];
+        let dump: Vec<u8> = records.iter().flat_map(Record::to_bytes).collect();
+        assert_eq!(records_from_bytes(&dump).unwrap(), records);
+        assert!(records_from_bytes(&dump[1..]).is_err());
+    }
+
+    #[test]
+    fn converts_to_chrome_trace() {
+        let report = Report::from_json(REPORT).unwrap();
+        let records = [
+            Record::new(EventKind::TaskEnter, 1, u32::MAX - 99),
+            Record::new(EventKind::LockAcquire, 0, u32::MAX),
+            Record::new(EventKind::Spawn, 0, 100),
+            Record::new(EventKind::LockRelease, 0, 200),
+            Record::new(EventKind::TaskExit, 1, 900),
+        ];
+        let trace: Value =
+            serde_json::from_str(&to_chrome_trace(&records, &report, Some(100_000_000))).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+
+        assert_eq!(events[0]["args"]["name"], "core 0");
+        assert_eq!(events[1]["name"], "Worker");
+        assert_eq!(events[1]["ph"], "B");
+        assert_eq!(events[2]["name"], "lock counter");
+        assert_eq!(events[3]["name"], "spawn Uart");
+        assert_eq!(events[3]["ph"], "i");
+        assert_eq!(events[5]["ph"], "E");
+        // 1000 cycles at 100 MHz, across the counter wrap-around
+        let duration = events[5]["ts"].as_f64().unwrap() - events[1]["ts"].as_f64().unwrap();
+        assert!((duration - 10.0).abs() < 1e-6);
+    }
+}
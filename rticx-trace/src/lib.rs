@@ -0,0 +1,130 @@
+//! Tracing of RTICX applications.
+//!
+//! With the `trace` feature of `rticx-core`, the generated code calls the hooks of a [Tracer] when
+//! a task starts and finishes, a shared resource is locked and released, and a software task is
+//! spawned. Tasks and resources are given by compact identifiers assigned by the core analysis,
+//! which also writes them, with their names, to an application report
+//! (`target/rticx/<binary>.trace.json`).
+//!
+//! The default [NoTracer] does nothing and compiles away. Distributions can record the events in
+//! a [RingBuffer], and dump it to the host where the `rticx-trace-decode` binary (`decoder`
+//! feature) turns the dump into a Chrome trace / Perfetto JSON file.
+#![cfg_attr(not(feature = "decoder"), no_std)]
+
+#[cfg(feature = "decoder")]
+pub mod decode;
+mod ring;
+
+pub use ring::RingBuffer;
+
+/// Hooks called by the generated code, all of them do nothing by default.
+///
+/// The hooks run in the context of the task they trace, possibly preempted by higher priority
+/// tasks, so implementations recording the events must protect their buffers, e.g. with a
+/// critical section.
+pub trait Tracer {
+    /// A job of the task `task` starts
+    #[inline(always)]
+    fn task_enter(_task: u16) {}
+
+    /// A job of the task `task` finishes
+    #[inline(always)]
+    fn task_exit(_task: u16) {}
+
+    /// The shared resource `resource` is locked, its ceiling is already raised
+    #[inline(always)]
+    fn lock_acquire(_resource: u16) {}
+
+    /// The shared resource `resource` is about to be released
+    #[inline(always)]
+    fn lock_release(_resource: u16) {}
+
+    /// The software task `task` is spawned, right before its dispatcher is pended
+    #[inline(always)]
+    fn spawn(_task: u16) {}
+}
+
+/// Tracer ignoring every event, the default of applications built with tracing
+pub struct NoTracer;
+
+impl Tracer for NoTracer {}
+
+/// Kind of a traced event
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    TaskEnter = 0,
+    TaskExit = 1,
+    LockAcquire = 2,
+    LockRelease = 3,
+    Spawn = 4,
+}
+
+impl EventKind {
+    pub const fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(Self::TaskEnter),
+            1 => Some(Self::TaskExit),
+            2 => Some(Self::LockAcquire),
+            3 => Some(Self::LockRelease),
+            4 => Some(Self::Spawn),
+            _ => None,
+        }
+    }
+}
+
+/// A traced event: its kind, the task or resource identifier and the counter value when it
+/// happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub kind: EventKind,
+    pub id: u16,
+    pub timestamp: u32,
+}
+
+impl Record {
+    /// Size of an encoded record in a dump
+    pub const SIZE: usize = 8;
+
+    pub const fn new(kind: EventKind, id: u16, timestamp: u32) -> Self {
+        Self {
+            kind,
+            id,
+            timestamp,
+        }
+    }
+
+    /// Encode the record for a dump: the kind, a reserved zero byte, then the identifier and the
+    /// timestamp in little endian
+    pub const fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let id = self.id.to_le_bytes();
+        let ts = self.timestamp.to_le_bytes();
+        [self.kind as u8, 0, id[0], id[1], ts[0], ts[1], ts[2], ts[3]]
+    }
+
+    /// Decode a record of a dump, `None` if its kind is unknown
+    pub const fn from_bytes(bytes: [u8; Self::SIZE]) -> Option<Self> {
+        let Some(kind) = EventKind::from_u8(bytes[0]) else {
+            return None;
+        };
+        Some(Self {
+            kind,
+            id: u16::from_le_bytes([bytes[2], bytes[3]]),
+            timestamp: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let record = Record::new(EventKind::LockRelease, 0x1234, 0xdead_beef);
+        let bytes = record.to_bytes();
+        assert_eq!(bytes, [3, 0, 0x34, 0x12, 0xef, 0xbe, 0xad, 0xde]);
+        assert_eq!(Record::from_bytes(bytes), Some(record));
+        assert_eq!(Record::from_bytes([9, 0, 0, 0, 0, 0, 0, 0]), None);
+    }
+}
@@ -0,0 +1,105 @@
+use core::cell::UnsafeCell;
+
+use crate::{EventKind, Record};
+
+/// In-memory recorder keeping the last `N` records, oldest ones are overwritten
+pub struct RingBuffer<const N: usize> {
+    records: UnsafeCell<[Record; N]>,
+    /// Number of records pushed since the last clear
+    pushed: UnsafeCell<usize>,
+}
+
+// SAFETY: the buffer is only accessed through the unsafe methods, whose callers guarantee
+// exclusive access
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a ring buffer needs room for at least one record") };
+        Self {
+            records: UnsafeCell::new([Record::new(EventKind::TaskEnter, 0, 0); N]),
+            pushed: UnsafeCell::new(0),
+        }
+    }
+
+    /// Record an event, overwriting the oldest record when the buffer is full
+    ///
+    /// # Safety
+    ///
+    /// The buffer must not be accessed concurrently, e.g. call this in a critical section.
+    pub unsafe fn push(&self, record: Record) {
+        let pushed = unsafe { &mut *self.pushed.get() };
+        let records = unsafe { &mut *self.records.get() };
+        records[*pushed % N] = record;
+        *pushed = pushed.wrapping_add(1);
+    }
+
+    /// Number of records lost because the buffer was full
+    ///
+    /// # Safety
+    ///
+    /// The buffer must not be pushed to concurrently.
+    pub unsafe fn overwritten(&self) -> usize {
+        unsafe { *self.pushed.get() }.saturating_sub(N)
+    }
+
+    /// The kept records, oldest first
+    ///
+    /// # Safety
+    ///
+    /// The buffer must not be pushed to while the records are read.
+    pub unsafe fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        let pushed = unsafe { *self.pushed.get() };
+        let records = unsafe { &*self.records.get() };
+        let kept = pushed.min(N);
+        (pushed - kept..pushed).map(move |idx| records[idx % N])
+    }
+
+    /// Drop all the records
+    ///
+    /// # Safety
+    ///
+    /// The buffer must not be accessed concurrently.
+    pub unsafe fn clear(&self) {
+        unsafe { *self.pushed.get() = 0 };
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: u16) -> Record {
+        Record::new(EventKind::TaskEnter, id, u32::from(id) * 10)
+    }
+
+    #[test]
+    fn keeps_records_in_order() {
+        let buffer = RingBuffer::<4>::new();
+        unsafe {
+            buffer.push(record(1));
+            buffer.push(record(2));
+            assert!(buffer.records().eq([record(1), record(2)]));
+            assert_eq!(buffer.overwritten(), 0);
+        }
+    }
+
+    #[test]
+    fn overwrites_oldest_records() {
+        let buffer = RingBuffer::<3>::new();
+        unsafe {
+            (1..=5).for_each(|id| buffer.push(record(id)));
+            assert!(buffer.records().eq([record(3), record(4), record(5)]));
+            assert_eq!(buffer.overwritten(), 2);
+
+            buffer.clear();
+            assert_eq!(buffer.records().count(), 0);
+        }
+    }
+}